@@ -0,0 +1,242 @@
+// 音声入力（マイクの音量・スペクトル）による見た目の変調
+//
+// JS側は AnalyserNode などから毎フレーム（または任意の間隔で）音量とスペクトルを渡す。
+// ここでは帯域ごとの強さに分け、包絡線で平滑化してから各パラメータの倍率へ写す。
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// スペクトルの帯域境界（ナイキスト周波数に対する比）。48kHz なら低域 〜375Hz、中域 〜3kHz、高域 〜12kHz
+pub const BAND_EDGES: [f32; 4] = [0.0, 1.0 / 64.0, 1.0 / 8.0, 0.5];
+
+/// この秒数入力が途絶えたら無音として扱う（マイク停止時に変調が残らないように）
+pub const AUDIO_STALE_S: f32 = 0.5;
+
+/// マッピングの上限数
+pub const MAX_AUDIO_MAPPINGS: usize = 16;
+
+/// 倍率の範囲（1.0 で変調なし）
+pub const MODULATION_MIN: f32 = 0.25;
+pub const MODULATION_MAX: f32 = 4.0;
+
+/// 変調の入力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSource {
+    /// 全体の音量
+    Level,
+    /// 低域（声の基音付近）
+    Low,
+    /// 中域（母音のフォルマント付近）
+    Mid,
+    /// 高域（子音）
+    High,
+}
+
+/// 変調先のパラメータ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioTarget {
+    FlowSpeed,
+    NodeGlow,
+    EdgeThickness,
+    NucleusSize,
+}
+
+/// `source` の強さ（0..1）に `gain` を掛けて `target` の倍率へ足す
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AudioMapping {
+    pub source: AudioSource,
+    pub target: AudioTarget,
+    pub gain: f32,
+}
+
+/// 包絡線の時定数とマッピング。JSONで省略したフィールドは既定値になる。
+/// 例: `{ "attack_s": 0.03, "mappings": [{ "source": "level", "target": "flow_speed", "gain": 2.0 }] }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// 立ち上がりの時定数（秒）
+    pub attack_s: f32,
+    /// 減衰の時定数（秒）
+    pub release_s: f32,
+    pub mappings: Vec<AudioMapping>,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        use AudioSource::*;
+        use AudioTarget::*;
+        Self {
+            attack_s: 0.05,
+            release_s: 0.4,
+            mappings: vec![
+                AudioMapping { source: Level, target: FlowSpeed, gain: 1.5 },
+                AudioMapping { source: Low, target: NucleusSize, gain: 0.8 },
+                AudioMapping { source: Mid, target: EdgeThickness, gain: 0.6 },
+                AudioMapping { source: High, target: NodeGlow, gain: 1.2 },
+            ],
+        }
+    }
+}
+
+impl AudioConfig {
+    pub fn validate(&self) -> Result<(), AudioConfigError> {
+        for (field, v) in [("attack_s", self.attack_s), ("release_s", self.release_s)] {
+            if !(v.is_finite() && (0.0..=10.0).contains(&v)) {
+                return Err(AudioConfigError::InvalidTime { field });
+            }
+        }
+        if self.mappings.len() > MAX_AUDIO_MAPPINGS {
+            return Err(AudioConfigError::TooManyMappings { count: self.mappings.len(), max: MAX_AUDIO_MAPPINGS });
+        }
+        for (index, m) in self.mappings.iter().enumerate() {
+            if !(m.gain.is_finite() && (-MODULATION_MAX..=MODULATION_MAX).contains(&m.gain)) {
+                return Err(AudioConfigError::InvalidGain { index });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioConfigError {
+    Parse(String),
+    InvalidTime { field: &'static str },
+    TooManyMappings { count: usize, max: usize },
+    InvalidGain { index: usize },
+}
+
+impl fmt::Display for AudioConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "invalid audio config json: {msg}"),
+            Self::InvalidTime { field } => write!(f, "{field} must be in 0..=10 seconds"),
+            Self::TooManyMappings { count, max } => write!(f, "mappings must contain at most {max} entries, got {count}"),
+            Self::InvalidGain { index } => write!(f, "mappings[{index}].gain must be in -{MODULATION_MAX}..={MODULATION_MAX}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioConfigError {}
+
+/// JSONを読み込み、検証済みの設定を返す
+pub fn parse_audio_config(json: &str) -> Result<AudioConfig, AudioConfigError> {
+    let cfg: AudioConfig = serde_json::from_str(json).map_err(|e| AudioConfigError::Parse(e.to_string()))?;
+    cfg.validate()?;
+    Ok(cfg)
+}
+
+/// 入力の強さ（各 0..1）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AudioSignals {
+    pub level: f32,
+    pub low: f32,
+    pub mid: f32,
+    pub high: f32,
+}
+
+impl AudioSignals {
+    pub fn get(&self, source: AudioSource) -> f32 {
+        match source {
+            AudioSource::Level => self.level,
+            AudioSource::Low => self.low,
+            AudioSource::Mid => self.mid,
+            AudioSource::High => self.high,
+        }
+    }
+
+    fn as_array(&self) -> [f32; 4] { [self.level, self.low, self.mid, self.high] }
+
+    fn from_array([level, low, mid, high]: [f32; 4]) -> Self { Self { level, low, mid, high } }
+}
+
+fn unit(v: f32) -> f32 {
+    if v.is_finite() { v.clamp(0.0, 1.0) } else { 0.0 }
+}
+
+/// スペクトル（ビン毎の強さ 0..1、0Hz からナイキストまで等間隔）を低/中/高域の平均に分ける。
+/// ビン数が少なくても各帯域に1ビン以上を割り当てる（空なら 0）。
+pub fn band_levels(spectrum: &[f32]) -> [f32; 3] {
+    let n = spectrum.len();
+    let mut out = [0.0; 3];
+    if n == 0 { return out; }
+    let mut start = 0;
+    for (b, v) in out.iter_mut().enumerate() {
+        if start >= n { break; }
+        let end = ((BAND_EDGES[b + 1] * n as f32).ceil() as usize).clamp(start + 1, n);
+        let bins = &spectrum[start..end];
+        *v = bins.iter().map(|&x| unit(x)).sum::<f32>() / bins.len() as f32;
+        start = end;
+    }
+    out
+}
+
+/// 入力を攻撃/減衰の時定数で平滑化する包絡線
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioEnvelope {
+    current: AudioSignals,
+    target: AudioSignals,
+    since_input: f32,
+}
+
+impl AudioEnvelope {
+    /// 最新の入力。`level` は 0..1（RMS など）、`spectrum` は `band_levels` と同じ形式
+    pub fn set_input(&mut self, level: f32, spectrum: &[f32]) {
+        let [low, mid, high] = band_levels(spectrum);
+        self.target = AudioSignals { level: unit(level), low, mid, high };
+        self.since_input = 0.0;
+    }
+
+    pub fn signals(&self) -> AudioSignals { self.current }
+
+    /// `dt` 秒進める。入力が `AUDIO_STALE_S` 秒途絶えたら無音へ戻す。
+    pub fn step(&mut self, dt: f32, cfg: &AudioConfig) {
+        let dt = dt.max(0.0);
+        self.since_input += dt;
+        if self.since_input > AUDIO_STALE_S { self.target = AudioSignals::default(); }
+        let follow = |tau: f32| if tau > 0.0 { 1.0 - (-dt / tau).exp() } else { 1.0 };
+        let (ka, kr) = (follow(cfg.attack_s), follow(cfg.release_s));
+        let mut cur = self.current.as_array();
+        for (c, t) in cur.iter_mut().zip(self.target.as_array()) {
+            let k = if t > *c { ka } else { kr };
+            *c += (t - *c) * k;
+            if *c < 1e-4 { *c = 0.0; } // 無音時は完全に戻す
+        }
+        self.current = AudioSignals::from_array(cur);
+    }
+}
+
+/// 各パラメータへの倍率（1.0 で変調なし）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioModulation {
+    pub flow_speed: f32,
+    pub node_glow: f32,
+    pub edge_thickness: f32,
+    pub nucleus_size: f32,
+}
+
+impl Default for AudioModulation {
+    fn default() -> Self { Self { flow_speed: 1.0, node_glow: 1.0, edge_thickness: 1.0, nucleus_size: 1.0 } }
+}
+
+impl AudioModulation {
+    /// 倍率 = 1 + Σ gain × 強さ（`MODULATION_MIN..=MODULATION_MAX` に丸める）
+    pub fn from_signals(signals: &AudioSignals, mappings: &[AudioMapping]) -> Self {
+        let mut m = Self::default();
+        for map in mappings {
+            let slot = match map.target {
+                AudioTarget::FlowSpeed => &mut m.flow_speed,
+                AudioTarget::NodeGlow => &mut m.node_glow,
+                AudioTarget::EdgeThickness => &mut m.edge_thickness,
+                AudioTarget::NucleusSize => &mut m.nucleus_size,
+            };
+            *slot += map.gain * signals.get(map.source);
+        }
+        for v in [&mut m.flow_speed, &mut m.node_glow, &mut m.edge_thickness, &mut m.nucleus_size] {
+            *v = v.clamp(MODULATION_MIN, MODULATION_MAX);
+        }
+        m
+    }
+}
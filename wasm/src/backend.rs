@@ -0,0 +1,81 @@
+// 描画バックエンドの選択（GPUに依存しない判断部分）
+//
+// start_graph は WebGPU を優先し、使えなければ `webgl` feature でビルドした時だけ WebGL2 へ落とす。
+// キャンバスは一度 getContext した種類から変えられないので、判定はサーフェス作成前に行う。
+
+use std::fmt;
+
+/// `webgl` feature（wgpu の GL バックエンド）を組み込んでビルドしたか
+pub const WEBGL_ENABLED: bool = cfg!(feature = "webgl");
+
+/// 実際に描いている経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphBackend {
+    WebGpu,
+    /// wgpu の GL バックエンド（`webgl` feature）
+    WebGl2,
+    /// CPU 描画（`src/raster.rs`）を Canvas2D へ転送
+    Software,
+}
+
+impl GraphBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WebGpu => "webgpu",
+            Self::WebGl2 => "webgl2",
+            Self::Software => "software",
+        }
+    }
+
+    /// wgpu のインスタンスに渡すバックエンド。CPU 描画は wgpu を使わない
+    pub fn wgpu_backends(self) -> Option<wgpu::Backends> {
+        match self {
+            Self::WebGpu => Some(wgpu::Backends::BROWSER_WEBGPU),
+            Self::WebGl2 => Some(wgpu::Backends::GL),
+            Self::Software => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// WebGPU が無く、WebGL2 へのフォールバックも組み込まれていない
+    WebGpuUnavailable,
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WebGpuUnavailable => write!(f, "WebGPU is not available (build with the `webgl` feature to fall back to WebGL2)"),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// WebGPU のアダプタが取れるか（`is_browser_webgpu_supported`）と `webgl` feature から選ぶ。
+/// WebGL2 自体が使えるかはサーフェス作成（getContext("webgl2")）まで分からない
+pub fn choose_backend(webgpu_available: bool, webgl_enabled: bool) -> Result<GraphBackend, BackendError> {
+    match (webgpu_available, webgl_enabled) {
+        (true, _) => Ok(GraphBackend::WebGpu),
+        (false, true) => Ok(GraphBackend::WebGl2),
+        (false, false) => Err(BackendError::WebGpuUnavailable),
+    }
+}
+
+/// サーフェスの形式。加算合成をリニアで行うため sRGB を優先する
+/// （WebGL2 でも wgpu が表示時に変換するので sRGB が選べる）
+pub fn preferred_surface_format(available: &[wgpu::TextureFormat]) -> Option<wgpu::TextureFormat> {
+    available.iter().copied().find(|f| f.is_srgb()).or(available.first().copied())
+}
+
+/// 合成アルファ。背景を透かすため乗算済みを優先する。
+/// WebGL2 は Opaque しか公開しないが、コンテキストは既定の alpha: true なので透過はそのまま効く
+pub fn preferred_alpha_mode(available: &[wgpu::CompositeAlphaMode]) -> Option<wgpu::CompositeAlphaMode> {
+    const PREFERRED: [wgpu::CompositeAlphaMode; 3] = [
+        wgpu::CompositeAlphaMode::PreMultiplied,
+        wgpu::CompositeAlphaMode::PostMultiplied,
+        wgpu::CompositeAlphaMode::Inherit,
+    ];
+    PREFERRED.into_iter().find(|mode| available.contains(mode)).or(available.first().copied())
+}
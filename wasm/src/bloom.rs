@@ -0,0 +1,271 @@
+// ブルーム（HDR ターゲット + 縮小/拡大ぼかし + トーンマップ）のGPU部分。
+// シーンは `HDR_FORMAT` のターゲットへ加算合成で描き（重なっても 1 で切れない）、
+// 明部を半分の解像度から縮小段でぼかして、拡大しながら重ね、最後に表示先へトーンマップして書く。
+// キャンバス（wasm）とオフスクリーン（ネイティブ）で共用する。設定は `params::BloomConfig`。
+
+use crate::params::BloomConfig;
+
+pub const BLOOM_SHADER_WGSL: &str = include_str!("bloom_shader.wgsl");
+
+/// シーンを描く HDR ターゲットと縮小段のフォーマット
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// 縮小段の最大数（半分の解像度から数える）
+pub const BLOOM_MAX_MIPS: usize = 6;
+
+/// 縮小段の各サイズ。半分の解像度から始め、短辺が 4 を切るか `BLOOM_MAX_MIPS` 段で止める
+pub fn bloom_mip_sizes(width: u32, height: u32) -> Vec<(u32, u32)> {
+    let (mut w, mut h) = ((width / 2).max(1), (height / 2).max(1));
+    let mut sizes = vec![(w, h)];
+    while sizes.len() < BLOOM_MAX_MIPS && w.min(h) >= 4 {
+        (w, h) = ((w / 2).max(1), (h / 2).max(1));
+        sizes.push((w, h));
+    }
+    sizes
+}
+
+/// HDR ターゲットに使えるか（描画先・サンプル・線形補間・加算合成）。
+/// WebGPU では必ず使えるが、WebGL2 は EXT_color_buffer_float が無いと描画先にできない
+pub fn hdr_target_supported(features: wgpu::TextureFormatFeatures) -> bool {
+    features.allowed_usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+        && features.flags.contains(wgpu::TextureFormatFeatureFlags::FILTERABLE | wgpu::TextureFormatFeatureFlags::BLENDABLE)
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct UBloom {
+    params: [f32; 4], // threshold, intensity, 未使用, 未使用
+}
+
+/// サイズに紐づくテクスチャとバインドグループ（リサイズで作り直す）
+struct BloomTargets {
+    hdr: wgpu::Texture,
+    hdr_view: wgpu::TextureView,
+    mips: Vec<wgpu::Texture>,
+    mip_views: Vec<wgpu::TextureView>,
+    /// [0] は HDR ターゲット、[i + 1] は mips[i] を読むバインドグループ
+    src_binds: Vec<wgpu::BindGroup>,
+    composite_bind: wgpu::BindGroup,
+}
+
+/// HDR ターゲットとブルームのパス一式。`output_format` は最後に書く表示先のフォーマット
+pub struct BloomChain {
+    pipe_prefilter: wgpu::RenderPipeline,
+    pipe_down: wgpu::RenderPipeline,
+    pipe_up: wgpu::RenderPipeline,
+    pipe_composite: wgpu::RenderPipeline,
+    bgl_pass: wgpu::BindGroupLayout,
+    bgl_composite: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    ubo: wgpu::Buffer,
+    targets: BloomTargets,
+    radius: f32,
+    width: u32,
+    height: u32,
+}
+
+fn texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture { sample_type: wgpu::TextureSampleType::Float { filterable: true }, view_dimension: wgpu::TextureViewDimension::D2, multisampled: false },
+        count: None,
+    }
+}
+
+fn hdr_texture(device: &wgpu::Device, label: &str, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: HDR_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
+
+impl BloomChain {
+    pub fn new(device: &wgpu::Device, output_format: wgpu::TextureFormat, width: u32, height: u32) -> BloomChain {
+        let ubo = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("bloom_ubo"),
+            size: std::mem::size_of::<UBloom>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+            count: None,
+        };
+        let sampler_entry = wgpu::BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let bgl_pass = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("bloom_bgl_pass"),
+            entries: &[uniform_entry, texture_entry(1), sampler_entry],
+        });
+        let bgl_composite = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("bloom_bgl_composite"),
+            entries: &[uniform_entry, texture_entry(1), sampler_entry, texture_entry(3)],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("bloom_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("bloom_shader"),
+            source: wgpu::ShaderSource::Wgsl(BLOOM_SHADER_WGSL.into()),
+        });
+        let layout_pass = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("bloom_layout_pass"),
+            bind_group_layouts: &[&bgl_pass],
+            push_constant_ranges: &[],
+        });
+        let layout_composite = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("bloom_layout_composite"),
+            bind_group_layouts: &[&bgl_composite],
+            push_constant_ranges: &[],
+        });
+        // 拡大パスは書き込み先へ src * radius を足す（ブレンド定数 = radius）。粗い段ほど何度も掛かるので radius で広がりが決まる
+        let scatter = wgpu::BlendComponent { src_factor: wgpu::BlendFactor::Constant, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add };
+        let pipeline = |label: &str, layout: &wgpu::PipelineLayout, fs: &str, format: wgpu::TextureFormat, blend: Option<wgpu::BlendState>| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                vertex: wgpu::VertexState { module: &shader, entry_point: Some("vs_fullscreen"), buffers: &[], compilation_options: wgpu::PipelineCompilationOptions::default() },
+                fragment: Some(wgpu::FragmentState { module: &shader, entry_point: Some(fs), targets: &[Some(wgpu::ColorTargetState { format, blend, write_mask: wgpu::ColorWrites::ALL })], compilation_options: wgpu::PipelineCompilationOptions::default() }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let pipe_prefilter = pipeline("bloom_prefilter", &layout_pass, "fs_prefilter", HDR_FORMAT, None);
+        let pipe_down = pipeline("bloom_down", &layout_pass, "fs_downsample", HDR_FORMAT, None);
+        let pipe_up = pipeline("bloom_up", &layout_pass, "fs_upsample", HDR_FORMAT, Some(wgpu::BlendState { color: scatter, alpha: scatter }));
+        let pipe_composite = pipeline("bloom_composite", &layout_composite, "fs_composite", output_format, None);
+
+        let targets = Self::create_targets(device, &bgl_pass, &bgl_composite, &sampler, &ubo, width, height);
+        BloomChain {
+            pipe_prefilter, pipe_down, pipe_up, pipe_composite,
+            bgl_pass, bgl_composite, sampler, ubo, targets,
+            radius: BloomConfig::default().radius,
+            width,
+            height,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        bgl_pass: &wgpu::BindGroupLayout,
+        bgl_composite: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        ubo: &wgpu::Buffer,
+        width: u32,
+        height: u32,
+    ) -> BloomTargets {
+        let hdr = hdr_texture(device, "bloom_hdr", width, height);
+        let hdr_view = hdr.create_view(&wgpu::TextureViewDescriptor::default());
+        let mips: Vec<wgpu::Texture> = bloom_mip_sizes(width, height).into_iter().map(|(w, h)| hdr_texture(device, "bloom_mip", w, h)).collect();
+        let mip_views: Vec<wgpu::TextureView> = mips.iter().map(|t| t.create_view(&wgpu::TextureViewDescriptor::default())).collect();
+        let src_binds = std::iter::once(&hdr_view)
+            .chain(&mip_views)
+            .map(|view| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("bloom_bind_pass"),
+                    layout: bgl_pass,
+                    entries: &[
+                        wgpu::BindGroupEntry { binding: 0, resource: ubo.as_entire_binding() },
+                        wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(view) },
+                        wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+                    ],
+                })
+            })
+            .collect();
+        let composite_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("bloom_bind_composite"),
+            layout: bgl_composite,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: ubo.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&hdr_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&mip_views[0]) },
+            ],
+        });
+        BloomTargets { hdr, hdr_view, mips, mip_views, src_binds, composite_bind }
+    }
+
+    /// シーンの描画先（`HDR_FORMAT`、`size()` と同じサイズ。深度も同じサイズで用意する）
+    pub fn hdr_view(&self) -> &wgpu::TextureView { &self.targets.hdr_view }
+
+    pub fn size(&self) -> (u32, u32) { (self.width, self.height) }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) { return; }
+        let targets = Self::create_targets(device, &self.bgl_pass, &self.bgl_composite, &self.sampler, &self.ubo, width, height);
+        std::mem::replace(&mut self.targets, targets).destroy();
+        (self.width, self.height) = (width, height);
+    }
+
+    /// threshold / intensity を uniform へ書き、radius は次の `encode` のブレンド定数にする。値は丸め済みを想定
+    pub fn write_config(&mut self, queue: &wgpu::Queue, config: &BloomConfig) {
+        let u = UBloom { params: [config.threshold, config.intensity, 0.0, 0.0] };
+        queue.write_buffer(&self.ubo, 0, bytemuck::bytes_of(&u));
+        self.radius = config.radius;
+    }
+
+    /// `hdr_view()` に描いたシーンをぼかして重ね、`output` へトーンマップして書く（`output` は全面を上書き）
+    pub fn encode(&self, enc: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        let t = &self.targets;
+        let pass = |enc: &mut wgpu::CommandEncoder, label: &str, view: &wgpu::TextureView, load: wgpu::LoadOp<wgpu::Color>, pipe: &wgpu::RenderPipeline, bind: &wgpu::BindGroup, blend: Option<f32>| {
+            let mut rp = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment { view, resolve_target: None, ops: wgpu::Operations { load, store: wgpu::StoreOp::Store } })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            rp.set_pipeline(pipe);
+            rp.set_bind_group(0, bind, &[]);
+            if let Some(c) = blend {
+                let c = c as f64;
+                rp.set_blend_constant(wgpu::Color { r: c, g: c, b: c, a: c });
+            }
+            rp.draw(0..3, 0..1);
+        };
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+        // 明部抽出（HDR → 半分の解像度）と縮小
+        pass(enc, "rpass_bloom_prefilter", &t.mip_views[0], clear, &self.pipe_prefilter, &t.src_binds[0], None);
+        for i in 1..t.mip_views.len() {
+            pass(enc, "rpass_bloom_down", &t.mip_views[i], clear, &self.pipe_down, &t.src_binds[i], None);
+        }
+        // 小さい段から拡大して一つ上の段へ重ねる
+        for i in (0..t.mip_views.len() - 1).rev() {
+            pass(enc, "rpass_bloom_up", &t.mip_views[i], wgpu::LoadOp::Load, &self.pipe_up, &t.src_binds[i + 2], Some(self.radius));
+        }
+        pass(enc, "rpass_bloom_composite", output, clear, &self.pipe_composite, &t.composite_bind, None);
+    }
+
+    /// テクスチャとバッファを GC を待たずに解放する
+    pub fn destroy(&self) {
+        self.targets.destroy();
+        self.ubo.destroy();
+    }
+}
+
+impl BloomTargets {
+    fn destroy(&self) {
+        self.hdr.destroy();
+        for mip in &self.mips { mip.destroy(); }
+    }
+}
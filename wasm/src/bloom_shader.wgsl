@@ -0,0 +1,100 @@
+// ブルーム: HDR ターゲットの明部抽出 → 縮小段でぼかす → 拡大しながら重ねる → 合成してトーンマップ。
+// どのパスも全画面三角形1枚（頂点バッファなし）。式は shader_math.rs の bloom_bright_pass / aces_tonemap と同じ
+
+struct UBloom {
+  params: vec4<f32>, // threshold, intensity, 未使用, 未使用
+};
+@group(0) @binding(0) var<uniform> u: UBloom;
+@group(0) @binding(1) var src_tex: texture_2d<f32>;
+@group(0) @binding(2) var src_samp: sampler;
+@group(0) @binding(3) var bloom_tex: texture_2d<f32>; // fs_composite のみ
+
+struct VOut {
+  @builtin(position) pos: vec4<f32>,
+  @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vi: u32) -> VOut {
+  // (0,0) (2,0) (0,2) の三角形で画面全体を覆う
+  let xy = vec2<f32>(f32((vi << 1u) & 2u), f32(vi & 2u));
+  var out: VOut;
+  out.pos = vec4<f32>(xy * 2.0 - 1.0, 0.0, 1.0);
+  out.uv = vec2<f32>(xy.x, 1.0 - xy.y);
+  return out;
+}
+
+fn texel() -> vec2<f32> {
+  return 1.0 / vec2<f32>(textureDimensions(src_tex));
+}
+
+// 縮小: 1テクセルずらした4点（各点は線形補間で2x2の平均）= 4x4 の平均
+fn box4(uv: vec2<f32>) -> vec3<f32> {
+  let t = texel();
+  let a = textureSample(src_tex, src_samp, uv + vec2<f32>(-t.x, -t.y)).rgb;
+  let b = textureSample(src_tex, src_samp, uv + vec2<f32>( t.x, -t.y)).rgb;
+  let c = textureSample(src_tex, src_samp, uv + vec2<f32>(-t.x,  t.y)).rgb;
+  let d = textureSample(src_tex, src_samp, uv + vec2<f32>( t.x,  t.y)).rgb;
+  return (a + b + c + d) * 0.25;
+}
+
+fn bright_pass(col: vec3<f32>, threshold: f32) -> vec3<f32> {
+  let br = max(col.r, max(col.g, col.b));
+  let knee = threshold * 0.5;
+  var soft = clamp(br - threshold + knee, 0.0, 2.0 * knee);
+  soft = soft * soft / (4.0 * knee + 1e-4);
+  let contrib = max(soft, br - threshold) / max(br, 1e-4);
+  return col * contrib;
+}
+
+fn aces(col: vec3<f32>) -> vec3<f32> {
+  let x = max(col, vec3<f32>(0.0));
+  return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn bright_tap(uv: vec2<f32>) -> vec3<f32> {
+  return bright_pass(textureSample(src_tex, src_samp, uv).rgb, u.params.x);
+}
+
+// 明部抽出は box4 と同じ4点で、平均する前に各点へ掛ける（細い線の明るさが平均で薄まらないように）
+@fragment
+fn fs_prefilter(inp: VOut) -> @location(0) vec4<f32> {
+  let t = texel();
+  let a = bright_tap(inp.uv + vec2<f32>(-t.x, -t.y));
+  let b = bright_tap(inp.uv + vec2<f32>( t.x, -t.y));
+  let c = bright_tap(inp.uv + vec2<f32>(-t.x,  t.y));
+  let d = bright_tap(inp.uv + vec2<f32>( t.x,  t.y));
+  return vec4<f32>((a + b + c + d) * 0.25, 1.0);
+}
+
+@fragment
+fn fs_downsample(inp: VOut) -> @location(0) vec4<f32> {
+  return vec4<f32>(box4(inp.uv), 1.0);
+}
+
+// 拡大: 3x3 のテントフィルタ。書き込み先との混ぜ具合（radius）はブレンド定数で決める
+@fragment
+fn fs_upsample(inp: VOut) -> @location(0) vec4<f32> {
+  let t = texel();
+  var sum = textureSample(src_tex, src_samp, inp.uv).rgb * 4.0;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>(-t.x, 0.0)).rgb * 2.0;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>( t.x, 0.0)).rgb * 2.0;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>(0.0, -t.y)).rgb * 2.0;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>(0.0,  t.y)).rgb * 2.0;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>(-t.x, -t.y)).rgb;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>( t.x, -t.y)).rgb;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>(-t.x,  t.y)).rgb;
+  sum += textureSample(src_tex, src_samp, inp.uv + vec2<f32>( t.x,  t.y)).rgb;
+  return vec4<f32>(sum / 16.0, 1.0);
+}
+
+// 合成: シーン + にじみ をトーンマップで 0..1 へ。
+// 透過の背景ではにじみの明るさをアルファにする（乗算済みアルファとして rgb <= a を保つ）
+@fragment
+fn fs_composite(inp: VOut) -> @location(0) vec4<f32> {
+  let scene = textureSample(src_tex, src_samp, inp.uv);
+  let glow = textureSample(bloom_tex, src_samp, inp.uv).rgb;
+  let col = aces(scene.rgb + glow * u.params.y);
+  let a = max(clamp(scene.a, 0.0, 1.0), max(col.r, max(col.g, col.b)));
+  return vec4<f32>(col, a);
+}
@@ -0,0 +1,340 @@
+// 操作可能なオービットカメラ（ドラッグ回転・ホイール/ピンチのズーム・パン・慣性・自動回転）
+//
+// イベント処理は `Gestures` が画素単位の差分に集約し、`OrbitCamera::update` がフレーム毎に
+// 反映する。web-sys に依存しないのでホスト側でテストできる。
+
+use glam::{Mat4, Vec3};
+
+use crate::easing::Easing;
+
+/// 1ピクセルのドラッグで回る角度（rad）
+pub const ROTATE_RAD_PER_PX: f32 = 0.005;
+/// ホイール1ピクセル分のズーム（対数）。`deltaY = 100` で約 1.1 倍
+pub const WHEEL_ZOOM_PER_PX: f32 = 0.001;
+/// これ以上動いたらクリックではなくドラッグとみなす（ピクセル）
+pub const DRAG_THRESHOLD_PX: f32 = 4.0;
+
+/// 1フレーム分の入力（ピクセル/対数ズーム）
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CameraDelta {
+    pub rotate: [f32; 2],
+    pub pan: [f32; 2],
+    /// 距離に掛ける倍率の対数（正: 遠ざかる）
+    pub zoom: f32,
+}
+
+impl CameraDelta {
+    pub fn is_zero(&self) -> bool { *self == Self::default() }
+}
+
+/// ポインタ/ホイールイベントを `CameraDelta` に集約する。
+/// 1本指（左ボタン）は回転、右ボタン/Shift付きはパン、2本指はピンチズーム＋パン。
+#[derive(Debug, Default)]
+pub struct Gestures {
+    pointers: Vec<(i32, [f32; 2])>,
+    pan_mode: bool,
+    travel: f32,
+    pending: CameraDelta,
+}
+
+impl Gestures {
+    pub fn pointer_down(&mut self, id: i32, x: f32, y: f32, pan: bool) {
+        if self.pointers.is_empty() {
+            self.pan_mode = pan;
+            self.travel = 0.0;
+        }
+        self.pointers.retain(|&(p, _)| p != id);
+        if self.pointers.len() < 2 {
+            self.pointers.push((id, [x, y]));
+        }
+    }
+
+    pub fn pointer_move(&mut self, id: i32, x: f32, y: f32) {
+        let Some(slot) = self.pointers.iter().position(|&(p, _)| p == id) else { return };
+        let old = self.pointers[slot].1;
+        let (dx, dy) = (x - old[0], y - old[1]);
+        if self.pointers.len() == 2 {
+            let other = self.pointers[1 - slot].1;
+            let old_d = dist(old, other);
+            let new_d = dist([x, y], other);
+            if old_d > 1.0 && new_d > 1.0 {
+                self.pending.zoom += (old_d / new_d).ln();
+            }
+            // 中点の移動量 = 動いた指の半分
+            self.pending.pan[0] += dx * 0.5;
+            self.pending.pan[1] += dy * 0.5;
+        } else if self.pan_mode {
+            self.pending.pan[0] += dx;
+            self.pending.pan[1] += dy;
+        } else {
+            self.pending.rotate[0] += dx;
+            self.pending.rotate[1] += dy;
+        }
+        self.travel += (dx * dx + dy * dy).sqrt();
+        self.pointers[slot].1 = [x, y];
+    }
+
+    pub fn pointer_up(&mut self, id: i32) {
+        self.pointers.retain(|&(p, _)| p != id);
+    }
+
+    pub fn wheel(&mut self, delta_y_px: f32) {
+        self.pending.zoom += delta_y_px * WHEEL_ZOOM_PER_PX;
+    }
+
+    /// 指/ボタンが押されたままか（押している間は慣性を効かせない）
+    pub fn is_held(&self) -> bool { !self.pointers.is_empty() }
+
+    /// 直近（または進行中）の操作がドラッグだったか。クリック判定の抑制に使う。
+    pub fn was_drag(&self) -> bool { self.travel > DRAG_THRESHOLD_PX }
+
+    pub fn take(&mut self) -> CameraDelta { std::mem::take(&mut self.pending) }
+}
+
+fn dist(a: [f32; 2], b: [f32; 2]) -> f32 { ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt() }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraLimits {
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// 注視点の原点からの最大距離
+    pub max_target_offset: f32,
+}
+
+impl Default for CameraLimits {
+    fn default() -> Self {
+        Self { min_distance: 1.2, max_distance: 12.0, min_pitch: -1.4, max_pitch: 1.4, max_target_offset: 3.0 }
+    }
+}
+
+/// カメラの姿勢（飛行の始点/終点）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub target: [f32; 3],
+}
+
+/// 飛行中の姿勢（`e` はイージング後の係数 0..1）。向きは近い回りで、距離は比で補間する（ドリーが一定の速さに見える）
+pub fn interpolate_pose(from: CameraPose, to: CameraPose, e: f32) -> CameraPose {
+    let tau = std::f32::consts::TAU;
+    let d_yaw = (to.yaw - from.yaw + std::f32::consts::PI).rem_euclid(tau) - std::f32::consts::PI;
+    CameraPose {
+        yaw: (from.yaw + d_yaw * e).rem_euclid(tau),
+        pitch: from.pitch + (to.pitch - from.pitch) * e,
+        distance: from.distance * (to.distance / from.distance.max(1e-6)).powf(e),
+        target: Vec3::from(from.target).lerp(Vec3::from(to.target), e).to_array(),
+    }
+}
+
+/// 注視点へのフォーカス移動の途中経過
+#[derive(Debug, Clone, PartialEq)]
+struct Flight {
+    from: CameraPose,
+    to: CameraPose,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+/// フレーミング時に点群の外側へ残す余白（半径に対する倍率）
+const FRAMING_MARGIN: f32 = 1.15;
+
+/// 注視点まわりを回るカメラ。既定値は従来の固定カメラ（半径3.0・高さ0.9・FOV45°）と同じ視点。
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub target: [f32; 3],
+    pub fov_y: f32,
+    /// 放置時の自動回転速度（rad/s）
+    pub auto_rotate_speed: f32,
+    /// 最後の操作からこの秒数が経つと自動回転を再開する
+    pub resume_after_s: f32,
+    /// 慣性の減衰率（1/s）。大きいほど早く止まる
+    pub damping: f32,
+    pub limits: CameraLimits,
+    velocity: [f32; 2], // (yaw, pitch) rad/s
+    idle_s: f32,
+    flight: Option<Flight>,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.9f32.atan2(3.0),
+            distance: (3.0f32 * 3.0 + 0.9 * 0.9).sqrt(),
+            target: [0.0; 3],
+            fov_y: 45.0f32.to_radians(),
+            auto_rotate_speed: 0.0,
+            resume_after_s: 3.0,
+            damping: 4.0,
+            limits: CameraLimits::default(),
+            velocity: [0.0; 2],
+            // 起動直後は自動回転から始める
+            idle_s: f32::INFINITY,
+            flight: None,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn eye(&self) -> [f32; 3] {
+        let dir = Vec3::new(self.pitch.cos() * self.yaw.cos(), self.pitch.sin(), self.pitch.cos() * self.yaw.sin());
+        (Vec3::from(self.target) + dir * self.distance).to_array()
+    }
+
+    pub fn view_proj(&self, aspect: f32) -> [[f32; 4]; 4] {
+        let view = Mat4::look_at_rh(Vec3::from(self.eye()), Vec3::from(self.target), Vec3::Y);
+        let proj = Mat4::perspective_rh(self.fov_y, aspect.max(0.1), 0.1, 100.0);
+        (proj * view).to_cols_array_2d()
+    }
+
+    pub fn is_idle(&self) -> bool { self.idle_s >= self.resume_after_s }
+
+    /// 慣性と飛行を止め、次のフレームから自動回転させる
+    pub fn stop(&mut self) {
+        self.velocity = [0.0; 2];
+        self.idle_s = f32::INFINITY;
+        self.flight = None;
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        CameraPose { yaw: self.yaw, pitch: self.pitch, distance: self.distance, target: self.target }
+    }
+
+    /// 既定の視点（従来の固定カメラ）
+    pub fn home_pose() -> CameraPose { Self::default().pose() }
+
+    fn set_pose(&mut self, pose: CameraPose) {
+        self.yaw = pose.yaw;
+        self.pitch = pose.pitch;
+        self.distance = pose.distance;
+        self.target = pose.target;
+    }
+
+    /// 視点（向き・距離・注視点）だけ既定に戻す。速度や再開時間などの設定は保つ。
+    pub fn reset_view(&mut self) {
+        self.set_pose(Self::home_pose());
+        self.stop();
+    }
+
+    /// `pose` へ `duration_s` 秒かけて移動する。移動中は自動回転・慣性を止め、ユーザー操作があれば中断する。
+    /// 到着後は通常の待機と同じく `resume_after_s` 秒で自動回転（新しい注視点まわり）に戻る。
+    pub fn fly_to(&mut self, pose: CameraPose, duration_s: f32, easing: Easing) {
+        self.velocity = [0.0; 2];
+        self.idle_s = 0.0;
+        let mut to = pose;
+        to.yaw = to.yaw.rem_euclid(std::f32::consts::TAU);
+        if duration_s <= 0.0 {
+            self.flight = None;
+            self.set_pose(to);
+            self.clamp();
+        } else {
+            self.flight = Some(Flight { from: self.pose(), to, elapsed: 0.0, duration: duration_s, easing });
+        }
+    }
+
+    pub fn is_flying(&self) -> bool { self.flight.is_some() }
+
+    /// 外部（タイムライン）が決めた姿勢をそのまま表示する。慣性・飛行は止め、操作が止んだ扱いにする
+    pub fn show_pose(&mut self, pose: CameraPose) {
+        self.velocity = [0.0; 2];
+        self.flight = None;
+        self.idle_s = 0.0;
+        self.set_pose(pose);
+        self.clamp();
+    }
+
+    /// `focus` を注視し、`points` が全て画面に収まる距離の姿勢。向き（yaw/pitch）は現在のまま。
+    /// 縦長の画面では水平方向の画角で決める。距離・注視点は制限内に丸める。
+    pub fn framing_pose(&self, focus: [f32; 3], points: &[[f32; 3]], aspect: f32) -> CameraPose {
+        let f = Vec3::from(focus);
+        let radius = points.iter().map(|&p| Vec3::from(p).distance(f)).fold(0.0f32, f32::max);
+        let half_v = 0.5 * self.fov_y;
+        let half_h = (half_v.tan() * aspect.max(0.1)).atan();
+        let half = half_v.min(half_h);
+        let l = &self.limits;
+        let distance = (radius * FRAMING_MARGIN / half.sin()).clamp(l.min_distance, l.max_distance);
+        let target = if f.length() > l.max_target_offset { f.normalize() * l.max_target_offset } else { f };
+        CameraPose { yaw: self.yaw, pitch: self.pitch, distance, target: target.to_array() }
+    }
+
+    /// 1フレーム分の入力を反映する。`held` は指/ボタンが押されたままか、`viewport_h` はパン換算用の表示高さ（px）。
+    pub fn update(&mut self, input: CameraDelta, held: bool, dt: f32, viewport_h: f32) {
+        let dt = dt.max(0.0);
+        if held || !input.is_zero() {
+            self.flight = None;
+        }
+        if let Some(f) = self.flight.as_mut() {
+            f.elapsed += dt;
+            let e = f.easing.apply(f.elapsed / f.duration);
+            let (from, to) = (f.from, f.to);
+            let done = f.elapsed >= f.duration;
+            self.set_pose(interpolate_pose(from, to, e));
+            self.idle_s = 0.0;
+            if done {
+                self.flight = None;
+                self.set_pose(to); // 丸め誤差を残さない
+            }
+            self.clamp();
+            return;
+        }
+        if held || !input.is_zero() {
+            self.idle_s = 0.0;
+        } else {
+            self.idle_s += dt;
+        }
+
+        let d_yaw = input.rotate[0] * ROTATE_RAD_PER_PX;
+        let d_pitch = input.rotate[1] * ROTATE_RAD_PER_PX;
+        if held {
+            // 押している間は指に追従し、離した瞬間の速度を慣性として残す
+            self.yaw += d_yaw;
+            self.pitch += d_pitch;
+            self.velocity = if dt > 0.0 { [d_yaw / dt, d_pitch / dt] } else { [0.0; 2] };
+        } else {
+            self.yaw += d_yaw + self.velocity[0] * dt;
+            self.pitch += d_pitch + self.velocity[1] * dt;
+            let decay = (-self.damping * dt).exp();
+            self.velocity = self.velocity.map(|v| if (v * decay).abs() < 1e-3 { 0.0 } else { v * decay });
+        }
+        if self.is_idle() {
+            // 再開直後の1秒で滑らかに加速する
+            let ramp = ((self.idle_s - self.resume_after_s) / 1.0).clamp(0.0, 1.0);
+            let ramp = ramp * ramp * (3.0 - 2.0 * ramp);
+            self.yaw += self.auto_rotate_speed * ramp * dt;
+        }
+        self.yaw = self.yaw.rem_euclid(std::f32::consts::TAU);
+
+        self.distance *= input.zoom.exp();
+
+        if input.pan != [0.0; 2] {
+            // 画面上で指に追従する量: 注視点の距離での視野の高さ / 表示高さ
+            let world_per_px = 2.0 * self.distance * (0.5 * self.fov_y).tan() / viewport_h.max(1.0);
+            let eye = Vec3::from(self.eye());
+            let forward = (Vec3::from(self.target) - eye).normalize_or_zero();
+            let right = forward.cross(Vec3::Y).normalize_or_zero();
+            let up = right.cross(forward);
+            let shift = (-right * input.pan[0] + up * input.pan[1]) * world_per_px;
+            self.target = (Vec3::from(self.target) + shift).to_array();
+        }
+        self.clamp();
+    }
+
+    fn clamp(&mut self) {
+        let l = &self.limits;
+        self.pitch = self.pitch.clamp(l.min_pitch, l.max_pitch);
+        if self.pitch == l.min_pitch || self.pitch == l.max_pitch { self.velocity[1] = 0.0; }
+        self.distance = self.distance.clamp(l.min_distance, l.max_distance);
+        let t = Vec3::from(self.target);
+        if t.length() > l.max_target_offset {
+            self.target = (t.normalize() * l.max_target_offset).to_array();
+        }
+    }
+}
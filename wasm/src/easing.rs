@@ -0,0 +1,84 @@
+// 補間用のイージング関数（カメラ移動などの時間→進捗の変換）
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    #[default]
+    EaseInOutCubic,
+    EaseInOutSine,
+    EaseOutExpo,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 9] = [
+        Self::Linear, Self::EaseInQuad, Self::EaseOutQuad, Self::EaseInOutQuad,
+        Self::EaseInCubic, Self::EaseOutCubic, Self::EaseInOutCubic, Self::EaseInOutSine, Self::EaseOutExpo,
+    ];
+
+    /// 進捗 `t`（0..1 に丸める）→ 補間係数。両端は必ず 0 と 1。
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseInQuad => t * t,
+            Self::EaseOutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::EaseInOutQuad => if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) * 0.5 },
+            Self::EaseInCubic => t * t * t,
+            Self::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOutCubic => if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) * 0.5 },
+            Self::EaseInOutSine => 0.5 - 0.5 * (std::f32::consts::PI * t).cos(),
+            Self::EaseOutExpo => if t >= 1.0 { 1.0 } else { 1.0 - 2f32.powf(-10.0 * t) },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::EaseInQuad => "ease-in-quad",
+            Self::EaseOutQuad => "ease-out-quad",
+            Self::EaseInOutQuad => "ease-in-out-quad",
+            Self::EaseInCubic => "ease-in-cubic",
+            Self::EaseOutCubic => "ease-out-cubic",
+            Self::EaseInOutCubic => "ease-in-out-cubic",
+            Self::EaseInOutSine => "ease-in-out-sine",
+            Self::EaseOutExpo => "ease-out-expo",
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEasing(pub String);
+
+impl fmt::Display for UnknownEasing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Easing::ALL.iter().map(|e| e.name()).collect();
+        write!(f, "unknown easing \"{}\" (available: {})", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownEasing {}
+
+/// 空文字は既定（ease-in-out-cubic）
+impl FromStr for Easing {
+    type Err = UnknownEasing;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() { return Ok(Self::default()); }
+        Self::ALL.into_iter().find(|e| e.name() == s).ok_or_else(|| UnknownEasing(s.to_string()))
+    }
+}
@@ -15,24 +15,11 @@ pub struct Edge {
     pub kind: EdgeKind,
 }
 
-// シンプルなLCG（依存ライブラリなし、決定論的）
-#[derive(Clone)]
-struct Lcg(u64);
-impl Lcg {
-    fn new(seed: u64) -> Self { Self(seed | 1) }
-    fn next_u32(&mut self) -> u32 {
-        // Numerical Recipes系 LCG
-        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
-        (self.0 >> 32) as u32
-    }
-    fn next_f32(&mut self) -> f32 { (self.next_u32() as f32) / (u32::MAX as f32) }
-    fn range_f32(&mut self, lo: f32, hi: f32) -> f32 { lo + (hi - lo) * self.next_f32() }
-    fn pick_usize(&mut self, n: usize) -> usize { (self.next_u32() as usize) % n.max(1) }
-}
+use crate::rng::Lcg;
 
 pub fn generate(seed: u64, num_nodes: usize, spine_segments: usize) -> (Vec<Node>, Vec<Edge>) {
     let n = num_nodes.max(4);
-    let spine_n = spine_segments.max(2) + 1; // ノード数 = セグメント+1
+    let spine_n = spine_segments.max(2).saturating_add(1); // ノード数 = セグメント+1
     let spine_n = spine_n.min(n);
     let mut rng = Lcg::new(seed);
 
@@ -54,7 +41,7 @@ pub fn generate(seed: u64, num_nodes: usize, spine_segments: usize) -> (Vec<Node
     for i in spine_n..n {
         // 放射状に中心近辺へ。半径は0.15..0.85、角度は全域。
         let r = rng.range_f32(0.15, 0.85);
-        let th = rng.range_f32(-3.14159, 3.14159);
+        let th = rng.range_f32(-std::f32::consts::PI, std::f32::consts::PI);
         let x = r * th.cos() * 0.8; // 横は少し抑える
         let y = r * th.sin() * 0.9;
         nodes.push(Node { id: i as u32, pos: [x, y], level: 1 });
@@ -117,7 +104,23 @@ pub fn theme_edge_color(kind: EdgeKind3) -> [f32; 4] {
 
 /// ノード色: ハブ(i%23==0)→マゼンタ、通常→シアン
 pub fn theme_node_color(index: usize) -> [f32; 4] {
-    if index % 23 == 0 { palette_color(Palette::Magenta) } else { palette_color(Palette::Cyan) }
+    if index.is_multiple_of(23) { palette_color(Palette::Magenta) } else { palette_color(Palette::Cyan) }
+}
+
+/// ノードサイズ倍率: たまに大きいハブ(i%29==0)→1.6、通常→1.0
+pub fn theme_node_size(index: usize) -> f32 {
+    if index.is_multiple_of(29) { 1.6 } else { 1.0 }
+}
+
+/// カテゴリ付きノードの色: パレットを循環して割り当てる
+pub fn theme_category_color(category: u32) -> [f32; 4] {
+    const ORDER: [Palette; 5] = [Palette::Cyan, Palette::Magenta, Palette::Sky, Palette::Purple, Palette::Green];
+    palette_color(ORDER[category as usize % ORDER.len()])
+}
+
+/// エッジ重み→太さ倍率（重み1.0で等倍、極端な値は抑える）
+pub fn edge_weight_scale(weight: f32) -> f32 {
+    weight.clamp(0.25, 4.0).sqrt()
 }
 
 /// Graph用のキャンバスクリアカラー（sRGB空間、RGB）
@@ -1,4 +1,11 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
+
+pub mod adjacency;
+pub mod force;
+pub mod spatial;
+
+use spatial::KdTree;
+use crate::rng::Lcg;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Node3 {
@@ -12,18 +19,6 @@ pub enum EdgeKind3 { Mesh, Extra }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Edge3 { pub a: u32, pub b: u32, pub kind: EdgeKind3 }
 
-// Simple RNG (LCG) for determinism.
-#[derive(Clone)]
-struct Lcg(u64);
-impl Lcg {
-    fn new(seed: u64) -> Self { Self(seed | 1) }
-    fn next_u32(&mut self) -> u32 {
-        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
-        (self.0 >> 32) as u32
-    }
-    fn next_f32(&mut self) -> f32 { (self.next_u32() as f32) / (u32::MAX as f32) }
-}
-
 fn fib_sphere_points(n: usize) -> Vec<[f32; 3]> {
     // Even-ish distribution on unit sphere using Fibonacci spiral
     // https://stackoverflow.com/a/26127012
@@ -53,15 +48,14 @@ pub fn generate_sphere(seed: u64, num_nodes: usize, neighbors: usize, extra_rati
         .map(|(i,p)| Node3 { id: i as u32, pos: p })
         .collect();
 
-    // Build neighbor edges by k-NN (brute-force)
-    let k = neighbors.max(1).min(12);
-    let mut set: HashSet<(u32,u32)> = HashSet::new();
-    for i in 0..nodes.len() {
+    // Build neighbor edges by k-NN (k-d tree)
+    let k = neighbors.clamp(1, 12);
+    let pts: Vec<[f32;3]> = nodes.iter().map(|n| n.pos).collect();
+    let tree = KdTree::from_subset(&pts, 0..pts.len());
+    let mut set: BTreeSet<(u32,u32)> = BTreeSet::new();
+    for (i, &p) in pts.iter().enumerate() {
         // find k nearest distinct j
-        let mut d: Vec<(f32, usize)> = (0..nodes.len()).filter(|&j| j!=i)
-            .map(|j| { let a = nodes[i].pos; let b = nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; ((dx*dx+dy*dy+dz*dz), j) }).collect();
-        d.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for m in 0..k { let j = d[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); set.insert(key); }
+        for (_, j) in tree.nearest(p, k, Some(i)) { set.insert(edge_key(i, j)); }
     }
 
     // extra cross edges
@@ -76,7 +70,7 @@ pub fn generate_sphere(seed: u64, num_nodes: usize, neighbors: usize, extra_rati
     }
 
     let mut edges: Vec<Edge3> = Vec::with_capacity(set.len());
-    for (a,b) in set.into_iter() { edges.push(Edge3 { a, b, kind: EdgeKind3::Mesh }); }
+    for (a,b) in set { edges.push(Edge3 { a, b, kind: EdgeKind3::Mesh }); }
     (nodes, edges)
 }
 
@@ -108,28 +102,25 @@ pub fn generate_cloud(
     }
 
     // k近傍 + ハブ増線 + ランダム長距離
-    let k = neighbors.max(1).min(12);
-    use std::collections::HashSet;
-    let mut set: HashSet<(u32,u32)> = HashSet::new();
+    let k = neighbors.clamp(1, 12);
+    let pts: Vec<[f32;3]> = nodes.iter().map(|n| n.pos).collect();
+    let tree = KdTree::from_subset(&pts, 0..n);
+    let mut set: BTreeSet<(u32,u32)> = BTreeSet::new();
     // k-NN 全体
-    for i in 0..n {
-        let mut d: Vec<(f32, usize)> = (0..n).filter(|&j| j!=i)
-            .map(|j| { let a=nodes[i].pos; let b=nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) }).collect();
-        d.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for m in 0..k.min(d.len()) { let j=d[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); set.insert(key); }
+    for (i, &p) in pts.iter().enumerate() {
+        for (_, j) in tree.nearest(p, k, Some(i)) { set.insert(edge_key(i, j)); }
     }
 
     // ハブ: 近傍を追加
     let hubs = ((n as f32)*hub_ratio).round().max(1.0) as usize;
-    for _ in 0..hubs { let i=(rng.next_u32() as usize)%n; let mut d: Vec<(f32, usize)>=(0..n).filter(|&j| j!=i)
-            .map(|j| { let a=nodes[i].pos; let b=nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) }).collect();
-        d.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for m in 0..(k+4).min(d.len()) { let j=d[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); set.insert(key); }
+    for _ in 0..hubs {
+        let i = (rng.next_u32() as usize)%n;
+        for (_, j) in tree.nearest(pts[i], k+4, Some(i)) { set.insert(edge_key(i, j)); }
     }
 
     // ランダム長距離: Extra として区別
     let extra = ((n as f32)*extra_ratio).round() as usize;
-    let mut extra_edges: HashSet<(u32,u32)> = HashSet::new();
+    let mut extra_edges: BTreeSet<(u32,u32)> = BTreeSet::new();
     for _ in 0..extra { let a=(rng.next_u32() as usize)%n; let mut b=(rng.next_u32() as usize)%n; if a==b{b=(b+1)%n;} let (a,b)=(a.min(b) as u32, a.max(b) as u32); extra_edges.insert((a,b)); }
 
     let mut edges: Vec<Edge3> = Vec::with_capacity(set.len() + extra_edges.len());
@@ -138,6 +129,7 @@ pub fn generate_cloud(
     (nodes, edges)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_shells(
     seed: u64,
     num_nodes: usize,
@@ -161,10 +153,10 @@ pub fn generate_shells(
     let mut rng = Lcg::new(seed);
     let mut layer_of = vec![0usize; n];
     let mut per_layer = vec![0usize; radii.len()];
-    for i in 0..n {
+    for slot in layer_of.iter_mut() {
         let r = rng.next_f32();
         let mut li = 0usize; while li+1<acc.len() && r>acc[li] { li+=1; }
-        layer_of[i] = li; per_layer[li]+=1;
+        *slot = li; per_layer[li]+=1;
     }
 
     // レイヤ毎にFibonacci分布
@@ -178,8 +170,8 @@ pub fn generate_shells(
     // ノード配置（レイヤ内の循環割当＋微小ジッタ）
     let mut idx_in_layer = vec![0usize; radii.len()];
     let mut nodes: Vec<Node3> = Vec::with_capacity(n);
-    for i in 0..n {
-        let li = layer_of[i]; let k = idx_in_layer[li]; idx_in_layer[li]+=1; let mut p = base_points[li][k % base_points[li].len()];
+    for (i, &li) in layer_of.iter().enumerate() {
+        let k = idx_in_layer[li]; idx_in_layer[li]+=1; let mut p = base_points[li][k % base_points[li].len()];
         // 微小ジッタ
         let jx = (rng.next_f32() - 0.5) * (radii[li]*0.03);
         let jy = (rng.next_f32() - 0.5) * (radii[li]*0.03);
@@ -188,20 +180,16 @@ pub fn generate_shells(
         nodes.push(Node3 { id: i as u32, pos: p });
     }
 
-    // 近傍探索（総当たり）
-    let mut edges_intra: HashSet<(u32,u32)> = HashSet::new();
-    let mut edges_cross: HashSet<(u32,u32)> = HashSet::new();
-    let k = k_intra.max(1).min(12);
-    for li in 0..radii.len() {
-        // 当該レイヤのインデックス集合
-        let idxs: Vec<usize> = (0..n).filter(|&i| layer_of[i]==li).collect();
-        for &i in &idxs {
-            let mut d: Vec<(f32, usize)> = idxs.iter().copied().filter(|&j| j!=i)
-                .map(|j| { let a=nodes[i].pos; let b=nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) })
-                .collect();
-            d.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-            for m in 0..d.len().min(k) { let j = d[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); edges_intra.insert(key); }
-        }
+    // 近傍探索（レイヤ毎の k-d tree）
+    let pts: Vec<[f32;3]> = nodes.iter().map(|n| n.pos).collect();
+    let trees: Vec<KdTree> = (0..radii.len())
+        .map(|li| KdTree::from_subset(&pts, (0..n).filter(|&i| layer_of[i]==li)))
+        .collect();
+    let mut edges_intra: BTreeSet<(u32,u32)> = BTreeSet::new();
+    let mut edges_cross: BTreeSet<(u32,u32)> = BTreeSet::new();
+    let k = k_intra.clamp(1, 12);
+    for i in 0..n {
+        for (_, j) in trees[layer_of[i]].nearest(pts[i], k, Some(i)) { edges_intra.insert(edge_key(i, j)); }
     }
 
     // 隣接レイヤへの接続
@@ -211,23 +199,15 @@ pub fn generate_shells(
         for dir in [-1isize, 1] {
             let lj = li + dir; if lj<0 || lj>=radii.len() as isize { continue; }
             // 近い順にca本
-            let mut cand: Vec<(f32, usize)> = (0..n).filter(|&j| layer_of[j]==lj as usize && j!=i)
-                .map(|j| { let a=nodes[i].pos; let b=nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) })
-                .collect();
-            cand.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-            for m in 0..cand.len().min(ca) { let j=cand[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); edges_cross.insert(key); }
+            for (_, j) in trees[lj as usize].nearest(pts[i], ca, Some(i)) { edges_cross.insert(edge_key(i, j)); }
         }
     }
 
     // ハブ接続（各ハブから近傍を追加）
     let hubs = ((n as f32)*hub_ratio).round().max(1.0) as usize;
     for _ in 0..hubs {
-        let i = (rng.next_u32() as usize) % n; let li = layer_of[i];
-        let mut d: Vec<(f32, usize)> = (0..n).filter(|&j| layer_of[j]==li && j!=i)
-            .map(|j| { let a=nodes[i].pos; let b=nodes[j].pos; let dx=a[0]-b[0]; let dy=a[1]-b[1]; let dz=a[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) })
-            .collect();
-        d.sort_by(|a,b| a.0.partial_cmp(&b.0).unwrap());
-        for m in 0..d.len().min(k+4) { let j=d[m].1 as u32; let a=i as u32; let b=j; let key=(a.min(b), a.max(b)); edges_intra.insert(key); }
+        let i = (rng.next_u32() as usize) % n;
+        for (_, j) in trees[layer_of[i]].nearest(pts[i], k+4, Some(i)) { edges_intra.insert(edge_key(i, j)); }
     }
 
     // ランダム長距離
@@ -240,6 +220,22 @@ pub fn generate_shells(
     (nodes, edges)
 }
 
+/// 無向エッジのキー（小さい方を先に）
+fn edge_key(a: usize, b: usize) -> (u32, u32) {
+    (a.min(b) as u32, a.max(b) as u32)
+}
+
+/// 全ペア表示できるノード数の上限（1000 ノードで約50万エッジ。これを超えるとインスタンスが数百MBになる）
+pub const ALLPAIRS_MAX_NODES: usize = 1_000;
+
+/// 全ペア表示できるノード数か。上限を超える時は理由（設定・タイムラインのエラー文）を返す
+pub fn check_allpairs(node_count: usize) -> Result<(), String> {
+    if node_count > ALLPAIRS_MAX_NODES {
+        return Err(format!("all-pairs mode supports up to {ALLPAIRS_MAX_NODES} nodes (graph has {node_count})"));
+    }
+    Ok(())
+}
+
 /// 全ノード対全ノードの無向エッジ集合（a<b, kind=Mesh）を生成する。
 pub fn build_all_pairs_edges(nodes: &[Node3]) -> Vec<Edge3> {
     let n = nodes.len() as u32;
@@ -254,3 +250,9 @@ pub fn build_nucleus_segments(nodes: &[Node3], center: [f32;3]) -> Vec<([f32;3],
     for n in nodes { segs.push((center, n.pos)); }
     segs
 }
+
+/// `seeds` から `k` ホップ以内のノード（seeds を含む、インデックス昇順）。エッジは無向として辿る。
+pub fn k_hop_neighbourhood(node_count: usize, edges: &[Edge3], seeds: &[usize], k: usize) -> Vec<usize> {
+    let hops = adjacency::Adjacency::new(node_count, edges).hop_distances(seeds, k.min(u32::MAX as usize) as u32);
+    hops.iter().enumerate().filter(|(_, h)| h.is_some()).map(|(i, _)| i).collect()
+}
@@ -0,0 +1,67 @@
+// エッジ列から作る隣接リスト（CSR形式）。近傍探索・強調表示の基礎。
+
+use super::Edge3;
+
+/// 無向グラフの隣接リスト。範囲外の端点や自己ループのエッジは無視する。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Adjacency {
+    offsets: Vec<usize>, // node i の近傍は neighbours[offsets[i]..offsets[i+1]]
+    neighbours: Vec<u32>,
+}
+
+impl Adjacency {
+    pub fn new(node_count: usize, edges: &[Edge3]) -> Self {
+        let valid = |e: &&Edge3| e.a != e.b && (e.a as usize) < node_count && (e.b as usize) < node_count;
+        let mut degree = vec![0usize; node_count];
+        for e in edges.iter().filter(valid) {
+            degree[e.a as usize] += 1;
+            degree[e.b as usize] += 1;
+        }
+        let mut offsets = Vec::with_capacity(node_count + 1);
+        offsets.push(0);
+        for d in &degree { offsets.push(offsets[offsets.len() - 1] + d); }
+        let mut fill = offsets[..node_count].to_vec();
+        let mut neighbours = vec![0u32; offsets[node_count]];
+        for e in edges.iter().filter(valid) {
+            neighbours[fill[e.a as usize]] = e.b;
+            fill[e.a as usize] += 1;
+            neighbours[fill[e.b as usize]] = e.a;
+            fill[e.b as usize] += 1;
+        }
+        Self { offsets, neighbours }
+    }
+
+    pub fn node_count(&self) -> usize { self.offsets.len().saturating_sub(1) }
+
+    /// 近傍（エッジ順、多重エッジはそのまま重複する）
+    pub fn neighbours(&self, node: usize) -> &[u32] {
+        &self.neighbours[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// `seeds` からのホップ数（`max_hops` を超える/到達しないノードは None）
+    pub fn hop_distances(&self, seeds: &[usize], max_hops: u32) -> Vec<Option<u32>> {
+        let mut hops = vec![None; self.node_count()];
+        let mut frontier: Vec<usize> = Vec::new();
+        for &s in seeds {
+            if s < hops.len() && hops[s].is_none() {
+                hops[s] = Some(0);
+                frontier.push(s);
+            }
+        }
+        for h in 1..=max_hops {
+            let mut next = Vec::new();
+            for &i in &frontier {
+                for &j in self.neighbours(i) {
+                    let j = j as usize;
+                    if hops[j].is_none() {
+                        hops[j] = Some(h);
+                        next.push(j);
+                    }
+                }
+            }
+            if next.is_empty() { break; }
+            frontier = next;
+        }
+        hops
+    }
+}
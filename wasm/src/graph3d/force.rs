@@ -0,0 +1,299 @@
+// 力学モデル配置（Fruchterman–Reingold + Barnes–Hut 八分木近似）
+//
+// 反発力 k²/d を八分木で近似し、エッジの引力 d²/k と合わせて温度（1反復の最大移動量）で
+// 制限しながら動かす。温度は反復予算に対して線形に下げるため、同じ seed・同じ予算なら
+// 何フレームに分けて `step` しても結果は一致する。
+
+use serde::Deserialize;
+
+use super::{Edge3, Node3};
+use crate::rng::Lcg;
+
+/// 配置パラメータ。JSONで省略したフィールドは既定値。
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ForceLayoutConfig {
+    /// 初期ジッタの種（重なったノードを分離する）
+    pub seed: u64,
+    /// 反復予算（これを使い切ると停止）
+    pub iterations: usize,
+    /// 理想エッジ長 k。None なら初期配置の体積とノード数から決める
+    pub ideal_length: Option<f32>,
+    /// Barnes–Hut の開き角（小さいほど正確で遅い）
+    pub theta: f32,
+    /// 原点への引き戻し（連結成分が散らばらないように）
+    pub gravity: f32,
+    /// 初期半径へ戻す強さ（0で無効、1で毎反復シェル上へ射影）
+    pub radial_strength: f32,
+    /// 描画側で1フレームに進める反復数
+    pub steps_per_frame: usize,
+}
+
+impl Default for ForceLayoutConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1337,
+            iterations: 300,
+            ideal_length: None,
+            theta: 0.8,
+            gravity: 0.05,
+            radial_strength: 0.0,
+            steps_per_frame: 2,
+        }
+    }
+}
+
+pub struct ForceLayout {
+    config: ForceLayoutConfig,
+    positions: Vec<[f32; 3]>,
+    edges: Vec<(usize, usize)>,
+    pinned: Vec<bool>,
+    target_radius: Vec<f32>,
+    k: f32,
+    t0: f32,
+    iteration: usize,
+}
+
+impl ForceLayout {
+    /// `nodes` の現在位置から開始する。エッジの a/b はノード配列のインデックス。
+    pub fn new(nodes: &[Node3], edges: &[Edge3], config: ForceLayoutConfig) -> Self {
+        let n = nodes.len();
+        let mut rng = Lcg::new(config.seed);
+        let extent = nodes.iter().map(|nd| len(nd.pos)).fold(0.0f32, f32::max).max(1e-3);
+        let k = config.ideal_length.filter(|v| v.is_finite() && *v > 0.0).unwrap_or_else(|| {
+            // 半径 extent の球に n 個を詰めたときの平均間隔
+            let volume = 4.0 / 3.0 * std::f32::consts::PI * extent.powi(3);
+            (volume / n.max(1) as f32).cbrt()
+        });
+        let positions = nodes.iter()
+            .map(|nd| {
+                let j = k * 1e-3;
+                let p = nd.pos;
+                [p[0] + (rng.next_f32() - 0.5) * j, p[1] + (rng.next_f32() - 0.5) * j, p[2] + (rng.next_f32() - 0.5) * j]
+            })
+            .collect();
+        let edges = edges.iter()
+            .map(|e| (e.a as usize, e.b as usize))
+            .filter(|&(a, b)| a != b && a < n && b < n)
+            .collect();
+        let target_radius = nodes.iter().map(|nd| len(nd.pos)).collect();
+        Self { config, positions, edges, pinned: vec![false; n], target_radius, k, t0: extent * 0.1, iteration: 0 }
+    }
+
+    pub fn config(&self) -> &ForceLayoutConfig { &self.config }
+
+    /// 固定ノードは力を受けず、位置も変わらない（他ノードへの力は及ぼす）
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) {
+        if let Some(p) = self.pinned.get_mut(index) { *p = pinned; }
+    }
+
+    /// 固定ノードを指定位置へ動かす（ドラッグ等）
+    pub fn move_node(&mut self, index: usize, pos: [f32; 3]) {
+        if let Some(p) = self.positions.get_mut(index) { *p = pos; }
+    }
+
+    /// 半径拘束の目標（既定は初期配置での原点からの距離）
+    pub fn set_target_radius(&mut self, index: usize, radius: f32) {
+        if let Some(r) = self.target_radius.get_mut(index) { *r = radius.max(0.0); }
+    }
+
+    pub fn ideal_length(&self) -> f32 { self.k }
+
+    pub fn iteration(&self) -> usize { self.iteration }
+
+    pub fn is_done(&self) -> bool { self.iteration >= self.config.iterations }
+
+    pub fn positions(&self) -> &[[f32; 3]] { &self.positions }
+
+    /// 現在位置をノード配列へ書き戻す
+    pub fn write_to(&self, nodes: &mut [Node3]) {
+        for (nd, p) in nodes.iter_mut().zip(&self.positions) { nd.pos = *p; }
+    }
+
+    /// 反復予算を使い切るまで回す
+    pub fn run(&mut self) {
+        self.step(self.config.iterations);
+    }
+
+    /// 最大 `iterations` 回進める（予算を超えない）。まだ続きがあれば true。
+    pub fn step(&mut self, iterations: usize) -> bool {
+        for _ in 0..iterations {
+            if self.is_done() { break; }
+            self.iterate();
+        }
+        !self.is_done()
+    }
+
+    fn iterate(&mut self) {
+        let n = self.positions.len();
+        if n == 0 { self.iteration = self.config.iterations; return; }
+        let k = self.k;
+        let k2 = k * k;
+        let theta2 = self.config.theta.max(0.0).powi(2);
+
+        // 反発力（Barnes–Hut）
+        let tree = Octree::build(&self.positions);
+        let mut disp = vec![[0.0f32; 3]; n];
+        for (i, d) in disp.iter_mut().enumerate() {
+            if self.pinned[i] { continue; }
+            tree.repulsion(i, self.positions[i], k2, theta2, d);
+        }
+        // 引力（エッジ）
+        for &(a, b) in &self.edges {
+            let delta = sub(self.positions[a], self.positions[b]);
+            let dist = len(delta).max(1e-6);
+            let f = dist / k; // (d²/k) / d
+            for c in 0..3 {
+                if !self.pinned[a] { disp[a][c] -= delta[c] * f; }
+                if !self.pinned[b] { disp[b][c] += delta[c] * f; }
+            }
+        }
+
+        // 温度で移動量を制限（予算に対して線形に冷却）
+        let t = self.t0 * (1.0 - self.iteration as f32 / self.config.iterations.max(1) as f32);
+        let gravity = self.config.gravity.max(0.0);
+        let radial = self.config.radial_strength.clamp(0.0, 1.0);
+        for (i, d) in disp.iter_mut().enumerate() {
+            if self.pinned[i] { continue; }
+            let p = self.positions[i];
+            for c in 0..3 { d[c] -= p[c] * gravity * k; }
+            let m = len(*d);
+            let scale = if m > t { t / m } else { 1.0 };
+            let mut np = [p[0] + d[0] * scale, p[1] + d[1] * scale, p[2] + d[2] * scale];
+            if radial > 0.0 {
+                let r = len(np);
+                if r > 1e-6 {
+                    let want = r + (self.target_radius[i] - r) * radial;
+                    let s = want / r;
+                    np = [np[0] * s, np[1] * s, np[2] * s];
+                }
+            }
+            self.positions[i] = np;
+        }
+        self.iteration += 1;
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] { [a[0] - b[0], a[1] - b[1], a[2] - b[2]] }
+
+fn len(a: [f32; 3]) -> f32 { (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt() }
+
+// ---- Barnes–Hut 八分木 ----
+
+// 同一座標が大量にあっても分割が止まるよう深さを制限
+const MAX_DEPTH: u32 = 20;
+const NONE: u32 = u32::MAX;
+
+struct Cell {
+    center: [f32; 3],
+    half: f32,
+    mass: f32,
+    com: [f32; 3],    // 質量中心（構築中は重み付き和）
+    body: u32,        // 葉に入っている代表ノード（NONE: 内部ノード or 空）
+    children: u32,    // 子8個の先頭インデックス（NONE: 葉）
+}
+
+struct Octree {
+    cells: Vec<Cell>,
+}
+
+impl Octree {
+    fn build(points: &[[f32; 3]]) -> Self {
+        let mut lo = [f32::INFINITY; 3];
+        let mut hi = [f32::NEG_INFINITY; 3];
+        for p in points {
+            for c in 0..3 { lo[c] = lo[c].min(p[c]); hi[c] = hi[c].max(p[c]); }
+        }
+        let center = [(lo[0] + hi[0]) * 0.5, (lo[1] + hi[1]) * 0.5, (lo[2] + hi[2]) * 0.5];
+        let half = ((hi[0] - lo[0]).max(hi[1] - lo[1]).max(hi[2] - lo[2]) * 0.5).max(1e-6) * 1.0001;
+        let mut tree = Octree { cells: Vec::with_capacity(points.len() * 2) };
+        tree.cells.push(Cell { center, half, mass: 0.0, com: [0.0; 3], body: NONE, children: NONE });
+        for (i, p) in points.iter().enumerate() {
+            tree.insert(0, i as u32, *p, points, 0);
+        }
+        for c in &mut tree.cells {
+            if c.mass > 0.0 { for a in 0..3 { c.com[a] /= c.mass; } }
+        }
+        tree
+    }
+
+    fn insert(&mut self, cell: usize, body: u32, p: [f32; 3], points: &[[f32; 3]], depth: u32) {
+        let c = &mut self.cells[cell];
+        c.mass += 1.0;
+        for (s, v) in c.com.iter_mut().zip(p) { *s += v; }
+        if c.children == NONE {
+            if c.body == NONE && c.mass == 1.0 {
+                c.body = body;
+                return;
+            }
+            if depth >= MAX_DEPTH {
+                // これ以上分けない（代表ノードはそのまま、質量だけ加算）
+                return;
+            }
+            // 分割して既存ノードを子へ移す
+            let existing = c.body;
+            c.body = NONE;
+            let (center, half) = (c.center, c.half);
+            let first = self.cells.len() as u32;
+            self.cells[cell].children = first;
+            for o in 0..8u32 {
+                let h = half * 0.5;
+                let cc = [
+                    center[0] + if o & 1 != 0 { h } else { -h },
+                    center[1] + if o & 2 != 0 { h } else { -h },
+                    center[2] + if o & 4 != 0 { h } else { -h },
+                ];
+                self.cells.push(Cell { center: cc, half: h, mass: 0.0, com: [0.0; 3], body: NONE, children: NONE });
+            }
+            if existing != NONE {
+                let ep = points[existing as usize];
+                let child = first as usize + octant(center, ep);
+                self.insert(child, existing, ep, points, depth + 1);
+            }
+        }
+        let (center, first) = (self.cells[cell].center, self.cells[cell].children as usize);
+        self.insert(first + octant(center, p), body, p, points, depth + 1);
+    }
+
+    /// ノード i が受ける反発力（k² · m / d、方向は相手から離れる向き）を `out` に加算
+    fn repulsion(&self, i: usize, p: [f32; 3], k2: f32, theta2: f32, out: &mut [f32; 3]) {
+        let mut stack = vec![0usize];
+        while let Some(ci) = stack.pop() {
+            let c = &self.cells[ci];
+            if c.mass == 0.0 { continue; }
+            let is_leaf = c.children == NONE;
+            let holds_self = c.body == i as u32 || contains(c, p);
+            if !is_leaf && holds_self {
+                // 自分を含むセルは近似せず必ず開く
+                for o in 0..8 { stack.push(c.children as usize + o); }
+                continue;
+            }
+            let mut mass = c.mass;
+            let mut com = c.com;
+            if is_leaf && holds_self {
+                if mass <= 1.0 { continue; }
+                // 縮退葉（MAX_DEPTH で止まった葉）: 質量中心から自分を抜く
+                for a in 0..3 { com[a] = (com[a] * mass - p[a]) / (mass - 1.0); }
+                mass -= 1.0;
+            }
+            let delta = sub(p, com);
+            let d2 = delta[0] * delta[0] + delta[1] * delta[1] + delta[2] * delta[2];
+            let size = c.half * 2.0;
+            if is_leaf || size * size < theta2 * d2 {
+                let f = k2 * mass / d2.max(1e-8); // (k²·m/d) / d
+                for a in 0..3 { out[a] += delta[a] * f; }
+            } else {
+                for o in 0..8 { stack.push(c.children as usize + o); }
+            }
+        }
+    }
+}
+
+// 半開区間（octant の振り分け `>=` と揃える）
+fn contains(c: &Cell, p: [f32; 3]) -> bool {
+    (0..3).all(|a| p[a] >= c.center[a] - c.half && p[a] < c.center[a] + c.half)
+}
+
+fn octant(center: [f32; 3], p: [f32; 3]) -> usize {
+    (p[0] >= center[0]) as usize | ((p[1] >= center[1]) as usize) << 1 | ((p[2] >= center[2]) as usize) << 2
+}
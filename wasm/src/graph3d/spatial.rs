@@ -0,0 +1,110 @@
+// k近傍探索用の k-d tree（generate_* 共通）
+//
+// 総当たり（全距離を計算→安定ソート→先頭k件）と同じ結果を返すため、
+// 候補は (距離², 元インデックス) の辞書順で比較する。
+
+/// 点集合の k-d tree。各点は呼び出し側のインデックス（ノード番号）を保持する。
+pub struct KdTree {
+    // 暗黙木: 区間の中央要素が分割点、分割軸は深さ % 3。LEAF_SIZE 以下の区間は線形走査。
+    items: Vec<(usize, [f32; 3])>,
+}
+
+impl KdTree {
+    pub fn new(mut items: Vec<(usize, [f32; 3])>) -> Self {
+        build(&mut items, 0);
+        Self { items }
+    }
+
+    /// ノード配列のうち `indices` の点だけで木を作る
+    pub fn from_subset(points: &[[f32; 3]], indices: impl IntoIterator<Item = usize>) -> Self {
+        Self::new(indices.into_iter().map(|i| (i, points[i])).collect())
+    }
+
+    pub fn len(&self) -> usize { self.items.len() }
+
+    pub fn is_empty(&self) -> bool { self.items.is_empty() }
+
+    /// `q` に近い順に最大 `k` 件の (距離², インデックス) を返す。`exclude` のインデックスは除く。
+    /// 同距離はインデックスの小さい方を優先（総当たり＋安定ソートと一致）。
+    pub fn nearest(&self, q: [f32; 3], k: usize, exclude: Option<usize>) -> Vec<(f32, usize)> {
+        let mut best: Vec<(f32, usize)> = Vec::with_capacity(k + 1);
+        if k > 0 {
+            search(&self.items, 0, q, k, exclude, &mut [0.0; 3], 0.0, &mut best);
+        }
+        best
+    }
+}
+
+const LEAF_SIZE: usize = 8;
+
+fn build(items: &mut [(usize, [f32; 3])], depth: usize) {
+    if items.len() <= LEAF_SIZE { return; }
+    let axis = depth % 3;
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by(mid, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+    let (left, rest) = items.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut rest[1..], depth + 1);
+}
+
+fn dist2(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let dx = a[0] - b[0];
+    let dy = a[1] - b[1];
+    let dz = a[2] - b[2];
+    dx * dx + dy * dy + dz * dz
+}
+
+fn is_closer(a: (f32, usize), b: (f32, usize)) -> bool {
+    a.0 < b.0 || (a.0 == b.0 && a.1 < b.1)
+}
+
+fn offer(best: &mut Vec<(f32, usize)>, k: usize, cand: (f32, usize)) {
+    if best.len() < k || is_closer(cand, best[best.len() - 1]) {
+        let at = best.partition_point(|&b| is_closer(b, cand));
+        best.insert(at, cand);
+        best.truncate(k);
+    }
+}
+
+// 浮動小数の丸めで同距離の候補を取りこぼさないよう、枝刈りには僅かな余裕を持たせる
+const PRUNE_SLACK: f32 = 1.0 - 1e-5;
+
+/// `off` は q から現在の区間のセル境界までの軸別オフセット、`rd` はその二乗和（セルまでの距離²の下界）
+#[allow(clippy::too_many_arguments)]
+fn search(
+    items: &[(usize, [f32; 3])],
+    depth: usize,
+    q: [f32; 3],
+    k: usize,
+    exclude: Option<usize>,
+    off: &mut [f32; 3],
+    rd: f32,
+    best: &mut Vec<(f32, usize)>,
+) {
+    if items.len() <= LEAF_SIZE {
+        for &(idx, p) in items {
+            if exclude != Some(idx) { offer(best, k, (dist2(q, p), idx)); }
+        }
+        return;
+    }
+    let axis = depth % 3;
+    let mid = items.len() / 2;
+    let (idx, p) = items[mid];
+    if exclude != Some(idx) { offer(best, k, (dist2(q, p), idx)); }
+
+    let diff = q[axis] - p[axis];
+    let (near, far) = if diff <= 0.0 {
+        (&items[..mid], &items[mid + 1..])
+    } else {
+        (&items[mid + 1..], &items[..mid])
+    };
+    search(near, depth + 1, q, k, exclude, off, rd, best);
+    // 反対側のセルまでの距離²が現在のk番目以下なら候補があり得る（同距離も含める）
+    let old = off[axis];
+    let far_rd = rd - old * old + diff * diff;
+    if best.len() < k || far_rd * PRUNE_SLACK <= best[best.len() - 1].0 {
+        off[axis] = diff;
+        search(far, depth + 1, q, k, exclude, off, far_rd, best);
+        off[axis] = old;
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::graph3d::{Edge3, EdgeKind3, Node3};
+use crate::shell_config::ShellConfig;
+
+/// 読み込み上限の既定値（ブラウザのメモリ/描画負荷を考慮した目安）
+pub const DEFAULT_MAX_NODES: usize = 20_000;
+pub const DEFAULT_MAX_EDGES: usize = 200_000;
+
+/// JSから渡されるグラフ（`{ "nodes": [...], "edges": [...] }`）
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphDataInput {
+    pub nodes: Vec<NodeInput>,
+    #[serde(default)]
+    pub edges: Vec<EdgeInput>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeInput {
+    pub id: u32,
+    /// 省略時は既存のシェル生成器で補完する
+    #[serde(default)]
+    pub pos: Option<[f32; 3]>,
+    #[serde(default)]
+    pub category: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EdgeInput {
+    /// 端点はノードの `id` で指定する（配列インデックスではない）
+    pub a: u32,
+    pub b: u32,
+    #[serde(default)]
+    pub kind: EdgeKindInput,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKindInput {
+    #[default]
+    Mesh,
+    Extra,
+}
+
+fn default_weight() -> f32 { 1.0 }
+
+impl From<EdgeKindInput> for EdgeKind3 {
+    fn from(k: EdgeKindInput) -> Self {
+        match k {
+            EdgeKindInput::Mesh => EdgeKind3::Mesh,
+            EdgeKindInput::Extra => EdgeKind3::Extra,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphDataLimits {
+    pub max_nodes: usize,
+    pub max_edges: usize,
+}
+
+impl Default for GraphDataLimits {
+    fn default() -> Self { Self { max_nodes: DEFAULT_MAX_NODES, max_edges: DEFAULT_MAX_EDGES } }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphDataError {
+    Parse(String),
+    NoNodes,
+    TooManyNodes { count: usize, max: usize },
+    TooManyEdges { count: usize, max: usize },
+    DuplicateNodeId { id: u32, first: usize, second: usize },
+    NonFinitePosition { id: u32, index: usize },
+    DanglingEdge { edge: usize, id: u32 },
+    SelfLoop { edge: usize, id: u32 },
+    NonFiniteWeight { edge: usize },
+}
+
+impl fmt::Display for GraphDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "invalid graph json: {msg}"),
+            Self::NoNodes => write!(f, "graph must contain at least one node"),
+            Self::TooManyNodes { count, max } => write!(f, "too many nodes: {count} (limit {max})"),
+            Self::TooManyEdges { count, max } => write!(f, "too many edges: {count} (limit {max})"),
+            Self::DuplicateNodeId { id, first, second } => {
+                write!(f, "duplicate node id {id} at nodes[{first}] and nodes[{second}]")
+            }
+            Self::NonFinitePosition { id, index } => {
+                write!(f, "node id {id} (nodes[{index}]) has a non-finite position")
+            }
+            Self::DanglingEdge { edge, id } => write!(f, "edges[{edge}] references unknown node id {id}"),
+            Self::SelfLoop { edge, id } => write!(f, "edges[{edge}] is a self-loop on node id {id}"),
+            Self::NonFiniteWeight { edge } => write!(f, "edges[{edge}] has a non-finite weight"),
+        }
+    }
+}
+
+impl std::error::Error for GraphDataError {}
+
+/// 描画側が保持するグラフ。`Edge3` の a/b はノード配列のインデックス、
+/// `Node3::id` は呼び出し側のIDを保持する（生成グラフではインデックスと同一）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedGraph {
+    pub nodes: Vec<Node3>,
+    pub edges: Vec<Edge3>,
+    pub categories: Vec<Option<u32>>,
+    pub weights: Vec<f32>,
+    /// 外部ID → ノードインデックス（読み込み時に作る。IDは読み込み後に変わらない）
+    ids: HashMap<u32, usize>,
+}
+
+impl LoadedGraph {
+    /// 生成器の出力をそのまま包む（カテゴリなし、重み1.0）
+    pub fn from_generated(nodes: Vec<Node3>, edges: Vec<Edge3>) -> Self {
+        let categories = vec![None; nodes.len()];
+        let weights = vec![1.0; edges.len()];
+        let ids = nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+        Self { nodes, edges, categories, weights, ids }
+    }
+
+    /// 外部IDからノードインデックスを引く
+    pub fn index_of(&self, id: u32) -> Option<usize> {
+        self.ids.get(&id).copied()
+    }
+}
+
+pub fn parse_graph_data(json: &str) -> Result<GraphDataInput, GraphDataError> {
+    serde_json::from_str(json).map_err(|e| GraphDataError::Parse(e.to_string()))
+}
+
+/// 入力を検証して `LoadedGraph` を構築する。位置未指定のノードは
+/// 既定シェル設定（seed のみ `fill_seed`）で生成した同インデックスの座標で補完する。
+pub fn build_graph(
+    input: &GraphDataInput,
+    limits: GraphDataLimits,
+    fill_seed: u64,
+) -> Result<LoadedGraph, GraphDataError> {
+    let n = input.nodes.len();
+    if n == 0 { return Err(GraphDataError::NoNodes); }
+    if n > limits.max_nodes { return Err(GraphDataError::TooManyNodes { count: n, max: limits.max_nodes }); }
+    if input.edges.len() > limits.max_edges {
+        return Err(GraphDataError::TooManyEdges { count: input.edges.len(), max: limits.max_edges });
+    }
+
+    let mut index_of: HashMap<u32, usize> = HashMap::with_capacity(n);
+    for (i, node) in input.nodes.iter().enumerate() {
+        if let Some(&first) = index_of.get(&node.id) {
+            return Err(GraphDataError::DuplicateNodeId { id: node.id, first, second: i });
+        }
+        if let Some(p) = node.pos {
+            if !p.iter().all(|c| c.is_finite()) {
+                return Err(GraphDataError::NonFinitePosition { id: node.id, index: i });
+            }
+        }
+        index_of.insert(node.id, i);
+    }
+
+    let mut edges = Vec::with_capacity(input.edges.len());
+    let mut weights = Vec::with_capacity(input.edges.len());
+    for (ei, e) in input.edges.iter().enumerate() {
+        let a = *index_of.get(&e.a).ok_or(GraphDataError::DanglingEdge { edge: ei, id: e.a })?;
+        let b = *index_of.get(&e.b).ok_or(GraphDataError::DanglingEdge { edge: ei, id: e.b })?;
+        if a == b { return Err(GraphDataError::SelfLoop { edge: ei, id: e.a }); }
+        if !e.weight.is_finite() { return Err(GraphDataError::NonFiniteWeight { edge: ei }); }
+        edges.push(Edge3 { a: a as u32, b: b as u32, kind: e.kind.into() });
+        weights.push(e.weight);
+    }
+
+    // 位置未指定があれば既存のシェル生成器で補完（start_graph の既定レイアウトと同じ比率）
+    let fill = if input.nodes.iter().any(|nd| nd.pos.is_none()) {
+        let config = ShellConfig { seed: fill_seed, node_count: n, ..ShellConfig::default() };
+        let (generated, _) = config.generate();
+        Some(generated)
+    } else {
+        None
+    };
+    let nodes = input.nodes.iter().enumerate()
+        .map(|(i, nd)| {
+            let pos = nd.pos.unwrap_or_else(|| fill.as_ref().map(|g| g[i].pos).unwrap_or([0.0; 3]));
+            Node3 { id: nd.id, pos }
+        })
+        .collect();
+    let categories = input.nodes.iter().map(|nd| nd.category).collect();
+
+    Ok(LoadedGraph { nodes, edges, categories, weights, ids: index_of })
+}
+
+/// JSON文字列から直接読み込む（`parse_graph_data` + `build_graph`）
+pub fn load_graph_json(json: &str, limits: GraphDataLimits, fill_seed: u64) -> Result<LoadedGraph, GraphDataError> {
+    let input = parse_graph_data(json)?;
+    build_graph(&input, limits, fill_seed)
+}
@@ -1,9 +1,12 @@
 struct UGraph {
   view_proj: mat4x4<f32>,
-  misc0: vec4<f32>,   // x: time, y: edge_thickness (NDC), z: node_size (NDC), w: flow_speed
+  misc0: vec4<f32>,   // x: time, y: edge_thickness (NDC), z: node_size (NDC), w: flow_speed (drift; the stripe uses misc4.w)
   misc1: vec4<f32>,   // x: aspect, y: fogStart, z: fogEnd, w: fogStrength
-  misc2: vec4<f32>,   // x: link_on, y: link_off, z,w: reserved
-  misc3: vec4<f32>,   // x: nuc_link_on, y: nuc_link_off, z,w: reserved
+  misc2: vec4<f32>,   // x: link_on, y: link_off, z: pulse start time, w: pulse speed (hops/s)
+  misc3: vec4<f32>,   // x: nuc_link_on, y: nuc_link_off, z: pulse falloff per hop, w: nucleus presence (0..1)
+  misc4: vec4<f32>,   // x: node glow gain, y: nucleus size scale, z: nucleus instance index (<0: none), w: flow phase (cycles)
+  tint: vec4<f32>,    // rgb: tint colour, a: amount (0: original colours)
+  misc5: vec4<f32>,   // x: target height (px), y: analytic AA (0/1), z: analytic min edge width (px), w: unused
 };
 @group(0) @binding(0) var<uniform> u: UGraph;
 
@@ -46,6 +49,8 @@ struct VInEdge {
   @location(2) p2: vec3<f32>,
   @location(3) color: vec4<f32>,            // linear sRGB
   @location(4) eparams: vec3<f32>,          // x: curve_k, y: thickness scale, z: is_nucleus(0/1)
+  @location(5) emph: f32,                   // 強調係数（1: 通常、>1: 強調、<1: 減光）。別バッファ
+  @location(6) pulse: vec2<f32>,            // パルス: x: 起点側のホップ数（<0: 対象外）, y: 向き(+1: p1→p2, -1: p2→p1, 0: 両端から)
 };
 struct VOutEdge {
   @builtin(position) pos: vec4<f32>,
@@ -56,6 +61,9 @@ struct VOutEdge {
   @location(4) v_curv: f32,
   @location(5) v_tscale: f32,
   @location(6) v_rstr: f32,                 // 半径による強度（中心→外で弱く）
+  @location(7) v_emph: f32,
+  @location(8) v_pulse: vec2<f32>,
+  @location(9) v_cov: f32,                  // 解析的 AA で広げた分の明るさの係数（1: 広げていない）
 };
 
 @vertex
@@ -75,7 +83,7 @@ fn vs_edge(inp: VInEdge) -> VOutEdge {
   // link強度: 距離が近いペアのみ残す（スムーズにフェード）
   let dist = distance(p1, p2);
   let base_link = 1.0 - smoothstep(u.misc2.x, u.misc2.y, dist);
-  let nuc_link  = 1.0 - smoothstep(u.misc3.x, u.misc3.y, dist);
+  let nuc_link  = (1.0 - smoothstep(u.misc3.x, u.misc3.y, dist)) * u.misc3.w; // 核の表示/非表示はフェード
   let vlink = select(base_link, nuc_link, inp.eparams.z > 0.5);
   // radial強度: 中心→外側で弱く。下限minを設ける
   let r_mid = 0.5 * (length(p1) + length(p2));
@@ -94,7 +102,18 @@ fn vs_edge(inp: VInEdge) -> VOutEdge {
   let along2 = a2 + dir * t;
   // 放射方向でも少し細く（外側ほど薄い）
   let t_rad = 0.6 + 0.4 * vr; // center=1.0, outer=0.6
-  let thickness = u.misc0.y * inp.eparams.y * t_rad; // 曲線は細く＋外側でさらに細く
+  // 強調時は太く（減光時は太さを変えない）
+  let t_emph = 1.0 + 0.6 * max(inp.emph - 1.0, 0.0);
+  var thickness = u.misc0.y * inp.eparams.y * t_rad * t_emph; // 曲線は細く＋外側でさらに細く
+  // 解析的 AA: 最小幅に満たない線はその幅まで広げ、広げた割合だけ暗くする（shader_math::analytic_edge_width と同じ）
+  var cov = 1.0;
+  if (u.misc5.y > 0.5) {
+    let min_thickness = u.misc5.z / max(u.misc5.x, 1.0);
+    if (thickness > 0.0 && thickness < min_thickness) {
+      cov = thickness / min_thickness;
+      thickness = min_thickness;
+    }
+  }
   let world2 = along2 + ortho * side * thickness; // in scaled screen space
   // revert aspect scaling for x
   let world = vec2<f32>(world2.x / asp, world2.y);
@@ -108,9 +127,29 @@ fn vs_edge(inp: VInEdge) -> VOutEdge {
   out.v_curv = inp.eparams.x;
   out.v_tscale = inp.eparams.y;
   out.v_rstr = vr;
+  out.v_emph = inp.emph;
+  out.v_pulse = inp.pulse;
+  out.v_cov = cov;
   return out;
 }
 
+// セッション状態の色味: 明るさを保ったまま tint.rgb へ寄せる
+fn apply_tint(col: vec3<f32>) -> vec3<f32> {
+  let lum = max(col.r, max(col.g, col.b));
+  return mix(col, u.tint.rgb * lum, u.tint.a);
+}
+
+// 辺を伝わる波の明るさ（CPU側 pulse::pulse_intensity と同じ式）
+fn pulse_term(front: f32, e: vec2<f32>, t: f32, falloff: f32) -> f32 {
+  if (e.x < 0.0) { return 0.0; }
+  var along = t;
+  if (e.y < -0.5) { along = 1.0 - t; } else if (e.y < 0.5) { along = min(t, 1.0 - t); }
+  let d = (front - e.x) - along;
+  var shape = exp(-d * d * 60.0);
+  if (d >= 0.0) { shape = exp(-d * 2.0); }
+  return pow(falloff, e.x) * shape;
+}
+
 @fragment
 fn fs_edge(inp: VOutEdge) -> @location(0) vec4<f32> {
   // 横方向ガウシアンっぽい減衰 + 沿い方向のフロー縞
@@ -123,8 +162,19 @@ fn fs_edge(inp: VOutEdge) -> @location(0) vec4<f32> {
   let k_glow = 2.5 / max(inp.v_tscale, 0.5);
   let core = exp(-k_core * y * y);              // 中心光
   let glow = exp(-k_glow * y * y);
-  let flow = 0.65 + 0.35 * sin( (inp.v_uv.x * 40.0) - u.misc0.x * u.misc0.w * 6.28318 );
-  var col = inp.v_color.rgb * (core * 1.3 + glow * 0.6 * flow) * inp.v_link * inp.v_rstr;
+  // 強調時はフロー縞の振幅を強める
+  let flow_amp = 0.35 * max(inp.v_emph, 1.0);
+  let flow = max(0.65 + flow_amp * sin( (inp.v_uv.x * 40.0) - u.misc4.w * 6.28318 ), 0.0);
+  var col = inp.v_color.rgb * (core * 1.3 + glow * 0.6 * flow) * inp.v_link * inp.v_rstr * inp.v_emph;
+  // パルスの波頭（flow 縞と同じく線の断面に沿って光らせる）
+  let front = (u.misc0.x - u.misc2.z) * u.misc2.w;
+  let pulse = pulse_term(front, inp.v_pulse, t, u.misc3.z);
+  col += inp.v_color.rgb * (core * 1.3 + glow * 0.6) * pulse * 1.5 * inp.v_link * inp.v_rstr;
+  col = apply_tint(col);
+  // 解析的 AA: 帯の縁を1ピクセルでぼかす（fwidth は分岐の外で求める。shader_math::analytic_edge_fade と同じ）
+  let fw = fwidth(inp.v_uv.y);
+  let fade = clamp((1.0 - abs(inp.v_uv.y)) / max(fw, 1e-4), 0.0, 1.0);
+  col *= select(1.0, fade, u.misc5.y > 0.5) * inp.v_cov;
   // fog based on ndc.z mapped to [0,1]
   let depth01 = clamp(0.5 * (inp.v_depth + 1.0), 0.0, 1.0);
   let f = smoothstep(u.misc1.y, u.misc1.z, depth01) * u.misc1.w;
@@ -139,6 +189,8 @@ struct VInNode {
   @location(2) size: f32,
   @location(3) color: vec4<f32>,
   @location(4) phase: f32,
+  @location(5) emph: f32,                  // 強調係数（エッジと同じ）
+  @builtin(instance_index) iid: u32,
 };
 struct VOutNode {
   @builtin(position) pos: vec4<f32>,
@@ -147,6 +199,7 @@ struct VOutNode {
   @location(2) v_r: f32,                // 半径 = 0.5 * size * u.node_size
   @location(3) v_depth: f32,
   @location(4) v_phase: f32,
+  @location(5) v_emph: f32,
 };
 
 @vertex
@@ -157,7 +210,8 @@ fn vs_node(inp: VInNode) -> VOutNode {
   let center = select(vec3<f32>(0.0), normalize(inp.center + offc) * rc, rc >= 1e-6);
   let clip = u.view_proj * vec4<f32>(center, 1.0);
   let ndc = clip.xyz / clip.w;
-  let size = inp.size * u.misc0.z;
+  let nuc = select(1.0, u.misc4.y, i32(inp.iid) == i32(u.misc4.z)); // 核ノードだけ音声で拡縮
+  let size = inp.size * u.misc0.z * nuc * (1.0 + 0.35 * max(inp.emph - 1.0, 0.0));
   let local = inp.corner * size;            // in NDC units
   var out: VOutNode;
   out.pos = vec4<f32>(ndc.xy + local, ndc.z, 1.0);
@@ -166,6 +220,7 @@ fn vs_node(inp: VInNode) -> VOutNode {
   out.v_r = 0.5 * size; // 正規化用半径（辺の中心まで=1）
   out.v_depth = ndc.z;
   out.v_phase = inp.phase;
+  out.v_emph = inp.emph;
   return out;
 }
 
@@ -176,8 +231,8 @@ fn fs_node(inp: VOutNode) -> @location(0) vec4<f32> {
   let core = smoothstep(1.0, 0.0, d);           // 中心から滑らかに0へ
   let glow = exp(-4.0 * d * d);                 // ソフトグロー
   let spark = 0.35 + 0.65 * sin(u.misc0.x * 6.28318);
-  let intensity = core * 1.4 + glow * 0.5 * sin(u.misc0.x * 6.28318 + inp.v_phase);
-  var col = inp.v_color.rgb * intensity;
+  let intensity = core * 1.4 + glow * 0.5 * sin(u.misc0.x * 6.28318 + inp.v_phase) + glow * 0.6 * (u.misc4.x - 1.0);
+  var col = apply_tint(inp.v_color.rgb * intensity * inp.v_emph);
   let depth01 = clamp(0.5 * (inp.v_depth + 1.0), 0.0, 1.0);
   let f = smoothstep(u.misc1.y, u.misc1.z, depth01) * u.misc1.w;
   col *= (1.0 - f);
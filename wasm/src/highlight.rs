@@ -0,0 +1,92 @@
+// 近傍の強調表示: 注目ノードからのホップ数 → インスタンス毎の強調係数
+//
+// 係数は 1.0 が通常表示、1 より大きいと明るく/太く/フロー強め、1 未満は減光。
+// インスタンスバッファとは別の頂点バッファで渡すので、係数の更新だけなら EdgeInst を作り直さない。
+
+use crate::graph3d::Edge3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighlightConfig {
+    /// 強調する近傍の深さ（ホップ数）
+    pub hops: u32,
+    /// 近傍外の明るさ（0..1）
+    pub floor: f32,
+    /// 注目ノード（と直結エッジ）の強調係数（>= 1）。近傍は外側ほど 1.0 に近づく
+    pub boost: f32,
+    /// 係数が目標へ近づく速さ（1/s）。0 以下なら即時に切り替える
+    pub fade_rate: f32,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self { Self { hops: 1, floor: 0.15, boost: 1.8, fade_rate: 6.0 } }
+}
+
+impl HighlightConfig {
+    /// 範囲外の値を丸める（floor: 0..1, boost: 1..4, hops: 0..8）
+    pub fn clamped(self) -> Self {
+        Self {
+            hops: self.hops.min(8),
+            floor: if self.floor.is_finite() { self.floor.clamp(0.0, 1.0) } else { 0.0 },
+            boost: if self.boost.is_finite() { self.boost.clamp(1.0, 4.0) } else { 1.0 },
+            fade_rate: if self.fade_rate.is_finite() { self.fade_rate.max(0.0) } else { 0.0 },
+        }
+    }
+
+    /// ホップ数 h（<= hops）の係数: 0 で boost、hops+1 で 1.0 になる直線
+    fn at_hop(&self, h: u32) -> f32 {
+        let t = h as f32 / (self.hops + 1) as f32;
+        self.boost + (1.0 - self.boost) * t
+    }
+}
+
+/// ノードの係数。`hops` が全て None（強調なし）の時に呼ぶと全ノード floor になるので、
+/// 強調しない場合は呼び出し側で 1.0 を使う。
+pub fn node_emphasis(hops: &[Option<u32>], cfg: &HighlightConfig) -> Vec<f32> {
+    hops.iter().map(|h| h.map_or(cfg.floor, |h| cfg.at_hop(h))).collect()
+}
+
+/// エッジの係数。両端が近傍内で、ホップ差が1以内（近傍の木を辿るエッジ）なら近い側のホップ数で強調、それ以外は floor。
+pub fn edge_emphasis(hops: &[Option<u32>], edges: &[Edge3], cfg: &HighlightConfig) -> Vec<f32> {
+    edges.iter()
+        .map(|e| match (hops.get(e.a as usize).copied().flatten(), hops.get(e.b as usize).copied().flatten()) {
+            (Some(a), Some(b)) if a.abs_diff(b) <= 1 => cfg.at_hop(a.min(b)),
+            _ => cfg.floor,
+        })
+        .collect()
+}
+
+/// 係数の現在値と目標値。目標が変わると `fade_rate` で滑らかに追従する。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmphasisFade {
+    current: Vec<f32>,
+    target: Vec<f32>,
+}
+
+impl EmphasisFade {
+    /// 件数が変わった（インスタンスを作り直した）場合は補間せずに切り替える
+    pub fn set_target(&mut self, target: Vec<f32>) {
+        if target.len() != self.current.len() {
+            self.current = target.clone();
+        }
+        self.target = target;
+    }
+
+    pub fn values(&self) -> &[f32] { &self.current }
+
+    pub fn is_settled(&self) -> bool { self.current == self.target }
+
+    /// フェードせずに目標値へ揃える
+    pub fn snap(&mut self) { self.current.clone_from(&self.target); }
+
+    /// `dt` 秒進める。値が変わったら true（GPUへ書き込みが必要）。
+    pub fn step(&mut self, dt: f32, fade_rate: f32) -> bool {
+        if self.is_settled() { return false; }
+        let k = if fade_rate > 0.0 { 1.0 - (-fade_rate * dt.max(0.0)).exp() } else { 1.0 };
+        for (c, &t) in self.current.iter_mut().zip(&self.target) {
+            let next = *c + (t - *c) * k;
+            // 十分近づいたら目標値に揃えて更新を止める
+            *c = if (t - next).abs() < 1e-3 { t } else { next };
+        }
+        true
+    }
+}
@@ -0,0 +1,215 @@
+// 配置（レイアウト）の共通インターフェイスと名前付きレジストリ
+//
+// 各生成器をパラメータ構造体で包み、`Layout` として位置とエッジ（Edge3）を返す。
+// レジストリは名前 → JSONパラメータからの生成関数で、lib.rs から実行時に切り替える。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::graph::{self, EdgeKind};
+use crate::graph3d::{generate_cloud, generate_sphere, Edge3, EdgeKind3, Node3};
+use crate::graph_data::DEFAULT_MAX_NODES;
+use crate::rng::Lcg;
+use crate::shell_config::ShellConfig;
+
+pub trait Layout {
+    /// ノード位置（id はインデックス）と、ノードインデックスで結んだエッジを返す
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>);
+}
+
+impl Layout for ShellConfig {
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) { ShellConfig::generate(self) }
+}
+
+/// 単位球面上の Fibonacci 配置 + k-NN（`generate_sphere`）
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SphereLayout {
+    pub seed: u64,
+    pub node_count: usize,
+    pub neighbors: usize,
+    pub extra_ratio: f32,
+}
+
+impl Default for SphereLayout {
+    fn default() -> Self { Self { seed: 1337, node_count: 240, neighbors: 4, extra_ratio: 0.1 } }
+}
+
+impl Layout for SphereLayout {
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) {
+        generate_sphere(self.seed, self.node_count, self.neighbors, self.extra_ratio)
+    }
+}
+
+/// 球内一様分布 + k-NN + ハブ + 長距離（`generate_cloud`）
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CloudLayout {
+    pub seed: u64,
+    pub node_count: usize,
+    pub radius: f32,
+    pub neighbors: usize,
+    pub extra_ratio: f32,
+    pub hub_ratio: f32,
+}
+
+impl Default for CloudLayout {
+    fn default() -> Self {
+        Self { seed: 1337, node_count: 240, radius: 1.2, neighbors: 4, extra_ratio: 0.1, hub_ratio: 0.05 }
+    }
+}
+
+impl Layout for CloudLayout {
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) {
+        generate_cloud(self.seed, self.node_count, self.radius, self.neighbors, self.extra_ratio, self.hub_ratio)
+    }
+}
+
+/// 2Dスパイン配置（`graph::generate`）を3Dへ持ち上げたもの。
+/// スパインは z=0 の背骨、サテライトは背骨から離れるほど奥行き方向へ広げる。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SpineLayout {
+    pub seed: u64,
+    pub node_count: usize,
+    pub spine_segments: usize,
+    /// 奥行きの最大幅（NDC相当の x/y に対する比）
+    pub depth: f32,
+}
+
+impl Default for SpineLayout {
+    fn default() -> Self { Self { seed: 1337, node_count: 240, spine_segments: 12, depth: 0.6 } }
+}
+
+impl Layout for SpineLayout {
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) {
+        let (nodes2, edges2) = graph::generate(self.seed, self.node_count, self.spine_segments);
+        let mut rng = Lcg::new(self.seed ^ 0x5EED_D1CE);
+        let nodes = nodes2.iter()
+            .map(|n| {
+                let [x, y] = n.pos;
+                let z = if n.level == 0 { 0.0 } else { rng.range_f32(-1.0, 1.0) * self.depth * x.abs().max(0.1) };
+                Node3 { id: n.id, pos: [x, y, z] }
+            })
+            .collect();
+        // 2D生成器のIDはインデックスと同一
+        let edges = edges2.iter()
+            .filter(|e| e.a != e.b)
+            .map(|e| Edge3 {
+                a: e.a,
+                b: e.b,
+                kind: match e.kind { EdgeKind::Spine => EdgeKind3::Mesh, EdgeKind::Link => EdgeKind3::Extra },
+            })
+            .collect();
+        (nodes, edges)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutError {
+    UnknownLayout { name: String, known: Vec<String> },
+    Parse { name: String, msg: String },
+    Invalid { name: String, msg: String },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLayout { name, known } => write!(f, "unknown layout \"{name}\" (available: {})", known.join(", ")),
+            Self::Parse { name, msg } => write!(f, "invalid params for layout \"{name}\": {msg}"),
+            Self::Invalid { name, msg } => write!(f, "layout \"{name}\": {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// JSONパラメータから Layout を作る関数。エラーは文字列で返し、レジストリが名前を付ける。
+pub type LayoutFactory = fn(&str) -> Result<Box<dyn Layout>, FactoryError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FactoryError {
+    Parse(String),
+    Invalid(String),
+}
+
+pub struct LayoutRegistry {
+    factories: BTreeMap<String, LayoutFactory>,
+}
+
+impl Default for LayoutRegistry {
+    fn default() -> Self { Self::with_builtins() }
+}
+
+impl LayoutRegistry {
+    pub fn empty() -> Self { Self { factories: BTreeMap::new() } }
+
+    /// 組み込み: "shells" / "sphere" / "cloud" / "spine"
+    pub fn with_builtins() -> Self {
+        let mut r = Self::empty();
+        r.register("shells", |json| {
+            let cfg: ShellConfig = parse_params(json)?;
+            cfg.validate().map_err(|e| FactoryError::Invalid(e.to_string()))?;
+            Ok(Box::new(cfg))
+        });
+        r.register("sphere", |json| {
+            let p: SphereLayout = parse_params(json)?;
+            check_node_count(p.node_count)?;
+            Ok(Box::new(p))
+        });
+        r.register("cloud", |json| {
+            let p: CloudLayout = parse_params(json)?;
+            check_node_count(p.node_count)?;
+            if !(p.radius.is_finite() && p.radius > 0.0) {
+                return Err(FactoryError::Invalid("radius must be a positive finite number".into()));
+            }
+            Ok(Box::new(p))
+        });
+        r.register("spine", |json| {
+            let p: SpineLayout = parse_params(json)?;
+            check_node_count(p.node_count)?;
+            if !(1..p.node_count).contains(&p.spine_segments) {
+                return Err(FactoryError::Invalid(format!(
+                    "spine_segments must be in 1..={}, got {}", p.node_count - 1, p.spine_segments
+                )));
+            }
+            if !(p.depth.is_finite() && (0.0..=2.0).contains(&p.depth)) {
+                return Err(FactoryError::Invalid("depth must be a finite number in 0..=2".into()));
+            }
+            Ok(Box::new(p))
+        });
+        r
+    }
+
+    /// 同名があれば置き換える
+    pub fn register(&mut self, name: &str, factory: LayoutFactory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    pub fn names(&self) -> Vec<String> { self.factories.keys().cloned().collect() }
+
+    /// `json` が空文字なら既定パラメータ（`{}` と同じ）
+    pub fn create(&self, name: &str, json: &str) -> Result<Box<dyn Layout>, LayoutError> {
+        let factory = self.factories.get(name)
+            .ok_or_else(|| LayoutError::UnknownLayout { name: name.to_string(), known: self.names() })?;
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        factory(json).map_err(|e| match e {
+            FactoryError::Parse(msg) => LayoutError::Parse { name: name.to_string(), msg },
+            FactoryError::Invalid(msg) => LayoutError::Invalid { name: name.to_string(), msg },
+        })
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(json: &str) -> Result<T, FactoryError> {
+    serde_json::from_str(json).map_err(|e| FactoryError::Parse(e.to_string()))
+}
+
+fn check_node_count(count: usize) -> Result<(), FactoryError> {
+    if (4..=DEFAULT_MAX_NODES).contains(&count) {
+        Ok(())
+    } else {
+        Err(FactoryError::Invalid(format!("node_count must be in 4..={DEFAULT_MAX_NODES}, got {count}")))
+    }
+}
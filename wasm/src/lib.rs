@@ -5,15 +5,80 @@ use wasm_bindgen::JsCast;
 #[cfg(target_arch = "wasm32")]
 use web_sys::{window, HtmlCanvasElement};
 #[cfg(target_arch = "wasm32")]
-use wgpu::util::DeviceExt;
+use crate::graph_data::{GraphDataLimits, LoadedGraph};
+#[cfg(target_arch = "wasm32")]
+use crate::recovery::{GraphStatus, SurfaceAction};
+#[cfg(target_arch = "wasm32")]
+use crate::backend::GraphBackend;
+#[cfg(target_arch = "wasm32")]
+use crate::shell_config::ShellConfig;
+#[cfg(target_arch = "wasm32")]
+use crate::graph3d::force::{ForceLayout, ForceLayoutConfig};
+#[cfg(target_arch = "wasm32")]
+use crate::picking::PickView;
+#[cfg(target_arch = "wasm32")]
+use crate::camera::{Gestures, OrbitCamera};
+#[cfg(target_arch = "wasm32")]
+use crate::graph3d::adjacency::Adjacency;
+#[cfg(target_arch = "wasm32")]
+use crate::graph3d::{check_allpairs, Edge3};
+#[cfg(target_arch = "wasm32")]
+use crate::highlight::{EmphasisFade, HighlightConfig};
+#[cfg(target_arch = "wasm32")]
+use crate::pulse::Pulse;
+#[cfg(target_arch = "wasm32")]
+use crate::audio::{AudioConfig, AudioEnvelope, AudioModulation};
+#[cfg(target_arch = "wasm32")]
+use crate::session::{LookTransition, SessionLook, SessionPresets, SessionState};
+#[cfg(target_arch = "wasm32")]
+use crate::easing::Easing;
+#[cfg(target_arch = "wasm32")]
+use crate::tween::{ParamField, ParamTweens, TweenCurve, PARAM_COUNT};
+#[cfg(target_arch = "wasm32")]
+use crate::timeline::{Playback, Timeline, TimelineAction, TimelineBase, TimelineClock};
+#[cfg(target_arch = "wasm32")]
+use crate::render::{allpairs_edges, build_edge_insts, build_node_insts, edge_style, EdgeInst, GraphScene, InstanceAttrs, MsaaLimits, NodeInst, SceneTargets, UGraph};
+#[cfg(target_arch = "wasm32")]
+use crate::params::{fit_modes_to_graph, AntialiasConfig, BloomConfig, ConfigIssue, ConfigReport, GraphConfig, LayoutSettings, ModeFlags, ParamsConfig};
+#[cfg(target_arch = "wasm32")]
+use crate::bloom::{BloomChain, HDR_FORMAT};
+#[cfg(target_arch = "wasm32")]
+use crate::postfx::{post_active, tune_post_pass, validate_post_passes, PostEffect, PostPass, PostStack};
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc, sync::atomic::{AtomicBool, Ordering}, sync::Arc};
 
 pub mod graph;
 pub mod graph3d;
+pub mod graph_data;
+pub mod highlight;
+pub mod pulse;
+pub mod audio;
+pub mod session;
+pub mod tween;
+pub mod timeline;
 pub mod shader_math;
 pub mod params;
+pub mod presets;
+pub mod render;
+pub mod bloom;
+pub mod postfx;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod offscreen;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod png;
+pub mod raster;
+pub mod camera;
+pub mod easing;
+pub mod layout;
+pub mod picking;
+mod rng;
+pub mod recovery;
+pub mod renderer_slot;
+pub mod backend;
+pub mod shell_config;
 
 #[cfg(target_arch = "wasm32")]
-const GRAPH_SHADER_SRC: &str = include_str!("graph_shader.wgsl");
+const GRAPH_SHADER_SRC: &str = crate::render::GRAPH_SHADER_WGSL;
 
 // Graph 3D デフォルト値（外部テストから参照できるよう公開）
 pub const DEFAULT_GRAPH_EDGE_THICKNESS: f32 = 0.006;
@@ -23,10 +88,10 @@ pub const DEFAULT_GRAPH_ROT_SPEED: f32 = 0.04; // 要望: 0.04
 pub const DEFAULT_GRAPH_FOG_START: f32 = 0.55;
 pub const DEFAULT_GRAPH_FOG_END: f32 = 0.95;
 pub const DEFAULT_GRAPH_FOG_STRENGTH: f32 = 0.8; // 要望: 0.8
-
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-struct QuadVertex { corner: [f32; 2] }
+pub const DEFAULT_GRAPH_LINK_ON: f32 = 0.80;
+pub const DEFAULT_GRAPH_LINK_OFF: f32 = 1.40;
+pub const DEFAULT_GRAPH_NUC_LINK_ON: f32 = 0.30;
+pub const DEFAULT_GRAPH_NUC_LINK_OFF: f32 = 2.00;
 
 // (network3d用では未使用のUniforms/2Dグリッド系は削除)
 
@@ -40,7 +105,7 @@ pub fn bootstrap() {
 // 2D/Alt/Wave用のステートは削除
 #[cfg(target_arch = "wasm32")]
 thread_local! {
-    static STATE_GRAPH: std::cell::RefCell<Option<GraphState>> = std::cell::RefCell::new(None);
+    static STATE_GRAPH: std::cell::RefCell<crate::renderer_slot::RendererSlot<GraphRenderer>> = const { std::cell::RefCell::new(crate::renderer_slot::RendererSlot::new()) };
 }
 
 // Wave/2D用のグリッドやパラメータは削除
@@ -48,52 +113,120 @@ thread_local! {
 // Alt/Wave用のステートは削除
 
 // ---- GRAPH effect (neon network graph) ----
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-struct UGraph {
-    view_proj: [[f32;4];4],
-    misc0: [f32;4], // time, edge_thickness, node_size, flow_speed
-    misc1: [f32;4], // aspect, fogStart, fogEnd, fogStrength
-    misc2: [f32;4], // link_on, link_off, reserved, reserved
-    misc3: [f32;4], // nuc_link_on, nuc_link_off, reserved, reserved
+#[cfg(target_arch = "wasm32")]
+struct GraphState {
+    canvas: HtmlCanvasElement,
+    gpu: Option<GraphGpu>, // None: デバイス喪失から復旧中（software 時は常に None）
+    software: Option<SoftwareCanvas>, // Some: WebGPU の無い環境向けの CPU 描画（`src/raster.rs`）
+    backend: GraphBackend, // 開始時に決めた経路（復旧時も同じバックエンドで取り直す）
+    pending_device: Option<PendingDevice>,
+    failed_attempts: u32,
+    retry_at_ms: Option<f32>, // None: 次フレームで即再取得
+    width: u32,
+    height: u32,
+    params: GraphParams,
+    edge_mode_allpairs: bool,
+    edge_mode_nucleus: bool,
+    allpairs_edges: Option<Vec<Edge3>>, // 全ペア表示中のエッジ（作り直しの時だけ作る）
+    shell_config: ShellConfig, // 最後に生成したシェル設定（プリセット or regenerate）
+    layout_settings: LayoutSettings, // 現在のグラフの生成元（get_graph_config 用）
+    graph: LoadedGraph,
+    data_limits: GraphDataLimits,
+    layout: Option<ForceLayout>, // 力学配置の途中経過（毎フレーム少しずつ進める）
+    last_frame: Option<(f32, [[f32; 4]; 4])>, // 最後に描いたフレームの (シェーダの時刻[s], view_proj)。ピッキング用
+    last_clock: Option<f32>, // 最後の frame の time_ms[s]（dt 用。タイムライン再生中はシェーダの時刻と異なる）
+    pointer: Option<PointerTracker>, // ホバー/クリックのコールバック登録時のみ
+    camera: OrbitCamera,
+    camera_controls: Option<CameraControls>, // None: 操作無効（自動回転のみ）
+    adjacency: Adjacency, // graph.edges から作る（グラフ差し替え時に更新）
+    highlight: Vec<usize>, // 強調する注目ノード（インデックス）。空なら強調なし
+    highlight_config: HighlightConfig,
+    edge_emphasis: EmphasisFade, // 描画中のエッジインスタンスと同じ並び
+    node_emphasis: EmphasisFade,
+    pulse: Option<Pulse>, // 発生中のパルス（新しく発生させると置き換える）
+    edge_pulse: Vec<[f32; 2]>, // エッジインスタンス毎の (起点側ホップ数, 向き)
+    audio: AudioEnvelope,
+    audio_config: AudioConfig,
+    flow_phase: f32, // フロー縞の位相（周期単位、0..1）。速度が変調されても縞が跳ばないよう積分する
+    session: SessionState,
+    session_presets: SessionPresets,
+    session_transition: Option<LookTransition>, // 状態遷移のクロスフェード中のみ
+    session_fade: (f32, Easing), // set_session_state の遷移時間（秒）と補間
+    tint: [f32; 4], // 色味（rgb へ寄せる量を a）
+    nucleus_presence: f32, // 核の表示フェード（0..1）。核モード中のみ意味を持つ
+    auto_pulse: crate::pulse::AutoPulse,
+    pulse_rng: crate::rng::Lcg, // 自動パルスの起点選び
+    tweens: ParamTweens, // params の項目毎のトゥイーン（frame で進める）
+    presets: crate::presets::PresetLibrary,
+    timeline: Option<TimelineRun>, // 読み込み中のタイムライン
+    bloom: BloomConfig, // 丸め済み。HDR ターゲットが使えない環境では従来の直接描画のまま
+    post: Vec<PostPass>, // 丸め済みのポストエフェクト列（この順に掛ける）
+    antialias: AntialiasConfig, // 丸め済み。MSAA のサンプル数はアダプタの対応範囲に下げて使う
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-struct EdgeInst { p1: [f32; 3], p2: [f32; 3], color: [f32; 4], params: [f32; 3] } // params: (curve_k, thickness_scale, is_nucleus)
+/// 読み込んだタイムラインと再生状態（`src/timeline.rs`）
+#[cfg(target_arch = "wasm32")]
+struct TimelineRun {
+    timeline: Timeline,
+    base: TimelineBase,
+    clock: TimelineClock, // フロー縞の位相とカメラ（飛行は読み込み時のグラフで決める）
+    playback: Playback,
+    pulse: Option<usize>, // 反映済みの pulse イベント
+    focus: Option<usize>, // 反映済みの focus / unfocus イベント
+    seeked: bool, // 読み込み/シーク直後は強調をフェードせずに揃える
+}
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-struct NodeInst { center: [f32; 3], size: f32, color: [f32; 4], phase: f32 }
+/// デバイスに紐づくGPU資源一式。デバイス喪失時はまるごと破棄して作り直す。
+#[cfg(target_arch = "wasm32")]
+struct GraphGpu {
+    // Surface<'static> は Instance を借用しないので所有して一緒に解放する
+    _instance: wgpu::Instance,
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    lost: Arc<AtomicBool>,
+    scene: GraphScene, // パイプラインとインスタンス（オフスクリーン描画と共用）
+    scene_targets: SceneTargets, // 深度と MSAA のカラー（シーンの描画先とサンプル数に合わせる）
+    msaa_limits: MsaaLimits,
+    bloom: Option<BloomChain>, // ブルーム有効時のみ（HDR ターゲットと縮小段）
+    hdr_supported: bool,
+    post: Option<PostStack>, // 有効なポストエフェクトがある時のみ（中間テクスチャ）
+}
 
+/// 非同期に取得するデバイスとサーフェス（初回生成と復旧で共用）
 #[cfg(target_arch = "wasm32")]
-struct GraphState {
-    _instance: &'static wgpu::Instance,
+struct GpuDevice {
+    instance: wgpu::Instance,
     surface: wgpu::Surface<'static>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
-    pipe_edge: wgpu::RenderPipeline,
-    pipe_node: wgpu::RenderPipeline,
-    bind: wgpu::BindGroup,
-    ubo: wgpu::Buffer,
-    depth_tex: wgpu::Texture,
-    depth_view: wgpu::TextureView,
-    quad_vbuf: wgpu::Buffer,
-    edge_buf: wgpu::Buffer,
-    node_buf: wgpu::Buffer,
-    edge_count: u32,
-    node_count: u32,
-    params: GraphParams,
-    edge_mode_allpairs: bool,
-    edge_mode_nucleus: bool,
-    shell_profile: u32, // 0: default, 1: inner-heavy
+    lost: Arc<AtomicBool>,
+    hdr_supported: bool, // `bloom::HDR_FORMAT` に描けるか（WebGL2 は拡張次第）
+    msaa_limits: MsaaLimits,
 }
 
+#[cfg(target_arch = "wasm32")]
+type PendingDevice = Rc<RefCell<Option<Result<GpuDevice, String>>>>;
+
 #[cfg(target_arch = "wasm32")]
 #[derive(Clone, Copy)]
 struct GraphParams { edge_thickness: f32, node_size: f32, flow_speed: f32, rot_speed: f32, fog_start: f32, fog_end: f32, fog_strength: f32, link_on: f32, link_off: f32, nuc_link_on: f32, nuc_link_off: f32 }
 
+/// トゥイーン用の配列表現（`tween::ParamField` の順）
+#[cfg(target_arch = "wasm32")]
+impl GraphParams {
+    fn to_array(self) -> [f32; PARAM_COUNT] {
+        [self.edge_thickness, self.node_size, self.flow_speed, self.rot_speed, self.fog_start, self.fog_end, self.fog_strength, self.link_on, self.link_off, self.nuc_link_on, self.nuc_link_off]
+    }
+
+    fn from_array(v: [f32; PARAM_COUNT]) -> Self {
+        let [edge_thickness, node_size, flow_speed, rot_speed, fog_start, fog_end, fog_strength, link_on, link_off, nuc_link_on, nuc_link_off] = v;
+        Self { edge_thickness, node_size, flow_speed, rot_speed, fog_start, fog_end, fog_strength, link_on, link_off, nuc_link_on, nuc_link_off }
+    }
+}
+
 
 // (removed 2D start)
 
@@ -120,615 +253,2191 @@ struct GraphParams { edge_thickness: f32, node_size: f32, flow_speed: f32, rot_s
 /* removed compute_view_proj() */
 
 #[cfg(target_arch = "wasm32")]
-fn compute_view_proj_graph(angle: f32, aspect: f32) -> [[f32;4];4] {
-    let radius = 3.0;
-    let eye = glam::Vec3::new(angle.cos()*radius, 0.9, angle.sin()*radius);
-    let target = glam::Vec3::ZERO;
-    let up = glam::Vec3::Y;
-    let view = glam::Mat4::look_at_rh(eye, target, up);
-    let proj = glam::Mat4::perspective_rh(45.0f32.to_radians(), aspect.max(0.1), 0.1, 100.0);
-    (proj * view).to_cols_array_2d()
+fn compute_view_proj_graph(camera: &OrbitCamera, aspect: f32) -> [[f32;4];4] {
+    camera.view_proj(aspect)
 }
 
 /* removed frame() */
 
 // ===================== GRAPH (neon network) =====================
+/// キャンバス1枚分のグラフ描画器。状態を自身で保持するため、1ページに複数生成できる。
+/// `dispose()` 後の呼び出しはエラーを返す。
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
-pub async fn start_graph(canvas_id: String) -> Result<(), JsValue> {
-    use crate::graph3d::{generate_shells, EdgeKind3};
-    use crate::graph::{theme_edge_color, theme_node_color};
+pub struct GraphRenderer {
+    state: Option<GraphState>, // None: disposed
+}
 
-    let doc = window().unwrap().document().unwrap();
-    let canvas = doc
-        .get_element_by_id(&canvas_id)
-        .ok_or_else(|| JsValue::from_str("canvas not found"))?
-        .dyn_into::<HtmlCanvasElement>()?;
-    let width = canvas.client_width() as u32;
-    let height = canvas.client_height() as u32;
-    canvas.set_width(width.max(1));
-    canvas.set_height(height.max(1));
-
-    let instance: &'static wgpu::Instance = Box::leak(Box::new(
-        wgpu::Instance::new(&wgpu::InstanceDescriptor::default()),
-    ));
-    let surface = instance
-        .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
-        .map_err(|e| JsValue::from_str(&format!("create_surface failed: {e}")))?;
-    let adapter = instance
-        .request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        })
-        .await
-        .map_err(|e| JsValue::from_str(&format!("request_adapter failed: {e}")))?;
-    let (device, queue) = adapter
-        .request_device(&wgpu::DeviceDescriptor {
-            label: Some("device"),
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
-            memory_hints: wgpu::MemoryHints::Performance,
-            trace: wgpu::Trace::Off,
-        })
-        .await
-        .map_err(|e| JsValue::from_str(&format!("request_device failed: {e}")))?;
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl GraphRenderer {
+    /// `await GraphRenderer.new(canvas)` で生成する（WebGPU初期化は非同期）
+    pub async fn new(canvas: HtmlCanvasElement) -> Result<GraphRenderer, JsValue> {
+        let state = GraphState::new(canvas).await?;
+        Ok(GraphRenderer { state: Some(state) })
+    }
 
-    let caps = surface.get_capabilities(&adapter);
-    let format = caps
-        .formats
-        .iter()
-        .copied()
-        .find(|f| f.is_srgb())
-        .unwrap_or(caps.formats[0]);
-    let preferred_alpha_modes = [
-        wgpu::CompositeAlphaMode::PreMultiplied,
-        wgpu::CompositeAlphaMode::PostMultiplied,
-        wgpu::CompositeAlphaMode::Inherit,
-    ];
-    let alpha_mode = preferred_alpha_modes
-        .into_iter()
-        .find(|mode| caps.alpha_modes.iter().any(|candidate| candidate == mode))
-        .unwrap_or(caps.alpha_modes[0]);
+    /// WebGPU の無いブラウザ向け。CPU で描いて Canvas2D へ転送する（同じ API がそのまま使える）
+    pub fn new_software(canvas: HtmlCanvasElement) -> Result<GraphRenderer, JsValue> {
+        let state = GraphState::new_software(canvas)?;
+        Ok(GraphRenderer { state: Some(state) })
+    }
 
-    let config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format,
-        width,
-        height,
-        present_mode: wgpu::PresentMode::Fifo,
-        alpha_mode,
-        view_formats: vec![],
-        desired_maximum_frame_latency: 2,
-    };
-    surface.configure(&device, &config);
-
-    // uniforms & params (デフォルトを定数化)
-    let params = GraphParams {
-        edge_thickness: DEFAULT_GRAPH_EDGE_THICKNESS,
-        node_size: DEFAULT_GRAPH_NODE_SIZE,
-        flow_speed: DEFAULT_GRAPH_FLOW_SPEED,
-        rot_speed: DEFAULT_GRAPH_ROT_SPEED,
-        fog_start: DEFAULT_GRAPH_FOG_START,
-        fog_end: DEFAULT_GRAPH_FOG_END,
-        fog_strength: DEFAULT_GRAPH_FOG_STRENGTH,
-        link_on: 0.80,
-        link_off: 1.40,
-        nuc_link_on: 0.30,
-        nuc_link_off: 2.00,
-    };
-    let aspect = (width.max(1) as f32) / (height.max(1) as f32);
-    let vp = compute_view_proj_graph(0.0, aspect);
-    let u_init = UGraph { view_proj: vp, misc0: [0.0, params.edge_thickness, params.node_size, params.flow_speed], misc1: [aspect, params.fog_start, params.fog_end, params.fog_strength], misc2: [params.link_on, params.link_off, 0.0, 0.0], misc3: [params.nuc_link_on, params.nuc_link_off, 0.0, 0.0] };
-    let ubo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("graph_ubo"),
-        contents: bytemuck::bytes_of(&u_init),
-        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-    });
-    let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("graph_bgl"),
-        entries: &[wgpu::BindGroupLayoutEntry {
-            binding: 0,
-            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
-            count: None,
-        }],
-    });
-    let bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("graph_bind"),
-        layout: &bgl,
-        entries: &[wgpu::BindGroupEntry { binding: 0, resource: ubo.as_entire_binding() }],
-    });
+    /// CPU 描画（`new_software`）なら true。破棄後は false
+    pub fn is_software(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.software.is_some())
+    }
 
-    // quad corners
-    let quad_data = [
-        QuadVertex { corner: [-0.5, -0.5] },
-        QuadVertex { corner: [ 0.5, -0.5] },
-        QuadVertex { corner: [-0.5,  0.5] },
-        QuadVertex { corner: [ 0.5,  0.5] },
-    ];
-    let quad_vbuf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("graph_quad"),
-        contents: bytemuck::cast_slice(&quad_data),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
+    /// "webgpu" | "webgl2"（`webgl` feature でのフォールバック）| "software"
+    pub fn backend(&mut self) -> Result<String, JsValue> {
+        Ok(self.state()?.backend.as_str().to_string())
+    }
 
-    // generate 3D layout (multi-shells)
-    let radii = [0.6, 0.8, 1.0, 1.2, 1.4];
-    let probs = [0.15, 0.20, 0.30, 0.20, 0.15];
-    let (nodes, edges) = generate_shells(1337, 240, &radii, &probs, 4, 1, 0.15, 0.05);
-    let mut edge_insts: Vec<EdgeInst> = Vec::with_capacity(edges.len()*3);
-    for e in &edges {
-        let a = nodes[e.a as usize].pos;
-        let b = nodes[e.b as usize].pos;
-        let col = theme_edge_color(e.kind);
-        // 同シェル/異シェルで太さ・明度を調整
-        let (ts_base, col_scale) = match e.kind {
-            EdgeKind3::Mesh => (0.85f32, 0.85f32), // 同シェルは少し細く/薄く
-            EdgeKind3::Extra => (1.00f32, 1.00f32), // 異シェルは基準
-        };
-        let col = [col[0]*col_scale, col[1]*col_scale, col[2]*col_scale, col[3]];
-        // 直線（k=0, ts=ts_base）
-        edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, ts_base, 0.0] });
-        // 曲線は全結線モードでは抑制（描画負荷を下げる）
-        if false { // placeholder; start_graph 初期構築では常に直線のみ（後で再構築関数で分岐）
-            let ra = (a[0]*a[0]+a[1]*a[1]+a[2]*a[2]).sqrt();
-            let rb = (b[0]*b[0]+b[1]*b[1]+b[2]*b[2]).sqrt();
-            let rm = 0.5*(ra+rb);
-            for (k, ts) in crate::graph::curve_variants_for_radius(rm) {
-                edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [k, ts*ts_base, 0.0] });
-            }
+    pub fn frame(&mut self, time_ms: f32) -> Result<(), JsValue> {
+        self.state()?.frame(time_ms)
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), JsValue> {
+        self.state()?.resize(width, height);
+        Ok(())
+    }
+
+    pub fn set_params(&mut self, edge_thickness: f32, node_size: f32, flow_speed: f32) -> Result<(), JsValue> {
+        self.state()?.tween_params(edge_thickness, node_size, flow_speed, 0.0, TweenCurve::default());
+        Ok(())
+    }
+
+    pub fn set_rot_speed(&mut self, rot_speed: f32) -> Result<(), JsValue> {
+        self.state()?.tween_rot_speed(rot_speed, 0.0, TweenCurve::default());
+        Ok(())
+    }
+
+    pub fn set_fog(&mut self, start: f32, end: f32, strength: f32) -> Result<(), JsValue> {
+        self.state()?.tween_fog(start, end, strength, 0.0, TweenCurve::default());
+        Ok(())
+    }
+
+    pub fn set_link_fade(&mut self, link_on: f32, link_off: f32) -> Result<(), JsValue> {
+        self.state()?.tween_link_fade(false, link_on, link_off, 0.0, TweenCurve::default());
+        Ok(())
+    }
+
+    pub fn set_nucleus_fade(&mut self, link_on: f32, link_off: f32) -> Result<(), JsValue> {
+        self.state()?.tween_link_fade(true, link_on, link_off, 0.0, TweenCurve::default());
+        Ok(())
+    }
+
+    /// `set_params` と同じ値を `duration_s` 秒（0..=30）かけて動かす。`curve` はイージング名か "spring"（空文字は ease-in-out-cubic）
+    pub fn tween_params(&mut self, edge_thickness: f32, node_size: f32, flow_speed: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+        let curve = parse_curve(curve)?;
+        self.state()?.tween_params(edge_thickness, node_size, flow_speed, duration_s, curve);
+        Ok(())
+    }
+
+    pub fn tween_rot_speed(&mut self, rot_speed: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+        let curve = parse_curve(curve)?;
+        self.state()?.tween_rot_speed(rot_speed, duration_s, curve);
+        Ok(())
+    }
+
+    pub fn tween_fog(&mut self, start: f32, end: f32, strength: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+        let curve = parse_curve(curve)?;
+        self.state()?.tween_fog(start, end, strength, duration_s, curve);
+        Ok(())
+    }
+
+    pub fn tween_link_fade(&mut self, link_on: f32, link_off: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+        let curve = parse_curve(curve)?;
+        self.state()?.tween_link_fade(false, link_on, link_off, duration_s, curve);
+        Ok(())
+    }
+
+    pub fn tween_nucleus_fade(&mut self, link_on: f32, link_off: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+        let curve = parse_curve(curve)?;
+        self.state()?.tween_link_fade(true, link_on, link_off, duration_s, curve);
+        Ok(())
+    }
+
+    /// ノードは現在のグラフのまま、エッジのみ全結線 or 通常。
+    /// 全結線は `ALLPAIRS_MAX_NODES` ノードまで（超えるグラフではエラーで何も変えない）
+    pub fn set_allpairs(&mut self, enabled: bool) -> Result<(), JsValue> {
+        let st = self.state()?;
+        if enabled {
+            check_allpairs(st.graph.nodes.len()).map_err(|e| JsValue::from_str(&e))?;
         }
+        st.edge_mode_allpairs = enabled;
+        st.rebuild_instances();
+        Ok(())
     }
-    let mut node_insts: Vec<NodeInst> = Vec::with_capacity(nodes.len());
-    let phase_of = |i: usize| -> f32 {
-        let mut x = i as u32;
-        // xorshift-like hash
-        x ^= x >> 16; x = x.wrapping_mul(747796405); x ^= x >> 16; x = x.wrapping_mul(2891336453); x ^= x >> 16;
-        (x as f32) / (u32::MAX as f32) * std::f32::consts::TAU
-    };
-    for (i,n) in nodes.iter().enumerate() {
-        let size = 1.0 + if i % 29 == 0 { 0.6 } else { 0.0 }; // たまに大きいハブ
-        // 色入替テーマに従う（ハブ→青、通常→マゼンタ）
-        let col = theme_node_color(i);
-        let phase = phase_of(i);
-        node_insts.push(NodeInst { center: n.pos, size, color: col, phase });
-    }
-
-    let edge_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("edge_buf"),
-        contents: bytemuck::cast_slice(&edge_insts),
-        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-    });
-    let node_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("node_buf"),
-        contents: bytemuck::cast_slice(&node_insts),
-        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-    });
 
-    // shader + pipelines
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("graph_shader"),
-        source: wgpu::ShaderSource::Wgsl(GRAPH_SHADER_SRC.into()),
-    });
-    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("graph_layout"),
-        bind_group_layouts: &[&bgl],
-        push_constant_ranges: &[],
-    });
-    let additive = wgpu::BlendState {
-        color: wgpu::BlendComponent { src_factor: wgpu::BlendFactor::One, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add },
-        alpha: wgpu::BlendComponent { src_factor: wgpu::BlendFactor::One, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add },
-    };
-    // edge pipeline
-    let edge_layouts = [
-        wgpu::VertexBufferLayout { // corners
-            array_stride: std::mem::size_of::<QuadVertex>() as u64,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute { shader_location: 0, format: wgpu::VertexFormat::Float32x2, offset: 0 }],
-        },
-        wgpu::VertexBufferLayout { // EdgeInst
-            array_stride: std::mem::size_of::<EdgeInst>() as u64,
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute { shader_location: 1, format: wgpu::VertexFormat::Float32x3, offset: 0 },
-                wgpu::VertexAttribute { shader_location: 2, format: wgpu::VertexFormat::Float32x3, offset: 12 },
-                wgpu::VertexAttribute { shader_location: 3, format: wgpu::VertexFormat::Float32x4, offset: 24 },
-                wgpu::VertexAttribute { shader_location: 4, format: wgpu::VertexFormat::Float32x3, offset: 40 },
-            ],
-        },
-    ];
-    let depth_fmt = wgpu::TextureFormat::Depth32Float;
-    let pipe_edge = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("pipe_edge"),
-        layout: Some(&layout),
-        vertex: wgpu::VertexState { module: &shader, entry_point: Some("vs_edge"), buffers: &edge_layouts, compilation_options: wgpu::PipelineCompilationOptions::default() },
-        fragment: Some(wgpu::FragmentState { module: &shader, entry_point: Some("fs_edge"), targets: &[Some(wgpu::ColorTargetState { format: config.format, blend: Some(additive), write_mask: wgpu::ColorWrites::ALL })], compilation_options: wgpu::PipelineCompilationOptions::default() }),
-        primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, strip_index_format: None, unclipped_depth: false, polygon_mode: wgpu::PolygonMode::Fill, conservative: false, cull_mode: None, front_face: wgpu::FrontFace::Ccw },
-        depth_stencil: Some(wgpu::DepthStencilState { format: depth_fmt, depth_write_enabled: false, depth_compare: wgpu::CompareFunction::LessEqual, stencil: wgpu::StencilState::default(), bias: wgpu::DepthBiasState::default() }),
-        multisample: wgpu::MultisampleState::default(),
-        multiview: None,
-        cache: None,
-    });
-    // node pipeline
-    let node_layouts = [
-        wgpu::VertexBufferLayout { // corners
-            array_stride: std::mem::size_of::<QuadVertex>() as u64,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute { shader_location: 0, format: wgpu::VertexFormat::Float32x2, offset: 0 }],
-        },
-        wgpu::VertexBufferLayout { // NodeInst
-            array_stride: std::mem::size_of::<NodeInst>() as u64,
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute { shader_location: 1, format: wgpu::VertexFormat::Float32x3, offset: 0 },
-                wgpu::VertexAttribute { shader_location: 2, format: wgpu::VertexFormat::Float32,   offset: 12 },
-                wgpu::VertexAttribute { shader_location: 3, format: wgpu::VertexFormat::Float32x4, offset: 16 },
-                wgpu::VertexAttribute { shader_location: 4, format: wgpu::VertexFormat::Float32,   offset: 32 },
-            ],
-        },
-    ];
-    let pipe_node = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("pipe_node"),
-        layout: Some(&layout),
-        vertex: wgpu::VertexState { module: &shader, entry_point: Some("vs_node"), buffers: &node_layouts, compilation_options: wgpu::PipelineCompilationOptions::default() },
-        fragment: Some(wgpu::FragmentState { module: &shader, entry_point: Some("fs_node"), targets: &[Some(wgpu::ColorTargetState { format: config.format, blend: Some(additive), write_mask: wgpu::ColorWrites::ALL })], compilation_options: wgpu::PipelineCompilationOptions::default() }),
-        primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, strip_index_format: None, unclipped_depth: false, polygon_mode: wgpu::PolygonMode::Fill, conservative: false, cull_mode: None, front_face: wgpu::FrontFace::Ccw },
-        depth_stencil: Some(wgpu::DepthStencilState { format: depth_fmt, depth_write_enabled: false, depth_compare: wgpu::CompareFunction::LessEqual, stencil: wgpu::StencilState::default(), bias: wgpu::DepthBiasState::default() }),
-        multisample: wgpu::MultisampleState::default(),
-        multiview: None,
-        cache: None,
-    });
+    pub fn set_nucleus(&mut self, enabled: bool) -> Result<(), JsValue> {
+        let st = self.state()?;
+        st.stop_session_transition();
+        st.nucleus_presence = 1.0;
+        st.edge_mode_nucleus = enabled;
+        st.rebuild_instances();
+        Ok(())
+    }
 
-    // Depth texture
-    let depth_tex = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("depth_tex_graph"),
-        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: depth_fmt,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        view_formats: &[],
-    });
-    let depth_view = depth_tex.create_view(&wgpu::TextureViewDescriptor::default());
-
-    STATE_GRAPH.with(|s| {
-        *s.borrow_mut() = Some(GraphState {
-            _instance: instance, surface, device, queue, config,
-            pipe_edge, pipe_node, bind, ubo,
-            depth_tex, depth_view,
-            quad_vbuf, edge_buf, node_buf,
-            edge_count: edge_insts.len() as u32,
-            node_count: node_insts.len() as u32,
-            params,
-            edge_mode_allpairs: false,
-            edge_mode_nucleus: false,
-            shell_profile: 0,
-        });
-    });
+    /// 0: default (balanced), 1: inner-heavy（`ShellConfig::preset` のプリセット）。
+    /// 常にシェルを作り直す（読み込んだデータ・他のレイアウト・力学配置からも同じプロファイルで戻せる）
+    pub fn set_shell_profile(&mut self, profile: u32) -> Result<(), JsValue> {
+        self.state()?.apply_shell_config(ShellConfig::preset(profile));
+        Ok(())
+    }
 
-    Ok(())
-}
+    /// シェル生成の全入力（JSON）を指定してグラフを作り直す。省略フィールドは既定プリセットの値。
+    /// 形式: `{ "seed", "node_count", "shells": [{ "radius", "weight" }], "k_intra", "cross_adj", "cross_long_ratio", "hub_ratio" }`
+    pub fn regenerate(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let config = crate::shell_config::parse_shell_config(config_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        st.apply_shell_config(config);
+        Ok(())
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn frame_graph(time_ms: f32) -> Result<(), JsValue> {
-    STATE_GRAPH.with(|s| -> Result<(), JsValue> {
-        let mut borrow = s.borrow_mut();
-        let st = borrow.as_mut().ok_or_else(|| JsValue::from_str("not started"))?;
-        let t = time_ms * 0.001;
-        let aspect = (st.config.width.max(1) as f32) / (st.config.height.max(1) as f32);
-        let vp = compute_view_proj_graph(t * st.params.rot_speed, aspect);
-        let u = UGraph { view_proj: vp, misc0: [t, st.params.edge_thickness, st.params.node_size, st.params.flow_speed], misc1: [aspect, st.params.fog_start, st.params.fog_end, st.params.fog_strength], misc2: [st.params.link_on, st.params.link_off, 0.0, 0.0], misc3: [st.params.nuc_link_on, st.params.nuc_link_off, 0.0, 0.0] };
-        st.queue.write_buffer(&st.ubo, 0, bytemuck::bytes_of(&u));
-
-        let surface_tex = match st.surface.get_current_texture() {
-            Ok(t) => t,
-            Err(_) => {
-                st.surface.configure(&st.device, &st.config);
-                st.surface.get_current_texture().map_err(|e| JsValue::from_str(&format!("surface acquire failed: {e}")))?
-            }
+    /// 呼び出し側のグラフ（JSON）を読み込み、描画バッファを再構築する。
+    /// 形式: `{ "nodes": [{ "id", "pos"?, "category"? }], "edges": [{ "a", "b", "kind"?, "weight"? }] }`
+    pub fn set_data(&mut self, json: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let graph = crate::graph_data::load_graph_json(json, st.data_limits, st.shell_config.seed)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        st.set_graph(graph);
+        st.layout_settings = LayoutSettings { name: crate::params::DATA_LAYOUT_NAME.to_string(), params: serde_json::Value::Null };
+        Ok(())
+    }
+
+    /// 登録済みレイアウト（"shells" / "sphere" / "cloud" / "spine"）でグラフを作り直す。
+    /// `params_json` は各レイアウトのパラメータ（空文字なら既定値）。"shells" は `regenerate` と同じ扱い。
+    pub fn set_layout(&mut self, name: &str, params_json: &str) -> Result<(), JsValue> {
+        let params = if params_json.trim().is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_str(params_json).map_err(|e| JsValue::from_str(&format!("invalid layout params json: {e}")))?
         };
-        let view = surface_tex.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let mut enc = st.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("enc_graph") });
-        {
-        let mut rp = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("rpass_graph"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: {
-                    let c = crate::graph::graph_clear_color_srgb();
-                    let a = crate::graph::graph_clear_alpha();
-                    wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: c[0] as f64, g: c[1] as f64, b: c[2] as f64, a: a as f64 }), store: wgpu::StoreOp::Store }
-                },
-            })],
-            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &st.depth_view,
-                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
-                stencil_ops: None,
-            }),
-            occlusion_query_set: None,
-            timestamp_writes: None,
-        });
-            // edges
-            rp.set_pipeline(&st.pipe_edge);
-            rp.set_bind_group(0, &st.bind, &[]);
-            rp.set_vertex_buffer(0, st.quad_vbuf.slice(..));
-            rp.set_vertex_buffer(1, st.edge_buf.slice(..));
-            rp.draw(0..4, 0..st.edge_count);
-            // nodes
-            rp.set_pipeline(&st.pipe_node);
-            rp.set_bind_group(0, &st.bind, &[]);
-            rp.set_vertex_buffer(0, st.quad_vbuf.slice(..));
-            rp.set_vertex_buffer(1, st.node_buf.slice(..));
-            rp.draw(0..4, 0..st.node_count);
-        }
-        st.queue.submit(Some(enc.finish()));
-        surface_tex.present();
+        let st = self.state()?;
+        let layout = st.prepare_layout(&LayoutSettings { name: name.to_string(), params }).map_err(|e| JsValue::from_str(&e))?;
+        st.install_layout(layout);
         Ok(())
-    })
-}
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn resize_graph(width: u32, height: u32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            if st.config.width != width || st.config.height != height {
-                st.config.width = width.max(1);
-                st.config.height = height.max(1);
-                st.surface.configure(&st.device, &st.config);
-                // recreate depth buffer
-                let depth_tex = st.device.create_texture(&wgpu::TextureDescriptor {
-                    label: Some("depth_tex_graph"),
-                    size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Depth32Float,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                    view_formats: &[],
-                });
-                st.depth_view = depth_tex.create_view(&wgpu::TextureViewDescriptor::default());
-                st.depth_tex = depth_tex;
-            }
-        }
-    });
-}
+    /// 設定一式（`src/params.rs` の `GraphConfig` JSON: `{ "params", "modes", "bloom", "post", "antialias", "layout"? }`）を検証して適用する。
+    /// 戻り値は検証結果の JSON `{ ok, errors: [{ path, message }], warnings, config }`。
+    /// errors があれば何も変えない。範囲外の値は丸めて適用し warnings に載せる。`layout` を省略すると現在のグラフを保つ。
+    pub fn set_graph_config(&mut self, json: &str) -> Result<String, JsValue> {
+        let st = self.state()?;
+        let report = match crate::params::parse_graph_config(json) {
+            Ok((config, warnings)) => st.apply_config(config, warnings),
+            Err(errors) => ConfigReport::failed(errors),
+        };
+        report_json(&report)
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph_params(edge_thickness: f32, node_size: f32, flow_speed: f32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            st.params.edge_thickness = edge_thickness.max(0.0005).min(0.05);
-            st.params.node_size = node_size.max(0.01).min(0.3);
-            st.params.flow_speed = flow_speed.max(0.1).min(5.0);
-        }
-    });
-}
+    /// 実効設定（丸め済み）の JSON。トゥイーン/状態遷移中はその目標値。`set_graph_config` にそのまま渡せる
+    pub fn get_graph_config(&mut self) -> Result<String, JsValue> {
+        let config = self.state()?.config();
+        serde_json::to_string(&config).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_params(rot_speed: f32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            st.params.rot_speed = rot_speed;
-        }
-    });
-}
+    /// プリセット名。組み込み（"calm-background" / "active-research" / "focus" / "presentation"）の後に独自プリセットを名前順で
+    pub fn preset_names(&mut self) -> Result<Vec<String>, JsValue> {
+        Ok(self.state()?.presets.names())
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_fog(start: f32, end: f32, strength: f32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            let mut fs = start.clamp(0.0, 1.0);
-            let mut fe = end.clamp(0.0, 1.0);
-            if fs > fe { std::mem::swap(&mut fs, &mut fe); }
-            st.params.fog_start = fs;
-            st.params.fog_end = fe;
-            st.params.fog_strength = strength.max(0.0);
-        }
-    });
-}
+    /// 名前付きプリセットを `set_graph_config` と同じく適用し、検証結果の JSON を返す
+    pub fn load_preset(&mut self, name: &str) -> Result<String, JsValue> {
+        let st = self.state()?;
+        let config = st.presets.get(name).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let (config, warnings) = crate::params::validate_graph_config(config);
+        report_json(&st.apply_config(config, warnings))
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_allpairs(enabled: bool) {
-    // 再生成: ノードは同一ルール、エッジのみ全結線 or 通常
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            // 再生成（CPU側）
-            let radii = [0.6, 0.8, 1.0, 1.2, 1.4];
-            let probs_default = [0.15, 0.20, 0.30, 0.20, 0.15];
-            let probs_inner   = [0.34, 0.26, 0.20, 0.12, 0.08];
-            let probs = if st.shell_profile==1 { &probs_inner } else { &probs_default };
-            let (nodes, base_edges) = crate::graph3d::generate_shells(1337, 240, &radii, probs, 4, 1, 0.15, 0.05);
-            let edges = if enabled { crate::graph3d::build_all_pairs_edges(&nodes) } else { base_edges };
-            // インスタンス列を再構築（全結線モードでは直線のみ）
-            use crate::graph::{theme_edge_color};
-            let mut edge_insts: Vec<EdgeInst> = Vec::with_capacity(edges.len() + nodes.len() + 1);
-            for e in &edges {
-                let a = nodes[e.a as usize].pos;
-                let b = nodes[e.b as usize].pos;
-                let base_col = theme_edge_color(e.kind);
-                let (ts_base, col_scale) = match e.kind {
-                    crate::graph3d::EdgeKind3::Mesh => (0.85f32, 0.85f32),
-                    crate::graph3d::EdgeKind3::Extra => (1.00f32, 1.00f32),
-                };
-                let col = [base_col[0]*col_scale, base_col[1]*col_scale, base_col[2]*col_scale, base_col[3]];
-                edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, ts_base, 0.0] });
-            }
-            // overlay nucleus edges if mode is enabled
-            if st.edge_mode_nucleus {
-                let center = [0.0f32, 0.0, 0.0];
-                let segs = crate::graph3d::build_nucleus_segments(&nodes, center);
-                let col = theme_edge_color(crate::graph3d::EdgeKind3::Extra);
-                for (a,b) in segs { edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, 0.90, 1.0] }); }
-            }
-            // ノードは色/サイズ計算を再実行
-            let mut node_insts: Vec<NodeInst> = Vec::with_capacity(nodes.len());
-            let phase_of = |i: usize| -> f32 {
-                let mut x = i as u32;
-                x ^= x >> 16; x = x.wrapping_mul(747796405); x ^= x >> 16; x = x.wrapping_mul(2891336453); x ^= x >> 16;
-                (x as f32) / (u32::MAX as f32) * std::f32::consts::TAU
-            };
-            for (i,n) in nodes.iter().enumerate() {
-                let size = 1.0 + if i % 29 == 0 { 0.6 } else { 0.0 };
-                let col = crate::graph::theme_node_color(i);
-                let phase = phase_of(i);
-                node_insts.push(NodeInst { center: n.pos, size, color: col, phase });
-            }
-            // GPUバッファ更新
-            st.edge_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("edge_buf"),
-                contents: bytemuck::cast_slice(&edge_insts),
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            });
-            st.node_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("node_buf"),
-                contents: bytemuck::cast_slice(&node_insts),
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            });
-            st.edge_count = edge_insts.len() as u32;
-            st.node_count = node_insts.len() as u32;
-            st.edge_mode_allpairs = enabled;
-        }
-    });
-}
+    /// 現在の実効設定（`get_graph_config` と同じ）を独自プリセット `name` として保存する（同名は上書き）
+    pub fn save_preset(&mut self, name: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let config = st.config();
+        st.presets.register(name, config).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_link_fade(link_on: f32, link_off: f32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            let mut on = link_on.max(0.0);
-            let mut off = link_off.max(0.0);
-            if on > off { std::mem::swap(&mut on, &mut off); }
-            st.params.link_on = on;
-            st.params.link_off = off;
-        }
-    });
-}
+    /// `set_graph_config` と同じ形式の JSON を独自プリセットとして登録する（範囲外の値は丸めて保存）
+    pub fn register_preset(&mut self, name: &str, config_json: &str) -> Result<(), JsValue> {
+        let (config, _) = crate::params::parse_graph_config(config_json).map_err(|errors| {
+            let msgs: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+            JsValue::from_str(&msgs.join("; "))
+        })?;
+        self.state()?.presets.register(name, config).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_nucleus_fade(link_on: f32, link_off: f32) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            let mut on = link_on.max(0.0);
-            let mut off = link_off.max(0.0);
-            if on > off { std::mem::swap(&mut on, &mut off); }
-            st.params.nuc_link_on = on;
-            st.params.nuc_link_off = off;
-        }
-    });
-}
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_nucleus(enabled: bool) {
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            // 再生成（CPU側）ノードのみ取得
-            const RADII: [f32;5] = [0.6, 0.8, 1.0, 1.2, 1.4];
-            const PROBS_DEFAULT: [f32;5] = [0.15, 0.20, 0.30, 0.20, 0.15];
-            const PROBS_INNER: [f32;5]   = [0.34, 0.26, 0.20, 0.12, 0.08];
-            let probs = if st.shell_profile==1 { &PROBS_INNER } else { &PROBS_DEFAULT };
-            let (nodes, base_edges) = crate::graph3d::generate_shells(1337, 240, &RADII, probs, 4, 1, 0.15, 0.05);
-            let edges = if st.edge_mode_allpairs { crate::graph3d::build_all_pairs_edges(&nodes) } else { base_edges };
-            // ベースエッジ
-            use crate::graph::theme_edge_color;
-            let mut edge_insts: Vec<EdgeInst> = Vec::with_capacity(edges.len() + nodes.len() + 1);
-            for e in &edges {
-                let a = nodes[e.a as usize].pos;
-                let b = nodes[e.b as usize].pos;
-                let base_col = theme_edge_color(e.kind);
-                let (ts_base, col_scale) = match e.kind {
-                    crate::graph3d::EdgeKind3::Mesh => (0.85f32, 0.85f32),
-                    crate::graph3d::EdgeKind3::Extra => (1.00f32, 1.00f32),
-                };
-                let col = [base_col[0]*col_scale, base_col[1]*col_scale, base_col[2]*col_scale, base_col[3]];
-                edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, ts_base, 0.0] });
-            }
-            // Nucleus overlay
-            let center = [0.0f32, 0.0, 0.0];
-            if enabled {
-                let segs = crate::graph3d::build_nucleus_segments(&nodes, center);
-                let col = theme_edge_color(crate::graph3d::EdgeKind3::Extra);
-                for (a,b) in segs { edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, 0.90, 1.0] }); }
-            }
-            // ノード: 既存ノード + 中央核ノード（大きめ、マゼンタ）
-            let mut node_insts: Vec<NodeInst> = Vec::with_capacity(nodes.len()+1);
-            let phase_of = |i: usize| -> f32 { let mut x=i as u32; x^=x>>16; x=x.wrapping_mul(747796405); x^=x>>16; x=x.wrapping_mul(2891336453); x^=x>>16; (x as f32)/(u32::MAX as f32)*std::f32::consts::TAU };
-            for (i,n) in nodes.iter().enumerate() {
-                let size = 1.0 + if i % 29 == 0 { 0.6 } else { 0.0 };
-                let coln = crate::graph::theme_node_color(i);
-                node_insts.push(NodeInst { center: n.pos, size, color: coln, phase: phase_of(i) });
-            }
-            // nucleus node（サイズ大/マゼンタ）
-            if enabled {
-                let nuc_color = crate::graph::palette_color(crate::graph::Palette::Magenta);
-                node_insts.push(NodeInst { center: center, size: 2.0, color: nuc_color, phase: 0.0 });
-            }
-            st.edge_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("edge_buf"), contents: bytemuck::cast_slice(&edge_insts), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
-            st.node_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("node_buf"), contents: bytemuck::cast_slice(&node_insts), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
-            st.edge_count = edge_insts.len() as u32;
-            st.node_count = node_insts.len() as u32;
-            st.edge_mode_nucleus = enabled;
-        }
-    });
-}
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn set_graph3d_shell_profile(profile: u32) {
-    // 0: default (balanced), 1: inner-heavy
-    const RADII: [f32;5] = [0.6, 0.8, 1.0, 1.2, 1.4];
-    const PROBS_DEFAULT: [f32;5] = [0.15, 0.20, 0.30, 0.20, 0.15];
-    const PROBS_INNER: [f32;5]   = [0.34, 0.26, 0.20, 0.12, 0.08];
-    let probs = if profile==1 { &PROBS_INNER } else { &PROBS_DEFAULT };
-    STATE_GRAPH.with(|s| {
-        if let Some(st) = s.borrow_mut().as_mut() {
-            st.shell_profile = if profile==1 { 1 } else { 0 };
-            let (nodes, base_edges) = crate::graph3d::generate_shells(1337, 240, &RADII, probs, 4, 1, 0.15, 0.05);
-            let edges = if st.edge_mode_allpairs { crate::graph3d::build_all_pairs_edges(&nodes) } else { base_edges };
-            // rebuild buffers
-            use crate::graph::{theme_edge_color, theme_node_color};
-            let mut edge_insts: Vec<EdgeInst> = Vec::with_capacity(edges.len() + nodes.len() + 1);
-            for e in &edges {
-                let a = nodes[e.a as usize].pos;
-                let b = nodes[e.b as usize].pos;
-                let base_col = theme_edge_color(e.kind);
-                let (ts_base, col_scale) = match e.kind {
-                    crate::graph3d::EdgeKind3::Mesh => (0.85f32, 0.85f32),
-                    crate::graph3d::EdgeKind3::Extra => (1.00f32, 1.00f32),
-                };
-                let col = [base_col[0]*col_scale, base_col[1]*col_scale, base_col[2]*col_scale, base_col[3]];
-                edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, ts_base, 0.0] });
-            }
-            if st.edge_mode_nucleus {
-                let center = [0.0f32, 0.0, 0.0];
-                let segs = crate::graph3d::build_nucleus_segments(&nodes, center);
-                let col = theme_edge_color(crate::graph3d::EdgeKind3::Extra);
-                for (a,b) in segs { edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, 0.90, 1.0] }); }
-            }
-            let mut node_insts: Vec<NodeInst> = Vec::with_capacity(nodes.len());
-            let phase_of = |i: usize| -> f32 {
-                let mut x = i as u32; x ^= x >> 16; x = x.wrapping_mul(747796405); x ^= x >> 16; x = x.wrapping_mul(2891336453); x ^= x >> 16;
-                (x as f32) / (u32::MAX as f32) * std::f32::consts::TAU
-            };
-            for (i,n) in nodes.iter().enumerate() {
-                let size = 1.0 + if i % 29 == 0 { 0.6 } else { 0.0 };
-                let col = theme_node_color(i);
-                let phase = phase_of(i);
-                node_insts.push(NodeInst { center: n.pos, size, color: col, phase });
+    /// 独自プリセットを消す。消したら true（組み込みは消せない）
+    pub fn remove_preset(&mut self, name: &str) -> Result<bool, JsValue> {
+        Ok(self.state()?.presets.remove(name))
+    }
+
+    /// プリセット（空文字なら現在の実効設定）を URL に載せられる短い文字列にする（`src/presets.rs`）
+    pub fn encode_preset(&mut self, name: &str) -> Result<String, JsValue> {
+        let st = self.state()?;
+        let config = if name.trim().is_empty() {
+            st.config()
+        } else {
+            st.presets.get(name).map_err(|e| JsValue::from_str(&e.to_string()))?
+        };
+        Ok(crate::presets::encode_preset(&config))
+    }
+
+    /// `encode_preset` の文字列を読み込んで適用し、検証結果の JSON を返す。壊れた文字列は errors に入る
+    pub fn load_preset_code(&mut self, code: &str) -> Result<String, JsValue> {
+        let st = self.state()?;
+        let report = match crate::presets::decode_preset(code) {
+            Ok(config) => {
+                let (config, warnings) = crate::params::validate_graph_config(config);
+                st.apply_config(config, warnings)
             }
-            st.edge_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("edge_buf"), contents: bytemuck::cast_slice(&edge_insts), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
-            st.node_buf = st.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("node_buf"), contents: bytemuck::cast_slice(&node_insts), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
-            st.edge_count = edge_insts.len() as u32;
-            st.node_count = node_insts.len() as u32;
-        }
-    });
+            Err(e) => ConfigReport::failed(vec![ConfigIssue { path: "code".to_string(), message: e.to_string() }]),
+        };
+        report_json(&report)
+    }
+
+    /// タイムライン（`src/timeline.rs` の JSON）を読み込む。現在の状態を起点に、停止状態の 0 秒から始まる（`play_timeline` で再生）。
+    /// 形式: `{ "duration_s"?, "loop"?, "events": [{ "at", "action": "params" | "nucleus" | "allpairs" | "pulse" | "focus" | "unfocus", ... }] }`
+    pub fn load_timeline(&mut self, json: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let timeline = crate::timeline::parse_timeline(json, st.graph.nodes.len(), |id| st.graph.index_of(id).is_some())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        st.load_timeline(timeline);
+        Ok(())
+    }
+
+    /// 現在の位置から再生する（`frame` の `time_ms` で進む）
+    pub fn play_timeline(&mut self) -> Result<(), JsValue> {
+        self.timeline_run()?.playback.play();
+        Ok(())
+    }
+
+    pub fn pause_timeline(&mut self) -> Result<(), JsValue> {
+        self.timeline_run()?.playback.pause();
+        Ok(())
+    }
+
+    /// 再生位置を `seconds` にする。強調などのフェードは飛ばして、その時刻の状態にそろえる
+    pub fn seek_timeline(&mut self, seconds: f32) -> Result<(), JsValue> {
+        let run = self.timeline_run()?;
+        run.playback.seek(seconds);
+        run.seeked = true;
+        Ok(())
+    }
+
+    pub fn set_timeline_loop(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.timeline_run()?.timeline.looping = enabled;
+        Ok(())
+    }
+
+    /// 台本上の現在時刻（秒、ループ時は折り返した値）
+    pub fn timeline_time(&mut self) -> Result<f32, JsValue> {
+        let run = self.timeline_run()?;
+        Ok(run.timeline.local_time(run.playback.position()))
+    }
+
+    /// タイムラインを外す（見た目はその時点のまま、以降は通常どおり setter/自動回転に従う）
+    pub fn clear_timeline(&mut self) -> Result<(), JsValue> {
+        self.state()?.clear_timeline();
+        Ok(())
+    }
+
+    /// マウスドラッグ（回転）・右ドラッグ/Shift+ドラッグ（パン）・ホイール/ピンチ（ズーム）を有効にする。
+    /// 背景用途ではページのスクロールを妨げないよう既定で無効。操作が止まると `set_camera_auto_resume` 秒後に自動回転へ戻る。
+    pub fn set_camera_controls(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.state()?.set_camera_controls(enabled)
+    }
+
+    /// 視点を既定（従来の固定カメラ）に戻し、自動回転を再開する
+    pub fn reset_camera(&mut self) -> Result<(), JsValue> {
+        self.state()?.camera.reset_view();
+        Ok(())
+    }
+
+    /// 最後の操作から自動回転を再開するまでの秒数
+    pub fn set_camera_auto_resume(&mut self, seconds: f32) -> Result<(), JsValue> {
+        self.state()?.camera.resume_after_s = seconds.max(0.0);
+        Ok(())
+    }
+
+    /// 指定ノード（複数なら重心）へ `duration_s` 秒で注視点を移し、`k_hops` ホップ以内の近傍が収まる距離まで寄る。
+    /// `easing` は "linear" / "ease-in-out-cubic" など（空文字は ease-in-out-cubic）。到着後は自動回転に戻る。
+    pub fn focus_nodes(&mut self, ids: Vec<u32>, k_hops: u32, duration_s: f32, easing: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let easing = easing.parse::<crate::easing::Easing>().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if ids.is_empty() { return Err(JsValue::from_str("no nodes to focus")); }
+        let seeds = ids.iter()
+            .map(|&id| st.graph.index_of(id).ok_or_else(|| JsValue::from_str(&format!("unknown node id {id}"))))
+            .collect::<Result<Vec<usize>, JsValue>>()?;
+        let pose = st.focus_pose(&seeds, k_hops, &st.camera);
+        st.camera.fly_to(pose, duration_s.clamp(0.0, MAX_FLIGHT_S), easing);
+        st.set_highlight(seeds);
+        Ok(())
+    }
+
+    /// 既定の視点へ `duration_s` 秒で戻り、強調表示も解除する
+    pub fn unfocus(&mut self, duration_s: f32, easing: &str) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let easing = easing.parse::<crate::easing::Easing>().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        st.camera.fly_to(OrbitCamera::home_pose(), duration_s.clamp(0.0, MAX_FLIGHT_S), easing);
+        st.set_highlight(Vec::new());
+        Ok(())
+    }
+
+    /// 指定ノードとその近傍（`set_highlight_style` の hops）を強調し、それ以外を減光する。空配列で解除。
+    /// カメラは動かさない（`focus_nodes` は移動と同時にこれを行う）。
+    pub fn highlight_nodes(&mut self, ids: Vec<u32>) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let seeds = ids.iter()
+            .map(|&id| st.graph.index_of(id).ok_or_else(|| JsValue::from_str(&format!("unknown node id {id}"))))
+            .collect::<Result<Vec<usize>, JsValue>>()?;
+        st.set_highlight(seeds);
+        Ok(())
+    }
+
+    pub fn clear_highlight(&mut self) -> Result<(), JsValue> {
+        self.state()?.set_highlight(Vec::new());
+        Ok(())
+    }
+
+    /// ブルーム（HDR ターゲット + にじみ + トーンマップ）。`threshold` はにじませ始める明るさ（0..4）、
+    /// `intensity` は強さ（0..4）、`radius` は広がり（0..1）。範囲外は丸める。HDR ターゲットが使えない環境では描画は変わらない
+    pub fn set_bloom(&mut self, enabled: bool, threshold: f32, intensity: f32, radius: f32) -> Result<(), JsValue> {
+        self.state()?.bloom = BloomConfig { enabled, threshold, intensity, radius }.clamped();
+        Ok(())
+    }
+
+    /// アンチエイリアス。`msaa` はサンプル数（1 / 4 / 8、その間の値は下げる。アダプタが対応しなければさらに下げる）、
+    /// `analytic` は MSAA の代わりに細い線を最小幅まで広げて明るさを下げる（CPU 描画でも効く）
+    pub fn set_antialias(&mut self, msaa: u32, analytic: bool) -> Result<(), JsValue> {
+        self.state()?.antialias = AntialiasConfig { msaa, analytic }.clamped();
+        Ok(())
+    }
+
+    /// 実際に使っている MSAA のサンプル数（次のフレームから反映。CPU 描画と復旧中は 1）
+    pub fn msaa_samples(&mut self) -> Result<u32, JsValue> {
+        Ok(self.state()?.gpu.as_ref().map_or(1, |gpu| gpu.scene.sample_count()))
+    }
+
+    /// ポストエフェクトの列を置き換える。`json` は `[{ "effect": "vignette", "strength": 0.4 }, ...]`（この順に掛ける、
+    /// 省略した項目は既定値）。戻り値は `set_graph_config` と同じ検証結果の JSON（errors があれば何も変えない）
+    pub fn set_post_effects(&mut self, json: &str) -> Result<String, JsValue> {
+        let st = self.state()?;
+        let report = match serde_json::from_str::<Vec<PostPass>>(json) {
+            Ok(passes) => {
+                let (post, warnings) = validate_post_passes(passes);
+                st.post = post;
+                ConfigReport { ok: true, errors: Vec::new(), warnings, config: Some(st.config()) }
+            }
+            Err(e) => ConfigReport::failed(vec![ConfigIssue { path: "post".to_string(), message: format!("invalid post effects json: {e}") }]),
+        };
+        report_json(&report)
+    }
+
+    /// `name`（"vignette" / "chromatic_aberration" / "film_grain" / "scanlines" / "color_grade"）の最初のパスを有効/無効にする。
+    /// 列に無ければ、有効にする時だけ既定値で末尾に足す
+    pub fn set_post_effect_enabled(&mut self, name: &str, enabled: bool) -> Result<(), JsValue> {
+        let effect = post_effect(name)?;
+        let st = self.state()?;
+        match st.post.iter_mut().find(|p| p.effect() == effect) {
+            Some(pass) => pass.set_enabled(enabled),
+            None if enabled => { tune_post_pass(&mut st.post, effect, "{}").map_err(|e| JsValue::from_str(&e))?; }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// `name` の最初のパスの項目を `params_json`（`{ "strength": 0.5 }` など）で上書きする（列に無ければ既定値で末尾に足す）。
+    /// 戻り値は `set_graph_config` と同じ検証結果の JSON
+    pub fn tune_post_effect(&mut self, name: &str, params_json: &str) -> Result<String, JsValue> {
+        let effect = post_effect(name)?;
+        let st = self.state()?;
+        let mut post = st.post.clone();
+        let report = match tune_post_pass(&mut post, effect, params_json) {
+            Ok(warnings) => {
+                st.post = post;
+                ConfigReport { ok: true, errors: Vec::new(), warnings, config: Some(st.config()) }
+            }
+            Err(message) => ConfigReport::failed(vec![ConfigIssue { path: "post".to_string(), message }]),
+        };
+        report_json(&report)
+    }
+
+    /// 強調の深さ（0..=8 ホップ）、近傍外の明るさ（0..1）、注目ノードの強調係数（1..4）、切り替えの速さ（1/s、0で即時）
+    pub fn set_highlight_style(&mut self, hops: u32, floor: f32, boost: f32, fade_rate: f32) -> Result<(), JsValue> {
+        let st = self.state()?;
+        st.highlight_config = HighlightConfig { hops, floor, boost, fade_rate }.clamped();
+        st.refresh_emphasis();
+        Ok(())
+    }
+
+    /// `node_id` から辺に沿って波を広げる（BFS順、1ホップ毎に減衰）。`speed` はホップ/秒（0.1..20）、
+    /// `hops` は広がる深さ（1..=16）。発生中のパルスは置き換える。
+    pub fn emit_pulse(&mut self, node_id: u32, speed: f32, hops: u32) -> Result<(), JsValue> {
+        if !speed.is_finite() || speed <= 0.0 { return Err(JsValue::from_str("pulse speed must be positive")); }
+        let st = self.state()?;
+        let source = st.graph.index_of(node_id).ok_or_else(|| JsValue::from_str(&format!("unknown node id {node_id}")))?;
+        st.emit_pulse(source, speed.clamp(0.1, 20.0), hops.clamp(1, crate::pulse::MAX_PULSE_HOPS));
+        Ok(())
+    }
+
+    /// マイク等の音量（0..1）とスペクトル（ビン毎 0..1、0Hz〜ナイキスト。例: `getByteFrequencyData` / 255）を渡す。
+    /// 値は包絡線で平滑化して次の frame から反映する。`AUDIO_STALE_S` 秒渡さなければ無音に戻る。
+    pub fn set_audio_input(&mut self, level: f32, spectrum: &[f32]) -> Result<(), JsValue> {
+        self.state()?.audio.set_input(level, spectrum);
+        Ok(())
+    }
+
+    /// 包絡線の時定数と「入力→パラメータ」のマッピング（`src/audio.rs` の `AudioConfig` JSON、空文字なら既定値）
+    pub fn set_audio_mapping(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = if config_json.trim().is_empty() {
+            AudioConfig::default()
+        } else {
+            crate::audio::parse_audio_config(config_json).map_err(|e| JsValue::from_str(&e.to_string()))?
+        };
+        self.state()?.audio_config = config;
+        Ok(())
+    }
+
+    /// セッション状態（"idle" / "optimizing" / "ready" / "researching"）を切り替え、
+    /// 見た目（GraphParams の全項目・核・色味・自動パルス）を現在の値からその状態のプリセットへクロスフェードする。
+    pub fn set_session_state(&mut self, name: &str) -> Result<(), JsValue> {
+        let state = name.parse::<SessionState>().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.state()?.set_session_state(state);
+        Ok(())
+    }
+
+    /// 現在のセッション状態（遷移中は遷移先）
+    pub fn session_state(&mut self) -> Result<String, JsValue> {
+        Ok(self.state()?.session.name().to_string())
+    }
+
+    /// 以降の状態切り替えにかける時間（0..=30秒、0で即時）と補間（空文字は ease-in-out-sine）
+    pub fn set_session_transition(&mut self, duration_s: f32, easing: &str) -> Result<(), JsValue> {
+        let easing = if easing.trim().is_empty() {
+            Easing::EaseInOutSine
+        } else {
+            easing.parse::<Easing>().map_err(|e| JsValue::from_str(&e.to_string()))?
+        };
+        let duration = if duration_s.is_finite() { duration_s.clamp(0.0, MAX_FLIGHT_S) } else { DEFAULT_SESSION_FADE_S };
+        self.state()?.session_fade = (duration, easing);
+        Ok(())
+    }
+
+    /// 状態 `name` のプリセットを差し替える（`src/session.rs` の `SessionLook` JSON。省略項目は idle の値）。
+    /// 現在その状態なら新しいプリセットへクロスフェードする。
+    pub fn set_session_preset(&mut self, name: &str, look_json: &str) -> Result<(), JsValue> {
+        let state = name.parse::<SessionState>().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let look = crate::session::parse_session_look(look_json).map_err(|e| JsValue::from_str(&e))?;
+        let st = self.state()?;
+        st.session_presets.set(state, look);
+        if st.session == state { st.set_session_state(state); }
+        Ok(())
+    }
+
+    /// ホバー中のノードが変わると `callback(nodeId | null)` を呼ぶ（null: ノード外/キャンバス外）。
+    /// 判定は frame 毎に最後の描画と同じ投影で行う。`null`/`undefined` を渡すと解除。
+    pub fn on_node_hover(&mut self, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        self.state()?.set_pointer_callback(true, callback)
+    }
+
+    /// クリック時に `callback(nodeId | null, edgeIndex | null)` を呼ぶ。ノード優先、無ければエッジ（データ順の添字）。
+    pub fn on_node_click(&mut self, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        self.state()?.set_pointer_callback(false, callback)
+    }
+
+    /// キャンバス上の座標（CSSピクセル、左上原点）にあるノードID。未描画または該当なしは undefined。
+    pub fn pick_node(&mut self, x: f32, y: f32) -> Result<Option<u32>, JsValue> {
+        let st = self.state()?;
+        let Some(view) = st.pick_view() else { return Ok(None) };
+        Ok(st.pick_node_index(&view, x, y).map(|i| st.graph.nodes[i].id))
+    }
+
+    /// キャンバス上の座標にあるエッジの添字（`set_graph3d_data` の edges 順）。全ペア表示中は常に undefined。
+    pub fn pick_edge(&mut self, x: f32, y: f32) -> Result<Option<u32>, JsValue> {
+        let st = self.state()?;
+        let Some(view) = st.pick_view() else { return Ok(None) };
+        Ok(st.pick_edge_index(&view, x, y).map(|e| e as u32))
+    }
+
+    /// 現在のグラフを初期配置として力学配置を開始し、`steps_per_frame` ずつ frame で進める。
+    /// 形式: `{ "seed", "iterations", "ideal_length"?, "theta", "gravity", "radial_strength", "steps_per_frame" }`
+    /// `pinned` はノードID（固定して動かさない）
+    pub fn start_force_layout(&mut self, config_json: &str, pinned: Vec<u32>) -> Result<(), JsValue> {
+        let st = self.state()?;
+        let config: ForceLayoutConfig = serde_json::from_str(config_json)
+            .map_err(|e| JsValue::from_str(&format!("invalid layout config: {e}")))?;
+        let mut layout = ForceLayout::new(&st.graph.nodes, &st.graph.edges, config);
+        for id in pinned {
+            let index = st.graph.index_of(id).ok_or_else(|| JsValue::from_str(&format!("unknown pinned node id {id}")))?;
+            layout.set_pinned(index, true);
+        }
+        st.layout = Some(layout);
+        Ok(())
+    }
+
+    /// 力学配置を現在位置で止める
+    pub fn stop_force_layout(&mut self) -> Result<(), JsValue> {
+        self.state()?.layout = None;
+        Ok(())
+    }
+
+    pub fn set_data_limits(&mut self, max_nodes: u32, max_edges: u32) -> Result<(), JsValue> {
+        self.state()?.data_limits = GraphDataLimits { max_nodes: max_nodes as usize, max_edges: max_edges as usize };
+        Ok(())
+    }
+
+    /// GPU資源（バッファ/深度テクスチャ/デバイス）を解放し、キャンバスの構成を解除する。
+    /// 以後の呼び出しはエラーになる。複数回呼んでもよい。
+    pub fn dispose(&mut self) {
+        // 実際の解放は GraphState の Drop で行う（JS側の free() でも同じ経路を通る）
+        self.state = None;
+    }
+
+    pub fn is_disposed(&self) -> bool {
+        self.state.is_none()
+    }
+
+    /// "ready" | "recovering"（デバイス再取得中）| "lost"（再試行待ち）| "disposed"
+    pub fn status(&self) -> String {
+        self.state.as_ref().map_or(GraphStatus::Disposed, GraphState::status).as_str().to_string()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GraphRenderer {
+    fn state(&mut self) -> Result<&mut GraphState, JsValue> {
+        self.state.as_mut().ok_or_else(|| JsValue::from_str("renderer disposed"))
+    }
+
+    fn timeline_run(&mut self) -> Result<&mut TimelineRun, JsValue> {
+        self.state()?.timeline.as_mut().ok_or_else(|| JsValue::from_str("no timeline loaded"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GraphState {
+    async fn new(canvas: HtmlCanvasElement) -> Result<GraphState, JsValue> {
+        let width = (canvas.client_width() as u32).max(1);
+        let height = (canvas.client_height() as u32).max(1);
+        canvas.set_width(width);
+        canvas.set_height(height);
+
+        // WebGPU 優先。キャンバスの getContext より前に判定する（後から種類を変えられない）
+        let webgpu = wgpu::util::is_browser_webgpu_supported().await;
+        let backend = crate::backend::choose_backend(webgpu, crate::backend::WEBGL_ENABLED).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if backend != GraphBackend::WebGpu { log::info!("graph: WebGPU unavailable, using {}", backend.as_str()); }
+        let dev = request_gpu_device(canvas.clone(), backend).await.map_err(|e| JsValue::from_str(&e))?;
+        let mut st = Self::with_canvas(canvas, width, height);
+        st.backend = backend;
+        st.install_gpu(dev);
+        Ok(st)
+    }
+
+    /// CPU 描画。描画解像度は `software_resolution` で抑え、キャンバスの表示サイズへは CSS で拡大する
+    fn new_software(canvas: HtmlCanvasElement) -> Result<GraphState, JsValue> {
+        let width = (canvas.client_width() as u32).max(1);
+        let height = (canvas.client_height() as u32).max(1);
+        // GPU 初期化に失敗した後は webgpu/webgl2 のコンテキストが残り "2d" を取れない。
+        // 同じ id の新しいキャンバスに差し替えてから描く
+        let canvas = match canvas.get_context("2d")? {
+            Some(_) => canvas,
+            None => replace_canvas(&canvas)?,
+        };
+        let software = SoftwareCanvas::new(&canvas)?;
+        let mut st = Self::with_canvas(canvas, width, height);
+        st.software = Some(software);
+        st.backend = GraphBackend::Software;
+        st.resize(width, height);
+        st.upload_instances();
+        Ok(st)
+    }
+
+    /// 描画先を持たない初期状態（既定のグラフ・パラメータ）
+    fn with_canvas(canvas: HtmlCanvasElement, width: u32, height: u32) -> GraphState {
+        // params (デフォルトを定数化)
+        let params = GraphParams {
+            edge_thickness: DEFAULT_GRAPH_EDGE_THICKNESS,
+            node_size: DEFAULT_GRAPH_NODE_SIZE,
+            flow_speed: DEFAULT_GRAPH_FLOW_SPEED,
+            rot_speed: DEFAULT_GRAPH_ROT_SPEED,
+            fog_start: DEFAULT_GRAPH_FOG_START,
+            fog_end: DEFAULT_GRAPH_FOG_END,
+            fog_strength: DEFAULT_GRAPH_FOG_STRENGTH,
+            link_on: DEFAULT_GRAPH_LINK_ON,
+            link_off: DEFAULT_GRAPH_LINK_OFF,
+            nuc_link_on: DEFAULT_GRAPH_NUC_LINK_ON,
+            nuc_link_off: DEFAULT_GRAPH_NUC_LINK_OFF,
+        };
+        let mut st = GraphState {
+            canvas,
+            gpu: None,
+            software: None,
+            backend: GraphBackend::WebGpu,
+            pending_device: None,
+            failed_attempts: 0,
+            retry_at_ms: None,
+            width,
+            height,
+            params,
+            edge_mode_allpairs: false,
+            edge_mode_nucleus: false,
+            allpairs_edges: None,
+            shell_config: ShellConfig::default(),
+            layout_settings: shell_layout_settings(&ShellConfig::default()),
+            // 既定の多層シェルグラフ（set_graph3d_data で差し替え可能）
+            graph: ShellConfig::default().graph(),
+            data_limits: GraphDataLimits::default(),
+            layout: None,
+            last_frame: None,
+            last_clock: None,
+            pointer: None,
+            camera: OrbitCamera::default(),
+            camera_controls: None,
+            adjacency: Adjacency::default(),
+            highlight: Vec::new(),
+            highlight_config: HighlightConfig::default(),
+            edge_emphasis: EmphasisFade::default(),
+            node_emphasis: EmphasisFade::default(),
+            pulse: None,
+            edge_pulse: Vec::new(),
+            audio: AudioEnvelope::default(),
+            audio_config: AudioConfig::default(),
+            flow_phase: 0.0,
+            session: SessionState::Idle,
+            session_presets: SessionPresets::default(),
+            session_transition: None,
+            session_fade: (DEFAULT_SESSION_FADE_S, Easing::EaseInOutSine),
+            tint: SessionLook::default().tint,
+            nucleus_presence: 1.0,
+            auto_pulse: crate::pulse::AutoPulse::default(),
+            pulse_rng: crate::rng::Lcg::new(0x5e55),
+            tweens: ParamTweens::default(),
+            presets: crate::presets::PresetLibrary::default(),
+            timeline: None,
+            bloom: BloomConfig::default(),
+            post: Vec::new(),
+            antialias: AntialiasConfig::default(),
+        };
+        st.adjacency = Adjacency::new(st.graph.nodes.len(), &st.graph.edges);
+        st.refresh_emphasis();
+        st.refresh_pulse();
+        st
+    }
+
+    fn status(&self) -> GraphStatus {
+        if self.gpu.is_some() || self.software.is_some() {
+            GraphStatus::Ready
+        } else if self.pending_device.is_some() || self.retry_at_ms.is_none() {
+            GraphStatus::Recovering
+        } else {
+            GraphStatus::Lost
+        }
+    }
+
+    /// 取得済みデバイスに現在のシーン（パラメータ/グラフ/モード）からGPU資源を構築する
+    fn install_gpu(&mut self, dev: GpuDevice) {
+        let edge_insts = build_edge_insts(&self.graph, self.allpairs_edges.as_deref(), self.edge_mode_nucleus);
+        let node_insts = build_node_insts(&self.graph, self.edge_mode_nucleus);
+        let attrs = InstanceAttrs { edge_emphasis: self.edge_emphasis.values(), node_emphasis: self.node_emphasis.values(), edge_pulse: &self.edge_pulse };
+        self.gpu = Some(GraphGpu::new(dev, self.width, self.height, &edge_insts, &node_insts, attrs));
+    }
+
+    /// デバイス喪失を検知したら資源を捨てて再取得を開始し、再取得できたら組み直す。
+    /// 描画可能なら true。
+    fn ensure_gpu(&mut self, time_ms: f32) -> bool {
+        if self.software.is_some() { return true; } // 失うデバイスが無い
+        if self.gpu.as_ref().is_some_and(|g| g.lost.load(Ordering::Acquire)) {
+            log::warn!("graph: GPU device lost, recreating resources");
+            self.gpu = None; // 残骸は Drop で解放
+            self.failed_attempts = 0;
+            self.retry_at_ms = None;
+        }
+        if self.gpu.is_some() { return true; }
+
+        if let Some(slot) = &self.pending_device {
+            let result = slot.borrow_mut().take();
+            match result {
+                None => return false,
+                Some(Ok(dev)) => {
+                    self.pending_device = None;
+                    self.failed_attempts = 0;
+                    self.install_gpu(dev);
+                    log::info!("graph: GPU device recovered");
+                    return true;
+                }
+                Some(Err(e)) => {
+                    self.pending_device = None;
+                    let delay = crate::recovery::retry_delay_ms(self.failed_attempts);
+                    self.failed_attempts += 1;
+                    self.retry_at_ms = Some(time_ms + delay);
+                    log::warn!("graph: device recovery failed ({e}), retrying in {delay}ms");
+                    return false;
+                }
+            }
+        }
+
+        if self.retry_at_ms.is_none_or(|at| time_ms >= at) {
+            self.retry_at_ms = None;
+            let slot: PendingDevice = Rc::new(RefCell::new(None));
+            let canvas = self.canvas.clone();
+            let backend = self.backend;
+            let out = slot.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = request_gpu_device(canvas, backend).await;
+                *out.borrow_mut() = Some(result);
+            });
+            self.pending_device = Some(slot);
+        }
+        false
+    }
+
+    fn frame(&mut self, time_ms: f32) -> Result<(), JsValue> {
+        // 復旧中はフレームをスキップ（JS側は通常通り呼び続けてよい）
+        if !self.ensure_gpu(time_ms) { return Ok(()); }
+        self.step_layout();
+
+        let clock = time_ms * 0.001;
+        let aspect = self.width as f32 / self.height as f32; // resize で 1 以上
+        let dt = self.last_clock.map_or(0.0, |prev| (clock - prev).clamp(0.0, 0.1));
+        self.last_clock = Some(clock);
+        // タイムライン読み込み中は dt で進むもの（状態遷移・トゥイーン・カメラ操作）を止め、台本の時刻だけで決める
+        let scripted = self.timeline.is_some();
+        if let Some(tr) = self.session_transition.as_mut().filter(|_| !scripted) {
+            let look = tr.step(dt);
+            let finished = tr.is_finished();
+            if finished { self.session_transition = None; }
+            self.apply_look(&look, finished);
+        }
+        if self.tweens.is_active() && !scripted {
+            let mut values = self.params.to_array();
+            self.tweens.step(dt, &mut values);
+            self.params = GraphParams::from_array(values);
+        }
+        // タイムライン再生中はシェーダの時刻も台本の時刻にする（同じ時刻は同じフレーム）
+        let timeline_t = self.step_timeline(clock);
+        let t = timeline_t.unwrap_or(clock);
+        let (input, held) = match &self.camera_controls {
+            Some(c) => {
+                let mut g = c.gestures.borrow_mut();
+                (g.take(), g.is_held())
+            }
+            None => Default::default(),
+        };
+        match timeline_t {
+            Some(tt) => self.apply_timeline_camera(tt),
+            None => {
+                self.camera.auto_rotate_speed = self.params.rot_speed;
+                self.camera.update(input, held, dt, self.canvas.client_height().max(1) as f32);
+            }
+        }
+        let vp = compute_view_proj_graph(&self.camera, aspect);
+        self.last_frame = Some((t, vp));
+        self.dispatch_pointer();
+        if self.timeline.is_none() && self.auto_pulse.step(dt) && !self.graph.nodes.is_empty() {
+            let source = self.pulse_rng.pick_usize(self.graph.nodes.len());
+            self.emit_pulse(source, crate::pulse::AUTO_PULSE_SPEED, crate::pulse::AUTO_PULSE_HOPS);
+        }
+        let fade_rate = self.highlight_config.fade_rate;
+        let edges_changed = self.edge_emphasis.step(dt, fade_rate);
+        let nodes_changed = self.node_emphasis.step(dt, fade_rate);
+        let pulse_ended = self.pulse.is_some_and(|p| p.is_finished(t));
+        if pulse_ended {
+            self.pulse = None;
+            self.refresh_pulse();
+        }
+        let (pulse_start, pulse_speed) = self.pulse.map_or((0.0, 0.0), |p| (p.start, p.speed));
+        // 音声による変調（ドリフトは位置が跳ばないよう元の flow_speed のまま、縞の速さだけ変える）
+        self.audio.step(dt, &self.audio_config);
+        let m = AudioModulation::from_signals(&self.audio.signals(), &self.audio_config.mappings);
+        self.flow_phase = match self.timeline.as_mut() {
+            Some(run) => run.clock.flow_phase(t),
+            None => (self.flow_phase + dt * self.params.flow_speed * m.flow_speed).fract(),
+        };
+        let nucleus_index = if self.edge_mode_nucleus { self.graph.nodes.len() as f32 } else { -1.0 };
+        let u = UGraph { view_proj: vp, misc0: [t, self.params.edge_thickness * m.edge_thickness, self.params.node_size, self.params.flow_speed], misc1: [aspect, self.params.fog_start, self.params.fog_end, self.params.fog_strength], misc2: [self.params.link_on, self.params.link_off, pulse_start, pulse_speed], misc3: [self.params.nuc_link_on, self.params.nuc_link_off, crate::pulse::PULSE_FALLOFF, self.nucleus_presence], misc4: [m.node_glow, m.nucleus_size * self.nucleus_presence, nucleus_index, self.flow_phase], tint: self.tint, misc5: [0.0; 4] }
+            .with_antialias(self.height, self.antialias.analytic);
+        if let Some(sw) = self.software.as_mut() {
+            let attrs = InstanceAttrs { edge_emphasis: self.edge_emphasis.values(), node_emphasis: self.node_emphasis.values(), edge_pulse: &self.edge_pulse };
+            return sw.draw(&u, attrs);
+        }
+        let Some(gpu) = self.gpu.as_mut() else { return Ok(()) };
+        if edges_changed || nodes_changed {
+            gpu.write_emphasis(self.edge_emphasis.values(), self.node_emphasis.values());
+        }
+        if pulse_ended { gpu.write_pulse(&self.edge_pulse); }
+        gpu.scene.write_uniforms(&gpu.queue, &u);
+
+        let surface_tex = match gpu.surface.get_current_texture() {
+            Ok(t) => t,
+            Err(e) => match crate::recovery::surface_error_action(&e) {
+                SurfaceAction::SkipFrame => return Ok(()),
+                SurfaceAction::RecoverDevice => {
+                    log::warn!("graph: surface acquire failed ({e}), recreating device");
+                    gpu.lost.store(true, Ordering::Release);
+                    return Ok(());
+                }
+                SurfaceAction::Reconfigure => {
+                    gpu.surface.configure(&gpu.device, &gpu.config);
+                    match gpu.surface.get_current_texture() {
+                        Ok(t) => t,
+                        Err(e) => {
+                            log::warn!("graph: surface acquire failed after reconfigure ({e}), skipping frame");
+                            return Ok(());
+                        }
+                    }
+                }
+            },
+        };
+        let view = surface_tex.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut enc = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("enc_graph") });
+        let hdr = gpu.sync_bloom(&self.bloom);
+        gpu.sync_scene_target(hdr, self.antialias.msaa);
+        let post = gpu.sync_post(&self.post, t);
+        // ポストエフェクトがあれば、ここまでの結果は中間テクスチャへ描いてから順に掛ける
+        let post = gpu.post.as_ref().filter(|_| post);
+        let out = post.map_or(&view, |p| p.input_view());
+        match gpu.bloom.as_ref().filter(|_| hdr) {
+            // エッジ/ノードは HDR へ描き、ブルームの合成でトーンマップする
+            Some(bloom) => {
+                gpu.scene.encode(&mut enc, bloom.hdr_view(), &gpu.scene_targets, crate::render::graph_clear_color());
+                bloom.encode(&mut enc, out);
+            }
+            None => gpu.scene.encode(&mut enc, out, &gpu.scene_targets, crate::render::graph_clear_color()),
+        }
+        if let Some(post) = post { post.encode(&mut enc, &self.post, &view); }
+        gpu.queue.submit(Some(enc.finish()));
+        surface_tex.present();
+        Ok(())
+
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        // 復旧中でもサイズは保持し、再構築時に反映する
+        self.width = width.max(1);
+        self.height = height.max(1);
+        if let Some(gpu) = self.gpu.as_mut() {
+            gpu.resize(self.width, self.height);
+        }
+        if self.software.is_some() {
+            let (w, h) = crate::raster::software_resolution(self.width, self.height, crate::raster::SOFTWARE_MAX_PIXELS);
+            self.canvas.set_width(w);
+            self.canvas.set_height(h);
+        }
+    }
+
+    /// 最後に描いたフレームの投影。キャンバスの表示サイズ（CSSピクセル）はポインタ座標と合わせる。
+    fn pick_view(&self) -> Option<PickView> {
+        let (time, view_proj) = self.last_frame?;
+        let css_w = self.canvas.client_width();
+        let css_h = self.canvas.client_height();
+        let (width, height) = if css_w > 0 && css_h > 0 { (css_w as f32, css_h as f32) } else { (self.width as f32, self.height as f32) };
+        Some(PickView { view_proj, time, flow_speed: self.params.flow_speed, width, height })
+    }
+
+    fn pick_node_index(&self, view: &PickView, x: f32, y: f32) -> Option<usize> {
+        let positions: Vec<[f32; 3]> = self.graph.nodes.iter().map(|n| n.pos).collect();
+        let node_size = self.params.node_size;
+        let emphasis = self.node_emphasis.values();
+        // vs_node と同じく強調中のノードは大きく描かれる
+        let radius = |i: usize| {
+            let grow = 1.0 + 0.35 * (emphasis.get(i).copied().unwrap_or(1.0) - 1.0).max(0.0);
+            0.5 * crate::graph::theme_node_size(i) * node_size * grow
+        };
+        crate::picking::pick_node(view, &positions, radius, x, y, PICK_TOLERANCE_PX)
+    }
+
+    /// 全ペア表示中は描画中のエッジがデータのエッジと対応しないため None
+    fn pick_edge_index(&self, view: &PickView, x: f32, y: f32) -> Option<usize> {
+        if self.edge_mode_allpairs { return None; }
+        let positions: Vec<[f32; 3]> = self.graph.nodes.iter().map(|n| n.pos).collect();
+        let edges = &self.graph.edges;
+        let half_width = |i: usize| {
+            self.params.edge_thickness * edge_style(edges[i].kind).0 * crate::graph::edge_weight_scale(self.graph.weights[i])
+        };
+        let link_fade = (self.params.link_on, self.params.link_off);
+        crate::picking::pick_edge(view, &positions, edges, half_width, link_fade, x, y, PICK_TOLERANCE_PX)
+    }
+
+    /// 溜まったポインタ入力を今フレームの投影で判定し、コールバックを呼ぶ。
+    /// ノードは漂い・回転するため、ポインタが止まっていてもホバー対象は毎フレーム判定し直す。
+    fn dispatch_pointer(&mut self) {
+        let Some(tracker) = self.pointer.as_ref() else { return };
+        let Some(view) = self.pick_view() else { return };
+        let (pos, mut clicks) = {
+            let mut input = tracker.input.borrow_mut();
+            (input.pos, std::mem::take(&mut input.clicks))
+        };
+        // カメラ操作のドラッグ終わりに来る click は選択扱いしない
+        if self.camera_controls.as_ref().is_some_and(|c| c.gestures.borrow().was_drag()) {
+            clicks.clear();
+        }
+        let hovered = pos.and_then(|(x, y)| self.pick_node_index(&view, x, y)).map(|i| self.graph.nodes[i].id);
+        let clicked: Vec<(Option<u32>, Option<u32>)> = clicks.into_iter()
+            .map(|(x, y)| match self.pick_node_index(&view, x, y) {
+                Some(i) => (Some(self.graph.nodes[i].id), None),
+                None => (None, self.pick_edge_index(&view, x, y).map(|e| e as u32)),
+            })
+            .collect();
+        let Some(tracker) = self.pointer.as_mut() else { return };
+        if tracker.hovered != hovered {
+            tracker.hovered = hovered;
+            if let Some(cb) = &tracker.on_hover { call_pointer_callback(cb, hovered, None); }
+        }
+        if let Some(cb) = &tracker.on_click {
+            for (node, edge) in clicked { call_pointer_callback(cb, node, edge); }
+        }
+    }
+
+    fn set_camera_controls(&mut self, enabled: bool) -> Result<(), JsValue> {
+        if enabled == self.camera_controls.is_some() { return Ok(()); }
+        self.camera_controls = if enabled { Some(CameraControls::attach(&self.canvas)?) } else { None };
+        Ok(())
+    }
+
+    /// コールバックの登録/解除。どちらも無くなったらイベントリスナも外す。
+    fn set_pointer_callback(&mut self, hover: bool, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        if self.pointer.is_none() {
+            if callback.is_none() { return Ok(()); }
+            self.pointer = Some(PointerTracker::attach(&self.canvas)?);
+        }
+        let Some(tracker) = self.pointer.as_mut() else { return Ok(()) };
+        if hover {
+            tracker.on_hover = callback;
+            tracker.hovered = None;
+        } else {
+            tracker.on_click = callback;
+        }
+        if tracker.on_hover.is_none() && tracker.on_click.is_none() {
+            self.pointer = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn parse_curve(curve: &str) -> Result<TweenCurve, JsValue> {
+    curve.parse::<TweenCurve>().map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// セッション状態の切り替えにかける既定の時間（秒）
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_SESSION_FADE_S: f32 = 1.5;
+
+/// フォーカス移動の最長時間（秒）
+#[cfg(target_arch = "wasm32")]
+const MAX_FLIGHT_S: f32 = 30.0;
+
+/// ホバー/クリック判定の余裕（CSSピクセル）。小さなノードや細いエッジでも選べるように。
+#[cfg(target_arch = "wasm32")]
+const PICK_TOLERANCE_PX: f32 = 3.0;
+
+/// キャンバス上のポインタ入力。イベントでは記録のみ行い、判定は次の frame で行う。
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct PointerInput {
+    pos: Option<(f32, f32)>, // None: キャンバス外
+    clicks: Vec<(f32, f32)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+type PointerListener = wasm_bindgen::closure::Closure<dyn FnMut(web_sys::MouseEvent)>;
+
+/// キャンバスのポインタイベント購読とJSコールバック。破棄時にリスナを外す。
+#[cfg(target_arch = "wasm32")]
+struct PointerTracker {
+    canvas: HtmlCanvasElement,
+    input: Rc<RefCell<PointerInput>>,
+    listeners: Vec<(&'static str, PointerListener)>,
+    on_hover: Option<js_sys::Function>,
+    on_click: Option<js_sys::Function>,
+    hovered: Option<u32>, // 最後に通知したノードID
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PointerTracker {
+    fn attach(canvas: &HtmlCanvasElement) -> Result<Self, JsValue> {
+        let input = Rc::new(RefCell::new(PointerInput::default()));
+        let mut listeners: Vec<(&'static str, PointerListener)> = Vec::new();
+        {
+            let input = input.clone();
+            listeners.push(("pointermove", PointerListener::new(move |e: web_sys::MouseEvent| {
+                input.borrow_mut().pos = Some((e.offset_x() as f32, e.offset_y() as f32));
+            })));
+        }
+        {
+            let input = input.clone();
+            listeners.push(("pointerleave", PointerListener::new(move |_: web_sys::MouseEvent| {
+                input.borrow_mut().pos = None;
+            })));
+        }
+        {
+            let input = input.clone();
+            listeners.push(("click", PointerListener::new(move |e: web_sys::MouseEvent| {
+                input.borrow_mut().clicks.push((e.offset_x() as f32, e.offset_y() as f32));
+            })));
+        }
+        for (name, listener) in &listeners {
+            canvas.add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())?;
+        }
+        Ok(Self { canvas: canvas.clone(), input, listeners, on_hover: None, on_click: None, hovered: None })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for PointerTracker {
+    fn drop(&mut self) {
+        for (name, listener) in &self.listeners {
+            let _ = self.canvas.remove_event_listener_with_callback(name, listener.as_ref().unchecked_ref());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+type EventListener = wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>;
+
+/// カメラ操作用のキャンバスイベント購読。ドラッグ/ホイール/ピンチを `Gestures` に溜め、frame で反映する。
+/// タッチでページがスクロールしないよう有効中は `touch-action: none` にし、破棄時に元へ戻す。
+#[cfg(target_arch = "wasm32")]
+struct CameraControls {
+    canvas: HtmlCanvasElement,
+    gestures: Rc<RefCell<Gestures>>,
+    listeners: Vec<(&'static str, EventListener)>,
+    prev_touch_action: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl CameraControls {
+    fn attach(canvas: &HtmlCanvasElement) -> Result<Self, JsValue> {
+        let gestures = Rc::new(RefCell::new(Gestures::default()));
+        let mut listeners: Vec<(&'static str, EventListener)> = Vec::new();
+        {
+            let gestures = gestures.clone();
+            let canvas = canvas.clone();
+            listeners.push(("pointerdown", EventListener::new(move |e: web_sys::Event| {
+                let e: &web_sys::PointerEvent = e.unchecked_ref();
+                // 右ボタン/Shift付きはパン。キャンバス外に出ても追従するようキャプチャする
+                let _ = canvas.set_pointer_capture(e.pointer_id());
+                gestures.borrow_mut().pointer_down(e.pointer_id(), e.offset_x() as f32, e.offset_y() as f32, e.button() == 2 || e.shift_key());
+            })));
+        }
+        {
+            let gestures = gestures.clone();
+            listeners.push(("pointermove", EventListener::new(move |e: web_sys::Event| {
+                let e: &web_sys::PointerEvent = e.unchecked_ref();
+                gestures.borrow_mut().pointer_move(e.pointer_id(), e.offset_x() as f32, e.offset_y() as f32);
+            })));
+        }
+        for name in ["pointerup", "pointercancel"] {
+            let gestures = gestures.clone();
+            listeners.push((name, EventListener::new(move |e: web_sys::Event| {
+                let e: &web_sys::PointerEvent = e.unchecked_ref();
+                gestures.borrow_mut().pointer_up(e.pointer_id());
+            })));
+        }
+        {
+            let gestures = gestures.clone();
+            listeners.push(("wheel", EventListener::new(move |e: web_sys::Event| {
+                e.prevent_default(); // ページのスクロールを止める
+                let e: &web_sys::WheelEvent = e.unchecked_ref();
+                // deltaMode: 0=px, 1=行, 2=ページ
+                let scale = match e.delta_mode() { 1 => 16.0, 2 => 400.0, _ => 1.0 };
+                gestures.borrow_mut().wheel(e.delta_y() as f32 * scale);
+            })));
+        }
+        // 右ドラッグのパンでメニューを出さない
+        listeners.push(("contextmenu", EventListener::new(|e: web_sys::Event| e.prevent_default())));
+
+        let opts = web_sys::AddEventListenerOptions::new();
+        opts.set_passive(false); // wheel で preventDefault するため
+        for (name, listener) in &listeners {
+            canvas.add_event_listener_with_callback_and_add_event_listener_options(name, listener.as_ref().unchecked_ref(), &opts)?;
+        }
+        let style = canvas.style();
+        let prev_touch_action = style.get_property_value("touch-action").unwrap_or_default();
+        style.set_property("touch-action", "none")?;
+        Ok(Self { canvas: canvas.clone(), gestures, listeners, prev_touch_action })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for CameraControls {
+    fn drop(&mut self) {
+        for (name, listener) in &self.listeners {
+            let _ = self.canvas.remove_event_listener_with_callback(name, listener.as_ref().unchecked_ref());
+        }
+        let _ = self.canvas.style().set_property("touch-action", &self.prev_touch_action);
+    }
+}
+
+/// `cb(nodeId | null, edgeIndex | null)` を現在のタスク終了後（マイクロタスク）に呼ぶ。
+/// frame 中はレンダラを借用しているため、同期的に呼ぶとコールバック内からの操作が再入エラーになる。
+#[cfg(target_arch = "wasm32")]
+fn call_pointer_callback(cb: &js_sys::Function, node: Option<u32>, edge: Option<u32>) {
+    let cb = cb.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let node = node.map_or(JsValue::NULL, JsValue::from);
+        let edge = edge.map_or(JsValue::NULL, JsValue::from);
+        if let Err(e) = cb.call2(&JsValue::NULL, &node, &edge) {
+            log::warn!("graph: pointer callback threw: {e:?}");
+        }
+    });
+}
+
+/// アダプタ/デバイス/サーフェスを取得する。デバイス喪失は `lost` フラグで通知される。
+#[cfg(target_arch = "wasm32")]
+async fn request_gpu_device(canvas: HtmlCanvasElement, backend: GraphBackend) -> Result<GpuDevice, String> {
+    let backends = backend.wgpu_backends().ok_or("software backend has no GPU device")?;
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor { backends, ..Default::default() });
+    let surface = instance
+        .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
+        .map_err(|e| format!("create_surface failed: {e}"))?;
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        })
+        .await
+        .map_err(|e| format!("request_adapter failed: {e}"))?;
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor {
+            label: Some("device"),
+            // 8x MSAA などアダプタ固有のサンプル数を使えるように（ブラウザの WebGPU には無く 4x まで）
+            required_features: adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+            required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+            memory_hints: wgpu::MemoryHints::Performance,
+            trace: wgpu::Trace::Off,
+        })
+        .await
+        .map_err(|e| format!("request_device failed: {e}"))?;
+
+    let lost = Arc::new(AtomicBool::new(false));
+    let flag = lost.clone();
+    device.set_device_lost_callback(move |reason, msg| {
+        // dispose/復旧時の自前 destroy() は対象外
+        if reason != wgpu::DeviceLostReason::Destroyed {
+            log::warn!("graph: device lost ({reason:?}): {msg}");
+            flag.store(true, Ordering::Release);
+        }
+    });
+
+    let hdr_supported = crate::bloom::hdr_target_supported(adapter.get_texture_format_features(HDR_FORMAT));
+    let caps = surface.get_capabilities(&adapter);
+    let format = crate::backend::preferred_surface_format(&caps.formats).ok_or("surface reports no formats")?;
+    let alpha_mode = crate::backend::preferred_alpha_mode(&caps.alpha_modes).ok_or("surface reports no alpha modes")?;
+    let msaa_limits = MsaaLimits::query(Some(&adapter), &device, format);
+
+    // サイズは GraphGpu::new で確定して configure する
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format,
+        width: 1,
+        height: 1,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode,
+        view_formats: vec![],
+        desired_maximum_frame_latency: 2,
+    };
+    Ok(GpuDevice { instance, surface, device, queue, config, lost, hdr_supported, msaa_limits })
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GraphGpu {
+    fn new(dev: GpuDevice, width: u32, height: u32, edge_insts: &[EdgeInst], node_insts: &[NodeInst], attrs: InstanceAttrs) -> GraphGpu {
+        let GpuDevice { instance, surface, device, queue, mut config, lost, hdr_supported, msaa_limits } = dev;
+        if !hdr_supported { log::info!("graph: {HDR_FORMAT:?} render targets unavailable, bloom disabled"); }
+        config.width = width;
+        config.height = height;
+        surface.configure(&device, &config);
+
+        let scene = GraphScene::new(&device, GRAPH_SHADER_SRC, config.format, edge_insts, node_insts, attrs);
+        let scene_targets = SceneTargets::new(&device, config.format, 1, width, height);
+
+        GraphGpu { _instance: instance, surface, device, queue, config, lost, scene, scene_targets, msaa_limits, bloom: None, hdr_supported, post: None }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        if self.config.width != width || self.config.height != height {
+            self.config.width = width;
+            self.config.height = height;
+            self.surface.configure(&self.device, &self.config);
+            // 深度と MSAA のカラーをサイズに合わせて作り直す
+            let (format, samples) = (self.scene_targets.format(), self.scene_targets.samples());
+            self.scene_targets.configure(&self.device, format, samples, width, height);
+            if let Some(bloom) = self.bloom.as_mut() { bloom.resize(&self.device, width, height); }
+            if let Some(post) = self.post.as_mut() { post.resize(&self.device, width, height); }
+        }
+    }
+
+    /// ブルームの有無に合わせて HDR ターゲットを用意/解放する。HDR 経由なら true
+    fn sync_bloom(&mut self, config: &BloomConfig) -> bool {
+        if !(config.enabled && self.hdr_supported) {
+            if let Some(bloom) = self.bloom.take() { bloom.destroy(); }
+            return false;
+        }
+        let bloom = self.bloom.get_or_insert_with(|| BloomChain::new(&self.device, self.config.format, self.config.width, self.config.height));
+        bloom.write_config(&self.queue, config);
+        true
+    }
+
+    /// シーンの描画先（表示先か HDR ターゲット）と MSAA のサンプル数（`msaa` をアダプタの対応範囲に下げる）に
+    /// パイプラインと深度/マルチサンプルのテクスチャを揃える
+    fn sync_scene_target(&mut self, hdr: bool, msaa: u32) {
+        let format = if hdr { HDR_FORMAT } else { self.config.format };
+        let samples = self.msaa_limits.samples(msaa, format);
+        self.scene.set_color_target(&self.device, format, samples);
+        self.scene_targets.configure(&self.device, format, samples, self.config.width, self.config.height);
+    }
+
+    /// 有効なポストエフェクトがあれば中間テクスチャを用意して uniform を書く（`time` は秒）。無ければ解放する。掛けるなら true
+    fn sync_post(&mut self, passes: &[PostPass], time: f32) -> bool {
+        if !post_active(passes) {
+            if let Some(post) = self.post.take() { post.destroy(); }
+            return false;
+        }
+        let post = self.post.get_or_insert_with(|| PostStack::new(&self.device, self.config.format, self.config.width, self.config.height));
+        post.prepare(&self.device, &self.queue, passes, time);
+        true
+    }
+
+    fn replace_instances(&mut self, edge_insts: &[EdgeInst], node_insts: &[NodeInst], attrs: InstanceAttrs) {
+        self.scene.replace_instances(&self.device, &self.queue, edge_insts, node_insts, attrs);
+    }
+
+    fn write_emphasis(&self, edge_w: &[f32], node_w: &[f32]) {
+        self.scene.write_emphasis(&self.queue, edge_w, node_w);
+    }
+
+    fn write_pulse(&self, edge_pulse: &[[f32; 2]]) {
+        self.scene.write_pulse(&self.queue, edge_pulse);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for GraphGpu {
+    fn drop(&mut self) {
+        self.scene.destroy();
+        self.scene_targets.destroy();
+        if let Some(bloom) = &self.bloom { bloom.destroy(); }
+        if let Some(post) = &self.post { post.destroy(); }
+        self.device.destroy();
+        // 残りのハンドル（surface/pipeline/instance）はフィールドの drop で解放される
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for GraphState {
+    fn drop(&mut self) {
+        self.gpu = None;
+        if self.software.is_none() { unconfigure_canvas(&self.canvas); }
+    }
+}
+
+/// WebGPU の無い環境の描画先。インスタンスは GPU 版と同じものを CPU 側に持ち、
+/// 毎フレーム `raster::Framebuffer` で描いて Canvas2D の ImageData へ転送する
+#[cfg(target_arch = "wasm32")]
+struct SoftwareCanvas {
+    ctx: web_sys::CanvasRenderingContext2d,
+    framebuffer: crate::raster::Framebuffer,
+    pixels: Vec<u8>, // ImageData へ渡す sRGB 8bit（毎フレーム使い回す）
+    edge_insts: Vec<EdgeInst>,
+    node_insts: Vec<NodeInst>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SoftwareCanvas {
+    fn new(canvas: &HtmlCanvasElement) -> Result<Self, JsValue> {
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("canvas 2d context unavailable"))?
+            .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+        Ok(Self { ctx, framebuffer: crate::raster::Framebuffer::new(1, 1), pixels: Vec::new(), edge_insts: Vec::new(), node_insts: Vec::new() })
+    }
+
+    /// キャンバスの現在の解像度で描いて転送する
+    fn draw(&mut self, u: &UGraph, attrs: InstanceAttrs) -> Result<(), JsValue> {
+        let canvas = self.ctx.canvas().ok_or_else(|| JsValue::from_str("canvas detached"))?;
+        let (w, h) = (canvas.width().max(1), canvas.height().max(1));
+        self.framebuffer.resize(w, h);
+        self.framebuffer.clear(crate::render::graph_clear_color());
+        self.framebuffer.draw(u, &self.edge_insts, &self.node_insts, attrs);
+        self.framebuffer.write_rgba8(&mut self.pixels);
+        let image = web_sys::ImageData::new_with_u8_clamped_array_and_sh(wasm_bindgen::Clamped(&self.pixels), w, h)?;
+        self.ctx.put_image_data(&image, 0.0, 0.0)
+    }
+}
+
+/// 属性（id・class・style）を保ったままキャンバス要素を複製して DOM 上で置き換える。
+/// 複製したキャンバスはまだどのコンテキストにも結び付いていない
+#[cfg(target_arch = "wasm32")]
+fn replace_canvas(canvas: &HtmlCanvasElement) -> Result<HtmlCanvasElement, JsValue> {
+    let fresh = canvas.clone_node()?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.replace_with_with_node_1(&fresh)?;
+    Ok(fresh)
+}
+
+/// wgpu は Surface の構成解除を公開していないため、GPUCanvasContext.unconfigure() を直接呼ぶ。
+/// これで同じキャンバスに対して再度 start_graph できる。
+#[cfg(target_arch = "wasm32")]
+fn unconfigure_canvas(canvas: &HtmlCanvasElement) {
+    let Ok(Some(ctx)) = canvas.get_context("webgpu") else { return };
+    let unconfigure = js_sys::Reflect::get(&ctx, &JsValue::from_str("unconfigure"))
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+    if let Some(f) = unconfigure {
+        let _ = f.call0(&ctx);
+    }
+}
+
+// ---- legacy function exports (既定のレンダラ1つを thread_local で保持) ----
+#[cfg(target_arch = "wasm32")]
+fn with_graph<R>(f: impl FnOnce(&mut GraphRenderer) -> Result<R, JsValue>) -> Result<R, JsValue> {
+    STATE_GRAPH.with(|s| s.borrow_mut().with(f))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub async fn start_graph(canvas_id: String) -> Result<(), JsValue> {
+    let doc = window().unwrap().document().unwrap();
+    let canvas = doc
+        .get_element_by_id(&canvas_id)
+        .ok_or_else(|| JsValue::from_str("canvas not found"))?
+        .dyn_into::<HtmlCanvasElement>()?;
+    // 再マウント等で二重に開始された場合は先に既存レンダラを解放する。
+    // デバイス取得を待つ間に別の開始や dispose_graph があれば、この結果は捨てる
+    dispose_graph();
+    let ticket = STATE_GRAPH.with(|s| s.borrow_mut().begin());
+    let renderer = GraphRenderer::new(canvas).await?;
+    if let Some(mut stale) = STATE_GRAPH.with(|s| s.borrow_mut().install(ticket, renderer)) { stale.dispose(); }
+    Ok(())
+}
+
+/// `start_graph` の CPU 描画版（WebGPU の無いブラウザ向け）。以降の関数はそのまま使える
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_graph_software(canvas_id: String) -> Result<(), JsValue> {
+    let doc = window().unwrap().document().unwrap();
+    let canvas = doc
+        .get_element_by_id(&canvas_id)
+        .ok_or_else(|| JsValue::from_str("canvas not found"))?
+        .dyn_into::<HtmlCanvasElement>()?;
+    dispose_graph();
+    let ticket = STATE_GRAPH.with(|s| s.borrow_mut().begin());
+    let renderer = GraphRenderer::new_software(canvas)?;
+    if let Some(mut stale) = STATE_GRAPH.with(|s| s.borrow_mut().install(ticket, renderer)) { stale.dispose(); }
+    Ok(())
+}
+
+/// 既定レンダラを破棄する（未開始なら何もしない）。後から start_graph を再実行できる。
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn dispose_graph() {
+    let renderer = STATE_GRAPH.with(|s| s.borrow_mut().take());
+    if let Some(mut r) = renderer {
+        r.dispose();
+    }
+}
+
+/// 既定レンダラの状態。未開始なら "not_started"。
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph_status() -> String {
+    STATE_GRAPH.with(|s| match s.borrow().get() {
+        Some(r) => r.status(),
+        None => GraphStatus::NotStarted.as_str().to_string(),
+    })
+}
+
+/// 既定レンダラの描画経路（`GraphRenderer.backend()`）
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph_backend() -> Result<String, JsValue> {
+    with_graph(|r| r.backend())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn frame_graph(time_ms: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.frame(time_ms))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn resize_graph(width: u32, height: u32) -> Result<(), JsValue> {
+    with_graph(|r| r.resize(width, height))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph_params(edge_thickness: f32, node_size: f32, flow_speed: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_params(edge_thickness, node_size, flow_speed))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_params(rot_speed: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_rot_speed(rot_speed))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_fog(start: f32, end: f32, strength: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_fog(start, end, strength))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_allpairs(enabled: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_allpairs(enabled))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_link_fade(link_on: f32, link_off: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_link_fade(link_on, link_off))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_nucleus_fade(link_on: f32, link_off: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_nucleus_fade(link_on, link_off))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_nucleus(enabled: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_nucleus(enabled))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_shell_profile(profile: u32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_shell_profile(profile))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn regenerate_graph3d(config_json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.regenerate(config_json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_data(json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_data(json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_layout(name: &str, json_params: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_layout(name, json_params))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_camera_controls(enabled: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_camera_controls(enabled))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn reset_graph3d_camera() -> Result<(), JsValue> {
+    with_graph(|r| r.reset_camera())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_camera_auto_resume(seconds: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_camera_auto_resume(seconds))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn focus_graph3d_nodes(ids: Vec<u32>, k_hops: u32, duration_s: f32, easing: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.focus_nodes(ids, k_hops, duration_s, easing))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn unfocus_graph3d(duration_s: f32, easing: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.unfocus(duration_s, easing))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn highlight_graph3d_nodes(ids: Vec<u32>) -> Result<(), JsValue> {
+    with_graph(|r| r.highlight_nodes(ids))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn clear_graph3d_highlight() -> Result<(), JsValue> {
+    with_graph(|r| r.clear_highlight())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_highlight_style(hops: u32, floor: f32, boost: f32, fade_rate: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_highlight_style(hops, floor, boost, fade_rate))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_bloom(enabled: bool, threshold: f32, intensity: f32, radius: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_bloom(enabled, threshold, intensity, radius))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_antialias(msaa: u32, analytic: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_antialias(msaa, analytic))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph3d_msaa_samples() -> Result<u32, JsValue> {
+    with_graph(|r| r.msaa_samples())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_post_effects(json: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.set_post_effects(json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_post_effect_enabled(name: &str, enabled: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_post_effect_enabled(name, enabled))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tune_graph3d_post_effect(name: &str, params_json: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.tune_post_effect(name, params_json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn emit_graph3d_pulse(node_id: u32, speed: f32, hops: u32) -> Result<(), JsValue> {
+    with_graph(|r| r.emit_pulse(node_id, speed, hops))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_audio_input(level: f32, spectrum: &[f32]) -> Result<(), JsValue> {
+    with_graph(|r| r.set_audio_input(level, spectrum))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_audio_mapping(config_json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_audio_mapping(config_json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tween_graph_params(edge_thickness: f32, node_size: f32, flow_speed: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.tween_params(edge_thickness, node_size, flow_speed, duration_s, curve))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tween_graph3d_params(rot_speed: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.tween_rot_speed(rot_speed, duration_s, curve))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tween_graph3d_fog(start: f32, end: f32, strength: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.tween_fog(start, end, strength, duration_s, curve))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tween_graph3d_link_fade(link_on: f32, link_off: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.tween_link_fade(link_on, link_off, duration_s, curve))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn tween_graph3d_nucleus_fade(link_on: f32, link_off: f32, duration_s: f32, curve: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.tween_nucleus_fade(link_on, link_off, duration_s, curve))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_config(json: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.set_graph_config(json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn get_graph3d_config() -> Result<String, JsValue> {
+    with_graph(|r| r.get_graph_config())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph3d_preset_names() -> Result<Vec<String>, JsValue> {
+    with_graph(|r| r.preset_names())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn load_graph3d_preset(name: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.load_preset(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn save_graph3d_preset(name: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.save_preset(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn register_graph3d_preset(name: &str, config_json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.register_preset(name, config_json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn remove_graph3d_preset(name: &str) -> Result<bool, JsValue> {
+    with_graph(|r| r.remove_preset(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn encode_graph3d_preset(name: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.encode_preset(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn load_graph3d_preset_code(code: &str) -> Result<String, JsValue> {
+    with_graph(|r| r.load_preset_code(code))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn load_graph3d_timeline(json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.load_timeline(json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn play_graph3d_timeline() -> Result<(), JsValue> {
+    with_graph(|r| r.play_timeline())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn pause_graph3d_timeline() -> Result<(), JsValue> {
+    with_graph(|r| r.pause_timeline())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn seek_graph3d_timeline(seconds: f32) -> Result<(), JsValue> {
+    with_graph(|r| r.seek_timeline(seconds))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_timeline_loop(enabled: bool) -> Result<(), JsValue> {
+    with_graph(|r| r.set_timeline_loop(enabled))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph3d_timeline_time() -> Result<f32, JsValue> {
+    with_graph(|r| r.timeline_time())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn clear_graph3d_timeline() -> Result<(), JsValue> {
+    with_graph(|r| r.clear_timeline())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_session_state(name: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_session_state(name))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn graph3d_session_state() -> Result<String, JsValue> {
+    with_graph(|r| r.session_state())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_session_transition(duration_s: f32, easing: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_session_transition(duration_s, easing))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_session_preset(name: &str, look_json: &str) -> Result<(), JsValue> {
+    with_graph(|r| r.set_session_preset(name, look_json))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_graph3d_node_hover(callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+    with_graph(|r| r.on_node_hover(callback))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_graph3d_node_click(callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+    with_graph(|r| r.on_node_click(callback))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn pick_graph3d_node(x: f32, y: f32) -> Result<Option<u32>, JsValue> {
+    with_graph(|r| r.pick_node(x, y))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn pick_graph3d_edge(x: f32, y: f32) -> Result<Option<u32>, JsValue> {
+    with_graph(|r| r.pick_edge(x, y))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_graph3d_data_limits(max_nodes: u32, max_edges: u32) -> Result<(), JsValue> {
+    with_graph(|r| r.set_data_limits(max_nodes, max_edges))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn start_graph3d_force_layout(config_json: &str, pinned: Vec<u32>) -> Result<(), JsValue> {
+    with_graph(|r| r.start_force_layout(config_json, pinned))
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn stop_graph3d_force_layout() -> Result<(), JsValue> {
+    with_graph(|r| r.stop_force_layout())
+}
+
+// ---- instance building (shared by start_graph / mode toggles / data loading) ----
+#[cfg(target_arch = "wasm32")]
+fn post_effect(name: &str) -> Result<PostEffect, JsValue> {
+    PostEffect::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = PostEffect::ALL.iter().map(|e| e.name()).collect();
+        JsValue::from_str(&format!("unknown post effect \"{name}\" (available: {})", known.join(", ")))
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn report_json(report: &ConfigReport) -> Result<String, JsValue> {
+    serde_json::to_string(report).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn shell_layout_settings(config: &ShellConfig) -> LayoutSettings {
+    LayoutSettings { name: "shells".to_string(), params: serde_json::to_value(config).unwrap_or_default() }
+}
+
+/// 検証済みのレイアウト（`GraphState::prepare_layout` → `install_layout`）
+#[cfg(target_arch = "wasm32")]
+enum PreparedLayout {
+    Keep, // "data": 現在のグラフを保つ
+    Shells(ShellConfig),
+    Generated(LoadedGraph, LayoutSettings),
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GraphState {
+    fn apply_shell_config(&mut self, config: ShellConfig) {
+        self.set_graph(config.graph());
+        self.layout_settings = shell_layout_settings(&config);
+        self.shell_config = config;
+    }
+
+    /// レイアウト設定を検証してグラフを作る（適用は `install_layout`）。"shells" は `regenerate` と同じ扱い
+    fn prepare_layout(&self, settings: &LayoutSettings) -> Result<PreparedLayout, String> {
+        let json = if settings.params.is_null() { "{}".to_string() } else { settings.params.to_string() };
+        match settings.name.as_str() {
+            crate::params::DATA_LAYOUT_NAME => Ok(PreparedLayout::Keep),
+            "shells" => crate::shell_config::parse_shell_config(&json).map(PreparedLayout::Shells).map_err(|e| e.to_string()),
+            name => {
+                let layout = crate::layout::LayoutRegistry::with_builtins().create(name, &json).map_err(|e| e.to_string())?;
+                let (nodes, edges) = layout.generate();
+                Ok(PreparedLayout::Generated(LoadedGraph::from_generated(nodes, edges), settings.clone()))
+            }
+        }
+    }
+
+    fn install_layout(&mut self, layout: PreparedLayout) {
+        match layout {
+            PreparedLayout::Keep => {}
+            PreparedLayout::Shells(config) => self.apply_shell_config(config),
+            PreparedLayout::Generated(graph, settings) => {
+                self.set_graph(graph);
+                self.layout_settings = settings;
+            }
+        }
+    }
+
+    /// 実効設定。トゥイーン中の項目はその目標値、状態遷移中は遷移先の見た目
+    fn config(&self) -> GraphConfig {
+        let mut values = self.params.to_array();
+        let mut nucleus = self.edge_mode_nucleus;
+        if let Some(tr) = &self.session_transition {
+            let to = tr.target();
+            values = to.params().to_array();
+            nucleus = to.nucleus > 0.0;
+        }
+        for f in ParamField::ALL {
+            if let Some(v) = self.tweens.target(f) { values[f.index()] = v; }
+        }
+        GraphConfig {
+            params: ParamsConfig::from_array(values),
+            modes: ModeFlags { allpairs: self.edge_mode_allpairs, nucleus },
+            bloom: self.bloom,
+            post: self.post.clone(),
+            antialias: self.antialias,
+            layout: Some(self.layout_settings.clone()),
+        }
+    }
+
+    /// 検証済み（params は丸め済み）の設定を適用する。レイアウトは現在と異なる時のみ作り直し、
+    /// 作れなければ何も変えずにエラーを返す。進行中のトゥイーン/状態遷移は止める
+    fn apply_config(&mut self, config: GraphConfig, mut warnings: Vec<ConfigIssue>) -> ConfigReport {
+        let layout = match &config.layout {
+            Some(settings) if *settings != self.layout_settings => match self.prepare_layout(settings) {
+                Ok(layout) => layout,
+                Err(message) => return ConfigReport::failed(vec![ConfigIssue { path: "layout".to_string(), message }]),
+            },
+            _ => PreparedLayout::Keep,
+        };
+        let node_count = match &layout {
+            PreparedLayout::Keep => self.graph.nodes.len(),
+            PreparedLayout::Shells(shells) => shells.node_count,
+            PreparedLayout::Generated(graph, _) => graph.nodes.len(),
+        };
+        let (config, modes_warning) = fit_modes_to_graph(config, node_count);
+        warnings.extend(modes_warning);
+        self.stop_session_transition();
+        self.tweens.clear();
+        self.params = GraphParams::from_array(config.params.to_array());
+        self.nucleus_presence = 1.0;
+        let modes_changed = self.edge_mode_allpairs != config.modes.allpairs || self.edge_mode_nucleus != config.modes.nucleus;
+        self.edge_mode_allpairs = config.modes.allpairs;
+        self.edge_mode_nucleus = config.modes.nucleus;
+        self.bloom = config.bloom;
+        self.post = config.post;
+        self.antialias = config.antialias;
+        if matches!(layout, PreparedLayout::Keep) {
+            if modes_changed { self.rebuild_instances(); }
+        } else {
+            self.install_layout(layout);
+        }
+        ConfigReport { ok: true, errors: Vec::new(), warnings, config: Some(self.config()) }
+    }
+
+    /// グラフを差し替える（進行中の力学配置は破棄）
+    /// 全ペア表示の上限を超えるグラフでは全ペア表示を解除する（設定の報告と描画を一致させる）
+    fn set_graph(&mut self, graph: LoadedGraph) {
+        self.graph = graph;
+        if self.edge_mode_allpairs {
+            if let Err(e) = check_allpairs(self.graph.nodes.len()) {
+                log::warn!("graph: {e}; all-pairs mode turned off");
+                self.edge_mode_allpairs = false;
+            }
+        }
+        self.layout = None;
+        self.adjacency = Adjacency::new(self.graph.nodes.len(), &self.graph.edges);
+        self.highlight.clear(); // インデックスが変わるので引き継がない
+        self.pulse = None;
+        self.rebuild_instances();
+    }
+
+    /// 力学配置を1フレーム分進め、位置をグラフへ反映する
+    fn step_layout(&mut self) {
+        let Some(layout) = self.layout.as_mut() else { return };
+        let running = layout.step(layout.config().steps_per_frame.max(1));
+        layout.write_to(&mut self.graph.nodes);
+        if !running { self.layout = None; }
+        self.upload_instances();
+    }
+
+    /// 現在のグラフとモードからインスタンス列を作り直し、GPUバッファを差し替える
+    fn rebuild_instances(&mut self) {
+        self.allpairs_edges = allpairs_edges(&self.graph, self.edge_mode_allpairs);
+        self.refresh_emphasis();
+        self.refresh_pulse();
+        self.upload_instances();
+    }
+
+    /// 位置だけが変わった時（力学配置）: 件数・強調は変わらないのでインスタンスだけ書き直す
+    fn upload_instances(&mut self) {
+        let edge_insts = build_edge_insts(&self.graph, self.allpairs_edges.as_deref(), self.edge_mode_nucleus);
+        let node_insts = build_node_insts(&self.graph, self.edge_mode_nucleus);
+        // 復旧中は何もしない（再構築時に現在のグラフから作られる）
+        if let Some(gpu) = self.gpu.as_mut() {
+            let attrs = InstanceAttrs { edge_emphasis: self.edge_emphasis.values(), node_emphasis: self.node_emphasis.values(), edge_pulse: &self.edge_pulse };
+            gpu.replace_instances(&edge_insts, &node_insts, attrs);
+        } else if let Some(sw) = self.software.as_mut() {
+            sw.edge_insts = edge_insts;
+            sw.node_insts = node_insts;
+        }
+    }
+
+    /// 注目ノードとモードから強調係数の目標値を作り直す（値の切り替えは frame でフェードする）。
+    /// 並びは build_edge_insts / build_node_insts と同じ（描画エッジ → 核エッジ、ノード → 核ノード）。
+    fn refresh_emphasis(&mut self) {
+        use crate::highlight::{edge_emphasis, node_emphasis};
+        let edges = self.allpairs_edges.as_deref().unwrap_or(&self.graph.edges);
+        let n_nucleus = if self.edge_mode_nucleus { self.graph.nodes.len() } else { 0 };
+        let (mut edge_w, mut node_w) = if self.highlight.is_empty() {
+            (vec![1.0; edges.len()], vec![1.0; self.graph.nodes.len()])
+        } else {
+            let hops = self.adjacency.hop_distances(&self.highlight, self.highlight_config.hops);
+            (edge_emphasis(&hops, edges, &self.highlight_config), node_emphasis(&hops, &self.highlight_config))
+        };
+        if self.edge_mode_nucleus {
+            // 核エッジは対応ノードが近傍内なら通常、外なら減光。核ノードは強調中は減光
+            edge_w.extend(node_w[..n_nucleus].iter().map(|&w| w.min(1.0)));
+            let nucleus_w = if self.highlight.is_empty() { 1.0 } else { self.highlight_config.floor };
+            node_w.push(nucleus_w);
+        }
+        self.edge_emphasis.set_target(edge_w);
+        self.node_emphasis.set_target(node_w);
+    }
+
+    /// 現在の見た目（GraphParams と核・色味・自動パルス）
+    fn look(&self) -> SessionLook {
+        let p = &self.params;
+        SessionLook {
+            edge_thickness: p.edge_thickness,
+            node_size: p.node_size,
+            flow_speed: p.flow_speed,
+            rot_speed: p.rot_speed,
+            fog_start: p.fog_start,
+            fog_end: p.fog_end,
+            fog_strength: p.fog_strength,
+            link_on: p.link_on,
+            link_off: p.link_off,
+            nuc_link_on: p.nuc_link_on,
+            nuc_link_off: p.nuc_link_off,
+            nucleus: if self.edge_mode_nucleus { self.nucleus_presence } else { 0.0 },
+            tint: self.tint,
+            pulse_rate: self.auto_pulse.rate,
+        }
+    }
+
+    /// 補間中の見た目を反映する。核はフェードイン開始時にモードを入れ、フェードアウトの終端（`finished`）で切る
+    fn apply_look(&mut self, look: &SessionLook, finished: bool) {
+        self.params = GraphParams {
+            edge_thickness: look.edge_thickness,
+            node_size: look.node_size,
+            flow_speed: look.flow_speed,
+            rot_speed: look.rot_speed,
+            fog_start: look.fog_start,
+            fog_end: look.fog_end,
+            fog_strength: look.fog_strength,
+            link_on: look.link_on,
+            link_off: look.link_off,
+            nuc_link_on: look.nuc_link_on,
+            nuc_link_off: look.nuc_link_off,
+        };
+        self.tint = look.tint;
+        self.auto_pulse.rate = look.pulse_rate;
+        self.nucleus_presence = look.nucleus;
+        let nucleus_on = look.nucleus > 0.0 || (self.edge_mode_nucleus && !finished);
+        if nucleus_on != self.edge_mode_nucleus {
+            self.edge_mode_nucleus = nucleus_on;
+            self.rebuild_instances();
+        }
+    }
+
+    fn set_session_state(&mut self, state: SessionState) {
+        let (duration, easing) = self.session_fade;
+        self.session = state;
+        self.tweens.clear(); // 全項目をプリセットへ動かすので個別のトゥイーンは止める
+        // 遷移中でも現在の見た目から始めるので途切れない
+        self.session_transition = Some(LookTransition::new(self.look(), *self.session_presets.get(state), duration, easing));
+    }
+
+    /// 項目を現在値から目標へ動かす（`duration` 0 なら即時）。setter もこれを通す
+    fn tween_fields(&mut self, targets: &[(ParamField, f32)], duration: f32, curve: TweenCurve) {
+        self.stop_session_transition();
+        let mut values = self.params.to_array();
+        for &(field, to) in targets {
+            self.tweens.start(field, values[field.index()], to, duration, curve);
+        }
+        self.tweens.step(0.0, &mut values); // 0秒のものはここで確定
+        self.params = GraphParams::from_array(values);
+    }
+
+    fn tween_params(&mut self, edge_thickness: f32, node_size: f32, flow_speed: f32, duration: f32, curve: TweenCurve) {
+        use crate::params::clamp_param;
+        self.tween_fields(&[
+            (ParamField::EdgeThickness, clamp_param(ParamField::EdgeThickness, edge_thickness)),
+            (ParamField::NodeSize, clamp_param(ParamField::NodeSize, node_size)),
+            (ParamField::FlowSpeed, clamp_param(ParamField::FlowSpeed, flow_speed)),
+        ], duration, curve);
+    }
+
+    fn tween_rot_speed(&mut self, rot_speed: f32, duration: f32, curve: TweenCurve) {
+        self.tween_fields(&[(ParamField::RotSpeed, crate::params::clamp_param(ParamField::RotSpeed, rot_speed))], duration, curve);
+    }
+
+    fn tween_fog(&mut self, start: f32, end: f32, strength: f32, duration: f32, curve: TweenCurve) {
+        let (fs, fe, st) = crate::params::clamp_fog(start, end, strength);
+        self.tween_fields(&[(ParamField::FogStart, fs), (ParamField::FogEnd, fe), (ParamField::FogStrength, st)], duration, curve);
+    }
+
+    /// `nucleus`: 核エッジ側（nuc_link_on/off）
+    fn tween_link_fade(&mut self, nucleus: bool, link_on: f32, link_off: f32, duration: f32, curve: TweenCurve) {
+        let (on, off) = crate::params::clamp_link_fade(nucleus, link_on, link_off);
+        let (f_on, f_off) = if nucleus { (ParamField::NucLinkOn, ParamField::NucLinkOff) } else { (ParamField::LinkOn, ParamField::LinkOff) };
+        self.tween_fields(&[(f_on, on), (f_off, off)], duration, curve);
+    }
+
+    /// 個別の setter による直接の指定を優先する（進行中の状態遷移はその時点の値で止める）
+    fn stop_session_transition(&mut self) {
+        self.session_transition = None;
+    }
+
+    fn set_highlight(&mut self, seeds: Vec<usize>) {
+        self.highlight = seeds;
+        self.refresh_emphasis();
+    }
+
+    /// 発生中のパルスとモードからエッジ毎のホップ数を作り直す（並びは refresh_emphasis と同じ。核エッジは対象外）
+    fn refresh_pulse(&mut self) {
+        use crate::pulse::{pulse_edge_hops, PULSE_NONE};
+        let edges = self.allpairs_edges.as_deref().unwrap_or(&self.graph.edges);
+        let mut edge_pulse = match self.pulse {
+            Some(p) => pulse_edge_hops(&self.adjacency.hop_distances(&[p.source], p.hops), edges, p.hops),
+            None => vec![PULSE_NONE; edges.len()],
+        };
+        if self.edge_mode_nucleus {
+            edge_pulse.extend(std::iter::repeat_n(PULSE_NONE, self.graph.nodes.len()));
+        }
+        self.edge_pulse = edge_pulse;
+    }
+
+    fn emit_pulse(&mut self, source: usize, speed: f32, hops: u32) {
+        let start = self.last_frame.map_or(0.0, |(t, _)| t);
+        self.set_pulse(Some(Pulse { source, hops, speed, start }));
+    }
+
+    fn set_pulse(&mut self, pulse: Option<Pulse>) {
+        self.pulse = pulse;
+        self.refresh_pulse();
+        if let Some(gpu) = self.gpu.as_ref() { gpu.write_pulse(&self.edge_pulse); }
+    }
+
+    /// `seeds`（複数なら重心）を注視し、`k_hops` ホップ以内の近傍が収まる姿勢（向きは `camera` のまま）
+    fn focus_pose(&self, seeds: &[usize], k_hops: u32, camera: &OrbitCamera) -> crate::camera::CameraPose {
+        let hops = self.adjacency.hop_distances(seeds, k_hops);
+        let mut focus = glam::Vec3::ZERO;
+        for &i in seeds { focus += glam::Vec3::from(self.graph.nodes[i].pos); }
+        let focus = (focus / seeds.len().max(1) as f32).to_array();
+        let points: Vec<[f32; 3]> = self.graph.nodes.iter().zip(&hops).filter(|(_, h)| h.is_some()).map(|(n, _)| n.pos).collect();
+        let aspect = self.width as f32 / self.height as f32;
+        camera.framing_pose(focus, &points, aspect)
+    }
+
+    /// 現在の状態を起点にタイムラインを読み込む（停止状態で 0 秒から）。進行中のトゥイーン/状態遷移は止める
+    fn load_timeline(&mut self, timeline: Timeline) {
+        self.stop_session_transition();
+        self.tweens.clear();
+        let base = TimelineBase {
+            params: self.params.to_array(),
+            nucleus: if self.edge_mode_nucleus { self.nucleus_presence } else { 0.0 },
+            allpairs: self.edge_mode_allpairs,
+            camera: self.camera.pose(),
+        };
+        let flights = timeline.camera_flights(base.camera, OrbitCamera::home_pose(), |ids, k_hops, from| {
+            let seeds: Vec<usize> = ids.iter().filter_map(|&id| self.graph.index_of(id)).collect();
+            let mut camera = self.camera.clone();
+            camera.yaw = from.yaw;
+            camera.pitch = from.pitch;
+            self.focus_pose(&seeds, k_hops, &camera)
+        });
+        let clock = TimelineClock::new(&timeline, &base, flights);
+        self.timeline = Some(TimelineRun {
+            timeline, base, clock,
+            playback: Playback::default(),
+            pulse: None,
+            focus: None,
+            seeked: true,
+        });
+    }
+
+    fn clear_timeline(&mut self) {
+        if self.timeline.take().is_some() { self.set_pulse(None); }
+    }
+
+    /// 再生位置を進め、その時刻の状態（params / 核 / 全ペア / パルス / 強調）を反映する。台本の時刻を返す
+    fn step_timeline(&mut self, clock: f32) -> Option<f32> {
+        let run = self.timeline.as_mut()?;
+        let t = run.timeline.local_time(run.playback.advance(clock));
+        let frame = run.timeline.sample(t, &run.base);
+        let pulse_cue = (frame.pulse != run.pulse).then(|| frame.pulse.map(|i| run.timeline.events[i].clone()));
+        let focus_cue = (frame.focus != run.focus).then(|| frame.focus.map(|i| run.timeline.events[i].action.clone()));
+        run.pulse = frame.pulse;
+        run.focus = frame.focus;
+        let snap = std::mem::take(&mut run.seeked);
+
+        let mut values = self.params.to_array();
+        for (v, f) in values.iter_mut().zip(frame.params) {
+            if let Some(f) = f { *v = f; }
+        }
+        self.params = GraphParams::from_array(values);
+        let mut rebuild = false;
+        if let Some(presence) = frame.nucleus {
+            self.nucleus_presence = presence;
+            rebuild |= (presence > 0.0) != self.edge_mode_nucleus;
+            self.edge_mode_nucleus = presence > 0.0;
+        }
+        if let Some(on) = frame.allpairs {
+            // 読み込み後に上限を超えるグラフへ差し替えた時は入れない
+            let on = on && check_allpairs(self.graph.nodes.len()).is_ok();
+            rebuild |= on != self.edge_mode_allpairs;
+            self.edge_mode_allpairs = on;
+        }
+        if rebuild { self.rebuild_instances(); }
+        if let Some(cue) = pulse_cue {
+            // 発生時刻は台本上の時刻（シェーダの時刻も台本に合わせているので、シーク後も同じ位置に波頭が来る）
+            let pulse = cue.and_then(|ev| match ev.action {
+                TimelineAction::Pulse { node, speed, hops } => self.graph.index_of(node).map(|source| Pulse { source, hops, speed, start: ev.at }),
+                _ => None,
+            });
+            self.set_pulse(pulse);
+        }
+        if let Some(cue) = focus_cue {
+            let seeds = match cue {
+                Some(TimelineAction::Focus { nodes, .. }) => nodes.iter().filter_map(|&id| self.graph.index_of(id)).collect(),
+                _ => Vec::new(),
+            };
+            self.set_highlight(seeds);
+        }
+        if snap {
+            self.edge_emphasis.snap();
+            self.node_emphasis.snap();
+            if let Some(gpu) = self.gpu.as_ref() { gpu.write_emphasis(self.edge_emphasis.values(), self.node_emphasis.values()); }
+        }
+        Some(t)
+    }
+
+    /// その時刻のカメラ姿勢にする（飛行、無ければ読み込み時の姿勢を rot_speed の積分で回す。操作より優先）
+    fn apply_timeline_camera(&mut self, t: f32) {
+        let Some(run) = self.timeline.as_ref() else { return };
+        self.camera.show_pose(run.clock.camera(t));
+    }
 }
@@ -0,0 +1,268 @@
+// キャンバスを使わないグラフ描画（ネイティブ用）。`wgpu::Texture` に描いて RGBA / PNG で読み戻す。
+// OG画像のサムネイル生成や、ソフトウェアアダプタ（lavapipe/llvmpipe）でのスナップショットテストに使う。
+// パイプラインとインスタンスの組み立ては wasm 側と同じ `render::GraphScene`（ブルームも同じ `bloom::BloomChain`）。
+
+use std::fmt;
+use std::future::Future;
+use std::sync::{mpsc, Arc};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::bloom::{BloomChain, HDR_FORMAT};
+use crate::postfx::{post_active, PostStack};
+use crate::render::{GraphScene, InstanceAttrs, MsaaLimits, SceneTargets};
+
+pub use crate::raster::RgbaImage;
+pub use crate::render::Snapshot;
+
+/// 描画先のフォーマット（キャンバスと同じく sRGB で書き込む）
+pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffscreenError {
+    NoAdapter(String),
+    RequestDevice(String),
+    InvalidSize { width: u32, height: u32, max: u32 },
+    Readback(String),
+}
+
+impl fmt::Display for OffscreenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter(msg) => write!(f, "no wgpu adapter available: {msg}"),
+            Self::RequestDevice(msg) => write!(f, "request_device failed: {msg}"),
+            Self::InvalidSize { width, height, max } => write!(f, "invalid size {width}x{height} (1..={max} per side)"),
+            Self::Readback(msg) => write!(f, "readback failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for OffscreenError {}
+
+/// `copy_texture_to_buffer` の1行のバイト数（`COPY_BYTES_PER_ROW_ALIGNMENT` に揃える）
+pub fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// 行末の詰め物を除いて詰め直す
+pub fn unpad_rows(padded: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let stride = padded_bytes_per_row(width) as usize;
+    let row = width as usize * 4;
+    padded.chunks(stride).take(height as usize).flat_map(|r| &r[..row]).copied().collect()
+}
+
+/// テクスチャ1枚に描くレンダラ。デバイスは自前で取得するか、呼び出し側のものを使う
+pub struct OffscreenRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    scene: GraphScene,
+    bloom: Option<BloomChain>, // ブルームを有効にしたスナップショットを描いた時に作る
+    post: Option<PostStack>,   // ポストエフェクトを掛けた時に作る
+    target: wgpu::Texture,
+    scene_targets: SceneTargets, // 深度と MSAA のカラー（描画先のフォーマットとサンプル数に合わせて作り直す）
+    msaa_limits: MsaaLimits,
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+}
+
+impl OffscreenRenderer {
+    /// 既定のアダプタでデバイスを取得する。`WGPU_BACKEND` 等の環境変数に従い、
+    /// GPU が無い環境ではソフトウェアアダプタ（lavapipe/llvmpipe）が選ばれる
+    pub fn new(width: u32, height: u32) -> Result<Self, OffscreenError> {
+        Self::with_instance(wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default()), width, height)
+    }
+
+    /// バックエンドを指定する（`Backends::GL` で WebGL2 と同じ GL 経路の確認に使う）
+    pub fn with_backends(backends: wgpu::Backends, width: u32, height: u32) -> Result<Self, OffscreenError> {
+        Self::with_instance(wgpu::Instance::new(&wgpu::InstanceDescriptor { backends, ..wgpu::InstanceDescriptor::from_env_or_default() }), width, height)
+    }
+
+    fn with_instance(instance: wgpu::Instance, width: u32, height: u32) -> Result<Self, OffscreenError> {
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::from_env().unwrap_or_default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .map_err(|e| OffscreenError::NoAdapter(e.to_string()))?;
+        let (device, queue) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("offscreen_device"),
+            // 8x MSAA などアダプタ固有のサンプル数を使えるように（あれば）
+            required_features: adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+            required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+            memory_hints: wgpu::MemoryHints::MemoryUsage,
+            trace: wgpu::Trace::Off,
+        }))
+        .map_err(|e| OffscreenError::RequestDevice(e.to_string()))?;
+        let msaa_limits = MsaaLimits::query(Some(&adapter), &device, OFFSCREEN_FORMAT);
+        Self::build(device, queue, msaa_limits, width, height)
+    }
+
+    /// 呼び出し側のデバイスで作る（ネイティブアプリへの組み込み用）。MSAA はフォーマットが保証するサンプル数まで
+    pub fn with_device(device: wgpu::Device, queue: wgpu::Queue, width: u32, height: u32) -> Result<Self, OffscreenError> {
+        let msaa_limits = MsaaLimits::query(None, &device, OFFSCREEN_FORMAT);
+        Self::build(device, queue, msaa_limits, width, height)
+    }
+
+    fn build(device: wgpu::Device, queue: wgpu::Queue, msaa_limits: MsaaLimits, width: u32, height: u32) -> Result<Self, OffscreenError> {
+        check_size(&device, width, height)?;
+        let empty = InstanceAttrs { edge_emphasis: &[], node_emphasis: &[], edge_pulse: &[] };
+        let scene = GraphScene::new(&device, crate::render::GRAPH_SHADER_WGSL, OFFSCREEN_FORMAT, &[], &[], empty);
+        let (target, readback) = create_targets(&device, width, height);
+        let scene_targets = SceneTargets::new(&device, OFFSCREEN_FORMAT, 1, width, height);
+        Ok(Self { device, queue, scene, bloom: None, post: None, target, scene_targets, msaa_limits, readback, width, height })
+    }
+
+    pub fn device(&self) -> &wgpu::Device { &self.device }
+
+    pub fn queue(&self) -> &wgpu::Queue { &self.queue }
+
+    /// 描画先（`RENDER_ATTACHMENT | COPY_SRC | TEXTURE_BINDING`）。`render` の後に読める
+    pub fn texture(&self) -> &wgpu::Texture { &self.target }
+
+    pub fn size(&self) -> (u32, u32) { (self.width, self.height) }
+
+    /// 使える MSAA のサンプル数の上限
+    pub fn msaa_limits(&self) -> MsaaLimits { self.msaa_limits }
+
+    /// 直前の `render` で使ったサンプル数（設定をアダプタの対応範囲に下げた値）
+    pub fn msaa_samples(&self) -> u32 { self.scene.sample_count() }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), OffscreenError> {
+        if (width, height) == (self.width, self.height) { return Ok(()); }
+        check_size(&self.device, width, height)?;
+        let (target, readback) = create_targets(&self.device, width, height);
+        std::mem::replace(&mut self.target, target).destroy();
+        std::mem::replace(&mut self.readback, readback).destroy();
+        let (format, samples) = (self.scene_targets.format(), self.scene_targets.samples());
+        self.scene_targets.configure(&self.device, format, samples, width, height);
+        if let Some(bloom) = self.bloom.as_mut() { bloom.resize(&self.device, width, height); }
+        if let Some(post) = self.post.as_mut() { post.resize(&self.device, width, height); }
+        (self.width, self.height) = (width, height);
+        Ok(())
+    }
+
+    /// `texture()` へ描く（完了は待たない）。`snap.bloom` が有効なら HDR ターゲットを経由し、
+    /// `snap.post` に有効なパスがあれば中間テクスチャへ描いてから順に掛ける。`snap.antialias.msaa` が 2 以上なら MSAA で描いて resolve する
+    pub fn render(&mut self, snap: &Snapshot) {
+        let frame = snap.still_frame(self.width, self.height);
+        self.scene.replace_instances(&self.device, &self.queue, &frame.edges, &frame.nodes, frame.attrs());
+        self.scene.write_uniforms(&self.queue, &frame.uniforms);
+
+        let color = self.target.create_view(&wgpu::TextureViewDescriptor::default());
+        let format = if snap.bloom.enabled { HDR_FORMAT } else { OFFSCREEN_FORMAT };
+        let samples = self.msaa_limits.samples(snap.antialias.clamped().msaa, format);
+        self.scene.set_color_target(&self.device, format, samples);
+        self.scene_targets.configure(&self.device, format, samples, self.width, self.height);
+        let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("enc_offscreen") });
+        let (device, width, height) = (&self.device, self.width, self.height);
+        let post = post_active(&snap.post).then(|| {
+            let post = self.post.get_or_insert_with(|| PostStack::new(device, OFFSCREEN_FORMAT, width, height));
+            post.prepare(device, &self.queue, &snap.post, snap.time);
+            &*post
+        });
+        let out = post.map_or(&color, |p| p.input_view());
+        if snap.bloom.enabled {
+            let bloom = self.bloom.get_or_insert_with(|| BloomChain::new(device, OFFSCREEN_FORMAT, width, height));
+            bloom.write_config(&self.queue, &snap.bloom.clamped());
+            self.scene.encode(&mut enc, bloom.hdr_view(), &self.scene_targets, snap.background);
+            bloom.encode(&mut enc, out);
+        } else {
+            self.scene.encode(&mut enc, out, &self.scene_targets, snap.background);
+        }
+        if let Some(post) = post { post.encode(&mut enc, &snap.post, &color); }
+        self.queue.submit(Some(enc.finish()));
+    }
+
+    /// `texture()` の中身を読み戻す（GPU の完了まで待つ）
+    pub fn read_rgba(&self) -> Result<RgbaImage, OffscreenError> {
+        let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("enc_readback") });
+        enc.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback,
+                layout: wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(padded_bytes_per_row(self.width)), rows_per_image: Some(self.height) },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(Some(enc.finish()));
+
+        let slice = self.readback.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |r| { let _ = tx.send(r); });
+        self.device.poll(wgpu::PollType::Wait).map_err(|e| OffscreenError::Readback(e.to_string()))?;
+        rx.recv()
+            .map_err(|e| OffscreenError::Readback(e.to_string()))?
+            .map_err(|e| OffscreenError::Readback(e.to_string()))?;
+        let pixels = unpad_rows(&slice.get_mapped_range(), self.width, self.height);
+        self.readback.unmap();
+        Ok(RgbaImage { width: self.width, height: self.height, pixels })
+    }
+
+    /// 描いて読み戻す
+    pub fn snapshot(&mut self, snap: &Snapshot) -> Result<RgbaImage, OffscreenError> {
+        self.render(snap);
+        self.read_rgba()
+    }
+
+    pub fn snapshot_png(&mut self, snap: &Snapshot) -> Result<Vec<u8>, OffscreenError> {
+        Ok(self.snapshot(snap)?.to_png())
+    }
+}
+
+impl Drop for OffscreenRenderer {
+    fn drop(&mut self) {
+        self.scene.destroy();
+        if let Some(bloom) = &self.bloom { bloom.destroy(); }
+        if let Some(post) = &self.post { post.destroy(); }
+        self.target.destroy();
+        self.scene_targets.destroy();
+        self.readback.destroy();
+    }
+}
+
+fn check_size(device: &wgpu::Device, width: u32, height: u32) -> Result<(), OffscreenError> {
+    let max = device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || width > max || height > max {
+        return Err(OffscreenError::InvalidSize { width, height, max });
+    }
+    Ok(())
+}
+
+fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::Buffer) {
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offscreen_color"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: OFFSCREEN_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("offscreen_readback"),
+        size: padded_bytes_per_row(width) as u64 * height as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (target, readback)
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) { self.0.unpark(); }
+}
+
+/// アダプタ/デバイス取得用の最小の executor（ネイティブの wgpu は実質すぐ完了する）
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = std::pin::pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(v) => return v,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
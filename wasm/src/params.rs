@@ -1,3 +1,48 @@
+// パラメータの範囲と丸め（wasm の setter / トゥイーン / セッションのプリセット / JSON 設定で共通）
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tween::{ParamField, PARAM_COUNT};
+use crate::postfx::{validate_post_passes, PostPass};
+
+/// 各項目の許容範囲
+pub fn param_range(field: ParamField) -> RangeInclusive<f32> {
+    match field {
+        ParamField::EdgeThickness => 0.0005..=0.05,
+        ParamField::NodeSize => 0.01..=0.3,
+        ParamField::FlowSpeed => 0.1..=5.0,
+        ParamField::RotSpeed => -1.0..=1.0,
+        ParamField::FogStart | ParamField::FogEnd => 0.0..=1.0,
+        // 1 を超えると fog 係数 (1 - f) が負になり加算合成で色が削れる
+        ParamField::FogStrength => 0.0..=1.0,
+        ParamField::LinkOn | ParamField::LinkOff => 0.0..=3.0,
+        ParamField::NucLinkOn | ParamField::NucLinkOff => 0.0..=5.0,
+    }
+}
+
+pub fn clamp_param(field: ParamField, v: f32) -> f32 {
+    let r = param_range(field);
+    v.clamp(*r.start(), *r.end())
+}
+
+fn ordered(a: f32, b: f32) -> (f32, f32) {
+    if a > b { (b, a) } else { (a, b) }
+}
+
+/// (start, end, strength)。start > end は入れ替える
+pub fn clamp_fog(start: f32, end: f32, strength: f32) -> (f32, f32, f32) {
+    let (s, e) = ordered(clamp_param(ParamField::FogStart, start), clamp_param(ParamField::FogEnd, end));
+    (s, e, clamp_param(ParamField::FogStrength, strength))
+}
+
+/// (on, off)。`nucleus` なら核エッジ側の範囲。on > off は入れ替える
+pub fn clamp_link_fade(nucleus: bool, on: f32, off: f32) -> (f32, f32) {
+    let (f_on, f_off) = if nucleus { (ParamField::NucLinkOn, ParamField::NucLinkOff) } else { (ParamField::LinkOn, ParamField::LinkOff) };
+    ordered(clamp_param(f_on, on), clamp_param(f_off, off))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GraphParamInput {
     pub edge_thickness: f32,
@@ -23,16 +68,276 @@ pub struct GraphParamOutput {
 }
 
 pub fn clamp_graph_params(inp: GraphParamInput) -> GraphParamOutput {
-    let edge_thickness = inp.edge_thickness.clamp(0.0005, 0.05);
-    let node_size = inp.node_size.clamp(0.01, 0.3);
-    let flow_speed = inp.flow_speed.clamp(0.1, 5.0);
-    let mut fog_start = inp.fog_start.clamp(0.0, 1.0);
-    let mut fog_end = inp.fog_end.clamp(0.0, 1.0);
-    if fog_start > fog_end { std::mem::swap(&mut fog_start, &mut fog_end); }
-    let fog_strength = inp.fog_strength.clamp(0.0, 1.0);
-    // link distances: clamp to reasonable range and order
-    let mut link_on = inp.link_on.clamp(0.0, 3.0);
-    let mut link_off = inp.link_off.clamp(0.0, 3.0);
-    if link_on > link_off { std::mem::swap(&mut link_on, &mut link_off); }
+    let edge_thickness = clamp_param(ParamField::EdgeThickness, inp.edge_thickness);
+    let node_size = clamp_param(ParamField::NodeSize, inp.node_size);
+    let flow_speed = clamp_param(ParamField::FlowSpeed, inp.flow_speed);
+    let (fog_start, fog_end, fog_strength) = clamp_fog(inp.fog_start, inp.fog_end, inp.fog_strength);
+    let (link_on, link_off) = clamp_link_fade(false, inp.link_on, inp.link_off);
     GraphParamOutput { edge_thickness, node_size, flow_speed, fog_start, fog_end, fog_strength, link_on, link_off }
 }
+
+/// GraphParams の全項目（JSON設定用）。省略したフィールドは既定値
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParamsConfig {
+    pub edge_thickness: f32,
+    pub node_size: f32,
+    pub flow_speed: f32,
+    pub rot_speed: f32,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub fog_strength: f32,
+    pub link_on: f32,
+    pub link_off: f32,
+    pub nuc_link_on: f32,
+    pub nuc_link_off: f32,
+}
+
+impl Default for ParamsConfig {
+    fn default() -> Self {
+        Self {
+            edge_thickness: crate::DEFAULT_GRAPH_EDGE_THICKNESS,
+            node_size: crate::DEFAULT_GRAPH_NODE_SIZE,
+            flow_speed: crate::DEFAULT_GRAPH_FLOW_SPEED,
+            rot_speed: crate::DEFAULT_GRAPH_ROT_SPEED,
+            fog_start: crate::DEFAULT_GRAPH_FOG_START,
+            fog_end: crate::DEFAULT_GRAPH_FOG_END,
+            fog_strength: crate::DEFAULT_GRAPH_FOG_STRENGTH,
+            link_on: crate::DEFAULT_GRAPH_LINK_ON,
+            link_off: crate::DEFAULT_GRAPH_LINK_OFF,
+            nuc_link_on: crate::DEFAULT_GRAPH_NUC_LINK_ON,
+            nuc_link_off: crate::DEFAULT_GRAPH_NUC_LINK_OFF,
+        }
+    }
+}
+
+impl ParamsConfig {
+    /// `ParamField` の順
+    pub fn to_array(self) -> [f32; PARAM_COUNT] {
+        [
+            self.edge_thickness, self.node_size, self.flow_speed, self.rot_speed,
+            self.fog_start, self.fog_end, self.fog_strength,
+            self.link_on, self.link_off, self.nuc_link_on, self.nuc_link_off,
+        ]
+    }
+
+    pub fn from_array(v: [f32; PARAM_COUNT]) -> Self {
+        let [edge_thickness, node_size, flow_speed, rot_speed, fog_start, fog_end, fog_strength, link_on, link_off, nuc_link_on, nuc_link_off] = v;
+        Self { edge_thickness, node_size, flow_speed, rot_speed, fog_start, fog_end, fog_strength, link_on, link_off, nuc_link_on, nuc_link_off }
+    }
+
+    /// 範囲に丸める（フォグとリンクの組は入れ替え）。非有限値は既定値に戻す
+    pub fn clamped(self) -> Self {
+        let d = Self::default();
+        let fin = |v: f32, d: f32| if v.is_finite() { v } else { d };
+        let (fog_start, fog_end, fog_strength) = clamp_fog(
+            fin(self.fog_start, d.fog_start), fin(self.fog_end, d.fog_end), fin(self.fog_strength, d.fog_strength),
+        );
+        let (link_on, link_off) = clamp_link_fade(false, fin(self.link_on, d.link_on), fin(self.link_off, d.link_off));
+        let (nuc_link_on, nuc_link_off) = clamp_link_fade(true, fin(self.nuc_link_on, d.nuc_link_on), fin(self.nuc_link_off, d.nuc_link_off));
+        Self {
+            edge_thickness: clamp_param(ParamField::EdgeThickness, fin(self.edge_thickness, d.edge_thickness)),
+            node_size: clamp_param(ParamField::NodeSize, fin(self.node_size, d.node_size)),
+            flow_speed: clamp_param(ParamField::FlowSpeed, fin(self.flow_speed, d.flow_speed)),
+            rot_speed: clamp_param(ParamField::RotSpeed, fin(self.rot_speed, d.rot_speed)),
+            fog_start,
+            fog_end,
+            fog_strength,
+            link_on,
+            link_off,
+            nuc_link_on,
+            nuc_link_off,
+        }
+    }
+}
+
+/// ブルーム（HDR ターゲットへ描いて明部をにじませる。`src/bloom.rs`）。既定は無効で従来の直接描画
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BloomConfig {
+    pub enabled: bool,
+    /// にじませ始める明るさ（リニア、rgb の最大成分）
+    pub threshold: f32,
+    /// 合成時のにじみの強さ
+    pub intensity: f32,
+    /// 0: 細い光輪 〜 1: 縮小段の粗い方まで広げる
+    pub radius: f32,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self { enabled: false, threshold: 0.35, intensity: 0.8, radius: 0.6 }
+    }
+}
+
+impl BloomConfig {
+    pub const THRESHOLD_RANGE: RangeInclusive<f32> = 0.0..=4.0;
+    pub const INTENSITY_RANGE: RangeInclusive<f32> = 0.0..=4.0;
+    pub const RADIUS_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+    /// (名前, 値, 範囲)。検証の警告用
+    fn fields(&self) -> [(&'static str, f32, RangeInclusive<f32>); 3] {
+        [
+            ("threshold", self.threshold, Self::THRESHOLD_RANGE),
+            ("intensity", self.intensity, Self::INTENSITY_RANGE),
+            ("radius", self.radius, Self::RADIUS_RANGE),
+        ]
+    }
+
+    /// 範囲に丸める。非有限値は既定値に戻す
+    pub fn clamped(self) -> Self {
+        let d = Self::default();
+        let fit = |v: f32, d: f32, r: RangeInclusive<f32>| if v.is_finite() { v.clamp(*r.start(), *r.end()) } else { d };
+        Self {
+            enabled: self.enabled,
+            threshold: fit(self.threshold, d.threshold, Self::THRESHOLD_RANGE),
+            intensity: fit(self.intensity, d.intensity, Self::INTENSITY_RANGE),
+            radius: fit(self.radius, d.radius, Self::RADIUS_RANGE),
+        }
+    }
+}
+
+/// 選べる MSAA のサンプル数
+pub const MSAA_SAMPLE_COUNTS: [u32; 3] = [1, 4, 8];
+
+/// アンチエイリアス。`msaa` はサンプル数（1 / 4 / 8、アダプタが対応しなければ下げる）。
+/// `analytic` は MSAA が重い端末向けで、1 ピクセルに満たない線を広げて明るさを下げる（`fs_edge`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AntialiasConfig {
+    pub msaa: u32,
+    pub analytic: bool,
+}
+
+impl Default for AntialiasConfig {
+    fn default() -> Self {
+        Self { msaa: 1, analytic: false }
+    }
+}
+
+impl AntialiasConfig {
+    /// `msaa` を選べる値のうち以下で最大のものへ下げる（0 は 1）
+    pub fn clamped(self) -> Self {
+        let msaa = MSAA_SAMPLE_COUNTS.into_iter().rev().find(|&n| n <= self.msaa).unwrap_or(1);
+        Self { msaa, ..self }
+    }
+}
+
+/// 表示モード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModeFlags {
+    pub allpairs: bool,
+    pub nucleus: bool,
+}
+
+/// グラフの生成元。`name` は登録済みレイアウト（"shells" / "sphere" / "cloud" / "spine"）か、
+/// 外部データ読み込み中を表す "data"（設定では現在のグラフを保つ）。`params` は各レイアウトのパラメータ（null で既定値）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutSettings {
+    pub name: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// 外部データ読み込み中を表すレイアウト名
+pub const DATA_LAYOUT_NAME: &str = "data";
+
+/// レンダラ設定一式（保存/復元用）。省略した節は既定値、`layout` を省略すると現在のグラフを保つ
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphConfig {
+    pub params: ParamsConfig,
+    pub modes: ModeFlags,
+    pub bloom: BloomConfig,
+    /// ポストエフェクトの列（この順に掛ける。`src/postfx.rs`）
+    pub post: Vec<PostPass>,
+    pub antialias: AntialiasConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutSettings>,
+}
+
+/// 検証で見つかった問題（`path` は "params.fog_strength" のような位置）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigIssue {
+    pub path: String,
+    pub message: String,
+}
+
+/// `set_graph_config` の結果。`errors` があれば何も適用しない。`warnings` は丸めた値など（適用済み）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigReport {
+    pub ok: bool,
+    pub errors: Vec<ConfigIssue>,
+    pub warnings: Vec<ConfigIssue>,
+    /// 適用後の実効設定（エラー時は None）
+    pub config: Option<GraphConfig>,
+}
+
+impl ConfigReport {
+    pub fn failed(errors: Vec<ConfigIssue>) -> Self {
+        Self { ok: false, errors, warnings: Vec::new(), config: None }
+    }
+}
+
+/// params を範囲に丸める。戻り値は (丸めた設定, 丸めた項目の警告)。
+/// レイアウトの中身（名前やパラメータ）の検証は生成する側で行う。
+pub fn validate_graph_config(cfg: GraphConfig) -> (GraphConfig, Vec<ConfigIssue>) {
+    let before = cfg.params.to_array();
+    let after = cfg.params.clamped().to_array();
+    let mut warnings: Vec<ConfigIssue> = ParamField::ALL.iter()
+        .filter(|f| before[f.index()] != after[f.index()])
+        .map(|&f| {
+            let r = param_range(f);
+            ConfigIssue {
+                path: format!("params.{}", f.name()),
+                message: format!(
+                    "{} adjusted to {} (range {}..={}, on/off pairs are ordered)",
+                    before[f.index()], after[f.index()], r.start(), r.end(),
+                ),
+            }
+        })
+        .collect();
+    let bloom = cfg.bloom.clamped();
+    for ((name, v, r), (_, c, _)) in cfg.bloom.fields().into_iter().zip(bloom.fields()) {
+        if v != c {
+            warnings.push(ConfigIssue {
+                path: format!("bloom.{name}"),
+                message: format!("{v} adjusted to {c} (range {}..={})", r.start(), r.end()),
+            });
+        }
+    }
+    let (post, post_warnings) = validate_post_passes(cfg.post);
+    warnings.extend(post_warnings);
+    let antialias = cfg.antialias.clamped();
+    if antialias.msaa != cfg.antialias.msaa {
+        warnings.push(ConfigIssue {
+            path: "antialias.msaa".to_string(),
+            message: format!("{} adjusted to {} (1, 4 or 8 samples)", cfg.antialias.msaa, antialias.msaa),
+        });
+    }
+    (GraphConfig { params: ParamsConfig::from_array(after), bloom, post, antialias, ..cfg }, warnings)
+}
+
+/// 適用先のグラフ（`node_count` ノード）で使えないモードを外す。全ペア表示は `ALLPAIRS_MAX_NODES` まで。
+/// 外した時は `modes.allpairs` の警告を返す（報告する設定と描画を一致させる）
+pub fn fit_modes_to_graph(cfg: GraphConfig, node_count: usize) -> (GraphConfig, Option<ConfigIssue>) {
+    if !cfg.modes.allpairs {
+        return (cfg, None);
+    }
+    match crate::graph3d::check_allpairs(node_count) {
+        Ok(()) => (cfg, None),
+        Err(message) => (
+            GraphConfig { modes: ModeFlags { allpairs: false, ..cfg.modes }, ..cfg },
+            Some(ConfigIssue { path: "modes.allpairs".to_string(), message: format!("{message}; all-pairs mode turned off") }),
+        ),
+    }
+}
+
+/// JSONを読み込み、`validate_graph_config` で丸める
+pub fn parse_graph_config(json: &str) -> Result<(GraphConfig, Vec<ConfigIssue>), Vec<ConfigIssue>> {
+    let cfg: GraphConfig = serde_json::from_str(json)
+        .map_err(|e| vec![ConfigIssue { path: String::new(), message: format!("invalid graph config json: {e}") }])?;
+    Ok(validate_graph_config(cfg))
+}
@@ -0,0 +1,151 @@
+// CPUピッキング: キャンバス上の座標からノード/エッジを引く
+//
+// GPUの読み戻しは非同期でフレームを跨ぐため、シェーダ（graph_shader.wgsl）と同じ
+// ドリフト・投影をCPUで再現し、ビルボード円/エッジ帯との当たり判定を行う。
+
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::graph3d::Edge3;
+use crate::shader_math::link_strength;
+
+fn fract(x: f32) -> f32 { x - x.floor() }
+
+/// WGSL `hash31` と同じ式
+fn hash31(p: [f32; 3]) -> [f32; 3] {
+    let dot = |k: [f32; 3]| p[0] * k[0] + p[1] * k[1] + p[2] * k[2];
+    let q = [
+        dot([12.9898, 78.233, 37.719]),
+        dot([39.346, 11.135, 83.155]),
+        dot([73.156, 52.235, 9.151]),
+    ];
+    q.map(|v| fract(v.sin() * 43758.547)) // WGSL の 43758.5453 と同じ f32 値
+}
+
+/// WGSL `drift` と同じ式（球面の接線方向のオフセット）。
+/// GPUの sin は大きな引数で精度が落ちるため完全一致はしないが、振幅（半径の2.5%程度）の範囲に収まる。
+pub fn shader_drift(p: [f32; 3], time: f32, speed: f32) -> [f32; 3] {
+    let pv = Vec3::from(p);
+    let r = pv.length();
+    if r < 1e-6 { return [0.0; 3]; }
+    let n = pv / r;
+    let up = if n.y.abs() > 0.99 { Vec3::X } else { Vec3::Y };
+    let t1 = n.cross(up).normalize();
+    let t2 = n.cross(t1).normalize();
+    let h = hash31(p);
+    let w = time * speed * 0.35;
+    let amp = 0.025 * r * (0.6 + 0.4 * h[0]);
+    let f1 = 1.2 + 0.8 * h[1];
+    let f2 = 1.6 + 0.6 * h[2];
+    let tau = std::f32::consts::TAU;
+    let off = t1 * (amp * (w * f1 + tau * h[0]).sin()) + t2 * (amp * (w * f2 + tau * h[1]).cos());
+    off.to_array()
+}
+
+/// ドリフト後の位置（シェーダ同様に半径を保って球面上を滑らせる）
+pub fn drifted_position(p: [f32; 3], time: f32, speed: f32) -> [f32; 3] {
+    let pv = Vec3::from(p);
+    let r = pv.length();
+    if r < 1e-6 { return [0.0; 3]; }
+    ((pv + Vec3::from(shader_drift(p, time, speed))).normalize() * r).to_array()
+}
+
+/// ピッキング時点の描画状態（最後に描いたフレームと同じ値を渡す）
+#[derive(Debug, Clone, Copy)]
+pub struct PickView {
+    pub view_proj: [[f32; 4]; 4],
+    /// 秒（シェーダの misc0.x）
+    pub time: f32,
+    pub flow_speed: f32,
+    /// キャンバスの表示サイズ（CSSピクセル、ポインタ座標と同じ単位）
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PickView {
+    /// CSSピクセル（左上原点）→ NDC
+    pub fn pixel_to_ndc(&self, x: f32, y: f32) -> [f32; 2] {
+        [2.0 * x / self.width.max(1.0) - 1.0, 1.0 - 2.0 * y / self.height.max(1.0)]
+    }
+
+    /// ドリフト後の位置をNDCへ投影する。カメラの後ろ（w <= 0）は None。
+    pub fn project(&self, p: [f32; 3]) -> Option<[f32; 3]> {
+        let q = drifted_position(p, self.time, self.flow_speed);
+        let clip = Mat4::from_cols_array_2d(&self.view_proj) * Vec4::new(q[0], q[1], q[2], 1.0);
+        if clip.w <= 1e-6 { return None; }
+        Some([clip.x / clip.w, clip.y / clip.w, clip.z / clip.w])
+    }
+
+    fn aspect(&self) -> f32 { self.width.max(1.0) / self.height.max(1.0) }
+
+    /// ピクセル数 → NDCの縦方向の長さ
+    fn px_to_ndc(&self, px: f32) -> f32 { 2.0 * px / self.height.max(1.0) }
+}
+
+/// `(x, y)`（CSSピクセル）の下にあるノードのインデックス。重なる場合は手前（NDC z が小さい方）。
+/// `radius(i)` はビルボード円の半径（NDC、シェーダの `0.5 * size * node_size`）、`tolerance_px` は当たりの余裕。
+pub fn pick_node(
+    view: &PickView,
+    positions: &[[f32; 3]],
+    radius: impl Fn(usize) -> f32,
+    x: f32,
+    y: f32,
+    tolerance_px: f32,
+) -> Option<usize> {
+    let [px, py] = view.pixel_to_ndc(x, y);
+    let tol = view.px_to_ndc(tolerance_px);
+    let mut best: Option<(f32, usize)> = None;
+    for (i, &p) in positions.iter().enumerate() {
+        let Some([nx, ny, nz]) = view.project(p) else { continue };
+        if !(-1.0..=1.0).contains(&nz) { continue; }
+        // ビルボードはNDC上の円（アスペクト補正なし）
+        let r = radius(i) + tol;
+        let (dx, dy) = (px - nx, py - ny);
+        if dx * dx + dy * dy <= r * r && best.is_none_or(|(z, _)| nz < z) {
+            best = Some((nz, i));
+        }
+    }
+    best.map(|(_, i)| i)
+}
+
+/// `(x, y)` の下にあるエッジのインデックス（`edges` の添字）。曲線オフセットは無視し、端点間の帯で判定する。
+/// `half_width(i)` は帯の半幅（アスペクト補正後のNDC、シェーダの thickness）。
+/// `link_fade`（on, off）でほぼ見えないエッジ（リンク強度 < 0.05）は除く。複数当たれば線分に最も近いもの。
+#[allow(clippy::too_many_arguments)]
+pub fn pick_edge(
+    view: &PickView,
+    positions: &[[f32; 3]],
+    edges: &[Edge3],
+    half_width: impl Fn(usize) -> f32,
+    link_fade: (f32, f32),
+    x: f32,
+    y: f32,
+    tolerance_px: f32,
+) -> Option<usize> {
+    let [px, py] = view.pixel_to_ndc(x, y);
+    let asp = view.aspect();
+    let tol = view.px_to_ndc(tolerance_px);
+    let q = [px * asp, py];
+    let mut best: Option<(f32, usize)> = None;
+    for (i, e) in edges.iter().enumerate() {
+        let (Some(&pa), Some(&pb)) = (positions.get(e.a as usize), positions.get(e.b as usize)) else { continue };
+        let wa = drifted_position(pa, view.time, view.flow_speed);
+        let wb = drifted_position(pb, view.time, view.flow_speed);
+        if link_strength(Vec3::from(wa).distance(Vec3::from(wb)), link_fade.0, link_fade.1) < 0.05 { continue; }
+        let (Some(a), Some(b)) = (view.project(pa), view.project(pb)) else { continue };
+        let a2 = [a[0] * asp, a[1]];
+        let b2 = [b[0] * asp, b[1]];
+        let d = dist_to_segment(q, a2, b2);
+        if d <= half_width(i) + tol && best.is_none_or(|(bd, _)| d < bd) {
+            best = Some((d, i));
+        }
+    }
+    best.map(|(_, i)| i)
+}
+
+fn dist_to_segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let (abx, aby) = (b[0] - a[0], b[1] - a[1]);
+    let len2 = abx * abx + aby * aby;
+    let t = if len2 > 0.0 { (((p[0] - a[0]) * abx + (p[1] - a[1]) * aby) / len2).clamp(0.0, 1.0) } else { 0.0 };
+    let (cx, cy) = (a[0] + abx * t - p[0], a[1] + aby * t - p[1]);
+    (cx * cx + cy * cy).sqrt()
+}
@@ -0,0 +1,62 @@
+// RGBA8 ⇔ PNG（オフスクリーン描画のスナップショットと、ゴールデン画像の比較用。ネイティブのみ）
+//
+// 符号化/復号は png クレートに任せる。読み込みは 8bit の RGBA/RGB（RGB は不透明として読む）のみ。
+
+use std::fmt;
+
+pub use crate::raster::RgbaImage;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PngError {
+    Decode(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(msg) => write!(f, "invalid PNG: {msg}"),
+            Self::Unsupported(what) => write!(f, "unsupported PNG: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+impl RgbaImage {
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_rgba(self.width, self.height, &self.pixels)
+    }
+}
+
+/// `rgba` は上の行から `width * height * 4` バイト（ストレートアルファ）
+pub fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(rgba.len(), width as usize * height as usize * 4, "rgba length must be width * height * 4");
+    let mut out = Vec::new();
+    let mut encoder = ::png::Encoder::new(&mut out, width, height);
+    encoder.set_color(::png::ColorType::Rgba);
+    encoder.set_depth(::png::BitDepth::Eight);
+    // Vec への書き込みは失敗しない（寸法は呼び出し側の画像のまま）
+    let mut writer = encoder.write_header().expect("PNG header for a valid image size");
+    writer.write_image_data(rgba).expect("PNG data for a matching buffer");
+    writer.finish().expect("PNG trailer");
+    out
+}
+
+/// `encode_rgba` の逆
+pub fn decode_rgba(png: &[u8]) -> Result<RgbaImage, PngError> {
+    let decode = |e: ::png::DecodingError| PngError::Decode(e.to_string());
+    let mut reader = ::png::Decoder::new(png).read_info().map_err(decode)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf).map_err(decode)?;
+    if frame.bit_depth != ::png::BitDepth::Eight {
+        return Err(PngError::Unsupported(format!("bit depth {:?}", frame.bit_depth)));
+    }
+    buf.truncate(frame.buffer_size());
+    let pixels = match frame.color_type {
+        ::png::ColorType::Rgba => buf,
+        ::png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        other => return Err(PngError::Unsupported(format!("color type {other:?}"))),
+    };
+    Ok(RgbaImage { width: frame.width, height: frame.height, pixels })
+}
@@ -0,0 +1,501 @@
+// ポストエフェクト（グラフ/ブルームの後に掛ける全画面パスの列）
+//
+// 設定は `GraphConfig::post` の順序付きリストで、各要素が1パス（`{ "effect": "vignette", "strength": 0.4, ... }`）。
+// パス毎に WGSL（`src/postfx/*.wgsl`、共通部 `common.wgsl` の後ろに連結）と uniform を持ち、
+// 2枚のテクスチャを交互に読み書きして、最後のパスだけ表示先へ書く。無効なパスは飛ばす。
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::params::ConfigIssue;
+
+pub const POST_COMMON_WGSL: &str = include_str!("postfx/common.wgsl");
+
+/// 1フレームに掛けられるパスの上限（設定ではこれを超えた分を捨てて警告する）
+pub const MAX_POST_PASSES: usize = 8;
+
+/// エフェクトの種類（設定の "effect"）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostEffect {
+    Vignette,
+    ChromaticAberration,
+    FilmGrain,
+    Scanlines,
+    ColorGrade,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 5] = [Self::Vignette, Self::ChromaticAberration, Self::FilmGrain, Self::Scanlines, Self::ColorGrade];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Vignette => "vignette",
+            Self::ChromaticAberration => "chromatic_aberration",
+            Self::FilmGrain => "film_grain",
+            Self::Scanlines => "scanlines",
+            Self::ColorGrade => "color_grade",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// `fs_post` を定義するフラグメント部分
+    pub fn fragment_wgsl(self) -> &'static str {
+        match self {
+            Self::Vignette => include_str!("postfx/vignette.wgsl"),
+            Self::ChromaticAberration => include_str!("postfx/chromatic_aberration.wgsl"),
+            Self::FilmGrain => include_str!("postfx/film_grain.wgsl"),
+            Self::Scanlines => include_str!("postfx/scanlines.wgsl"),
+            Self::ColorGrade => include_str!("postfx/color_grade.wgsl"),
+        }
+    }
+
+    /// 共通部と連結したシェーダ全体（`vs_fullscreen` / `fs_post`）
+    pub fn shader_source(self) -> String {
+        format!("{POST_COMMON_WGSL}\n{}", self.fragment_wgsl())
+    }
+}
+
+/// 周辺減光。`radius` は減光し終える距離（画面の角で 1）、`softness` はその手前の幅
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VignetteParams {
+    pub enabled: bool,
+    pub strength: f32,
+    pub radius: f32,
+    pub softness: f32,
+}
+
+impl Default for VignetteParams {
+    fn default() -> Self {
+        Self { enabled: true, strength: 0.35, radius: 1.0, softness: 0.55 }
+    }
+}
+
+/// 色収差。`amount` は画面端での r/b のずれ（uv）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChromaticAberrationParams {
+    pub enabled: bool,
+    pub amount: f32,
+}
+
+impl Default for ChromaticAberrationParams {
+    fn default() -> Self {
+        Self { enabled: true, amount: 0.004 }
+    }
+}
+
+/// フィルムグレイン。`size` は粒の大きさ（ピクセル）、`fps` は粒を振り直す頻度（0 で固定）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilmGrainParams {
+    pub enabled: bool,
+    pub amount: f32,
+    pub size: f32,
+    pub fps: f32,
+}
+
+impl Default for FilmGrainParams {
+    fn default() -> Self {
+        Self { enabled: true, amount: 0.06, size: 1.5, fps: 24.0 }
+    }
+}
+
+/// 走査線。`spacing` は周期（ピクセル）、`speed` は流れる速さ（ピクセル/秒）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanlinesParams {
+    pub enabled: bool,
+    pub intensity: f32,
+    pub spacing: f32,
+    pub speed: f32,
+}
+
+impl Default for ScanlinesParams {
+    fn default() -> Self {
+        Self { enabled: true, intensity: 0.25, spacing: 3.0, speed: 0.0 }
+    }
+}
+
+/// 色調整。`gain * (c + lift * (1 - c))` の後に `1 / gamma` 乗し、彩度を掛ける（rgb 毎）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorGradeParams {
+    pub enabled: bool,
+    pub lift: [f32; 3],
+    pub gamma: [f32; 3],
+    pub gain: [f32; 3],
+    pub saturation: f32,
+}
+
+impl Default for ColorGradeParams {
+    fn default() -> Self {
+        Self { enabled: true, lift: [0.0; 3], gamma: [1.0; 3], gain: [1.0; 3], saturation: 1.0 }
+    }
+}
+
+/// 1パス分の設定。JSON では "effect" で種類を選ぶ（省略した項目は既定値）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum PostPass {
+    Vignette(VignetteParams),
+    ChromaticAberration(ChromaticAberrationParams),
+    FilmGrain(FilmGrainParams),
+    Scanlines(ScanlinesParams),
+    ColorGrade(ColorGradeParams),
+}
+
+type Field<'a> = (&'static str, &'a mut f32, RangeInclusive<f32>);
+
+fn rgb_fields<'a>(names: [&'static str; 3], v: &'a mut [f32; 3], range: RangeInclusive<f32>) -> impl Iterator<Item = Field<'a>> {
+    names.into_iter().zip(v.iter_mut()).map(move |(n, x)| (n, x, range.clone()))
+}
+
+impl PostPass {
+    /// 既定値のパス
+    pub fn new(effect: PostEffect) -> Self {
+        match effect {
+            PostEffect::Vignette => Self::Vignette(VignetteParams::default()),
+            PostEffect::ChromaticAberration => Self::ChromaticAberration(ChromaticAberrationParams::default()),
+            PostEffect::FilmGrain => Self::FilmGrain(FilmGrainParams::default()),
+            PostEffect::Scanlines => Self::Scanlines(ScanlinesParams::default()),
+            PostEffect::ColorGrade => Self::ColorGrade(ColorGradeParams::default()),
+        }
+    }
+
+    pub fn effect(&self) -> PostEffect {
+        match self {
+            Self::Vignette(_) => PostEffect::Vignette,
+            Self::ChromaticAberration(_) => PostEffect::ChromaticAberration,
+            Self::FilmGrain(_) => PostEffect::FilmGrain,
+            Self::Scanlines(_) => PostEffect::Scanlines,
+            Self::ColorGrade(_) => PostEffect::ColorGrade,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Vignette(p) => p.enabled,
+            Self::ChromaticAberration(p) => p.enabled,
+            Self::FilmGrain(p) => p.enabled,
+            Self::Scanlines(p) => p.enabled,
+            Self::ColorGrade(p) => p.enabled,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        match self {
+            Self::Vignette(p) => p.enabled = enabled,
+            Self::ChromaticAberration(p) => p.enabled = enabled,
+            Self::FilmGrain(p) => p.enabled = enabled,
+            Self::Scanlines(p) => p.enabled = enabled,
+            Self::ColorGrade(p) => p.enabled = enabled,
+        }
+    }
+
+    /// 数値の項目と許容範囲（検証と丸め用）
+    fn fields_mut(&mut self) -> Vec<Field<'_>> {
+        match self {
+            Self::Vignette(p) => vec![
+                ("strength", &mut p.strength, 0.0..=1.0),
+                ("radius", &mut p.radius, 0.1..=1.5),
+                ("softness", &mut p.softness, 0.01..=1.0),
+            ],
+            Self::ChromaticAberration(p) => vec![("amount", &mut p.amount, 0.0..=0.05)],
+            Self::FilmGrain(p) => vec![
+                ("amount", &mut p.amount, 0.0..=0.5),
+                ("size", &mut p.size, 1.0..=8.0),
+                ("fps", &mut p.fps, 0.0..=60.0),
+            ],
+            Self::Scanlines(p) => vec![
+                ("intensity", &mut p.intensity, 0.0..=1.0),
+                ("spacing", &mut p.spacing, 1.0..=16.0),
+                ("speed", &mut p.speed, -50.0..=50.0),
+            ],
+            Self::ColorGrade(p) => rgb_fields(["lift[0]", "lift[1]", "lift[2]"], &mut p.lift, -0.5..=0.5)
+                .chain(rgb_fields(["gamma[0]", "gamma[1]", "gamma[2]"], &mut p.gamma, 0.2..=5.0))
+                .chain(rgb_fields(["gain[0]", "gain[1]", "gain[2]"], &mut p.gain, 0.0..=4.0))
+                .chain(std::iter::once(("saturation", &mut p.saturation, 0.0..=2.0)))
+                .collect(),
+        }
+    }
+
+    /// 範囲に丸める。非有限値は既定値に戻す
+    pub fn clamped(mut self) -> Self {
+        let mut defaults = Self::new(self.effect());
+        for ((_, v, r), (_, d, _)) in self.fields_mut().into_iter().zip(defaults.fields_mut()) {
+            *v = if v.is_finite() { v.clamp(*r.start(), *r.end()) } else { *d };
+        }
+        self
+    }
+
+    /// uniform の p0..p2（各 WGSL の先頭コメントの並び）
+    pub fn uniform_params(&self) -> [[f32; 4]; 3] {
+        match *self {
+            Self::Vignette(p) => [[p.strength, p.radius, p.softness, 0.0], [0.0; 4], [0.0; 4]],
+            Self::ChromaticAberration(p) => [[p.amount, 0.0, 0.0, 0.0], [0.0; 4], [0.0; 4]],
+            Self::FilmGrain(p) => [[p.amount, p.size, p.fps, 0.0], [0.0; 4], [0.0; 4]],
+            Self::Scanlines(p) => [[p.intensity, p.spacing, p.speed, 0.0], [0.0; 4], [0.0; 4]],
+            Self::ColorGrade(p) => {
+                let [lr, lg, lb] = p.lift;
+                let [gr, gg, gb] = p.gamma;
+                let [kr, kg, kb] = p.gain;
+                [[lr, lg, lb, p.saturation], [gr, gg, gb, 0.0], [kr, kg, kb, 0.0]]
+            }
+        }
+    }
+}
+
+/// 各パスを範囲に丸め、`MAX_POST_PASSES` を超えた分を捨てる。戻り値は (丸めた列, 警告)。
+/// 警告の `path` は "post[1].strength" のような位置
+pub fn validate_post_passes(passes: Vec<PostPass>) -> (Vec<PostPass>, Vec<ConfigIssue>) {
+    let mut warnings = Vec::new();
+    if passes.len() > MAX_POST_PASSES {
+        warnings.push(ConfigIssue {
+            path: "post".to_string(),
+            message: format!("{} passes given, only the first {MAX_POST_PASSES} are used", passes.len()),
+        });
+    }
+    let clamped = passes.into_iter().take(MAX_POST_PASSES).enumerate().map(|(i, mut pass)| {
+        let mut out = pass.clamped();
+        for ((name, before, r), (_, after, _)) in pass.fields_mut().into_iter().zip(out.fields_mut()) {
+            if *before != *after {
+                warnings.push(ConfigIssue {
+                    path: format!("post[{i}].{name}"),
+                    message: format!("{before} adjusted to {after} (range {}..={})", r.start(), r.end()),
+                });
+            }
+        }
+        out
+    }).collect();
+    (clamped, warnings)
+}
+
+/// 有効なパスが1つでもあるか（無ければ中間テクスチャを使わず直接描く）
+pub fn post_active(passes: &[PostPass]) -> bool {
+    passes.iter().any(PostPass::enabled)
+}
+
+/// `effect` の最初のパスへ JSON のオブジェクトを上書きして丸める（無ければ既定値で末尾に足す）。
+/// 戻り値は丸めの警告
+pub fn tune_post_pass(passes: &mut Vec<PostPass>, effect: PostEffect, patch_json: &str) -> Result<Vec<ConfigIssue>, String> {
+    let patch: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(patch_json).map_err(|e| format!("invalid post effect json: {e}"))?;
+    let index = match passes.iter().position(|p| p.effect() == effect) {
+        Some(i) => i,
+        None if passes.len() < MAX_POST_PASSES => {
+            passes.push(PostPass::new(effect));
+            passes.len() - 1
+        }
+        None => return Err(format!("at most {MAX_POST_PASSES} post passes can be stacked")),
+    };
+    let mut value = serde_json::to_value(passes[index]).map_err(|e| e.to_string())?;
+    if let serde_json::Value::Object(map) = &mut value {
+        for (k, v) in patch {
+            if k == "effect" { continue; } // 種類は変えない
+            map.insert(k, v);
+        }
+    }
+    let pass: PostPass = serde_json::from_value(value).map_err(|e| format!("invalid {} params: {e}", effect.name()))?;
+    let (mut clamped, warnings) = validate_post_passes(vec![pass]);
+    passes[index] = clamped.remove(0);
+    Ok(warnings.into_iter().map(|w| ConfigIssue { path: w.path.replacen("post[0]", &format!("post[{index}]"), 1), ..w }).collect())
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct UPost {
+    frame: [f32; 4], // time, width, height, 未使用
+    params: [[f32; 4]; 3],
+}
+
+/// 入力（前段の結果）を交互に置く2枚と、それを読むバインドグループ（リサイズで作り直す）
+struct PostTargets {
+    textures: [wgpu::Texture; 2],
+    views: [wgpu::TextureView; 2],
+    /// binds[i][k]: i 番目のパスの uniform で views[k] を読む
+    binds: Vec<[wgpu::BindGroup; 2]>,
+}
+
+impl PostTargets {
+    fn destroy(&self) {
+        for t in &self.textures { t.destroy(); }
+    }
+}
+
+/// ポストエフェクトの GPU 資源。`format` は表示先と同じ（中間テクスチャも同じ形式で、sRGB なら読み書きはリニア）
+pub struct PostStack {
+    format: wgpu::TextureFormat,
+    bgl: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: [Option<wgpu::RenderPipeline>; PostEffect::ALL.len()], // 使った種類だけ作る
+    ubos: Vec<wgpu::Buffer>, // パスの位置毎（MAX_POST_PASSES 個）
+    targets: PostTargets,
+    width: u32,
+    height: u32,
+}
+
+impl PostStack {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> PostStack {
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post_bgl"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture { sample_type: wgpu::TextureSampleType::Float { filterable: true }, view_dimension: wgpu::TextureViewDimension::D2, multisampled: false },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post_layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("post_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let ubos: Vec<wgpu::Buffer> = (0..MAX_POST_PASSES)
+            .map(|_| device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("post_ubo"),
+                size: std::mem::size_of::<UPost>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }))
+            .collect();
+        let targets = Self::create_targets(device, &bgl, &sampler, &ubos, format, width, height);
+        PostStack { format, bgl, layout, sampler, pipelines: Default::default(), ubos, targets, width, height }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        bgl: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        ubos: &[wgpu::Buffer],
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> PostTargets {
+        let texture = || device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("post_target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let textures = [texture(), texture()];
+        let views = [0, 1].map(|k| textures[k].create_view(&wgpu::TextureViewDescriptor::default()));
+        let binds = ubos.iter().map(|ubo| [0, 1].map(|k| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_bind"),
+            layout: bgl,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: ubo.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&views[k]) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+            ],
+        }))).collect();
+        PostTargets { textures, views, binds }
+    }
+
+    /// 前段（グラフ/ブルームの合成）の描画先。表示先と同じフォーマット・サイズ
+    pub fn input_view(&self) -> &wgpu::TextureView { &self.targets.views[0] }
+
+    pub fn size(&self) -> (u32, u32) { (self.width, self.height) }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) { return; }
+        let targets = Self::create_targets(device, &self.bgl, &self.sampler, &self.ubos, self.format, width, height);
+        std::mem::replace(&mut self.targets, targets).destroy();
+        (self.width, self.height) = (width, height);
+    }
+
+    /// 有効なパスのパイプラインを用意し、uniform を書く。`time` はシェーダの時刻（秒）
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, passes: &[PostPass], time: f32) {
+        for (i, pass) in passes.iter().filter(|p| p.enabled()).take(MAX_POST_PASSES).enumerate() {
+            let effect = pass.effect();
+            if self.pipelines[effect.index()].is_none() {
+                self.pipelines[effect.index()] = Some(self.create_pipeline(device, effect));
+            }
+            let u = UPost { frame: [time, self.width as f32, self.height as f32, 0.0], params: pass.clamped().uniform_params() };
+            queue.write_buffer(&self.ubos[i], 0, bytemuck::bytes_of(&u));
+        }
+    }
+
+    fn create_pipeline(&self, device: &wgpu::Device, effect: PostEffect) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(effect.name()),
+            source: wgpu::ShaderSource::Wgsl(effect.shader_source().into()),
+        });
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(effect.name()),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState { module: &shader, entry_point: Some("vs_fullscreen"), buffers: &[], compilation_options: wgpu::PipelineCompilationOptions::default() },
+            fragment: Some(wgpu::FragmentState { module: &shader, entry_point: Some("fs_post"), targets: &[Some(wgpu::ColorTargetState { format: self.format, blend: None, write_mask: wgpu::ColorWrites::ALL })], compilation_options: wgpu::PipelineCompilationOptions::default() }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// `input_view()` の内容に有効なパスを順に掛け、最後のパスで `output` を上書きする。
+    /// 直前に同じ `passes` で `prepare` しておく。有効なパスが無ければ何もしない
+    pub fn encode(&self, enc: &mut wgpu::CommandEncoder, passes: &[PostPass], output: &wgpu::TextureView) {
+        let enabled: Vec<&PostPass> = passes.iter().filter(|p| p.enabled()).take(MAX_POST_PASSES).collect();
+        let mut src = 0;
+        for (i, pass) in enabled.iter().enumerate() {
+            let Some(pipeline) = &self.pipelines[pass.effect().index()] else { continue };
+            let dst = if i + 1 == enabled.len() { output } else { &self.targets.views[1 - src] };
+            let mut rp = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("rpass_post"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: dst,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            rp.set_pipeline(pipeline);
+            rp.set_bind_group(0, &self.targets.binds[i][src], &[]);
+            rp.draw(0..3, 0..1);
+            src = 1 - src;
+        }
+    }
+
+    /// テクスチャとバッファを GC を待たずに解放する
+    pub fn destroy(&self) {
+        self.targets.destroy();
+        for ubo in &self.ubos { ubo.destroy(); }
+    }
+}
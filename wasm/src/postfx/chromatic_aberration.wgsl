@@ -0,0 +1,12 @@
+// 色収差。中心から外へ r を、内へ b をずらす（ずれは中心からの距離に比例、画面端で amount）
+// p0: amount
+
+@fragment
+fn fs_post(inp: VOut) -> @location(0) vec4<f32> {
+  let off = (inp.uv - 0.5) * 2.0 * u.p0.x;
+  let r = textureSample(src_tex, src_samp, inp.uv + off);
+  let g = textureSample(src_tex, src_samp, inp.uv);
+  let b = textureSample(src_tex, src_samp, inp.uv - off);
+  // 乗算済みアルファなので、ずれた色が透過部分へ出る所はアルファも広げる
+  return vec4<f32>(r.r, g.g, b.b, max(g.a, max(r.a, b.a)));
+}
@@ -0,0 +1,14 @@
+// 色調整（lift / gamma / gain と彩度）。乗算済みアルファを外してから掛け、0..1 に収めて戻す
+// p0: lift.rgb, saturation / p1: gamma.rgb / p2: gain.rgb
+
+@fragment
+fn fs_post(inp: VOut) -> @location(0) vec4<f32> {
+  let c = textureSample(src_tex, src_samp, inp.uv);
+  if (c.a <= 0.0) { return c; }
+  var col = c.rgb / c.a;
+  col = u.p2.rgb * (col + u.p0.rgb * (1.0 - col));
+  col = pow(max(col, vec3<f32>(0.0)), 1.0 / u.p1.rgb);
+  let l = dot(col, vec3<f32>(0.2126, 0.7152, 0.0722));
+  col = clamp(mix(vec3<f32>(l), col, u.p0.w), vec3<f32>(0.0), vec3<f32>(1.0));
+  return vec4<f32>(col * c.a, c.a);
+}
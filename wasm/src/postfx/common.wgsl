@@ -0,0 +1,25 @@
+// ポストエフェクト共通部: 全画面三角形と入力（前段の結果）。各エフェクトの WGSL はこの後ろに連結され、`fs_post` を定義する
+
+struct UPost {
+  frame: vec4<f32>, // time[s], width, height, 未使用
+  p0: vec4<f32>,    // 以下エフェクト毎（postfx.rs の PostPass::uniform_params）
+  p1: vec4<f32>,
+  p2: vec4<f32>,
+};
+@group(0) @binding(0) var<uniform> u: UPost;
+@group(0) @binding(1) var src_tex: texture_2d<f32>;
+@group(0) @binding(2) var src_samp: sampler;
+
+struct VOut {
+  @builtin(position) pos: vec4<f32>,
+  @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vi: u32) -> VOut {
+  let xy = vec2<f32>(f32((vi << 1u) & 2u), f32(vi & 2u));
+  var out: VOut;
+  out.pos = vec4<f32>(xy * 2.0 - 1.0, 0.0, 1.0);
+  out.uv = vec2<f32>(xy.x, 1.0 - xy.y);
+  return out;
+}
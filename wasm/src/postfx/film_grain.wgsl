@@ -0,0 +1,18 @@
+// フィルムグレイン。`size` ピクセル角の粒を `fps` 毎に振り直す（0 なら止まったまま）。透過部分には載せない
+// p0: amount, size, fps
+
+fn pcg(v: u32) -> u32 {
+  let state = v * 747796405u + 2891336453u;
+  let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+  return (word >> 22u) ^ word;
+}
+
+@fragment
+fn fs_post(inp: VOut) -> @location(0) vec4<f32> {
+  let c = textureSample(src_tex, src_samp, inp.uv);
+  let cell = vec2<u32>(floor(inp.pos.xy / max(u.p0.y, 1.0)));
+  let frame = u32(floor(u.frame.x * u.p0.z));
+  let h = pcg(cell.x + pcg(cell.y + pcg(frame)));
+  let n = f32(h) / 4294967295.0 - 0.5;
+  return vec4<f32>(max(c.rgb + n * u.p0.x * c.a, vec3<f32>(0.0)), c.a);
+}
@@ -0,0 +1,10 @@
+// CRT 風の走査線。`spacing` ピクセル周期の暗い帯を `speed` ピクセル/秒で流す
+// p0: intensity, spacing, speed
+
+@fragment
+fn fs_post(inp: VOut) -> @location(0) vec4<f32> {
+  let c = textureSample(src_tex, src_samp, inp.uv);
+  let y = inp.pos.y + u.frame.x * u.p0.z;
+  let band = 0.5 + 0.5 * cos(y / u.p0.y * 6.28318);
+  return vec4<f32>(c.rgb * (1.0 - u.p0.x * band), c.a);
+}
@@ -0,0 +1,11 @@
+// 周辺減光。距離は画面の角で 1（縦横比で補正した楕円ではなく円）
+// p0: strength, radius, softness
+
+@fragment
+fn fs_post(inp: VOut) -> @location(0) vec4<f32> {
+  let c = textureSample(src_tex, src_samp, inp.uv);
+  let aspect = vec2<f32>(u.frame.y / max(u.frame.z, 1.0), 1.0);
+  let d = length((inp.uv - 0.5) * aspect) / (0.5 * length(aspect));
+  let shade = 1.0 - u.p0.x * smoothstep(u.p0.y - u.p0.z, u.p0.y, d);
+  return vec4<f32>(c.rgb * shade, c.a);
+}
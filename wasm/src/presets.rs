@@ -0,0 +1,258 @@
+// 名前付きの設定プリセット（組み込み＋JSから登録）と、共有用の URL-safe な文字列表現
+//
+// 文字列は base64url（パディングなし）で、中身は `[版] [タグ 長さ 値]...`。
+// タグは項目毎に固定し、読めないタグは読み飛ばし、無いタグは既定値にする。
+// 項目を増やしても新しいタグを足すだけなので、古いリンクはそのまま読める。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::params::{AntialiasConfig, BloomConfig, GraphConfig, LayoutSettings, ModeFlags, ParamsConfig};
+use crate::session::{SessionLook, SessionState};
+use crate::tween::{ParamField, PARAM_COUNT};
+
+/// 書き出す版。中身の意味を変える時だけ上げる（項目の追加では上げない）
+pub const PRESET_CODE_VERSION: u8 = 1;
+
+/// 登録できる独自プリセットの上限
+pub const MAX_CUSTOM_PRESETS: usize = 64;
+
+/// プリセット名の長さの上限（バイト）
+pub const MAX_PRESET_NAME_LEN: usize = 64;
+
+// タグ: params は 1 + ParamField の並び（f32 LE）、モードはビット、レイアウトは LayoutSettings の JSON、
+// ブルームは [有効, threshold, intensity, radius]（既定のままなら書かない）、ポストエフェクトは PostPass の列の JSON（空なら書かない）、
+// AA は [msaa, analytic]（既定のままなら書かない）
+const TAG_PARAM_BASE: u8 = 0x01;
+const TAG_MODES: u8 = 0x20;
+const TAG_BLOOM: u8 = 0x21;
+const TAG_POST: u8 = 0x22;
+const TAG_ANTIALIAS: u8 = 0x23;
+const TAG_LAYOUT: u8 = 0x30;
+const MODE_ALLPAIRS: u8 = 1 << 0;
+const MODE_NUCLEUS: u8 = 1 << 1;
+
+/// 組み込みプリセット名
+pub const BUILTIN_PRESETS: [&str; 4] = ["calm-background", "active-research", "focus", "presentation"];
+
+/// 組み込みプリセット。既定値（`DEFAULT_GRAPH_*`）からの差分で定義し、グラフ（レイアウト）は変えない
+pub fn builtin_preset(name: &str) -> Option<GraphConfig> {
+    let base = ParamsConfig::default();
+    let (params, modes) = match name {
+        // 背景用: 細く遅く、霧を濃くして主張を抑える
+        "calm-background" => (
+            ParamsConfig { edge_thickness: 0.004, node_size: 0.06, flow_speed: 0.5, rot_speed: 0.02, fog_start: 0.45, fog_strength: 0.9, ..base },
+            ModeFlags::default(),
+        ),
+        // リサーチ中: セッション状態 researching の見た目そのもの（流れと回転を速め、核を出す）。色味とパルスは状態側だけ
+        "active-research" => {
+            let look = SessionLook::preset(SessionState::Researching);
+            (look.params(), ModeFlags { allpairs: false, nucleus: look.nucleus > 0.0 })
+        }
+        // 注視: 回転を止め、近いリンクだけを残す
+        "focus" => (
+            ParamsConfig { node_size: 0.09, flow_speed: 0.8, rot_speed: 0.0, fog_start: 0.35, fog_end: 0.8, fog_strength: 1.0, link_on: 0.5, link_off: 1.0, ..base },
+            ModeFlags::default(),
+        ),
+        // 投影用: 太く大きく、霧を薄めて全体を見せる
+        "presentation" => (
+            ParamsConfig { edge_thickness: 0.01, node_size: 0.12, flow_speed: 1.2, rot_speed: 0.06, fog_start: 0.7, fog_strength: 0.5, link_off: 1.8, ..base },
+            ModeFlags { allpairs: false, nucleus: true },
+        ),
+        _ => return None,
+    };
+    Some(GraphConfig { params, modes, ..GraphConfig::default() })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetError {
+    UnknownPreset { name: String, known: Vec<String> },
+    BuiltinName(String),
+    InvalidName(String),
+    TooManyPresets { max: usize },
+    Base64,
+    Truncated,
+    UnsupportedVersion(u8),
+    InvalidLayout(String),
+    InvalidPost(String),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPreset { name, known } => write!(f, "unknown preset \"{name}\" (available: {})", known.join(", ")),
+            Self::BuiltinName(name) => write!(f, "\"{name}\" is a built-in preset and cannot be replaced"),
+            Self::InvalidName(name) => write!(f, "preset name \"{name}\" must be 1..={MAX_PRESET_NAME_LEN} bytes"),
+            Self::TooManyPresets { max } => write!(f, "at most {max} custom presets can be registered"),
+            Self::Base64 => write!(f, "preset code is not valid base64url"),
+            Self::Truncated => write!(f, "preset code is truncated"),
+            Self::UnsupportedVersion(v) => write!(f, "preset code version {v} is newer than supported ({PRESET_CODE_VERSION})"),
+            Self::InvalidLayout(msg) => write!(f, "invalid layout in preset code: {msg}"),
+            Self::InvalidPost(msg) => write!(f, "invalid post effects in preset code: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+/// 組み込み＋独自プリセット。独自プリセットは名前順
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresetLibrary {
+    custom: BTreeMap<String, GraphConfig>,
+}
+
+impl PresetLibrary {
+    /// 組み込み → 独自の順
+    pub fn names(&self) -> Vec<String> {
+        BUILTIN_PRESETS.iter().map(|s| s.to_string()).chain(self.custom.keys().cloned()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<GraphConfig, PresetError> {
+        builtin_preset(name)
+            .or_else(|| self.custom.get(name).cloned())
+            .ok_or_else(|| PresetError::UnknownPreset { name: name.to_string(), known: self.names() })
+    }
+
+    /// 独自プリセットを登録する（同名は上書き）。組み込みの名前は使えない
+    pub fn register(&mut self, name: &str, config: GraphConfig) -> Result<(), PresetError> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_PRESET_NAME_LEN { return Err(PresetError::InvalidName(name.to_string())); }
+        if BUILTIN_PRESETS.contains(&name) { return Err(PresetError::BuiltinName(name.to_string())); }
+        if !self.custom.contains_key(name) && self.custom.len() >= MAX_CUSTOM_PRESETS {
+            return Err(PresetError::TooManyPresets { max: MAX_CUSTOM_PRESETS });
+        }
+        self.custom.insert(name.to_string(), config);
+        Ok(())
+    }
+
+    /// 独自プリセットを消す。あれば true
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.custom.remove(name.trim()).is_some()
+    }
+}
+
+fn push_tlv(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    // 長さは LEB128（レイアウトの JSON は 127 バイトを超えうる）
+    let mut len = value.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 { out.push(byte); break; }
+        out.push(byte | 0x80);
+    }
+    out.extend_from_slice(value);
+}
+
+/// 設定を URL-safe な文字列にする（値は f32 のまま、丸めずに保つ）
+pub fn encode_preset(config: &GraphConfig) -> String {
+    let mut bytes = vec![PRESET_CODE_VERSION];
+    for (i, v) in config.params.to_array().iter().enumerate() {
+        push_tlv(&mut bytes, TAG_PARAM_BASE + i as u8, &v.to_le_bytes());
+    }
+    let modes = (if config.modes.allpairs { MODE_ALLPAIRS } else { 0 }) | (if config.modes.nucleus { MODE_NUCLEUS } else { 0 });
+    push_tlv(&mut bytes, TAG_MODES, &[modes]);
+    if config.bloom != BloomConfig::default() {
+        let b = &config.bloom;
+        let mut value = vec![b.enabled as u8];
+        for v in [b.threshold, b.intensity, b.radius] { value.extend_from_slice(&v.to_le_bytes()); }
+        push_tlv(&mut bytes, TAG_BLOOM, &value);
+    }
+    if !config.post.is_empty() {
+        let json = serde_json::to_string(&config.post).unwrap_or_default();
+        push_tlv(&mut bytes, TAG_POST, json.as_bytes());
+    }
+    if config.antialias != AntialiasConfig::default() {
+        let aa = &config.antialias;
+        push_tlv(&mut bytes, TAG_ANTIALIAS, &[aa.msaa.min(u8::MAX as u32) as u8, aa.analytic as u8]);
+    }
+    if let Some(layout) = &config.layout {
+        let json = serde_json::to_string(layout).unwrap_or_default();
+        push_tlv(&mut bytes, TAG_LAYOUT, json.as_bytes());
+    }
+    base64url_encode(&bytes)
+}
+
+/// `encode_preset` の逆。無いタグは既定値、未知のタグは読み飛ばす（params / bloom / post / antialias の範囲は呼び出し側で丸める）
+pub fn decode_preset(code: &str) -> Result<GraphConfig, PresetError> {
+    let bytes = base64url_decode(code.trim())?;
+    let (&version, mut rest) = bytes.split_first().ok_or(PresetError::Truncated)?;
+    if version == 0 || version > PRESET_CODE_VERSION { return Err(PresetError::UnsupportedVersion(version)); }
+    let mut values = ParamsConfig::default().to_array();
+    let mut config = GraphConfig::default();
+    while let Some((&tag, tail)) = rest.split_first() {
+        let (len, tail) = read_len(tail)?;
+        if tail.len() < len { return Err(PresetError::Truncated); }
+        let (value, tail) = tail.split_at(len);
+        rest = tail;
+        let param = (tag as usize).wrapping_sub(TAG_PARAM_BASE as usize);
+        match tag {
+            _ if param < PARAM_COUNT => {
+                let Ok(raw) = <[u8; 4]>::try_from(value) else { return Err(PresetError::Truncated) };
+                values[ParamField::ALL[param].index()] = f32::from_le_bytes(raw);
+            }
+            TAG_MODES => {
+                let m = value.first().copied().unwrap_or(0);
+                config.modes = ModeFlags { allpairs: m & MODE_ALLPAIRS != 0, nucleus: m & MODE_NUCLEUS != 0 };
+            }
+            TAG_BLOOM => {
+                let Some((&enabled, rest)) = value.split_first() else { return Err(PresetError::Truncated) };
+                let mut f = rest.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+                let (Some(threshold), Some(intensity), Some(radius)) = (f.next(), f.next(), f.next()) else { return Err(PresetError::Truncated) };
+                config.bloom = BloomConfig { enabled: enabled != 0, threshold, intensity, radius };
+            }
+            TAG_POST => {
+                config.post = serde_json::from_slice(value).map_err(|e| PresetError::InvalidPost(e.to_string()))?;
+            }
+            TAG_ANTIALIAS => {
+                let [msaa, analytic, ..] = value[..] else { return Err(PresetError::Truncated) };
+                config.antialias = AntialiasConfig { msaa: msaa as u32, analytic: analytic != 0 };
+            }
+            TAG_LAYOUT => {
+                let layout: LayoutSettings = serde_json::from_slice(value).map_err(|e| PresetError::InvalidLayout(e.to_string()))?;
+                config.layout = Some(layout);
+            }
+            _ => {} // 新しい版で足された項目
+        }
+    }
+    config.params = ParamsConfig::from_array(values);
+    Ok(config)
+}
+
+fn read_len(bytes: &[u8]) -> Result<(usize, &[u8]), PresetError> {
+    let mut len = 0usize;
+    for (i, &b) in bytes.iter().enumerate().take(4) {
+        len |= ((b & 0x7f) as usize) << (7 * i);
+        if b & 0x80 == 0 { return Ok((len, &bytes[i + 1..])); }
+    }
+    Err(PresetError::Truncated)
+}
+
+const B64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// base64url（RFC 4648 §5、パディングなし）
+pub fn base64url_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(B64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+pub fn base64url_decode(s: &str) -> Result<Vec<u8>, PresetError> {
+    let s = s.trim_end_matches('=');
+    if s.len() % 4 == 1 { return Err(PresetError::Base64); }
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let v = B64.iter().position(|&b| b == c).ok_or(PresetError::Base64)? as u32;
+            n |= v << (18 - 6 * i);
+        }
+        out.extend_from_slice(&n.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(out)
+}
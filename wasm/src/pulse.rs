@@ -0,0 +1,91 @@
+// ノードから辺に沿って広がる波（アクティビティパルス）
+//
+// エッジ毎に「起点側のホップ数と進む向き」を別バッファで持ち、fs_edge が
+// 経過時間から求めた波頭の位置（ホップ単位）と比べて光らせる。
+
+use crate::graph3d::Edge3;
+
+/// 1ホップ進む毎の明るさの減衰率
+pub const PULSE_FALLOFF: f32 = 0.7;
+
+/// 広がる深さの上限（ホップ数）
+pub const MAX_PULSE_HOPS: u32 = 16;
+
+/// 自動パルスの速さ（ホップ/秒）と深さ
+pub const AUTO_PULSE_SPEED: f32 = 3.0;
+pub const AUTO_PULSE_HOPS: u32 = 4;
+
+/// 到達しないエッジの値
+pub const PULSE_NONE: [f32; 2] = [-1.0, 0.0];
+
+/// 発生中のパルス
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pulse {
+    /// 起点ノード（インデックス）
+    pub source: usize,
+    /// 広がる深さ（ホップ数）
+    pub hops: u32,
+    /// 波頭の速さ（ホップ/秒）
+    pub speed: f32,
+    /// 発生時刻（秒、シェーダの time と同じ基準）
+    pub start: f32,
+}
+
+impl Pulse {
+    /// 波頭が最深部を過ぎ、尾も消えたか
+    pub fn is_finished(&self, time: f32) -> bool {
+        (time - self.start) * self.speed > self.hops as f32 + 4.0
+    }
+}
+
+/// 一定の頻度（回/秒）でパルスを発生させるタイマー。頻度が途中で変わっても間隔は連続的に変わる
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AutoPulse {
+    pub rate: f32,
+    clock: f32,
+}
+
+impl AutoPulse {
+    /// `dt` 秒進め、発生させる時に true
+    pub fn step(&mut self, dt: f32) -> bool {
+        if self.rate.is_nan() || self.rate <= 0.0 {
+            self.clock = 0.0;
+            return false;
+        }
+        self.clock += dt.max(0.0) * self.rate;
+        if self.clock < 1.0 { return false; }
+        self.clock = self.clock.fract(); // 長いフレーム落ちでもまとめて1回だけ
+        true
+    }
+}
+
+/// エッジ毎の (起点側のホップ数, 向き)。向きは +1: a→b、-1: b→a、0: 両端が同じホップ（両側から中央へ）。
+/// `hops` は起点からのホップ数（`Adjacency::hop_distances` の結果）。起点側が `max_hops` 以上のエッジは `PULSE_NONE`。
+pub fn pulse_edge_hops(hops: &[Option<u32>], edges: &[Edge3], max_hops: u32) -> Vec<[f32; 2]> {
+    edges.iter()
+        .map(|e| {
+            let ha = hops.get(e.a as usize).copied().flatten();
+            let hb = hops.get(e.b as usize).copied().flatten();
+            let (from, dir) = match (ha, hb) {
+                (Some(a), Some(b)) if a < b => (a, 1.0),
+                (Some(a), Some(b)) if a > b => (b, -1.0),
+                (Some(a), Some(_)) => (a, 0.0),
+                (Some(a), None) => (a, 1.0),
+                (None, Some(b)) => (b, -1.0),
+                (None, None) => return PULSE_NONE,
+            };
+            if from >= max_hops { PULSE_NONE } else { [from as f32, dir] }
+        })
+        .collect()
+}
+
+/// fs_edge の `pulse_term` と同じ式。`front` は起点からの波頭の位置（ホップ）、`t` はエッジ上の位置（p1→p2, 0..1）。
+pub fn pulse_intensity(front: f32, edge: [f32; 2], t: f32, falloff: f32) -> f32 {
+    let [from, dir] = edge;
+    if from < 0.0 { return 0.0; }
+    let along = if dir > 0.5 { t } else if dir < -0.5 { 1.0 - t } else { t.min(1.0 - t) };
+    let d = (front - from) - along; // 正: 波頭が通過済み
+    // 波頭の手前は鋭く、後ろは尾を引いて消える
+    let shape = if d >= 0.0 { (-d * 2.0).exp() } else { (-d * d * 60.0).exp() };
+    falloff.powf(from) * shape
+}
@@ -0,0 +1,246 @@
+// グラフ描画の CPU リファレンス実装（GPU を使わないソフトウェアラスタライザ）
+//
+// graph_shader.wgsl の vs_edge/fs_edge/vs_node/fs_node と同じ計算を画素毎に行う。
+// 入力は GPU 版と同じ `UGraph`/`EdgeInst`/`NodeInst` なので、WebGPU の無いブラウザの代替描画
+// （Canvas2D の ImageData へ転送）と、GPU 無しで決まった画像になるゴールデンテストの両方に使える。
+//
+// GPU 版に合わせる点:
+// - 四角形は w=1 なので varying は画面上で線形（逆アフィン変換で (t, side) / local を求める）
+// - 深度は LessEqual・書き込みなし（クリア値 1.0）なので、z が 0..1 の断片だけ残る
+// - 加算合成は断片毎に 0..1 へ飽和させる（unorm のターゲットと同じ。負の寄与もそのまま引く）
+// - 描く順はエッジ→ノード、それぞれインスタンス順
+// 画素中心でのサンプリングなので、境界の画素や sRGB の量子化のタイミングは GPU と僅かに異なる。
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::picking::drifted_position;
+use crate::pulse::{pulse_intensity, PULSE_NONE};
+use crate::render::{EdgeInst, InstanceAttrs, NodeInst, Snapshot, UGraph};
+use crate::shader_math::{analytic_edge_fade, analytic_edge_width, apply_tint, edge_profile, fog_factor, node_glow_intensity, radial_strength, smoothstep};
+
+/// 画素（上の行から、1画素 RGBA 4 バイト、sRGB・ストレートアルファ）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+}
+
+/// リニア値の RGBA 浮動小数バッファ（上の行から）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    color: Vec<[f32; 4]>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, color: vec![[0.0; 4]; width as usize * height as usize] }
+    }
+
+    pub fn size(&self) -> (u32, u32) { (self.width, self.height) }
+
+    /// サイズが変わった時だけ確保し直す（中身は次の `clear` まで不定）
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) { return; }
+        *self = Self::new(width, height);
+    }
+
+    /// リニア値でクリア（`GraphScene::encode` の clear と同じ意味）
+    pub fn clear(&mut self, c: wgpu::Color) {
+        let c = [c.r, c.g, c.b, c.a].map(|v| (v as f32).clamp(0.0, 1.0));
+        self.color.fill(c);
+    }
+
+    /// エッジ→ノードの順に加算合成で描く。`attrs` が短い/空ならその分は強調 1.0・パルスなし
+    pub fn draw(&mut self, u: &UGraph, edges: &[EdgeInst], nodes: &[NodeInst], attrs: InstanceAttrs) {
+        let vp = Mat4::from_cols_array_2d(&u.view_proj);
+        for (i, e) in edges.iter().enumerate() {
+            let emph = attrs.edge_emphasis.get(i).copied().unwrap_or(1.0);
+            let pulse = attrs.edge_pulse.get(i).copied().unwrap_or(PULSE_NONE);
+            self.draw_edge(u, vp, e, emph, pulse);
+        }
+        for (i, n) in nodes.iter().enumerate() {
+            let emph = attrs.node_emphasis.get(i).copied().unwrap_or(1.0);
+            self.draw_node(u, vp, i, n, emph);
+        }
+    }
+
+    /// sRGB 8bit の RGBA（`Rgba8UnormSrgb` のターゲットを読み戻したのと同じ並び）
+    pub fn write_rgba8(&self, out: &mut Vec<u8>) {
+        out.clear();
+        out.reserve(self.color.len() * 4);
+        for c in &self.color {
+            out.extend_from_slice(&[linear_to_srgb8(c[0]), linear_to_srgb8(c[1]), linear_to_srgb8(c[2]), (c[3] * 255.0).round() as u8]);
+        }
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        let mut pixels = Vec::new();
+        self.write_rgba8(&mut pixels);
+        RgbaImage { width: self.width, height: self.height, pixels }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, col: [f32; 3]) {
+        let dst = &mut self.color[y as usize * self.width as usize + x as usize];
+        for (d, s) in dst.iter_mut().zip(col) { *d = (*d + s).clamp(0.0, 1.0); }
+        dst[3] = (dst[3] + 1.0).min(1.0);
+    }
+
+    fn ndc_to_pixel(&self, ndc: Vec2) -> Vec2 {
+        Vec2::new((ndc.x + 1.0) * 0.5 * self.width as f32, (1.0 - ndc.y) * 0.5 * self.height as f32)
+    }
+
+    fn draw_edge(&mut self, u: &UGraph, vp: Mat4, e: &EdgeInst, emph: f32, pulse: [f32; 2]) {
+        // vs_edge
+        let (time, speed) = (u.misc0[0], u.misc0[3]);
+        let p1 = Vec3::from(drifted_position(e.p1, time, speed));
+        let p2 = Vec3::from(drifted_position(e.p2, time, speed));
+        let (Some(a), Some(b)) = (project(vp, p1), project(vp, p2)) else { return };
+        let dist = p1.distance(p2);
+        let vlink = if e.params[2] > 0.5 {
+            (1.0 - smoothstep(u.misc3[0], u.misc3[1], dist)) * u.misc3[3]
+        } else {
+            1.0 - smoothstep(u.misc2[0], u.misc2[1], dist)
+        };
+        let vr = radial_strength(0.5 * (p1.length() + p2.length()), 0.6, 1.4, 0.35);
+        let asp = u.misc1[0];
+        let dir = Vec2::new((b.x - a.x) * asp, b.y - a.y);
+        let ortho = Vec2::new(-dir.y, dir.x) / dir.length().max(1e-4);
+        let thickness = u.misc0[1] * e.params[1] * (0.6 + 0.4 * vr) * (1.0 + 0.6 * (emph - 1.0).max(0.0));
+        let analytic = u.misc5[1] > 0.5;
+        let (thickness, cov) = if analytic { analytic_edge_width(thickness, self.height as f32, u.misc5[2]) } else { (thickness, 1.0) };
+
+        // 画素座標での平行四辺形 P(t, side) = origin + t*along + side*across
+        let (w, h) = (self.width as f32 * 0.5, self.height as f32 * 0.5);
+        let origin = self.ndc_to_pixel(a.truncate().truncate());
+        let along = Vec2::new(dir.x / asp * w, -dir.y * h);
+        let across = Vec2::new(ortho.x * thickness / asp * w, -ortho.y * thickness * h);
+        let corners = [origin - across, origin + across, origin + along + across, origin + along - across];
+        let det = along.perp_dot(across);
+        if !det.is_finite() || det.abs() < 1e-12 { return; }
+        // side の画素あたりの変化（fwidth と同じ |d/dx| + |d/dy|）
+        let fw = (along.x.abs() + along.y.abs()) / det.abs();
+
+        // fs_edge の画素毎に変わらない部分
+        let front = (u.misc0[0] - u.misc2[2]) * u.misc2[3];
+        let rgb = Vec3::new(e.color[0], e.color[1], e.color[2]);
+        let (curve_k, tscale) = (e.params[0], e.params[1]);
+        let (az, bz) = (a.z, b.z);
+        self.fill_convex(&corners, |px| {
+            let rel = px - origin;
+            let t = rel.perp_dot(across) / det;
+            let side = along.perp_dot(rel) / det;
+            if !(0.0..=1.0).contains(&t) || !(-1.0..=1.0).contains(&side) { return None; }
+            let z = az + (bz - az) * t;
+            if !(0.0..=1.0).contains(&z) { return None; }
+            let (base, pulse_shape) = edge_profile(t, side, curve_k, tscale, emph, u.misc4[3]);
+            let p = pulse_intensity(front, pulse, t, u.misc3[2]);
+            let col = rgb * base * vlink * vr * emph + rgb * pulse_shape * p * 1.5 * vlink * vr;
+            let col = apply_tint(col.to_array(), u.tint);
+            let aa = if analytic { analytic_edge_fade(side, fw) } else { 1.0 } * cov;
+            let f = fog_factor(z, u.misc1[1], u.misc1[2], u.misc1[3]);
+            Some(col.map(|c| c * aa * (1.0 - f)))
+        });
+    }
+
+    fn draw_node(&mut self, u: &UGraph, vp: Mat4, index: usize, n: &NodeInst, emph: f32) {
+        // vs_node
+        let center = Vec3::from(drifted_position(n.center, u.misc0[0], u.misc0[3]));
+        let Some(ndc) = project(vp, center) else { return };
+        if !(0.0..=1.0).contains(&ndc.z) { return; }
+        let nuc = if index as i32 == u.misc4[2] as i32 { u.misc4[1] } else { 1.0 };
+        let size = n.size * u.misc0[2] * nuc * (1.0 + 0.35 * (emph - 1.0).max(0.0));
+        let half = 0.5 * size;
+        if half.is_nan() || half <= 0.0 { return; }
+        let c = ndc.truncate().truncate();
+        let lo = self.ndc_to_pixel(c + Vec2::new(-half, half));
+        let hi = self.ndc_to_pixel(c + Vec2::new(half, -half));
+        let corners = [lo, Vec2::new(hi.x, lo.y), hi, Vec2::new(lo.x, hi.y)];
+
+        // fs_node
+        let rgb = Vec3::new(n.color[0], n.color[1], n.color[2]) * emph;
+        let f = fog_factor(ndc.z, u.misc1[1], u.misc1[2], u.misc1[3]);
+        let (w, h) = (self.width as f32, self.height as f32);
+        self.fill_convex(&corners, |px| {
+            let local = Vec2::new(px.x / w * 2.0 - 1.0, 1.0 - px.y / h * 2.0) - c;
+            if local.x.abs() > half || local.y.abs() > half { return None; }
+            let d = local.length() / half.max(1e-6);
+            let intensity = node_glow_intensity(d, u.misc0[0], n.phase, u.misc4[0]);
+            let col = apply_tint((rgb * intensity).to_array(), u.tint);
+            Some(col.map(|c| c * (1.0 - f)))
+        });
+    }
+
+    /// 凸多角形（画素座標）に中心が入る画素へ `shade` の結果を加算する。None は破棄
+    fn fill_convex(&mut self, corners: &[Vec2; 4], mut shade: impl FnMut(Vec2) -> Option<[f32; 3]>) {
+        if corners.iter().any(|c| !c.is_finite()) { return; }
+        let (min_y, max_y) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), c| (lo.min(c.y), hi.max(c.y)));
+        let y0 = (min_y - 0.5).ceil().max(0.0) as u32;
+        let y1 = ((max_y - 0.5).floor() + 1.0).clamp(0.0, self.height as f32) as u32;
+        for y in y0..y1 {
+            let yc = y as f32 + 0.5;
+            // 行の中心を横切る範囲
+            let (mut x_lo, mut x_hi) = (f32::MAX, f32::MIN);
+            for i in 0..4 {
+                let (p, q) = (corners[i], corners[(i + 1) % 4]);
+                if (p.y <= yc) == (q.y <= yc) { continue; }
+                let x = p.x + (yc - p.y) * (q.x - p.x) / (q.y - p.y);
+                x_lo = x_lo.min(x);
+                x_hi = x_hi.max(x);
+            }
+            if x_lo > x_hi { continue; }
+            let x0 = (x_lo - 0.5).ceil().max(0.0) as u32;
+            let x1 = ((x_hi - 0.5).floor() + 1.0).clamp(0.0, self.width as f32) as u32;
+            for x in x0..x1 {
+                if let Some(col) = shade(Vec2::new(x as f32 + 0.5, yc)) { self.blend(x, y, col); }
+            }
+        }
+    }
+}
+
+/// クリップ座標 → NDC（シェーダと同じく w で割るだけ）。無限大/NaN は描かない
+fn project(vp: Mat4, p: Vec3) -> Option<Vec4> {
+    let clip = vp * p.extend(1.0);
+    let ndc = clip / clip.w;
+    ndc.is_finite().then_some(ndc)
+}
+
+/// リニア値 → sRGB 8bit（`*Srgb` フォーマットへの書き込みと同じ変換）
+pub fn linear_to_srgb8(v: f32) -> u8 {
+    let v = v.clamp(0.0, 1.0);
+    let s = if v <= 0.003_130_8 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 };
+    (s * 255.0).round() as u8
+}
+
+/// Canvas2D での代替描画の画素数の上限。超える分は縮小して描き、CSS で拡大表示する
+pub const SOFTWARE_MAX_PIXELS: u32 = 640 * 360;
+
+/// 表示サイズに対する CPU 描画の解像度（縦横比を保って `max_pixels` 以下に縮める）
+pub fn software_resolution(width: u32, height: u32, max_pixels: u32) -> (u32, u32) {
+    let (w, h) = (width.max(1), height.max(1));
+    let area = w as f64 * h as f64;
+    if area <= max_pixels as f64 { return (w, h); }
+    let scale = (max_pixels as f64 / area).sqrt();
+    let h = ((h as f64 * scale) as u32).max(1);
+    // 極端に細長い時は短辺が 1 に張り付くので、長辺側で上限に収める
+    let w = ((w as f64 * scale) as u32).clamp(1, (max_pixels / h).max(1));
+    (w, h)
+}
+
+/// `OffscreenRenderer::snapshot` の CPU 版
+pub fn rasterize_snapshot(snap: &Snapshot, width: u32, height: u32) -> RgbaImage {
+    let frame = snap.still_frame(width, height);
+    let mut fb = Framebuffer::new(width, height);
+    fb.clear(snap.background);
+    fb.draw(&frame.uniforms, &frame.edges, &frame.nodes, frame.attrs());
+    fb.to_image()
+}
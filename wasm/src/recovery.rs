@@ -0,0 +1,52 @@
+// デバイス喪失/サーフェス喪失からの復旧方針（GPUに依存しない判断部分）
+
+/// ホスト側から参照する描画状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphStatus {
+    /// 未開始（互換関数で start_graph 前）
+    NotStarted,
+    /// 描画可能
+    Ready,
+    /// デバイス喪失を検知し、再取得中
+    Recovering,
+    /// 再取得に失敗。バックオフ後に再試行する
+    Lost,
+    /// dispose 済み
+    Disposed,
+}
+
+impl GraphStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotStarted => "not_started",
+            Self::Ready => "ready",
+            Self::Recovering => "recovering",
+            Self::Lost => "lost",
+            Self::Disposed => "disposed",
+        }
+    }
+}
+
+/// `get_current_texture` 失敗時の対処
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceAction {
+    /// サーフェスを再構成して取り直す
+    Reconfigure,
+    /// このフレームは描かない（次フレームで再試行）
+    SkipFrame,
+    /// デバイスごと作り直す
+    RecoverDevice,
+}
+
+pub fn surface_error_action(err: &wgpu::SurfaceError) -> SurfaceAction {
+    match err {
+        wgpu::SurfaceError::Timeout => SurfaceAction::SkipFrame,
+        wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost | wgpu::SurfaceError::Other => SurfaceAction::Reconfigure,
+        wgpu::SurfaceError::OutOfMemory => SurfaceAction::RecoverDevice,
+    }
+}
+
+/// デバイス再取得の再試行間隔（ms）。0.5s から倍々、最大8s。
+pub fn retry_delay_ms(failed_attempts: u32) -> f32 {
+    (500.0 * 2f32.powi(failed_attempts.min(4) as i32)).min(8000.0)
+}
@@ -0,0 +1,616 @@
+// グラフ描画のGPU部分（パイプライン / インスタンス / 1フレームのエンコード）。
+// キャンバス（wasm）とオフスクリーン（ネイティブ）で共用する。描画先のカラー/深度テクスチャは呼び出し側が持つ。
+
+use wgpu::util::DeviceExt;
+
+use crate::camera::{CameraPose, OrbitCamera};
+use crate::graph_data::LoadedGraph;
+use crate::params::{AntialiasConfig, BloomConfig, GraphConfig, ModeFlags, ParamsConfig};
+use crate::postfx::{validate_post_passes, PostPass};
+
+pub const GRAPH_SHADER_WGSL: &str = include_str!("graph_shader.wgsl");
+
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// 核ノードの位置
+pub const NUCLEUS_CENTER: [f32; 3] = [0.0, 0.0, 0.0];
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct QuadVertex { corner: [f32; 2] }
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct UGraph {
+    pub view_proj: [[f32;4];4],
+    pub misc0: [f32;4], // time, edge_thickness, node_size, flow_speed
+    pub misc1: [f32;4], // aspect, fogStart, fogEnd, fogStrength
+    pub misc2: [f32;4], // link_on, link_off, pulse_start, pulse_speed
+    pub misc3: [f32;4], // nuc_link_on, nuc_link_off, pulse_falloff, nucleus_presence
+    pub misc4: [f32;4], // node_glow, nucleus_scale, nucleus_index, flow_phase
+    pub tint: [f32;4], // rgb, amount
+    pub misc5: [f32;4], // target_height_px, analytic_aa (0/1), analytic_min_px, 未使用
+}
+
+impl UGraph {
+    /// パルス・音声変調なしの1フレーム（スナップショット用）。`node_count` は核ノードの添字に使う
+    pub fn still(view_proj: [[f32; 4]; 4], aspect: f32, time: f32, params: &ParamsConfig, nucleus: bool, node_count: usize, tint: [f32; 4]) -> Self {
+        let p = params;
+        let nucleus_index = if nucleus { node_count as f32 } else { -1.0 };
+        let flow_phase = (time * p.flow_speed).rem_euclid(1.0);
+        UGraph {
+            view_proj,
+            misc0: [time, p.edge_thickness, p.node_size, p.flow_speed],
+            misc1: [aspect, p.fog_start, p.fog_end, p.fog_strength],
+            misc2: [p.link_on, p.link_off, 0.0, 0.0],
+            misc3: [p.nuc_link_on, p.nuc_link_off, crate::pulse::PULSE_FALLOFF, 1.0],
+            misc4: [1.0, 1.0, nucleus_index, flow_phase],
+            tint,
+            misc5: [0.0; 4],
+        }
+    }
+
+    /// 解析的 AA（`fs_edge` で細い線を広げて暗くする）の有無と、描画先の高さ（ピクセル）
+    pub fn with_antialias(self, height: u32, analytic: bool) -> Self {
+        let flag = if analytic { 1.0 } else { 0.0 };
+        Self { misc5: [height as f32, flag, crate::shader_math::ANALYTIC_MIN_EDGE_PX, 0.0], ..self }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EdgeInst { pub p1: [f32; 3], pub p2: [f32; 3], pub color: [f32; 4], pub params: [f32; 3] } // params: (curve_k, thickness_scale, is_nucleus)
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NodeInst { pub center: [f32; 3], pub size: f32, pub color: [f32; 4], pub phase: f32 }
+
+/// インスタンスと同じ並びの追加属性（EdgeInst/NodeInst とは別バッファ）
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceAttrs<'a> {
+    pub edge_emphasis: &'a [f32],
+    pub node_emphasis: &'a [f32],
+    pub edge_pulse: &'a [[f32; 2]],
+}
+
+/// 1枚分のシーン（オフスクリーン描画と CPU ラスタライザ共通）。
+/// 時刻を固定するのでパルスや音声変調は無く、同じ入力は同じ画像になる
+#[derive(Debug, Clone)]
+pub struct Snapshot<'a> {
+    pub graph: &'a LoadedGraph,
+    pub params: ParamsConfig,
+    pub modes: ModeFlags,
+    pub camera: CameraPose,
+    /// シェーダの時刻（秒）。流れの縞や明滅の位相が決まる
+    pub time: f32,
+    /// rgb と寄せる量（`SessionLook::tint` と同じ）
+    pub tint: [f32; 4],
+    /// 既定はキャンバスと同じ透過。OG画像など不透明にしたい時に変える（リニア値）
+    pub background: wgpu::Color,
+    /// GPU 描画のみ（CPU ラスタライザはブルームを描かない）
+    pub bloom: BloomConfig,
+    /// ポストエフェクトの列（GPU 描画のみ。CPU ラスタライザは掛けない）
+    pub post: Vec<PostPass>,
+    /// MSAA は GPU 描画のみ（使えるサンプル数に下げる）。解析的 AA は CPU ラスタライザも同じ式で描く
+    pub antialias: AntialiasConfig,
+}
+
+impl<'a> Snapshot<'a> {
+    /// 既定のパラメータ・ホームの視点・時刻 0
+    pub fn new(graph: &'a LoadedGraph) -> Self {
+        Self {
+            graph,
+            params: ParamsConfig::default(),
+            modes: ModeFlags::default(),
+            camera: OrbitCamera::home_pose(),
+            time: 0.0,
+            tint: crate::session::SessionLook::default().tint,
+            background: graph_clear_color(),
+            bloom: BloomConfig::default(),
+            post: Vec::new(),
+            antialias: AntialiasConfig::default(),
+        }
+    }
+
+    /// 設定（プリセット等）の params / モード / ブルーム / ポストエフェクト / AA を使う。レイアウトは `graph` のまま
+    pub fn with_config(self, config: &GraphConfig) -> Self {
+        let post = validate_post_passes(config.post.clone()).0;
+        let antialias = config.antialias.clamped();
+        Self { params: config.params.clamped(), modes: config.modes, bloom: config.bloom.clamped(), post, antialias, ..self }
+    }
+
+    /// `width`x`height` の1フレーム分のインスタンスと uniform に展開する
+    pub fn still_frame(&self, width: u32, height: u32) -> StillFrame {
+        let graph = self.graph;
+        let allpairs = allpairs_edges(graph, self.modes.allpairs);
+        let edges = build_edge_insts(graph, allpairs.as_deref(), self.modes.nucleus);
+        let nodes = build_node_insts(graph, self.modes.nucleus);
+        let aspect = width as f32 / height.max(1) as f32;
+        let mut camera = OrbitCamera::default();
+        camera.show_pose(self.camera);
+        let uniforms = UGraph::still(camera.view_proj(aspect), aspect, self.time, &self.params, self.modes.nucleus, graph.nodes.len(), self.tint)
+            .with_antialias(height, self.antialias.analytic);
+        // 強調なし（全て 1.0）・パルスなし
+        StillFrame {
+            uniforms,
+            edge_emphasis: vec![1.0; edges.len()],
+            node_emphasis: vec![1.0; nodes.len()],
+            edge_pulse: vec![crate::pulse::PULSE_NONE; edges.len()],
+            edges,
+            nodes,
+        }
+    }
+}
+
+/// `Snapshot::still_frame` の結果。GPU へはそのまま書き込み、CPU ラスタライザへもそのまま渡す
+#[derive(Debug, Clone, PartialEq)]
+pub struct StillFrame {
+    pub uniforms: UGraph,
+    pub edges: Vec<EdgeInst>,
+    pub nodes: Vec<NodeInst>,
+    pub edge_emphasis: Vec<f32>,
+    pub node_emphasis: Vec<f32>,
+    pub edge_pulse: Vec<[f32; 2]>,
+}
+
+impl StillFrame {
+    pub fn attrs(&self) -> InstanceAttrs<'_> {
+        InstanceAttrs { edge_emphasis: &self.edge_emphasis, node_emphasis: &self.node_emphasis, edge_pulse: &self.edge_pulse }
+    }
+}
+
+/// 強調係数（インスタンス毎の f32、location 5）。エッジ/ノード共通
+const EMPHASIS_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<f32>() as u64,
+    step_mode: wgpu::VertexStepMode::Instance,
+    attributes: &[wgpu::VertexAttribute { shader_location: 5, format: wgpu::VertexFormat::Float32, offset: 0 }],
+};
+
+/// パルス用のホップ数と向き（エッジインスタンス毎の vec2<f32>、location 6）
+const PULSE_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<[f32; 2]>() as u64,
+    step_mode: wgpu::VertexStepMode::Instance,
+    attributes: &[wgpu::VertexAttribute { shader_location: 6, format: wgpu::VertexFormat::Float32x2, offset: 0 }],
+};
+
+/// 全ペア表示のエッジ（`ALLPAIRS_MAX_NODES` を超えるグラフでは None。呼び出し側で全ペア表示を解除しておく）
+pub fn allpairs_edges(graph: &LoadedGraph, allpairs: bool) -> Option<Vec<crate::graph3d::Edge3>> {
+    (allpairs && crate::graph3d::check_allpairs(graph.nodes.len()).is_ok())
+        .then(|| crate::graph3d::build_all_pairs_edges(&graph.nodes))
+}
+
+/// `allpairs` は `allpairs_edges` の結果（None ならデータのエッジを重み付きで描く）
+pub fn build_edge_insts(graph: &LoadedGraph, allpairs: Option<&[crate::graph3d::Edge3]>, nucleus: bool) -> Vec<EdgeInst> {
+    use crate::graph::{edge_weight_scale, theme_edge_color};
+    use crate::graph3d::EdgeKind3;
+    let edges = allpairs.unwrap_or(&graph.edges);
+    let weights: Option<&[f32]> = if allpairs.is_some() { None } else { Some(&graph.weights) };
+    let mut edge_insts: Vec<EdgeInst> = Vec::with_capacity(edges.len() + graph.nodes.len());
+    for (i, e) in edges.iter().enumerate() {
+        let a = graph.nodes[e.a as usize].pos;
+        let b = graph.nodes[e.b as usize].pos;
+        let base_col = theme_edge_color(e.kind);
+        let (ts_base, col_scale) = edge_style(e.kind);
+        let ts = ts_base * weights.map(|w| edge_weight_scale(w[i])).unwrap_or(1.0);
+        let col = [base_col[0]*col_scale, base_col[1]*col_scale, base_col[2]*col_scale, base_col[3]];
+        edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, ts, 0.0] });
+    }
+    // overlay nucleus edges if mode is enabled
+    if nucleus {
+        let segs = crate::graph3d::build_nucleus_segments(&graph.nodes, NUCLEUS_CENTER);
+        let col = theme_edge_color(EdgeKind3::Extra);
+        for (a,b) in segs { edge_insts.push(EdgeInst { p1: a, p2: b, color: col, params: [0.0, 0.90, 1.0] }); }
+    }
+    edge_insts
+}
+
+/// 種別ごとの (太さ倍率, 明度倍率)。同シェルは少し細く/薄く、異シェルは基準。
+pub fn edge_style(kind: crate::graph3d::EdgeKind3) -> (f32, f32) {
+    match kind {
+        crate::graph3d::EdgeKind3::Mesh => (0.85, 0.85),
+        crate::graph3d::EdgeKind3::Extra => (1.00, 1.00),
+    }
+}
+
+pub fn build_node_insts(graph: &LoadedGraph, nucleus: bool) -> Vec<NodeInst> {
+    use crate::graph::{theme_category_color, theme_node_color, theme_node_size};
+    let mut node_insts: Vec<NodeInst> = Vec::with_capacity(graph.nodes.len() + 1);
+    for (i,n) in graph.nodes.iter().enumerate() {
+        let size = theme_node_size(i);
+        // カテゴリ指定があればカテゴリ色、無ければテーマ（ハブ→マゼンタ、通常→シアン）
+        let col = graph.categories[i].map(theme_category_color).unwrap_or_else(|| theme_node_color(i));
+        node_insts.push(NodeInst { center: n.pos, size, color: col, phase: node_phase(i) });
+    }
+    // nucleus node（サイズ大/マゼンタ）
+    if nucleus {
+        let nuc_color = crate::graph::palette_color(crate::graph::Palette::Magenta);
+        node_insts.push(NodeInst { center: NUCLEUS_CENTER, size: 2.0, color: nuc_color, phase: 0.0 });
+    }
+    node_insts
+}
+
+fn node_phase(i: usize) -> f32 {
+    let mut x = i as u32;
+    // xorshift-like hash
+    x ^= x >> 16; x = x.wrapping_mul(747796405); x ^= x >> 16; x = x.wrapping_mul(2891336453); x ^= x >> 16;
+    (x as f32) / (u32::MAX as f32) * std::f32::consts::TAU
+}
+
+fn create_instance_buffer<T: bytemuck::Pod>(device: &wgpu::Device, label: &str, data: &[T]) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(data),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+/// 描画先と同じサイズの深度テクスチャ（`sample_count` はカラーと揃える）
+pub fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("depth_tex_graph"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    })
+}
+
+/// `format` の機能。アダプタ固有の機能（8x MSAA など）はデバイスで有効にしてあり、アダプタが分かる時だけ使う
+pub fn texture_format_features(adapter: Option<&wgpu::Adapter>, device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::TextureFormatFeatures {
+    match adapter {
+        Some(adapter) if device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) => adapter.get_texture_format_features(format),
+        _ => format.guaranteed_format_features(device.features()),
+    }
+}
+
+/// カラー（resolve も要る）と深度の両方で使える最大のサンプル数（8 / 4 / 1）
+pub fn max_msaa_samples(color: wgpu::TextureFormatFeatures, depth: wgpu::TextureFormatFeatures) -> u32 {
+    let resolvable = color.flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE);
+    [8, 4]
+        .into_iter()
+        .find(|&n| resolvable && color.flags.sample_count_supported(n) && depth.flags.sample_count_supported(n))
+        .unwrap_or(1)
+}
+
+/// 設定のサンプル数を `max`（`max_msaa_samples`）に収める（8 が無ければ 4、それも無ければ 1）
+pub fn msaa_samples(requested: u32, max: u32) -> u32 {
+    crate::params::MSAA_SAMPLE_COUNTS.into_iter().rev().find(|&n| n <= requested.min(max)).unwrap_or(1)
+}
+
+/// 描画先ごとの MSAA の上限（表示先のフォーマット / ブルームの HDR ターゲット）。デバイスを作った時に調べる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsaaLimits {
+    pub direct: u32,
+    pub hdr: u32,
+}
+
+impl MsaaLimits {
+    pub fn query(adapter: Option<&wgpu::Adapter>, device: &wgpu::Device, direct_format: wgpu::TextureFormat) -> Self {
+        let depth = texture_format_features(adapter, device, DEPTH_FORMAT);
+        let max = |format| max_msaa_samples(texture_format_features(adapter, device, format), depth);
+        Self { direct: max(direct_format), hdr: max(crate::bloom::HDR_FORMAT) }
+    }
+
+    /// `format` へ描く時に使うサンプル数
+    pub fn samples(&self, requested: u32, format: wgpu::TextureFormat) -> u32 {
+        msaa_samples(requested, if format == crate::bloom::HDR_FORMAT { self.hdr } else { self.direct })
+    }
+}
+
+/// シーンの描画パスに付くテクスチャ: 深度と、MSAA 時のマルチサンプルのカラー（パスの終わりに描画先へ resolve する）。
+/// 描画先のフォーマット・サンプル数・サイズが変わったら `configure` で作り直す
+pub struct SceneTargets {
+    format: wgpu::TextureFormat,
+    samples: u32,
+    width: u32,
+    height: u32,
+    depth: wgpu::Texture,
+    depth_view: wgpu::TextureView,
+    msaa: Option<(wgpu::Texture, wgpu::TextureView)>, // samples > 1 の時のみ
+}
+
+impl SceneTargets {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, samples: u32, width: u32, height: u32) -> SceneTargets {
+        let depth = create_depth_texture(device, width, height, samples);
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa = (samples > 1).then(|| {
+            let tex = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("msaa_color_graph"),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: samples,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            let view = tex.create_view(&wgpu::TextureViewDescriptor::default());
+            (tex, view)
+        });
+        SceneTargets { format, samples, width, height, depth, depth_view, msaa }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat { self.format }
+
+    pub fn samples(&self) -> u32 { self.samples }
+
+    /// どれかが変わった時だけ作り直す（古いテクスチャは即時解放）
+    pub fn configure(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, samples: u32, width: u32, height: u32) {
+        if (format, samples, width, height) == (self.format, self.samples, self.width, self.height) { return; }
+        std::mem::replace(self, SceneTargets::new(device, format, samples, width, height)).destroy();
+    }
+
+    pub fn destroy(&self) {
+        self.depth.destroy();
+        if let Some((tex, _)) = &self.msaa { tex.destroy(); }
+    }
+}
+
+/// キャンバスと同じ背景（透過の黒）
+pub fn graph_clear_color() -> wgpu::Color {
+    let c = crate::graph::graph_clear_color_srgb();
+    let a = crate::graph::graph_clear_alpha();
+    wgpu::Color { r: c[0] as f64, g: c[1] as f64, b: c[2] as f64, a: a as f64 }
+}
+
+/// エッジとノードのパイプライン（加算合成、深度は読むだけ）
+fn create_pipelines(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> (wgpu::RenderPipeline, wgpu::RenderPipeline) {
+    let additive = wgpu::BlendState {
+        color: wgpu::BlendComponent { src_factor: wgpu::BlendFactor::One, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add },
+        alpha: wgpu::BlendComponent { src_factor: wgpu::BlendFactor::One, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add },
+    };
+    let quad_layout = wgpu::VertexBufferLayout { // corners
+        array_stride: std::mem::size_of::<QuadVertex>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[wgpu::VertexAttribute { shader_location: 0, format: wgpu::VertexFormat::Float32x2, offset: 0 }],
+    };
+    let pipeline = |label: &str, vs: &str, fs: &str, buffers: &[wgpu::VertexBufferLayout]| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState { module: shader, entry_point: Some(vs), buffers, compilation_options: wgpu::PipelineCompilationOptions::default() },
+            fragment: Some(wgpu::FragmentState { module: shader, entry_point: Some(fs), targets: &[Some(wgpu::ColorTargetState { format: color_format, blend: Some(additive), write_mask: wgpu::ColorWrites::ALL })], compilation_options: wgpu::PipelineCompilationOptions::default() }),
+            primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, strip_index_format: None, unclipped_depth: false, polygon_mode: wgpu::PolygonMode::Fill, conservative: false, cull_mode: None, front_face: wgpu::FrontFace::Ccw },
+            depth_stencil: Some(wgpu::DepthStencilState { format: DEPTH_FORMAT, depth_write_enabled: false, depth_compare: wgpu::CompareFunction::LessEqual, stencil: wgpu::StencilState::default(), bias: wgpu::DepthBiasState::default() }),
+            multisample: wgpu::MultisampleState { count: sample_count, ..Default::default() },
+            multiview: None,
+            cache: None,
+        })
+    };
+    // edge pipeline
+    let edge_layouts = [
+        quad_layout.clone(),
+        wgpu::VertexBufferLayout { // EdgeInst
+            array_stride: std::mem::size_of::<EdgeInst>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { shader_location: 1, format: wgpu::VertexFormat::Float32x3, offset: 0 },
+                wgpu::VertexAttribute { shader_location: 2, format: wgpu::VertexFormat::Float32x3, offset: 12 },
+                wgpu::VertexAttribute { shader_location: 3, format: wgpu::VertexFormat::Float32x4, offset: 24 },
+                wgpu::VertexAttribute { shader_location: 4, format: wgpu::VertexFormat::Float32x3, offset: 40 },
+            ],
+        },
+        EMPHASIS_LAYOUT,
+        PULSE_LAYOUT,
+    ];
+    let pipe_edge = pipeline("pipe_edge", "vs_edge", "fs_edge", &edge_layouts);
+    // node pipeline
+    let node_layouts = [
+        quad_layout,
+        wgpu::VertexBufferLayout { // NodeInst
+            array_stride: std::mem::size_of::<NodeInst>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { shader_location: 1, format: wgpu::VertexFormat::Float32x3, offset: 0 },
+                wgpu::VertexAttribute { shader_location: 2, format: wgpu::VertexFormat::Float32,   offset: 12 },
+                wgpu::VertexAttribute { shader_location: 3, format: wgpu::VertexFormat::Float32x4, offset: 16 },
+                wgpu::VertexAttribute { shader_location: 4, format: wgpu::VertexFormat::Float32,   offset: 32 },
+            ],
+        },
+        EMPHASIS_LAYOUT,
+    ];
+    let pipe_node = pipeline("pipe_node", "vs_node", "fs_node", &node_layouts);
+    (pipe_edge, pipe_node)
+}
+
+/// パイプラインとシーンのバッファ一式。デバイスに紐づき、描画先のフォーマットごとに作る。
+pub struct GraphScene {
+    pipe_edge: wgpu::RenderPipeline,
+    pipe_node: wgpu::RenderPipeline,
+    shader: wgpu::ShaderModule, // 描画先のフォーマットを変える時にパイプラインを作り直すため保持
+    layout: wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    sample_count: u32,
+    bind: wgpu::BindGroup,
+    ubo: wgpu::Buffer,
+    quad_vbuf: wgpu::Buffer,
+    edge_buf: wgpu::Buffer,
+    node_buf: wgpu::Buffer,
+    edge_emph_buf: wgpu::Buffer, // インスタンス毎の強調係数（f32）。EdgeInst とは別に更新する
+    node_emph_buf: wgpu::Buffer,
+    edge_pulse_buf: wgpu::Buffer, // エッジ毎のパルス用ホップ数（vec2<f32>）。パルス発生時のみ書き込む
+    edge_count: u32,
+    node_count: u32,
+}
+
+impl GraphScene {
+    /// `shader_src` は `GRAPH_SHADER_WGSL` と同じエントリポイント（vs/fs_edge, vs/fs_node）を持つ WGSL
+    pub fn new(
+        device: &wgpu::Device,
+        shader_src: &str,
+        color_format: wgpu::TextureFormat,
+        edge_insts: &[EdgeInst],
+        node_insts: &[NodeInst],
+        attrs: InstanceAttrs,
+    ) -> GraphScene {
+        // uniforms（毎フレーム write_uniforms で書き込む）
+        let ubo = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("graph_ubo"),
+            size: std::mem::size_of::<UGraph>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("graph_bgl"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                count: None,
+            }],
+        });
+        let bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("graph_bind"),
+            layout: &bgl,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: ubo.as_entire_binding() }],
+        });
+
+        // quad corners
+        let quad_data = [
+            QuadVertex { corner: [-0.5, -0.5] },
+            QuadVertex { corner: [ 0.5, -0.5] },
+            QuadVertex { corner: [-0.5,  0.5] },
+            QuadVertex { corner: [ 0.5,  0.5] },
+        ];
+        let quad_vbuf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("graph_quad"),
+            contents: bytemuck::cast_slice(&quad_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let edge_buf = create_instance_buffer(device, "edge_buf", edge_insts);
+        let node_buf = create_instance_buffer(device, "node_buf", node_insts);
+        let edge_emph_buf = create_instance_buffer(device, "edge_emph_buf", attrs.edge_emphasis);
+        let node_emph_buf = create_instance_buffer(device, "node_emph_buf", attrs.node_emphasis);
+        let edge_pulse_buf = create_instance_buffer(device, "edge_pulse_buf", attrs.edge_pulse);
+
+        // shader + pipelines
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("graph_shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_src.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("graph_layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+        let (pipe_edge, pipe_node) = create_pipelines(device, &shader, &layout, color_format, 1);
+
+        GraphScene {
+            pipe_edge, pipe_node, shader, layout, color_format, sample_count: 1, bind, ubo,
+            quad_vbuf, edge_buf, node_buf, edge_emph_buf, node_emph_buf, edge_pulse_buf,
+            edge_count: edge_insts.len() as u32,
+            node_count: node_insts.len() as u32,
+        }
+    }
+
+    pub fn edge_count(&self) -> u32 { self.edge_count }
+
+    pub fn color_format(&self) -> wgpu::TextureFormat { self.color_format }
+
+    pub fn sample_count(&self) -> u32 { self.sample_count }
+
+    /// 描画先のフォーマットとサンプル数を変える（ブルームの HDR ターゲットと表示先の切り替え、MSAA）。同じなら何もしない
+    pub fn set_color_target(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) {
+        if (format, sample_count) == (self.color_format, self.sample_count) { return; }
+        (self.pipe_edge, self.pipe_node) = create_pipelines(device, &self.shader, &self.layout, format, sample_count);
+        (self.color_format, self.sample_count) = (format, sample_count);
+    }
+
+    pub fn node_count(&self) -> u32 { self.node_count }
+
+    pub fn replace_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, edge_insts: &[EdgeInst], node_insts: &[NodeInst], attrs: InstanceAttrs) {
+        // 件数が同じなら既存バッファへ書き込む（力学配置で毎フレーム更新されるため）
+        if edge_insts.len() as u32 == self.edge_count && node_insts.len() as u32 == self.node_count {
+            if !edge_insts.is_empty() { queue.write_buffer(&self.edge_buf, 0, bytemuck::cast_slice(edge_insts)); }
+            if !node_insts.is_empty() { queue.write_buffer(&self.node_buf, 0, bytemuck::cast_slice(node_insts)); }
+            self.write_emphasis(queue, attrs.edge_emphasis, attrs.node_emphasis);
+            self.write_pulse(queue, attrs.edge_pulse);
+            return;
+        }
+        // 旧バッファは GC を待たず即時解放
+        std::mem::replace(&mut self.edge_buf, create_instance_buffer(device, "edge_buf", edge_insts)).destroy();
+        std::mem::replace(&mut self.node_buf, create_instance_buffer(device, "node_buf", node_insts)).destroy();
+        std::mem::replace(&mut self.edge_emph_buf, create_instance_buffer(device, "edge_emph_buf", attrs.edge_emphasis)).destroy();
+        std::mem::replace(&mut self.node_emph_buf, create_instance_buffer(device, "node_emph_buf", attrs.node_emphasis)).destroy();
+        std::mem::replace(&mut self.edge_pulse_buf, create_instance_buffer(device, "edge_pulse_buf", attrs.edge_pulse)).destroy();
+        self.edge_count = edge_insts.len() as u32;
+        self.node_count = node_insts.len() as u32;
+    }
+
+    pub fn write_emphasis(&self, queue: &wgpu::Queue, edge_w: &[f32], node_w: &[f32]) {
+        if !edge_w.is_empty() { queue.write_buffer(&self.edge_emph_buf, 0, bytemuck::cast_slice(edge_w)); }
+        if !node_w.is_empty() { queue.write_buffer(&self.node_emph_buf, 0, bytemuck::cast_slice(node_w)); }
+    }
+
+    pub fn write_pulse(&self, queue: &wgpu::Queue, edge_pulse: &[[f32; 2]]) {
+        if !edge_pulse.is_empty() { queue.write_buffer(&self.edge_pulse_buf, 0, bytemuck::cast_slice(edge_pulse)); }
+    }
+
+    pub fn write_uniforms(&self, queue: &wgpu::Queue, u: &UGraph) {
+        queue.write_buffer(&self.ubo, 0, bytemuck::bytes_of(u));
+    }
+
+    /// `color` をクリアしてエッジ → ノードの順に加算合成で描く。`targets` は `color` と同じサイズで、
+    /// フォーマットとサンプル数は `set_color_target` と揃える（MSAA ならマルチサンプルへ描いて `color` へ resolve する）
+    pub fn encode(&self, enc: &mut wgpu::CommandEncoder, color: &wgpu::TextureView, targets: &SceneTargets, clear: wgpu::Color) {
+        let (view, resolve_target, store) = match &targets.msaa {
+            Some((_, msaa)) => (msaa, Some(color), wgpu::StoreOp::Discard),
+            None => (color, None, wgpu::StoreOp::Store),
+        };
+        let mut rp = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("rpass_graph"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(clear), store },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &targets.depth_view,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        // edges（空バッファはsliceできないため件数0ならスキップ）
+        if self.edge_count > 0 {
+            rp.set_pipeline(&self.pipe_edge);
+            rp.set_bind_group(0, &self.bind, &[]);
+            rp.set_vertex_buffer(0, self.quad_vbuf.slice(..));
+            rp.set_vertex_buffer(1, self.edge_buf.slice(..));
+            rp.set_vertex_buffer(2, self.edge_emph_buf.slice(..));
+            rp.set_vertex_buffer(3, self.edge_pulse_buf.slice(..));
+            rp.draw(0..4, 0..self.edge_count);
+        }
+        // nodes
+        if self.node_count > 0 {
+            rp.set_pipeline(&self.pipe_node);
+            rp.set_bind_group(0, &self.bind, &[]);
+            rp.set_vertex_buffer(0, self.quad_vbuf.slice(..));
+            rp.set_vertex_buffer(1, self.node_buf.slice(..));
+            rp.set_vertex_buffer(2, self.node_emph_buf.slice(..));
+            rp.draw(0..4, 0..self.node_count);
+        }
+    }
+
+    /// バッファを GC を待たずに解放する（パイプライン等はハンドルの drop で解放される）
+    pub fn destroy(&self) {
+        self.edge_buf.destroy();
+        self.node_buf.destroy();
+        self.edge_emph_buf.destroy();
+        self.node_emph_buf.destroy();
+        self.edge_pulse_buf.destroy();
+        self.quad_vbuf.destroy();
+        self.ubo.destroy();
+    }
+}
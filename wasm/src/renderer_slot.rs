@@ -0,0 +1,58 @@
+// 互換関数（start_graph / frame_graph 等）が操作する既定レンダラの入れ物（JS に依らない部分）
+
+/// 未開始（または dispose_graph 後）の呼び出しで返すエラー文
+pub const NOT_STARTED: &str = "not started";
+
+/// 既定レンダラを1つだけ保持する。未開始の呼び出しは黙って無視せずエラーにする。
+/// 開始は非同期なので世代で区別し、後から始まった開始や破棄より前の結果は捨てる
+#[derive(Debug)]
+pub struct RendererSlot<T> {
+    current: Option<T>,
+    generation: u64,
+}
+
+/// `RendererSlot::begin` が返す開始の世代
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartTicket(u64);
+
+impl<T> Default for RendererSlot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RendererSlot<T> {
+    pub const fn new() -> Self {
+        Self { current: None, generation: 0 }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.current.as_ref()
+    }
+
+    /// 開始済みのレンダラで `f` を呼ぶ。未開始なら `NOT_STARTED`
+    pub fn with<R, E: From<&'static str>>(&mut self, f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E> {
+        f(self.current.as_mut().ok_or(NOT_STARTED)?)
+    }
+
+    /// 開始を始める。これより前の開始は古くなる
+    pub fn begin(&mut self) -> StartTicket {
+        self.generation += 1;
+        StartTicket(self.generation)
+    }
+
+    /// 開始したレンダラを入れる。戻り値は呼び出し側で破棄するレンダラ
+    /// （入れ替わった前のもの、または古くなった開始の結果そのもの）
+    pub fn install(&mut self, ticket: StartTicket, renderer: T) -> Option<T> {
+        if ticket.0 != self.generation {
+            return Some(renderer);
+        }
+        self.current.replace(renderer)
+    }
+
+    /// 取り出して未開始に戻す。進行中の開始も古くなる
+    pub fn take(&mut self) -> Option<T> {
+        self.generation += 1;
+        self.current.take()
+    }
+}
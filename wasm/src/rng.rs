@@ -0,0 +1,15 @@
+// シンプルなLCG（依存ライブラリなし、決定論的）。各生成器で共用する。
+#[derive(Clone)]
+pub(crate) struct Lcg(u64);
+
+impl Lcg {
+    pub(crate) fn new(seed: u64) -> Self { Self(seed | 1) }
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        // Numerical Recipes系 LCG
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 32) as u32
+    }
+    pub(crate) fn next_f32(&mut self) -> f32 { (self.next_u32() as f32) / (u32::MAX as f32) }
+    pub(crate) fn range_f32(&mut self, lo: f32, hi: f32) -> f32 { lo + (hi - lo) * self.next_f32() }
+    pub(crate) fn pick_usize(&mut self, n: usize) -> usize { (self.next_u32() as usize) % n.max(1) }
+}
@@ -0,0 +1,261 @@
+// 音声/リサーチのセッション状態 → 見た目のプリセットと、その間のクロスフェード
+//
+// 状態は `docs/voice-tech-spec.md` の status（idle / optimizing / ready / researching）に合わせる。
+// 各状態は `SessionLook`（GraphParams の全項目＋核の有無・色味・パルス頻度）に対応し、
+// 状態が変わると現在の見た目から目標のプリセットへ時間をかけて補間する。
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::easing::Easing;
+use crate::graph::{palette_color, Palette};
+use crate::params::ParamsConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    #[default]
+    Idle,
+    Optimizing,
+    Ready,
+    Researching,
+}
+
+impl SessionState {
+    pub const ALL: [SessionState; 4] = [Self::Idle, Self::Optimizing, Self::Ready, Self::Researching];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Optimizing => "optimizing",
+            Self::Ready => "ready",
+            Self::Researching => "researching",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&s| s == self).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSessionState(pub String);
+
+impl fmt::Display for UnknownSessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = SessionState::ALL.iter().map(|s| s.name()).collect();
+        write!(f, "unknown session state \"{}\" (available: {})", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownSessionState {}
+
+impl FromStr for SessionState {
+    type Err = UnknownSessionState;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::ALL.into_iter().find(|st| st.name() == s).ok_or_else(|| UnknownSessionState(s.to_string()))
+    }
+}
+
+/// 補間できる見た目一式。JSONで省略したフィールドは idle（既定の見た目）の値になる。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionLook {
+    pub edge_thickness: f32,
+    pub node_size: f32,
+    pub flow_speed: f32,
+    pub rot_speed: f32,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub fog_strength: f32,
+    pub link_on: f32,
+    pub link_off: f32,
+    pub nuc_link_on: f32,
+    pub nuc_link_off: f32,
+    /// 核（中心ノードと放射エッジ）の見え方。0: 無し、1: 表示。途中の値はフェード中
+    pub nucleus: f32,
+    /// 色味: rgb へ寄せる量を a（0: 元の色、1: 明るさを保ったまま rgb 一色）
+    pub tint: [f32; 4],
+    /// 自動パルスの頻度（回/秒、0 で無し）
+    pub pulse_rate: f32,
+}
+
+impl Default for SessionLook {
+    fn default() -> Self { Self::preset(SessionState::Idle) }
+}
+
+fn tint(p: Palette, amount: f32) -> [f32; 4] {
+    let c = palette_color(p);
+    [c[0], c[1], c[2], amount]
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 { a + (b - a) * t }
+
+impl SessionLook {
+    /// 組み込みのプリセット。idle は既定値（DEFAULT_GRAPH_*、核なし、色味なし）と同じ
+    pub fn preset(state: SessionState) -> Self {
+        let idle = Self {
+            edge_thickness: crate::DEFAULT_GRAPH_EDGE_THICKNESS,
+            node_size: crate::DEFAULT_GRAPH_NODE_SIZE,
+            flow_speed: crate::DEFAULT_GRAPH_FLOW_SPEED,
+            rot_speed: crate::DEFAULT_GRAPH_ROT_SPEED,
+            fog_start: crate::DEFAULT_GRAPH_FOG_START,
+            fog_end: crate::DEFAULT_GRAPH_FOG_END,
+            fog_strength: crate::DEFAULT_GRAPH_FOG_STRENGTH,
+            link_on: crate::DEFAULT_GRAPH_LINK_ON,
+            link_off: crate::DEFAULT_GRAPH_LINK_OFF,
+            nuc_link_on: crate::DEFAULT_GRAPH_NUC_LINK_ON,
+            nuc_link_off: crate::DEFAULT_GRAPH_NUC_LINK_OFF,
+            nucleus: 0.0,
+            tint: [1.0, 1.0, 1.0, 0.0],
+            pulse_rate: 0.0,
+        };
+        match state {
+            SessionState::Idle => idle,
+            // クエリ最適化中: 回転と流れを速め、紫寄りに。時々パルス
+            SessionState::Optimizing => Self {
+                edge_thickness: 0.007, flow_speed: 1.8, rot_speed: 0.12,
+                fog_start: 0.50, fog_strength: 0.7, link_on: 0.90, link_off: 1.60,
+                nucleus: 1.0, tint: tint(Palette::Purple, 0.25), pulse_rate: 0.5,
+                ..idle
+            },
+            // 候補が揃った: 落ち着いた回転、霧を薄めて全体を見せる
+            SessionState::Ready => Self {
+                node_size: 0.09, flow_speed: 1.2, rot_speed: 0.06,
+                fog_start: 0.60, fog_strength: 0.6, link_on: 0.85, link_off: 1.50,
+                nucleus: 1.0, tint: tint(Palette::Sky, 0.2),
+                ..idle
+            },
+            // リサーチ実行中: 最も活発。頻繁にパルスを出す
+            SessionState::Researching => Self {
+                edge_thickness: 0.008, node_size: 0.085, flow_speed: 2.4, rot_speed: 0.18,
+                fog_start: 0.45, fog_end: 0.90, fog_strength: 0.75, link_on: 1.0, link_off: 1.80,
+                nuc_link_off: 2.40, nucleus: 1.0, tint: tint(Palette::Magenta, 0.2), pulse_rate: 1.2,
+                ..idle
+            },
+        }
+    }
+
+    /// GraphParams に当たる項目（核・色味・パルスは含まない）
+    pub fn params(&self) -> ParamsConfig {
+        ParamsConfig {
+            edge_thickness: self.edge_thickness, node_size: self.node_size, flow_speed: self.flow_speed, rot_speed: self.rot_speed,
+            fog_start: self.fog_start, fog_end: self.fog_end, fog_strength: self.fog_strength,
+            link_on: self.link_on, link_off: self.link_off, nuc_link_on: self.nuc_link_on, nuc_link_off: self.nuc_link_off,
+        }
+    }
+
+    /// 各項目を直線補間（`t` は 0..1 に丸める）
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let l = |a: f32, b: f32| lerp(a, b, t);
+        Self {
+            edge_thickness: l(self.edge_thickness, to.edge_thickness),
+            node_size: l(self.node_size, to.node_size),
+            flow_speed: l(self.flow_speed, to.flow_speed),
+            rot_speed: l(self.rot_speed, to.rot_speed),
+            fog_start: l(self.fog_start, to.fog_start),
+            fog_end: l(self.fog_end, to.fog_end),
+            fog_strength: l(self.fog_strength, to.fog_strength),
+            link_on: l(self.link_on, to.link_on),
+            link_off: l(self.link_off, to.link_off),
+            nuc_link_on: l(self.nuc_link_on, to.nuc_link_on),
+            nuc_link_off: l(self.nuc_link_off, to.nuc_link_off),
+            nucleus: l(self.nucleus, to.nucleus),
+            tint: std::array::from_fn(|i| l(self.tint[i], to.tint[i])),
+            pulse_rate: l(self.pulse_rate, to.pulse_rate),
+        }
+    }
+
+    /// GraphParams の項目は `params::ParamsConfig::clamped` と同じ範囲に丸める（非有限値は idle の値に戻す）
+    pub fn clamped(self) -> Self {
+        use crate::params::ParamsConfig;
+        let fin = |v: f32, d: f32| if v.is_finite() { v } else { d };
+        let idle = Self::preset(SessionState::Idle);
+        let p = ParamsConfig {
+            edge_thickness: self.edge_thickness,
+            node_size: self.node_size,
+            flow_speed: self.flow_speed,
+            rot_speed: self.rot_speed,
+            fog_start: self.fog_start,
+            fog_end: self.fog_end,
+            fog_strength: self.fog_strength,
+            link_on: self.link_on,
+            link_off: self.link_off,
+            nuc_link_on: self.nuc_link_on,
+            nuc_link_off: self.nuc_link_off,
+        }.clamped();
+        Self {
+            edge_thickness: p.edge_thickness,
+            node_size: p.node_size,
+            flow_speed: p.flow_speed,
+            rot_speed: p.rot_speed,
+            fog_start: p.fog_start,
+            fog_end: p.fog_end,
+            fog_strength: p.fog_strength,
+            link_on: p.link_on,
+            link_off: p.link_off,
+            nuc_link_on: p.nuc_link_on,
+            nuc_link_off: p.nuc_link_off,
+            nucleus: fin(self.nucleus, 0.0).clamp(0.0, 1.0),
+            tint: std::array::from_fn(|i| fin(self.tint[i], idle.tint[i]).clamp(0.0, 1.0)),
+            pulse_rate: fin(self.pulse_rate, 0.0).clamp(0.0, 10.0),
+        }
+    }
+}
+
+/// 状態毎のプリセット（`set_session_preset` で差し替え可能）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionPresets([SessionLook; 4]);
+
+impl Default for SessionPresets {
+    fn default() -> Self { Self(SessionState::ALL.map(SessionLook::preset)) }
+}
+
+impl SessionPresets {
+    pub fn get(&self, state: SessionState) -> &SessionLook { &self.0[state.index()] }
+
+    pub fn set(&mut self, state: SessionState, look: SessionLook) { self.0[state.index()] = look.clamped(); }
+}
+
+/// JSON（`SessionLook`、省略項目は idle の値）を読み込み、範囲を丸めて返す
+pub fn parse_session_look(json: &str) -> Result<SessionLook, String> {
+    serde_json::from_str::<SessionLook>(json)
+        .map(SessionLook::clamped)
+        .map_err(|e| format!("invalid session preset json: {e}"))
+}
+
+/// 見た目のクロスフェード（開始時点の見た目 → 目標のプリセット）
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookTransition {
+    from: SessionLook,
+    to: SessionLook,
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl LookTransition {
+    /// `duration` が 0 以下なら次の `step` で目標に揃う
+    pub fn new(from: SessionLook, to: SessionLook, duration: f32, easing: Easing) -> Self {
+        Self { from, to, duration: duration.max(0.0), elapsed: 0.0, easing }
+    }
+
+    pub fn target(&self) -> &SessionLook { &self.to }
+
+    /// `dt` 秒進めて現在の見た目を返す。終端では目標そのもの
+    pub fn step(&mut self, dt: f32) -> SessionLook {
+        self.elapsed += dt.max(0.0);
+        if self.is_finished() { return self.to; }
+        self.from.lerp(&self.to, self.easing.apply(self.elapsed / self.duration))
+    }
+
+    pub fn is_finished(&self) -> bool { self.elapsed >= self.duration }
+}
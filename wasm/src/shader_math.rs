@@ -7,7 +7,7 @@ pub fn node_intensity(d_norm: f32, t: f32) -> f32 {
     core * 1.4 + glow * 0.5 * spark
 }
 
-fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
     t * t * (3.0 - 2.0 * t)
 }
@@ -46,3 +46,79 @@ pub fn radial_strength(r: f32, r0: f32, r1: f32, min_strength: f32) -> f32 {
     let s = 1.0 - (t * t * (3.0 - 2.0 * t)); // 1 - smoothstep(r0, r1, r)
     min + (1.0 - min) * s
 }
+
+// 以下は graph_shader.wgsl の各フラグメントと同じ式（CPU ラスタライザ用）
+
+/// fs_edge の明るさ係数: (フロー縞込み, パルス用)。`t` は沿い方向 0..1、`side` は横方向 -1..1
+pub fn edge_profile(t: f32, side: f32, curve_k: f32, tscale: f32, emph: f32, flow_phase: f32) -> (f32, f32) {
+    // 緩い放物線オフセット（端0、中心最大）
+    let curve = (curve_k * 0.9) * (4.0 * t * (1.0 - t));
+    let y = (side - curve).abs();
+    let k_core = 12.0 / tscale.max(0.5);
+    let k_glow = 2.5 / tscale.max(0.5);
+    let core = (-k_core * y * y).exp();
+    let glow = (-k_glow * y * y).exp();
+    let flow_amp = 0.35 * emph.max(1.0);
+    let flow = (0.65 + flow_amp * (t * 40.0 - flow_phase * std::f32::consts::TAU).sin()).max(0.0);
+    (core * 1.3 + glow * 0.6 * flow, core * 1.3 + glow * 0.6)
+}
+
+/// fs_node の明るさ（`d` は半径で正規化した距離、`glow_gain` は misc4.x）
+pub fn node_glow_intensity(d: f32, time: f32, phase: f32, glow_gain: f32) -> f32 {
+    let core = smoothstep(1.0, 0.0, d);
+    let glow = (-4.0 * d * d).exp();
+    core * 1.4 + glow * 0.5 * (time * std::f32::consts::TAU + phase).sin() + glow * 0.6 * (glow_gain - 1.0)
+}
+
+/// 明るさを保ったまま `tint.rgb` へ `tint[3]` だけ寄せる
+pub fn apply_tint(col: [f32; 3], tint: [f32; 4]) -> [f32; 3] {
+    let lum = col[0].max(col[1]).max(col[2]);
+    let a = tint[3];
+    [0, 1, 2].map(|i| col[i] + (tint[i] * lum - col[i]) * a)
+}
+
+/// 霧の濃さ（ndc.z を 0..1 に写してから smoothstep）
+pub fn fog_factor(ndc_z: f32, fog_start: f32, fog_end: f32, strength: f32) -> f32 {
+    let depth01 = (0.5 * (ndc_z + 1.0)).clamp(0.0, 1.0);
+    smoothstep(fog_start, fog_end, depth01) * strength
+}
+
+// 以下は bloom_shader.wgsl と同じ式
+
+/// 明部抽出（ソフトニー付き）。rgb の最大成分が `threshold` を超えた分だけ残す
+pub fn bloom_bright_pass(col: [f32; 3], threshold: f32) -> [f32; 3] {
+    let br = col[0].max(col[1]).max(col[2]);
+    let knee = threshold * 0.5;
+    let soft = (br - threshold + knee).clamp(0.0, 2.0 * knee);
+    let soft = soft * soft / (4.0 * knee + 1e-4);
+    let contrib = soft.max(br - threshold) / br.max(1e-4);
+    col.map(|c| c * contrib)
+}
+
+/// ACES の近似（Narkowicz 2015）でリニアの HDR 値を 0..1 へ写す。
+/// 露出は掛けない（暗部〜中間は少し持ち上がり、1 を超えた重なりは潰れずに圧縮される）
+pub fn aces_tonemap(col: [f32; 3]) -> [f32; 3] {
+    col.map(|c| {
+        let x = c.max(0.0);
+        ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+    })
+}
+
+/// 解析的 AA で広げる線の最小幅（ピクセル）
+pub const ANALYTIC_MIN_EDGE_PX: f32 = 1.5;
+
+/// vs_edge の解析的 AA: (広げた太さ, 明るさの係数)。`thickness` は NDC（y）での半幅、`height_px` は描画先の高さ。
+/// 最小幅に満たない線はその幅まで広げ、広げた割合だけ暗くする（画面上の光の総量を保つ）
+pub fn analytic_edge_width(thickness: f32, height_px: f32, min_px: f32) -> (f32, f32) {
+    let min_thickness = min_px / height_px.max(1.0);
+    if thickness > 0.0 && thickness < min_thickness {
+        (min_thickness, thickness / min_thickness)
+    } else {
+        (thickness, 1.0)
+    }
+}
+
+/// fs_edge の解析的 AA: 帯の縁（|side| = 1）を1ピクセルでぼかす係数。`fw` は side の画素あたりの変化（fwidth）
+pub fn analytic_edge_fade(side: f32, fw: f32) -> f32 {
+    ((1.0 - side.abs()) / fw.max(1e-4)).clamp(0.0, 1.0)
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::graph3d::{generate_shells, Edge3, Node3};
+use crate::graph_data::{LoadedGraph, DEFAULT_MAX_NODES};
+
+/// シェル数の上限（UIから過大な配列を渡されても生成が破綻しないように）
+pub const MAX_SHELLS: usize = 64;
+
+/// 多層シェル1枚分（半径と割当の重み。重みは全体で正規化される）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShellSpec {
+    pub radius: f32,
+    pub weight: f32,
+}
+
+/// `generate_shells` の全入力。JSONで省略したフィールドは既定プリセットの値になる。
+/// 例: `{ "seed": 42, "node_count": 400, "shells": [{ "radius": 0.8, "weight": 1 }] }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    pub seed: u64,
+    pub node_count: usize,
+    pub shells: Vec<ShellSpec>,
+    pub k_intra: usize,
+    pub cross_adj: usize,
+    pub cross_long_ratio: f32,
+    pub hub_ratio: f32,
+}
+
+const PRESET_RADII: [f32; 5] = [0.6, 0.8, 1.0, 1.2, 1.4];
+const PRESET_WEIGHTS_DEFAULT: [f32; 5] = [0.15, 0.20, 0.30, 0.20, 0.15];
+const PRESET_WEIGHTS_INNER: [f32; 5] = [0.34, 0.26, 0.20, 0.12, 0.08];
+
+impl Default for ShellConfig {
+    fn default() -> Self { Self::preset(0) }
+}
+
+impl ShellConfig {
+    /// 0: default (balanced), 1: inner-heavy（それ以外は default）
+    pub fn preset(profile: u32) -> Self {
+        let weights = if profile == 1 { &PRESET_WEIGHTS_INNER } else { &PRESET_WEIGHTS_DEFAULT };
+        let shells = PRESET_RADII.iter().zip(weights)
+            .map(|(&radius, &weight)| ShellSpec { radius, weight })
+            .collect();
+        Self { seed: 1337, node_count: 240, shells, k_intra: 4, cross_adj: 1, cross_long_ratio: 0.15, hub_ratio: 0.05 }
+    }
+
+    pub fn validate(&self) -> Result<(), ShellConfigError> {
+        if !(4..=DEFAULT_MAX_NODES).contains(&self.node_count) {
+            return Err(ShellConfigError::NodeCount { count: self.node_count, max: DEFAULT_MAX_NODES });
+        }
+        if self.shells.is_empty() || self.shells.len() > MAX_SHELLS {
+            return Err(ShellConfigError::ShellCount { count: self.shells.len(), max: MAX_SHELLS });
+        }
+        for (index, s) in self.shells.iter().enumerate() {
+            if !(s.radius.is_finite() && s.radius > 0.0) {
+                return Err(ShellConfigError::InvalidRadius { index });
+            }
+            if !(s.weight.is_finite() && s.weight >= 0.0) {
+                return Err(ShellConfigError::InvalidWeight { index });
+            }
+        }
+        if self.shells.iter().map(|s| s.weight).sum::<f32>() <= 0.0 {
+            return Err(ShellConfigError::ZeroTotalWeight);
+        }
+        for (field, v) in [("cross_long_ratio", self.cross_long_ratio), ("hub_ratio", self.hub_ratio)] {
+            if !(0.0..=1.0).contains(&v) {
+                return Err(ShellConfigError::RatioOutOfRange { field });
+            }
+        }
+        Ok(())
+    }
+
+    /// 検証は呼び出し側で行う（`validate`）。k_intra は生成器側で 1..=12 に丸められる。
+    pub fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) {
+        let radii: Vec<f32> = self.shells.iter().map(|s| s.radius).collect();
+        let weights: Vec<f32> = self.shells.iter().map(|s| s.weight).collect();
+        generate_shells(
+            self.seed, self.node_count, &radii, &weights,
+            self.k_intra, self.cross_adj, self.cross_long_ratio, self.hub_ratio,
+        )
+    }
+
+    /// 生成したシェルを描画用のグラフにする（`set_shell_profile` / `regenerate` が表示するグラフ）
+    pub fn graph(&self) -> LoadedGraph {
+        let (nodes, edges) = self.generate();
+        LoadedGraph::from_generated(nodes, edges)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellConfigError {
+    Parse(String),
+    NodeCount { count: usize, max: usize },
+    ShellCount { count: usize, max: usize },
+    InvalidRadius { index: usize },
+    InvalidWeight { index: usize },
+    ZeroTotalWeight,
+    RatioOutOfRange { field: &'static str },
+}
+
+impl fmt::Display for ShellConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "invalid shell config json: {msg}"),
+            Self::NodeCount { count, max } => write!(f, "node_count must be in 4..={max}, got {count}"),
+            Self::ShellCount { count, max } => write!(f, "shells must contain 1..={max} entries, got {count}"),
+            Self::InvalidRadius { index } => write!(f, "shells[{index}].radius must be a positive finite number"),
+            Self::InvalidWeight { index } => write!(f, "shells[{index}].weight must be a non-negative finite number"),
+            Self::ZeroTotalWeight => write!(f, "shell weights must not all be zero"),
+            Self::RatioOutOfRange { field } => write!(f, "{field} must be in 0..=1"),
+        }
+    }
+}
+
+impl std::error::Error for ShellConfigError {}
+
+/// JSONを読み込み、検証済みの設定を返す
+pub fn parse_shell_config(json: &str) -> Result<ShellConfig, ShellConfigError> {
+    let cfg: ShellConfig = serde_json::from_str(json).map_err(|e| ShellConfigError::Parse(e.to_string()))?;
+    cfg.validate()?;
+    Ok(cfg)
+}
@@ -0,0 +1,472 @@
+// 宣言的なタイムライン（デモ/オンボーディング用の台本）と、その再生位置の管理
+//
+// 台本は `{ "duration_s"?, "loop"?, "events": [{ "at": 秒, "action": ..., ... }] }` の JSON。
+// ある時刻の見た目は「読み込み時の状態（`TimelineBase`）＋その時刻までのイベント」だけで決まり、
+// フレームの刻み方やシーク順に依らない（同じ時刻は常に同じフレーム）。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::camera::{interpolate_pose, CameraPose};
+use crate::easing::Easing;
+use crate::graph3d::check_allpairs;
+use crate::params::clamp_param;
+use crate::tween::{ParamField, TweenCurve, PARAM_COUNT};
+
+/// イベント数の上限
+pub const MAX_TIMELINE_EVENTS: usize = 1024;
+
+/// 台本の長さ・イベント時刻・各区間の長さの上限（秒）
+pub const MAX_TIMELINE_S: f32 = 3600.0;
+
+/// 緩急カーブの積分（Simpson 則）の分割数。固定なので積分値も時刻だけで決まる
+const CURVE_INTEGRAL_PANELS: usize = 32;
+
+fn default_pulse_speed() -> f32 { crate::pulse::AUTO_PULSE_SPEED }
+fn default_pulse_hops() -> u32 { crate::pulse::AUTO_PULSE_HOPS }
+fn default_k_hops() -> u32 { 1 }
+
+/// イベントの種類（`action` で指定）
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TimelineAction {
+    /// GraphParams の項目をその時点の値から `duration_s` 秒で動かす。例: `{ "params": { "fog_strength": 0.4 }, "duration_s": 2, "curve": "spring" }`
+    Params {
+        params: BTreeMap<ParamField, f32>,
+        #[serde(default)]
+        duration_s: f32,
+        #[serde(default)]
+        curve: TweenCurve,
+    },
+    /// 核をフェードイン/アウトする
+    Nucleus {
+        on: bool,
+        #[serde(default)]
+        duration_s: f32,
+    },
+    /// 全ペア表示の切り替え
+    Allpairs { on: bool },
+    /// ノードID `node` からパルスを出す
+    Pulse {
+        node: u32,
+        #[serde(default = "default_pulse_speed")]
+        speed: f32,
+        #[serde(default = "default_pulse_hops")]
+        hops: u32,
+    },
+    /// `focus_nodes` と同じくノードへ寄り、近傍を強調する
+    Focus {
+        nodes: Vec<u32>,
+        #[serde(default = "default_k_hops")]
+        k_hops: u32,
+        #[serde(default)]
+        duration_s: f32,
+        #[serde(default)]
+        easing: Easing,
+    },
+    /// 既定の視点へ戻り、強調を解除する
+    Unfocus {
+        #[serde(default)]
+        duration_s: f32,
+        #[serde(default)]
+        easing: Easing,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TimelineEvent {
+    pub at: f32,
+    #[serde(flatten)]
+    pub action: TimelineAction,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimelineJson {
+    #[serde(default)]
+    duration_s: Option<f32>,
+    #[serde(default, rename = "loop")]
+    looping: bool,
+    events: Vec<TimelineEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineError {
+    Parse(String),
+    TooManyEvents { count: usize, max: usize },
+    InvalidDuration,
+    InvalidTime { index: usize, field: &'static str },
+    InvalidValue { index: usize, field: String },
+    EmptyFocus { index: usize },
+    UnknownNode { index: usize, id: u32 },
+    Allpairs { index: usize, reason: String },
+}
+
+impl fmt::Display for TimelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(msg) => write!(f, "invalid timeline json: {msg}"),
+            Self::TooManyEvents { count, max } => write!(f, "events must contain at most {max} entries, got {count}"),
+            Self::InvalidDuration => write!(f, "duration_s must be in 0..={MAX_TIMELINE_S} seconds"),
+            Self::InvalidTime { index, field } => write!(f, "events[{index}].{field} must be in 0..={MAX_TIMELINE_S} seconds"),
+            Self::InvalidValue { index, field } => write!(f, "events[{index}].{field} must be a finite number"),
+            Self::EmptyFocus { index } => write!(f, "events[{index}].nodes must not be empty"),
+            Self::UnknownNode { index, id } => write!(f, "events[{index}] refers to unknown node id {id}"),
+            Self::Allpairs { index, reason } => write!(f, "events[{index}]: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for TimelineError {}
+
+fn check_time(v: f32, index: usize, field: &'static str) -> Result<(), TimelineError> {
+    if v.is_finite() && (0.0..=MAX_TIMELINE_S).contains(&v) { Ok(()) } else { Err(TimelineError::InvalidTime { index, field }) }
+}
+
+/// 検証済みの台本。イベントは時刻順（同時刻は記述順）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    pub duration: f32,
+    pub looping: bool,
+    pub events: Vec<TimelineEvent>,
+    /// flow_speed / rot_speed を動かす params イベント（読み込み時に抜き出しておき、積分は区間ごとに求める）
+    flow_changes: Vec<SpeedChange>,
+    rot_changes: Vec<SpeedChange>,
+}
+
+/// params イベントのうち1項目の変化
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SpeedChange {
+    at: f32,
+    to: f32,
+    duration: f32,
+    curve: TweenCurve,
+}
+
+fn speed_changes(events: &[TimelineEvent], field: ParamField) -> Vec<SpeedChange> {
+    events.iter()
+        .filter_map(|ev| match &ev.action {
+            TimelineAction::Params { params, duration_s, curve } => params.get(&field)
+                .map(|&to| SpeedChange { at: ev.at, to, duration: *duration_s, curve: *curve }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// JSONを読み込み、検証済みの台本を返す。params の値は `param_range` に丸める。
+/// `node_count` は現在のグラフのノード数（全ペア表示を入れられるか）、`has_node` は pulse / focus のノードIDが現在のグラフにあるか。
+/// `duration_s` を省略すると最後のイベントが終わるまで
+pub fn parse_timeline(json: &str, node_count: usize, has_node: impl Fn(u32) -> bool) -> Result<Timeline, TimelineError> {
+    let raw: TimelineJson = serde_json::from_str(json).map_err(|e| TimelineError::Parse(e.to_string()))?;
+    if raw.events.len() > MAX_TIMELINE_EVENTS {
+        return Err(TimelineError::TooManyEvents { count: raw.events.len(), max: MAX_TIMELINE_EVENTS });
+    }
+    let mut events = raw.events;
+    let mut end = 0.0f32;
+    for (index, ev) in events.iter_mut().enumerate() {
+        check_time(ev.at, index, "at")?;
+        let span = match &mut ev.action {
+            TimelineAction::Params { params, duration_s, .. } => {
+                check_time(*duration_s, index, "duration_s")?;
+                for (field, v) in params.iter_mut() {
+                    if !v.is_finite() { return Err(TimelineError::InvalidValue { index, field: format!("params.{field}") }); }
+                    *v = clamp_param(*field, *v);
+                }
+                *duration_s
+            }
+            TimelineAction::Nucleus { duration_s, .. } | TimelineAction::Unfocus { duration_s, .. } => {
+                check_time(*duration_s, index, "duration_s")?;
+                *duration_s
+            }
+            TimelineAction::Allpairs { on } => {
+                if *on { check_allpairs(node_count).map_err(|reason| TimelineError::Allpairs { index, reason })?; }
+                0.0
+            }
+            TimelineAction::Pulse { node, speed, hops } => {
+                if !has_node(*node) { return Err(TimelineError::UnknownNode { index, id: *node }); }
+                if !speed.is_finite() { return Err(TimelineError::InvalidValue { index, field: "speed".into() }); }
+                *speed = speed.clamp(0.1, 20.0);
+                *hops = (*hops).clamp(1, crate::pulse::MAX_PULSE_HOPS);
+                (*hops as f32 + 4.0) / *speed
+            }
+            TimelineAction::Focus { nodes, duration_s, .. } => {
+                check_time(*duration_s, index, "duration_s")?;
+                if nodes.is_empty() { return Err(TimelineError::EmptyFocus { index }); }
+                if let Some(&id) = nodes.iter().find(|&&id| !has_node(id)) { return Err(TimelineError::UnknownNode { index, id }); }
+                *duration_s
+            }
+        };
+        end = end.max(ev.at + span);
+    }
+    let duration = match raw.duration_s {
+        Some(d) if d.is_finite() && (0.0..=MAX_TIMELINE_S).contains(&d) => d,
+        Some(_) => return Err(TimelineError::InvalidDuration),
+        None => end,
+    };
+    events.sort_by(|a, b| a.at.total_cmp(&b.at)); // 安定ソート: 同時刻は記述順
+    let flow_changes = speed_changes(&events, ParamField::FlowSpeed);
+    let rot_changes = speed_changes(&events, ParamField::RotSpeed);
+    Ok(Timeline { duration, looping: raw.looping, events, flow_changes, rot_changes })
+}
+
+/// 読み込み時の状態（台本に無い項目・最初のイベントより前はこの値）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineBase {
+    pub params: [f32; PARAM_COUNT],
+    /// 核の見え方（0: 無し、1: 表示）
+    pub nucleus: f32,
+    pub allpairs: bool,
+    pub camera: CameraPose,
+}
+
+/// ある時刻の状態。`None` の項目は台本が触れないので呼び出し側の値のまま
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineFrame {
+    pub params: [Option<f32>; PARAM_COUNT],
+    pub nucleus: Option<f32>,
+    pub allpairs: Option<bool>,
+    /// 直近の pulse イベント（添字）。終わっていても返す（終了判定は `Pulse::is_finished`）
+    pub pulse: Option<usize>,
+    /// 直近の focus / unfocus イベント（添字）
+    pub focus: Option<usize>,
+}
+
+/// 時刻 `at` から `duration` 秒で `from` → `to` へ動く区間
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    from: f32,
+    to: f32,
+    at: f32,
+    duration: f32,
+    curve: TweenCurve,
+}
+
+impl Segment {
+    fn hold(v: f32) -> Self { Self { from: v, to: v, at: 0.0, duration: 0.0, curve: TweenCurve::default() } }
+
+    fn value(&self, t: f32) -> f32 {
+        if self.duration <= 0.0 || t >= self.at + self.duration { return self.to; }
+        self.from + (self.to - self.from) * self.curve.apply((t - self.at) / self.duration)
+    }
+
+    /// 時刻 `at` にその時点の値から始め直す
+    fn retarget(&self, at: f32, to: f32, duration: f32, curve: TweenCurve) -> Self {
+        Self { from: self.value(at), to, at, duration, curve }
+    }
+
+    /// 区間の始まり（`at`）から `t` までの値の積分
+    fn integral(&self, t: f32) -> f64 {
+        let span = (t - self.at).max(0.0) as f64;
+        if self.duration <= 0.0 { return self.to as f64 * span; }
+        let d = self.duration as f64;
+        let moving = span.min(d);
+        let eased = (self.to - self.from) as f64 * d * curve_integral(self.curve, (moving / d) as f32);
+        self.from as f64 * moving + eased + self.to as f64 * (span - moving)
+    }
+}
+
+/// `curve.apply` の 0..x の積分
+fn curve_integral(curve: TweenCurve, x: f32) -> f64 {
+    if x <= 0.0 { return 0.0; }
+    let n = CURVE_INTEGRAL_PANELS;
+    let h = x as f64 / n as f64;
+    let f = |i: usize| curve.apply((i as f64 * h) as f32) as f64;
+    let inner: f64 = (1..n).map(|i| if i % 2 == 1 { 4.0 * f(i) } else { 2.0 * f(i) }).sum();
+    (f(0) + inner + f(n)) * h / 3.0
+}
+
+/// 速さ（base から params イベントで動く値）の 0 秒からの積分。区間ごとの積分を前もって足しておき、
+/// 任意の時刻をイベント数の対数で求める
+#[derive(Debug, Clone, PartialEq)]
+struct SpeedIntegral {
+    /// (区間, その区間の始まりまでの積分)。先頭は 0 秒からの base
+    knots: Vec<(Segment, f64)>,
+}
+
+impl SpeedIntegral {
+    fn new(changes: &[SpeedChange], base: f32) -> Self {
+        let mut knots = vec![(Segment::hold(base), 0.0)];
+        for c in changes {
+            let (prev, start) = knots[knots.len() - 1];
+            knots.push((prev.retarget(c.at, c.to, c.duration, c.curve), start + prev.integral(c.at)));
+        }
+        Self { knots }
+    }
+
+    fn at(&self, t: f32) -> f64 {
+        let t = t.max(0.0);
+        // 同時刻の変化は最後のものが有効（先頭の base は 0 秒なので必ず1つは当たる）
+        let i = self.knots.partition_point(|(seg, _)| seg.at <= t).max(1) - 1;
+        let (seg, start) = self.knots[i];
+        start + seg.integral(t)
+    }
+}
+
+impl Timeline {
+    /// 再生位置（`Playback::position`）→ 台本上の時刻。ループ時は長さで折り返す
+    pub fn local_time(&self, position: f32) -> f32 {
+        let p = position.max(0.0);
+        if self.looping && self.duration > 0.0 { p.rem_euclid(self.duration) } else { p.min(self.duration) }
+    }
+
+    fn events_until(&self, t: f32) -> impl Iterator<Item = (usize, &TimelineEvent)> {
+        self.events.iter().enumerate().take_while(move |(_, e)| e.at <= t)
+    }
+
+    /// 時刻 `t` の状態
+    pub fn sample(&self, t: f32, base: &TimelineBase) -> TimelineFrame {
+        let mut params: [Option<Segment>; PARAM_COUNT] = [None; PARAM_COUNT];
+        let mut nucleus: Option<Segment> = None;
+        // 台本が触れる項目は最初のイベントより前も base の値で固定する
+        for ev in &self.events {
+            match &ev.action {
+                TimelineAction::Params { params: p, .. } => {
+                    for f in p.keys() { params[f.index()] = Some(Segment::hold(base.params[f.index()])); }
+                }
+                TimelineAction::Nucleus { .. } => nucleus = Some(Segment::hold(base.nucleus)),
+                _ => {}
+            }
+        }
+        let mut frame = TimelineFrame {
+            params: [None; PARAM_COUNT],
+            nucleus: None,
+            allpairs: self.events.iter().any(|e| matches!(e.action, TimelineAction::Allpairs { .. })).then_some(base.allpairs),
+            pulse: None,
+            focus: None,
+        };
+        for (i, ev) in self.events_until(t) {
+            match &ev.action {
+                TimelineAction::Params { params: p, duration_s, curve } => {
+                    for (f, &to) in p {
+                        let slot = &mut params[f.index()];
+                        *slot = slot.map(|s| s.retarget(ev.at, to, *duration_s, *curve));
+                    }
+                }
+                TimelineAction::Nucleus { on, duration_s } => {
+                    let to = if *on { 1.0 } else { 0.0 };
+                    nucleus = nucleus.map(|s| s.retarget(ev.at, to, *duration_s, TweenCurve::default()));
+                }
+                TimelineAction::Allpairs { on } => frame.allpairs = Some(*on),
+                TimelineAction::Pulse { .. } => frame.pulse = Some(i),
+                TimelineAction::Focus { .. } | TimelineAction::Unfocus { .. } => frame.focus = Some(i),
+            }
+        }
+        frame.params = params.map(|s| s.map(|s| s.value(t)));
+        frame.nucleus = nucleus.map(|s| s.value(t));
+        frame
+    }
+
+    /// カメラを動かすイベントがあるか（無ければカメラは台本に従わない）
+    pub fn drives_camera(&self) -> bool {
+        self.events.iter().any(|e| matches!(e.action, TimelineAction::Focus { .. } | TimelineAction::Unfocus { .. }))
+    }
+
+    /// カメラの飛行を前から順に決める。`resolve(nodes, k_hops, from)` は focus の行き先（向きは `from` のまま）。
+    /// 行き先はグラフに依存するので読み込み時に1度だけ求める
+    pub fn camera_flights(&self, base: CameraPose, home: CameraPose, mut resolve: impl FnMut(&[u32], u32, CameraPose) -> CameraPose) -> Vec<CameraFlight> {
+        let mut flights: Vec<CameraFlight> = Vec::new();
+        for ev in &self.events {
+            let from = camera_pose_at(&flights, base, ev.at);
+            let (to, duration, easing) = match &ev.action {
+                TimelineAction::Focus { nodes, k_hops, duration_s, easing } => (resolve(nodes, *k_hops, from), *duration_s, *easing),
+                TimelineAction::Unfocus { duration_s, easing } => (home, *duration_s, *easing),
+                _ => continue,
+            };
+            flights.push(CameraFlight { at: ev.at, from, to, duration, easing });
+        }
+        flights
+    }
+}
+
+/// カメラの飛行1回分
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFlight {
+    pub at: f32,
+    pub from: CameraPose,
+    pub to: CameraPose,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+/// 時刻 `t` のカメラ姿勢（最初の飛行より前は `base`）
+pub fn camera_pose_at(flights: &[CameraFlight], base: CameraPose, t: f32) -> CameraPose {
+    let Some(f) = flights.iter().rev().find(|f| f.at <= t) else { return base };
+    if f.duration <= 0.0 || t >= f.at + f.duration { return f.to; }
+    interpolate_pose(f.from, f.to, f.easing.apply((t - f.at) / f.duration))
+}
+
+/// 再生中に時刻だけから決まる量: フロー縞の位相（flow_speed の積分）とカメラ（飛行、無ければ rot_speed の積分で回す）。
+/// 読み込み時に1度作り、以降は状態を持たないのでフレームの刻み方やシークの順に依らない
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineClock {
+    flow: SpeedIntegral,
+    spin: SpeedIntegral,
+    camera: CameraPose,
+    flights: Vec<CameraFlight>,
+    drives_camera: bool,
+}
+
+impl TimelineClock {
+    /// `flights` は `Timeline::camera_flights` の結果
+    pub fn new(timeline: &Timeline, base: &TimelineBase, flights: Vec<CameraFlight>) -> Self {
+        Self {
+            flow: SpeedIntegral::new(&timeline.flow_changes, base.params[ParamField::FlowSpeed.index()]),
+            spin: SpeedIntegral::new(&timeline.rot_changes, base.params[ParamField::RotSpeed.index()]),
+            camera: base.camera,
+            flights,
+            drives_camera: timeline.drives_camera(),
+        }
+    }
+
+    /// フロー縞の位相（周期単位 0..1）
+    pub fn flow_phase(&self, t: f32) -> f32 {
+        self.flow.at(t).rem_euclid(1.0) as f32
+    }
+
+    /// カメラの姿勢。focus / unfocus のある台本は飛行に従い、無ければ読み込み時の姿勢から rot_speed（rad/s）で回す
+    pub fn camera(&self, t: f32) -> CameraPose {
+        if self.drives_camera { return camera_pose_at(&self.flights, self.camera, t); }
+        let yaw = (self.camera.yaw as f64 + self.spin.at(t)).rem_euclid(std::f64::consts::TAU) as f32;
+        CameraPose { yaw, ..self.camera }
+    }
+}
+
+/// 再生位置。`advance` に毎フレームの時計（秒）を渡すと、再生中はその進み分だけ進む
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Playback {
+    position: f32,
+    playing: bool,
+    anchor: Option<f32>, // 再生中: position = clock - anchor
+}
+
+impl Playback {
+    pub fn play(&mut self) {
+        self.playing = true;
+        self.anchor = None; // 次の advance の時計から再開
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.anchor = None;
+    }
+
+    /// 位置を `position` 秒にする（再生中ならそこから続ける）
+    pub fn seek(&mut self, position: f32) {
+        self.position = if position.is_finite() { position.max(0.0) } else { 0.0 };
+        self.anchor = None;
+    }
+
+    pub fn is_playing(&self) -> bool { self.playing }
+
+    pub fn position(&self) -> f32 { self.position }
+
+    pub fn advance(&mut self, clock: f32) -> f32 {
+        if self.playing {
+            let anchor = *self.anchor.get_or_insert(clock - self.position);
+            self.position = (clock - anchor).max(0.0);
+        }
+        self.position
+    }
+}
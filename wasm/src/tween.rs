@@ -0,0 +1,196 @@
+// GraphParams の各項目を目標値へ時間をかけて動かすトゥイーン
+//
+// 項目毎に (開始値, 目標値, 時間, 曲線) を持ち、frame の経過時間で進める。
+// 途中で同じ項目に新しい目標を指定すると、その時点の値から始め直す。
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::easing::Easing;
+
+/// トゥイーン時間の上限（秒）
+pub const MAX_TWEEN_S: f32 = 30.0;
+
+/// 時間 → 進捗の曲線。イージング関数（`src/easing.rs`）に加えて、目標を少し行き過ぎて戻るバネ。
+/// JSON では名前の文字列（`FromStr` と同じ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TweenCurve {
+    Ease(Easing),
+    /// 減衰振動（約13%行き過ぎてから収まる）。終端で必ず 1
+    Spring,
+}
+
+impl Default for TweenCurve {
+    fn default() -> Self { Self::Ease(Easing::default()) }
+}
+
+impl TweenCurve {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Ease(e) => e.apply(t),
+            // cos の周期を 1.25 回にして t=1 で cos=0（= ちょうど 1）になるようにする
+            Self::Spring => if t >= 1.0 { 1.0 } else { 1.0 - (-5.0 * t).exp() * (2.5 * std::f32::consts::PI * t).cos() },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ease(e) => e.name(),
+            Self::Spring => "spring",
+        }
+    }
+}
+
+impl fmt::Display for TweenCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCurve(pub String);
+
+impl fmt::Display for UnknownCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Easing::ALL.iter().map(|e| e.name()).collect();
+        write!(f, "unknown tween curve \"{}\" (available: {}, spring)", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownCurve {}
+
+/// "spring" かイージング名。空文字はイージングの既定（ease-in-out-cubic）
+impl FromStr for TweenCurve {
+    type Err = UnknownCurve;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "spring" { return Ok(Self::Spring); }
+        s.parse::<Easing>().map(Self::Ease).map_err(|_| UnknownCurve(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for TweenCurve {
+    type Error = UnknownCurve;
+    fn try_from(s: String) -> Result<Self, Self::Error> { s.parse() }
+}
+
+/// トゥイーンできる項目（GraphParams のフィールド順）。JSON ではフィールド名（"fog_strength" など）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ParamField {
+    EdgeThickness,
+    NodeSize,
+    FlowSpeed,
+    RotSpeed,
+    FogStart,
+    FogEnd,
+    FogStrength,
+    LinkOn,
+    LinkOff,
+    NucLinkOn,
+    NucLinkOff,
+}
+
+pub const PARAM_COUNT: usize = 11;
+
+impl ParamField {
+    pub const ALL: [ParamField; PARAM_COUNT] = [
+        Self::EdgeThickness, Self::NodeSize, Self::FlowSpeed, Self::RotSpeed,
+        Self::FogStart, Self::FogEnd, Self::FogStrength,
+        Self::LinkOn, Self::LinkOff, Self::NucLinkOn, Self::NucLinkOff,
+    ];
+
+    pub fn index(self) -> usize { self as usize }
+
+    /// GraphParams / `params::ParamsConfig` のフィールド名
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EdgeThickness => "edge_thickness",
+            Self::NodeSize => "node_size",
+            Self::FlowSpeed => "flow_speed",
+            Self::RotSpeed => "rot_speed",
+            Self::FogStart => "fog_start",
+            Self::FogEnd => "fog_end",
+            Self::FogStrength => "fog_strength",
+            Self::LinkOn => "link_on",
+            Self::LinkOff => "link_off",
+            Self::NucLinkOn => "nuc_link_on",
+            Self::NucLinkOff => "nuc_link_off",
+        }
+    }
+}
+
+impl fmt::Display for ParamField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownParamField(pub String);
+
+impl fmt::Display for UnknownParamField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = ParamField::ALL.iter().map(|p| p.name()).collect();
+        write!(f, "unknown parameter \"{}\" (available: {})", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownParamField {}
+
+impl FromStr for ParamField {
+    type Err = UnknownParamField;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::ALL.into_iter().find(|p| p.name() == s).ok_or_else(|| UnknownParamField(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for ParamField {
+    type Error = UnknownParamField;
+    fn try_from(s: String) -> Result<Self, Self::Error> { s.parse() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tween {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    curve: TweenCurve,
+}
+
+/// 項目毎のトゥイーン。値そのものは呼び出し側（GraphParams）が持ち、`step` で書き換える
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParamTweens {
+    slots: [Option<Tween>; PARAM_COUNT],
+}
+
+impl ParamTweens {
+    /// `field` を現在値 `from` から `to` へ `duration` 秒（0..=MAX_TWEEN_S）で動かす。0 なら次の `step` で即時に揃う
+    pub fn start(&mut self, field: ParamField, from: f32, to: f32, duration: f32, curve: TweenCurve) {
+        let duration = if duration.is_finite() { duration.clamp(0.0, MAX_TWEEN_S) } else { 0.0 };
+        self.slots[field.index()] = Some(Tween { from, to, duration, elapsed: 0.0, curve });
+    }
+
+    pub fn clear(&mut self) { self.slots = Default::default(); }
+
+    pub fn is_active(&self) -> bool { self.slots.iter().any(Option::is_some) }
+
+    /// 進行中なら目標値
+    pub fn target(&self, field: ParamField) -> Option<f32> { self.slots[field.index()].map(|t| t.to) }
+
+    /// `dt` 秒進め、進行中の項目の現在値を `values`（ParamField 順）へ書き込む。終端の項目は目標値にして外す
+    pub fn step(&mut self, dt: f32, values: &mut [f32; PARAM_COUNT]) {
+        for (slot, v) in self.slots.iter_mut().zip(values.iter_mut()) {
+            let Some(tw) = slot.as_mut() else { continue };
+            tw.elapsed += dt.max(0.0);
+            if tw.elapsed >= tw.duration {
+                *v = tw.to;
+                *slot = None;
+            } else {
+                *v = tw.from + (tw.to - tw.from) * tw.curve.apply(tw.elapsed / tw.duration);
+            }
+        }
+    }
+}
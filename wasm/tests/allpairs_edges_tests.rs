@@ -12,8 +12,29 @@ fn all_pairs_edges_have_expected_count_and_no_self() {
     // no self-loops and undirected unique
     for e in &edges { assert_ne!(e.a, e.b); assert!(e.a < n && e.b < n); }
     // sorted unique pairs (a<b)
-    let mut v: Vec<(u32,u32)> = edges.iter().map(|e|(e.a.min(e.b), e.a.max(e.b))).collect();
+    let v: Vec<(u32,u32)> = edges.iter().map(|e|(e.a.min(e.b), e.a.max(e.b))).collect();
     let mut w = v.clone(); w.sort(); w.dedup();
     assert_eq!(v.len(), w.len());
 }
 
+#[test]
+fn all_pairs_mode_is_refused_above_the_node_cap() {
+    use wasm_wgpu_demo::graph3d::{check_allpairs, Node3, ALLPAIRS_MAX_NODES};
+    use wasm_wgpu_demo::graph_data::LoadedGraph;
+    use wasm_wgpu_demo::render::{allpairs_edges, build_edge_insts};
+    let graph = |n: usize| LoadedGraph::from_generated((0..n).map(|i| Node3 { id: i as u32 * 3, pos: [i as f32 * 0.001, 0.0, 0.0] }).collect(), Vec::new());
+    let capped = graph(ALLPAIRS_MAX_NODES);
+    assert_eq!(check_allpairs(capped.nodes.len()), Ok(()));
+    let pairs = allpairs_edges(&capped, true).expect("within the cap");
+    assert_eq!(pairs.len(), ALLPAIRS_MAX_NODES * (ALLPAIRS_MAX_NODES - 1) / 2);
+    assert_eq!(build_edge_insts(&capped, Some(&pairs), false).len(), pairs.len());
+    assert!(allpairs_edges(&capped, false).is_none());
+    // 上限を超えるグラフは理由付きで断る（読み込み時は全ペア表示を解除する）
+    let big = graph(ALLPAIRS_MAX_NODES + 1);
+    let err = check_allpairs(big.nodes.len()).unwrap_err();
+    assert!(err.contains("1000") && err.contains("1001"), "{err}");
+    assert!(allpairs_edges(&big, true).is_none());
+    assert!(build_edge_insts(&big, None, false).is_empty());
+    assert_eq!(big.index_of(3 * ALLPAIRS_MAX_NODES as u32), Some(ALLPAIRS_MAX_NODES));
+    assert_eq!(big.index_of(1), None);
+}
@@ -0,0 +1,210 @@
+// アンチエイリアス（MSAA のサンプル数の選択と、fs_edge の解析的カバレッジ）。選択と式は純関数で、描画はオフスクリーンで確認する
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{brightness, gl_renderer, lit, mean_diff, renderer, sphere};
+use wasm_wgpu_demo::offscreen::{OffscreenRenderer, Snapshot};
+use wasm_wgpu_demo::params::{parse_graph_config, AntialiasConfig, BloomConfig, GraphConfig, ParamsConfig};
+use wasm_wgpu_demo::png::RgbaImage;
+use wasm_wgpu_demo::presets::{base64url_encode, decode_preset, encode_preset, PresetError};
+use wasm_wgpu_demo::raster::rasterize_snapshot;
+use wasm_wgpu_demo::render::{max_msaa_samples, msaa_samples};
+use wasm_wgpu_demo::shader_math::{analytic_edge_fade, analytic_edge_width, ANALYTIC_MIN_EDGE_PX};
+
+#[test]
+fn antialias_config_defaults_off_and_snaps_sample_counts() {
+    let (cfg, warnings) = parse_graph_config("{}").unwrap();
+    assert_eq!(cfg.antialias, AntialiasConfig { msaa: 1, analytic: false });
+    assert!(warnings.is_empty());
+
+    let (cfg, warnings) = parse_graph_config(r#"{ "antialias": { "msaa": 6, "analytic": true } }"#).unwrap();
+    assert_eq!(cfg.antialias, AntialiasConfig { msaa: 4, analytic: true });
+    assert_eq!(warnings.iter().map(|w| w.path.as_str()).collect::<Vec<_>>(), ["antialias.msaa"]);
+    for (given, snapped) in [(0, 1), (1, 1), (2, 1), (4, 4), (7, 4), (8, 8), (64, 8)] {
+        assert_eq!(AntialiasConfig { msaa: given, analytic: false }.clamped().msaa, snapped, "{given}");
+    }
+
+    let errors = parse_graph_config(r#"{ "antialias": { "samples": 4 } }"#).unwrap_err();
+    assert!(errors[0].message.contains("samples"), "{errors:?}");
+    let json = serde_json::to_value(GraphConfig::default()).unwrap();
+    assert_eq!(json["antialias"], serde_json::json!({ "msaa": 1, "analytic": false }));
+}
+
+#[test]
+fn preset_codes_carry_antialias_only_when_changed() {
+    let plain = GraphConfig::default();
+    let smooth = GraphConfig { antialias: AntialiasConfig { msaa: 4, analytic: true }, ..plain.clone() };
+    assert!(encode_preset(&smooth).len() > encode_preset(&plain).len());
+    assert_eq!(decode_preset(&encode_preset(&smooth)).unwrap(), smooth);
+    assert_eq!(decode_preset(&encode_preset(&plain)).unwrap().antialias, AntialiasConfig::default());
+    // 版1・AA のタグだけ（値が欠けている）
+    assert_eq!(decode_preset(&base64url_encode(&[1, 0x23, 1, 4])), Err(PresetError::Truncated));
+}
+
+#[test]
+fn sample_count_falls_back_to_what_the_adapter_supports() {
+    use wgpu::{TextureFormatFeatureFlags as Flags, TextureFormatFeatures, TextureUsages as Usages};
+    let features = |flags: Flags| TextureFormatFeatures { allowed_usages: Usages::RENDER_ATTACHMENT, flags };
+    let x8 = features(Flags::MULTISAMPLE_X4 | Flags::MULTISAMPLE_X8 | Flags::MULTISAMPLE_RESOLVE);
+    let x4 = features(Flags::MULTISAMPLE_X4 | Flags::MULTISAMPLE_RESOLVE);
+    let depth_x8 = features(Flags::MULTISAMPLE_X4 | Flags::MULTISAMPLE_X8);
+    assert_eq!(max_msaa_samples(x8, depth_x8), 8);
+    // ブラウザの WebGPU はフォーマットが保証する 4x まで。深度が 4x までならカラーも 4x
+    assert_eq!(max_msaa_samples(x4, depth_x8), 4);
+    assert_eq!(max_msaa_samples(x8, features(Flags::MULTISAMPLE_X4)), 4);
+    // resolve できないカラー（WebGL2 で拡張の無い浮動小数点など）は 1x
+    assert_eq!(max_msaa_samples(features(Flags::MULTISAMPLE_X4), depth_x8), 1);
+    assert_eq!(max_msaa_samples(features(Flags::empty()), depth_x8), 1);
+
+    assert_eq!([1, 4, 8].map(|n| msaa_samples(n, 8)), [1, 4, 8]);
+    assert_eq!([1, 4, 8].map(|n| msaa_samples(n, 4)), [1, 4, 4]);
+    assert_eq!([1, 4, 8].map(|n| msaa_samples(n, 1)), [1, 1, 1]);
+}
+
+#[test]
+fn analytic_coverage_widens_thin_lines_and_keeps_their_energy() {
+    let height = 400.0;
+    let min = ANALYTIC_MIN_EDGE_PX / height;
+    // 最小幅より太い線はそのまま
+    assert_eq!(analytic_edge_width(0.01, height, ANALYTIC_MIN_EDGE_PX), (0.01, 1.0));
+    assert_eq!(analytic_edge_width(0.0, height, ANALYTIC_MIN_EDGE_PX), (0.0, 1.0));
+    for thin in [0.0001, 0.0005, min * 0.5] {
+        let (widened, cov) = analytic_edge_width(thin, height, ANALYTIC_MIN_EDGE_PX);
+        assert_eq!(widened, min);
+        assert!(cov < 1.0 && (widened * cov - thin).abs() < 1e-9, "{thin}: {widened} x {cov}");
+    }
+    // 縁の1ピクセルだけ落とす
+    assert_eq!(analytic_edge_fade(0.0, 0.1), 1.0);
+    assert_eq!(analytic_edge_fade(0.85, 0.1), 1.0);
+    assert!((analytic_edge_fade(0.95, 0.1) - 0.5).abs() < 1e-5);
+    assert_eq!(analytic_edge_fade(-1.0, 0.1), 0.0);
+}
+
+/// factor×factor の箱フィルタで縮小（スーパーサンプリングの参照画像）
+fn downsample(img: &RgbaImage, factor: u32) -> RgbaImage {
+    let (w, h) = (img.width / factor, img.height / factor);
+    let mut pixels = Vec::with_capacity((w * h * 4) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            for c in 0..4 {
+                let sum: u32 = (0..factor * factor)
+                    .map(|i| img.pixels[(((y * factor + i / factor) * img.width + x * factor + i % factor) * 4 + c) as usize] as u32)
+                    .sum();
+                pixels.push((sum as f32 / (factor * factor) as f32).round() as u8);
+            }
+        }
+    }
+    RgbaImage { width: w, height: h, pixels }
+}
+
+#[test]
+fn msaa_resolves_into_the_target_and_follows_resize() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let graph = sphere();
+    let limits = r.msaa_limits();
+    assert!(limits.direct >= 4 && limits.hdr >= 4, "{limits:?}"); // WebGPU が保証する 4x
+    let plain = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let aliased = r.snapshot(&plain).unwrap();
+    assert_eq!(r.msaa_samples(), 1);
+
+    let msaa = |n: u32| Snapshot { antialias: AntialiasConfig { msaa: n, analytic: false }, ..plain.clone() };
+    let x4 = r.snapshot(&msaa(4)).unwrap();
+    assert_eq!(r.msaa_samples(), 4);
+    assert_eq!(x4.pixel(0, 0), [0, 0, 0, 0], "transparent clear is resolved too");
+    // 同じ絵のまま縁だけならされる。4倍の解像度で描いて縮小した画像に 1x より近い
+    assert!(mean_diff(&x4, &aliased) > 0.0 && mean_diff(&x4, &aliased) < 6.0, "{}", mean_diff(&x4, &aliased));
+    let mut big = renderer(640, 400).unwrap();
+    let reference = downsample(&big.snapshot(&plain).unwrap(), 4);
+    assert!(mean_diff(&x4, &reference) < mean_diff(&aliased, &reference), "{} vs {}", mean_diff(&x4, &reference), mean_diff(&aliased, &reference));
+
+    let x8 = r.snapshot(&msaa(8)).unwrap();
+    assert_eq!(r.msaa_samples(), limits.direct.min(8));
+    assert_eq!((x8.width, x8.height), (160, 100));
+
+    // 1x に戻すと元の画像
+    assert_eq!(r.snapshot(&plain).unwrap(), aliased);
+
+    r.resize(200, 60).unwrap();
+    let resized = r.snapshot(&msaa(4)).unwrap();
+    assert_eq!((resized.width, resized.height), (200, 60));
+    assert!(lit(&resized, 4) > 0);
+}
+
+#[test]
+fn msaa_works_with_the_hdr_bloom_target() {
+    let Some(mut r) = renderer(120, 80) else { return };
+    let graph = sphere();
+    let bloom = BloomConfig { enabled: true, ..BloomConfig::default() };
+    let snap = |msaa: u32| Snapshot { time: 1.25, bloom, antialias: AntialiasConfig { msaa, analytic: false }, ..Snapshot::new(&graph) };
+    let a = r.snapshot(&snap(1)).unwrap();
+    let b = r.snapshot(&snap(4)).unwrap();
+    assert_eq!(r.msaa_samples(), 4);
+    let ratio = brightness(&b) as f64 / brightness(&a) as f64;
+    assert!((0.8..1.25).contains(&ratio), "{ratio}");
+}
+
+/// 細い線（太さの下限）: 解析的 AA では画素の間に落ちて途切れず、回転しても明るさの合計が揺れにくい
+#[test]
+fn analytic_mode_stabilises_sub_pixel_edges() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let graph = sphere();
+    let thin = ParamsConfig { edge_thickness: 0.0005, node_size: 0.01, ..ParamsConfig::default() };
+    let frame = |analytic: bool, time: f32| Snapshot {
+        time,
+        params: thin,
+        antialias: AntialiasConfig { msaa: 1, analytic },
+        ..Snapshot::new(&graph)
+    };
+    let plain = r.snapshot(&frame(false, 1.25)).unwrap();
+    let smooth = r.snapshot(&frame(true, 1.25)).unwrap();
+    assert!(lit(&smooth, 4) > lit(&plain, 4) * 2, "{} vs {}", lit(&smooth, 4), lit(&plain, 4));
+
+    // 時刻（ドリフト）を少しずつ進めた時の明るさの合計のばらつき（変動係数）
+    let variation = |r: &mut OffscreenRenderer, analytic: bool| {
+        let sums: Vec<f64> = (0..8).map(|i| brightness(&r.snapshot(&frame(analytic, 1.0 + i as f32 * 0.37)).unwrap()) as f64).collect();
+        let mean = sums.iter().sum::<f64>() / sums.len() as f64;
+        (sums.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / sums.len() as f64).sqrt() / mean
+    };
+    let (off, on) = (variation(&mut r, false), variation(&mut r, true));
+    assert!(on < off, "analytic {on} vs off {off}");
+
+    // 太い線は最小幅を超えるので広げない（縁のぼかしだけ）
+    let normal = |analytic: bool| Snapshot { time: 1.25, antialias: AntialiasConfig { msaa: 1, analytic }, ..Snapshot::new(&graph) };
+    let a = r.snapshot(&normal(false)).unwrap();
+    let b = r.snapshot(&normal(true)).unwrap();
+    let ratio = brightness(&b) as f64 / brightness(&a) as f64;
+    assert!((0.85..=1.0).contains(&ratio), "{ratio}");
+}
+
+/// CPU ラスタライザ（Canvas2D の代替描画）も同じ式で広げる
+#[test]
+fn cpu_reference_matches_the_analytic_mode() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let graph = sphere();
+    let snap = Snapshot {
+        time: 1.25,
+        params: ParamsConfig { edge_thickness: 0.0008, ..ParamsConfig::default() },
+        antialias: AntialiasConfig { msaa: 1, analytic: true },
+        ..Snapshot::new(&graph)
+    };
+    let gpu = r.snapshot(&snap).unwrap();
+    let cpu = rasterize_snapshot(&snap, 160, 100);
+    let mean = mean_diff(&gpu, &cpu);
+    assert!(mean < 3.0, "mean abs diff {mean}");
+    let plain = rasterize_snapshot(&Snapshot { antialias: AntialiasConfig::default(), ..snap.clone() }, 160, 100);
+    assert!(lit(&cpu, 4) > lit(&plain, 4));
+}
+
+/// WebGL2 と同じ GL 経路でも MSAA（resolve）と解析的 AA が同じ画像になる
+#[test]
+fn gl_backend_renders_the_same_antialiasing() {
+    let Some(mut r) = renderer(120, 80) else { return };
+    let Some(mut gl) = gl_renderer(120, 80) else { return };
+    let graph = sphere();
+    for antialias in [AntialiasConfig { msaa: 4, analytic: false }, AntialiasConfig { msaa: 1, analytic: true }] {
+        let snap = Snapshot { time: 1.25, antialias, ..Snapshot::new(&graph) };
+        let mean = mean_diff(&r.snapshot(&snap).unwrap(), &gl.snapshot(&snap).unwrap());
+        assert!(mean < 2.0, "{antialias:?}: mean abs diff {mean}");
+    }
+}
@@ -0,0 +1,68 @@
+use wasm_wgpu_demo::audio::{
+    band_levels, parse_audio_config, AudioConfig, AudioConfigError, AudioEnvelope, AudioMapping,
+    AudioModulation, AudioSignals, AudioSource, AudioTarget, AUDIO_STALE_S, MODULATION_MAX,
+};
+
+#[test]
+fn spectrum_is_split_into_low_mid_high_bands() {
+    // 64ビン: 低域 [0,1)、中域 [1,8)、高域 [8,32)、それより上は使わない
+    let mut spec = vec![0.0; 64];
+    spec[0] = 1.0;
+    spec[1..8].fill(0.5);
+    spec[8..32].fill(0.25);
+    spec[32..].fill(1.0);
+    assert_eq!(band_levels(&spec), [1.0, 0.5, 0.25]);
+    // 範囲外/NaN は 0..1 に丸める。ビンが少なくても各帯域に1ビン
+    assert_eq!(band_levels(&[2.0, f32::NAN, -1.0]), [1.0, 0.0, 0.0]);
+    assert_eq!(band_levels(&[]), [0.0; 3]);
+}
+
+#[test]
+fn envelope_rises_fast_falls_slowly_and_goes_silent_when_stale() {
+    let cfg = AudioConfig { attack_s: 0.05, release_s: 0.5, mappings: vec![] };
+    let mut env = AudioEnvelope::default();
+    env.set_input(1.0, &[]);
+    env.step(0.05, &cfg);
+    let up = env.signals().level;
+    assert!((up - (1.0 - (-1.0f32).exp())).abs() < 1e-5);
+    env.set_input(0.0, &[]);
+    env.step(0.05, &cfg);
+    let down = env.signals().level;
+    assert!(down < up && down > up * 0.85, "release is slower than attack: {up} -> {down}");
+    // 入力が途絶えたら無音へ戻る
+    env.set_input(1.0, &[]);
+    for _ in 0..((AUDIO_STALE_S / 0.016) as usize + 400) { env.step(0.016, &cfg); }
+    assert_eq!(env.signals(), AudioSignals::default());
+}
+
+#[test]
+fn mappings_sum_into_clamped_multipliers() {
+    let s = AudioSignals { level: 0.5, low: 1.0, mid: 0.0, high: 0.25 };
+    let maps = [
+        AudioMapping { source: AudioSource::Level, target: AudioTarget::FlowSpeed, gain: 2.0 },
+        AudioMapping { source: AudioSource::High, target: AudioTarget::FlowSpeed, gain: 4.0 },
+        AudioMapping { source: AudioSource::Low, target: AudioTarget::NucleusSize, gain: 4.0 },
+        AudioMapping { source: AudioSource::Low, target: AudioTarget::EdgeThickness, gain: -4.0 },
+    ];
+    let m = AudioModulation::from_signals(&s, &maps);
+    assert_eq!(m.flow_speed, 3.0);
+    assert_eq!(m.nucleus_size, MODULATION_MAX);
+    assert_eq!(m.edge_thickness, 0.25);
+    assert_eq!(m.node_glow, 1.0);
+    // 無音なら変調なし
+    let cfg = AudioConfig::default();
+    assert_eq!(AudioModulation::from_signals(&AudioSignals::default(), &cfg.mappings), AudioModulation::default());
+}
+
+#[test]
+fn config_json_uses_defaults_and_is_validated() {
+    let cfg = parse_audio_config(r#"{ "attack_s": 0.02, "mappings": [{ "source": "mid", "target": "node_glow", "gain": 1.0 }] }"#).unwrap();
+    assert_eq!(cfg.release_s, AudioConfig::default().release_s);
+    assert_eq!(cfg.mappings, vec![AudioMapping { source: AudioSource::Mid, target: AudioTarget::NodeGlow, gain: 1.0 }]);
+    assert_eq!(parse_audio_config(r#"{ "release_s": -1 }"#), Err(AudioConfigError::InvalidTime { field: "release_s" }));
+    assert_eq!(
+        parse_audio_config(r#"{ "mappings": [{ "source": "level", "target": "flow_speed", "gain": 9 }] }"#),
+        Err(AudioConfigError::InvalidGain { index: 0 }),
+    );
+    assert!(matches!(parse_audio_config(r#"{ "mappings": [{ "source": "bass" }] }"#), Err(AudioConfigError::Parse(_))));
+}
@@ -0,0 +1,184 @@
+// ブルーム（HDR ターゲット + にじみ + トーンマップ）。設定の検証と式は純関数で、描画はオフスクリーンで確認する
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{brightness, gl_renderer, lit, mean_diff, renderer, sphere};
+use wasm_wgpu_demo::bloom::{bloom_mip_sizes, hdr_target_supported, BLOOM_MAX_MIPS, BLOOM_SHADER_WGSL};
+use wasm_wgpu_demo::graph3d::generate_sphere;
+use wasm_wgpu_demo::graph_data::LoadedGraph;
+use wasm_wgpu_demo::offscreen::Snapshot;
+use wasm_wgpu_demo::params::{parse_graph_config, BloomConfig, GraphConfig};
+use wasm_wgpu_demo::presets::{builtin_preset, decode_preset, encode_preset, PresetError};
+use wasm_wgpu_demo::shader_math::{aces_tonemap, bloom_bright_pass};
+
+#[test]
+fn bloom_config_defaults_off_and_is_clamped_with_warnings() {
+    let (cfg, warnings) = parse_graph_config("{}").unwrap();
+    assert_eq!(cfg.bloom, BloomConfig::default());
+    assert!(!cfg.bloom.enabled && warnings.is_empty());
+
+    let (cfg, warnings) = parse_graph_config(r#"{ "bloom": { "enabled": true, "threshold": 9, "radius": -1 } }"#).unwrap();
+    assert!(cfg.bloom.enabled);
+    assert_eq!((cfg.bloom.threshold, cfg.bloom.radius), (4.0, 0.0));
+    assert_eq!(cfg.bloom.intensity, BloomConfig::default().intensity);
+    let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(paths, ["bloom.threshold", "bloom.radius"]);
+
+    let errors = parse_graph_config(r#"{ "bloom": { "strength": 1 } }"#).unwrap_err();
+    assert!(errors[0].message.contains("strength"), "{errors:?}");
+
+    let nan = BloomConfig { threshold: f32::NAN, intensity: f32::INFINITY, ..BloomConfig::default() }.clamped();
+    assert_eq!(nan, BloomConfig::default());
+    // get_graph_config の JSON にも載る
+    let json = serde_json::to_value(GraphConfig::default()).unwrap();
+    assert_eq!(json["bloom"]["enabled"], false);
+}
+
+#[test]
+fn preset_codes_carry_bloom_only_when_changed() {
+    let focus = builtin_preset("focus").unwrap();
+    let code = encode_preset(&focus);
+    let bloomy = GraphConfig { bloom: BloomConfig { enabled: true, ..BloomConfig::default() }, ..focus.clone() };
+    let bloomy_code = encode_preset(&bloomy);
+    assert!(bloomy_code.len() > code.len());
+    assert_eq!(decode_preset(&code).unwrap().bloom, BloomConfig::default());
+    assert_eq!(decode_preset(&bloomy_code).unwrap(), bloomy);
+
+    // 版1・ブルームのタグだけ（値が欠けている）
+    let broken = wasm_wgpu_demo::presets::base64url_encode(&[1, 0x21, 3, 1, 0, 0]);
+    assert_eq!(decode_preset(&broken), Err(PresetError::Truncated));
+}
+
+#[test]
+fn mip_chain_halves_until_small() {
+    let sizes = bloom_mip_sizes(1920, 1080);
+    assert_eq!(sizes.len(), BLOOM_MAX_MIPS);
+    assert_eq!(sizes[0], (960, 540));
+    assert_eq!(sizes[5], (30, 16));
+    assert_eq!(bloom_mip_sizes(16, 8), [(8, 4), (4, 2)]);
+    assert_eq!(bloom_mip_sizes(1, 1), [(1, 1)]);
+    for w in sizes.windows(2) {
+        assert_eq!(w[1], ((w[0].0 / 2).max(1), (w[0].1 / 2).max(1)));
+    }
+}
+
+#[test]
+fn hdr_support_needs_render_blend_and_filter() {
+    use wgpu::{TextureFormatFeatureFlags as Flags, TextureFormatFeatures, TextureUsages as Usages};
+    let full = TextureFormatFeatures {
+        allowed_usages: Usages::RENDER_ATTACHMENT | Usages::TEXTURE_BINDING | Usages::COPY_SRC,
+        flags: Flags::FILTERABLE | Flags::BLENDABLE,
+    };
+    assert!(hdr_target_supported(full));
+    // WebGL2 で EXT_color_buffer_float が無い時はサンプルのみ
+    assert!(!hdr_target_supported(TextureFormatFeatures { allowed_usages: Usages::TEXTURE_BINDING, ..full }));
+    assert!(!hdr_target_supported(TextureFormatFeatures { flags: Flags::FILTERABLE, ..full }));
+}
+
+#[test]
+fn bright_pass_keeps_only_the_excess_and_tonemap_is_monotonic() {
+    assert_eq!(bloom_bright_pass([0.2, 0.3, 0.1], 0.8), [0.0; 3]);
+    // 閾値より十分明るければ、超えた分の割合で残る（色相は保つ）
+    let out = bloom_bright_pass([3.0, 1.5, 0.0], 1.0);
+    assert!((out[0] - 2.0).abs() < 1e-4 && (out[1] - 1.0).abs() < 1e-4 && out[2] == 0.0, "{out:?}");
+    // ニーの区間で連続
+    let a = bloom_bright_pass([1.4999, 0.0, 0.0], 1.0)[0];
+    let b = bloom_bright_pass([1.5001, 0.0, 0.0], 1.0)[0];
+    assert!((a - b).abs() < 1e-3, "{a} {b}");
+
+    assert_eq!(aces_tonemap([0.0; 3]), [0.0; 3]);
+    let mut prev = 0.0;
+    for i in 1..200 {
+        let v = aces_tonemap([i as f32 * 0.1; 3])[0];
+        assert!(if prev < 1.0 { v > prev } else { v == 1.0 }, "{i}: {v}");
+        prev = v;
+    }
+    // 加算で 1 を超えた重なりも潰れずに差が残る
+    assert!(aces_tonemap([2.0; 3])[0] - aces_tonemap([1.0; 3])[0] > 0.05);
+}
+
+#[test]
+fn bloom_shader_validates_and_translates_to_webgl2() {
+    use naga::back::glsl;
+    let module = naga::front::wgsl::parse_str(BLOOM_SHADER_WGSL).expect("parse WGSL");
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .expect("validate WGSL");
+    let options = glsl::Options {
+        version: glsl::Version::Embedded { version: 300, is_webgl: true },
+        writer_flags: glsl::WriterFlags::ADJUST_COORDINATE_SPACE | glsl::WriterFlags::FORCE_POINT_SIZE,
+        ..Default::default()
+    };
+    let stages = [
+        (naga::ShaderStage::Vertex, "vs_fullscreen"),
+        (naga::ShaderStage::Fragment, "fs_prefilter"),
+        (naga::ShaderStage::Fragment, "fs_downsample"),
+        (naga::ShaderStage::Fragment, "fs_upsample"),
+        (naga::ShaderStage::Fragment, "fs_composite"),
+    ];
+    for (stage, entry) in stages {
+        let pipeline = glsl::PipelineOptions { shader_stage: stage, entry_point: entry.into(), multiview: None };
+        let mut out = String::new();
+        glsl::Writer::new(&mut out, &module, &info, &options, &pipeline, naga::proc::BoundsCheckPolicies::default())
+            .and_then(|mut w| w.write())
+            .unwrap_or_else(|e| panic!("{entry}: {e}"));
+    }
+}
+
+/// にじみは周囲へ広がり、背景の透過は保つ。無効に戻すと従来の直接描画と一致する
+#[test]
+fn bloom_spreads_glow_and_can_be_turned_off() {
+    let Some(mut r) = renderer(200, 120) else { return };
+    let graph = sphere();
+    let plain = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let direct = r.snapshot(&plain).unwrap();
+
+    let bloom = |intensity: f32, radius: f32| Snapshot {
+        bloom: BloomConfig { enabled: true, threshold: 0.2, intensity, radius },
+        ..plain.clone()
+    };
+    let tonemapped = r.snapshot(&bloom(0.0, 0.6)).unwrap();
+    let glowing = r.snapshot(&bloom(2.0, 0.6)).unwrap();
+    let wide = r.snapshot(&bloom(2.0, 1.0)).unwrap();
+
+    for img in [&tonemapped, &glowing, &wide] {
+        assert_eq!(img.pixel(0, 0), [0, 0, 0, 0], "transparent corner");
+    }
+    assert!(lit(&tonemapped, 8) > 0);
+    assert!(lit(&glowing, 8) > lit(&tonemapped, 8), "{} vs {}", lit(&glowing, 8), lit(&tonemapped, 8));
+    assert!(brightness(&glowing) > brightness(&tonemapped));
+    assert!(lit(&wide, 8) > lit(&glowing, 8), "{} vs {}", lit(&wide, 8), lit(&glowing, 8));
+    // にじみは足すだけ（どの画素も暗くならない）
+    let darker = tonemapped.pixels.iter().zip(&glowing.pixels).filter(|(&a, &b)| b as u16 + 2 < a as u16).count();
+    assert_eq!(darker, 0);
+    // 透過の背景へ広がったにじみにもアルファが付く
+    assert!(glowing.pixels.chunks(4).all(|p| p[3] > 0 || p[..3].iter().all(|&c| c <= 8)));
+
+    assert_eq!(r.snapshot(&plain).unwrap(), direct, "disabling bloom restores the direct path");
+}
+
+#[test]
+fn bloom_follows_resize() {
+    let Some(mut r) = renderer(64, 48) else { return };
+    let (nodes, edges) = generate_sphere(3, 40, 2, 0.1);
+    let graph = LoadedGraph::from_generated(nodes, edges);
+    let snap = Snapshot { bloom: BloomConfig { enabled: true, ..BloomConfig::default() }, ..Snapshot::new(&graph) };
+    let small = r.snapshot(&snap).unwrap();
+    r.resize(150, 90).unwrap();
+    let large = r.snapshot(&snap).unwrap();
+    assert_eq!((small.width, small.height), (64, 48));
+    assert_eq!((large.width, large.height), (150, 90));
+    assert!(lit(&large, 8) > lit(&small, 8));
+}
+
+/// WebGL2 と同じ GL 経路（全画面三角形・ブレンド定数・半精度の描画先）でも同じ画像になる
+#[test]
+fn gl_backend_renders_the_same_bloom() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let Some(mut gl) = gl_renderer(160, 100) else { return };
+    let graph = sphere();
+    let snap = Snapshot { time: 1.25, bloom: BloomConfig { enabled: true, intensity: 1.5, ..BloomConfig::default() }, ..Snapshot::new(&graph) };
+    let mean = mean_diff(&r.snapshot(&snap).unwrap(), &gl.snapshot(&snap).unwrap());
+    assert!(mean < 2.0, "mean abs diff {mean}");
+}
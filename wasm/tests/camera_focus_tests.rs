@@ -0,0 +1,79 @@
+use glam::Vec3;
+use wasm_wgpu_demo::camera::{CameraDelta, CameraPose, OrbitCamera};
+use wasm_wgpu_demo::easing::Easing;
+use wasm_wgpu_demo::graph3d::{k_hop_neighbourhood, Edge3, EdgeKind3};
+
+fn edge(a: u32, b: u32) -> Edge3 { Edge3 { a, b, kind: EdgeKind3::Mesh } }
+
+#[test]
+fn easings_hit_endpoints_and_parse_by_name() {
+    for e in Easing::ALL {
+        assert_eq!(e.apply(0.0), 0.0, "{e}");
+        assert!((e.apply(1.0) - 1.0).abs() < 1e-6, "{e}");
+        assert!((0.0..=1.0).contains(&e.apply(0.5)), "{e}");
+        assert_eq!(e.name().parse::<Easing>(), Ok(e));
+    }
+    assert_eq!("".parse::<Easing>(), Ok(Easing::EaseInOutCubic));
+    assert!(Easing::EaseInOutCubic.apply(0.25) < 0.25);
+    assert!("bounce".parse::<Easing>().unwrap_err().to_string().contains("ease-out-expo"));
+}
+
+#[test]
+fn k_hop_follows_undirected_edges() {
+    // 0-1-2-3 の鎖と 4-5
+    let edges = [edge(0, 1), edge(2, 1), edge(2, 3), edge(4, 5)];
+    assert_eq!(k_hop_neighbourhood(6, &edges, &[0], 0), vec![0]);
+    assert_eq!(k_hop_neighbourhood(6, &edges, &[0], 2), vec![0, 1, 2]);
+    assert_eq!(k_hop_neighbourhood(6, &edges, &[3, 5], 1), vec![2, 3, 4, 5]);
+    assert_eq!(k_hop_neighbourhood(6, &edges, &[0], 10), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn framing_fits_neighbourhood_in_view() {
+    let cam = OrbitCamera::default();
+    let points = [[0.5, 0.0, 0.0], [0.5, 0.6, 0.0], [0.5, 0.0, -0.6]];
+    let pose = cam.framing_pose([0.5, 0.0, 0.0], &points, 1.0);
+    assert_eq!(pose.target, [0.5, 0.0, 0.0]);
+    assert_eq!((pose.yaw, pose.pitch), (cam.yaw, cam.pitch));
+    let half = 0.5 * cam.fov_y;
+    assert!(pose.distance * half.sin() >= 0.6);
+    // 縦長の画面では水平方向で決まるので遠くなる
+    assert!(cam.framing_pose([0.5, 0.0, 0.0], &points, 0.5).distance > pose.distance);
+    // 1点だけなら最小距離
+    assert_eq!(cam.framing_pose([0.0; 3], &[[0.0; 3]], 1.0).distance, cam.limits.min_distance);
+}
+
+#[test]
+fn fly_to_eases_to_pose_then_resumes_orbit() {
+    let mut cam = OrbitCamera::default();
+    cam.auto_rotate_speed = 0.5;
+    cam.resume_after_s = 1.0;
+    let to = CameraPose { yaw: 0.5, pitch: 0.1, distance: 2.0, target: [0.3, 0.2, 0.1] };
+    cam.fly_to(to, 1.0, Easing::Linear);
+    cam.update(CameraDelta::default(), false, 0.5, 600.0);
+    assert!(cam.is_flying());
+    assert!((cam.yaw - 0.25).abs() < 1e-5);
+    assert!((Vec3::from(cam.target) - Vec3::new(0.15, 0.1, 0.05)).length() < 1e-5);
+    cam.update(CameraDelta::default(), false, 0.6, 600.0);
+    assert!(!cam.is_flying());
+    assert_eq!(cam.pose(), to);
+    // 到着直後は止まり、待機時間の後に注視点まわりで自動回転
+    cam.update(CameraDelta::default(), false, 0.5, 600.0);
+    assert_eq!(cam.yaw, 0.5);
+    for _ in 0..20 { cam.update(CameraDelta::default(), false, 0.1, 600.0); }
+    assert!(cam.yaw > 0.5 && cam.target == to.target);
+}
+
+#[test]
+fn fly_to_takes_shortest_yaw_arc_and_user_input_cancels() {
+    let mut cam = OrbitCamera::default();
+    cam.yaw = 6.2;
+    cam.fly_to(CameraPose { yaw: 0.1, ..cam.pose() }, 1.0, Easing::Linear);
+    cam.update(CameraDelta::default(), false, 0.5, 600.0);
+    let mid = cam.yaw;
+    assert!(!(0.2..6.1).contains(&mid), "went the long way: {mid}");
+    cam.update(CameraDelta { zoom: 0.1, ..Default::default() }, false, 0.1, 600.0);
+    assert!(!cam.is_flying());
+    cam.fly_to(OrbitCamera::home_pose(), 0.0, Easing::Linear);
+    assert_eq!(cam.pose(), OrbitCamera::home_pose());
+}
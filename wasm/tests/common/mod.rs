@@ -0,0 +1,50 @@
+// オフスクリーン描画のテストで共有する準備と画素の指標（ネイティブのみ）。
+// テストごとに使う関数が違うので、使わないものがあっても警告しない
+#![allow(dead_code)]
+
+use wasm_wgpu_demo::graph3d::generate_sphere;
+use wasm_wgpu_demo::graph_data::LoadedGraph;
+use wasm_wgpu_demo::offscreen::{OffscreenError, OffscreenRenderer};
+use wasm_wgpu_demo::png::RgbaImage;
+
+/// アダプタが無い環境（CI のコンテナ等）では飛ばす。lavapipe/llvmpipe があれば実際に描く
+pub fn renderer(width: u32, height: u32) -> Option<OffscreenRenderer> {
+    skip_without_adapter("GPU render", OffscreenRenderer::new(width, height))
+}
+
+/// WebGL2 と同じ GL バックエンド（ネイティブの GL ドライバ）。無ければ飛ばす
+pub fn gl_renderer(width: u32, height: u32) -> Option<OffscreenRenderer> {
+    skip_without_adapter("GL render", OffscreenRenderer::with_backends(wgpu::Backends::GL, width, height))
+}
+
+fn skip_without_adapter(what: &str, result: Result<OffscreenRenderer, OffscreenError>) -> Option<OffscreenRenderer> {
+    match result {
+        Ok(r) => Some(r),
+        Err(e @ (OffscreenError::NoAdapter(_) | OffscreenError::RequestDevice(_))) => {
+            eprintln!("skipping {what}: {e}");
+            None
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// 描画テスト共通のシーン（60 ノードの球、seed 7）
+pub fn sphere() -> LoadedGraph {
+    let (nodes, edges) = generate_sphere(7, 60, 3, 0.1);
+    LoadedGraph::from_generated(nodes, edges)
+}
+
+/// RGB のどれかが `above` を超える画素の数
+pub fn lit(img: &RgbaImage, above: u8) -> usize {
+    img.pixels.chunks(4).filter(|p| p[..3].iter().any(|&c| c > above)).count()
+}
+
+/// RGB の合計（加算で明るくなったかの比較用）
+pub fn brightness(img: &RgbaImage) -> u64 {
+    img.pixels.chunks(4).map(|p| p[..3].iter().map(|&c| c as u64).sum::<u64>()).sum()
+}
+
+/// 全チャンネルの差の絶対値の平均
+pub fn mean_diff(a: &RgbaImage, b: &RgbaImage) -> f32 {
+    a.pixels.iter().zip(&b.pixels).map(|(x, y)| x.abs_diff(*y) as u32).sum::<u32>() as f32 / a.pixels.len() as f32
+}
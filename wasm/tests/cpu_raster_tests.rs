@@ -0,0 +1,172 @@
+// ゴールデン画像の読み書き（PNG）はネイティブのみ
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::{lit, mean_diff, renderer, sphere};
+use wasm_wgpu_demo::png::{decode_rgba, encode_rgba, PngError, RgbaImage};
+use wasm_wgpu_demo::presets::builtin_preset;
+use wasm_wgpu_demo::raster::{linear_to_srgb8, rasterize_snapshot, software_resolution, Framebuffer, SOFTWARE_MAX_PIXELS};
+use wasm_wgpu_demo::render::{EdgeInst, InstanceAttrs, NodeInst, Snapshot, UGraph};
+
+const W: u32 = 96;
+const H: u32 = 64;
+
+/// tests/golden/<name>.png と比べる。`UPDATE_GOLDEN=1` で書き直す。
+/// 超越関数の実装差を見込み、各チャンネル ±3 まで・外れ画素 0.2% までは一致とみなす
+fn assert_golden(name: &str, img: &RgbaImage) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.png"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, img.to_png()).unwrap();
+        return;
+    }
+    let bytes = std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {e} (run with UPDATE_GOLDEN=1 to create)", path.display()));
+    let golden = decode_rgba(&bytes).unwrap();
+    assert_eq!((golden.width, golden.height), (img.width, img.height), "{name}: size");
+    let off = golden.pixels.chunks(4).zip(img.pixels.chunks(4))
+        .filter(|(a, b)| a.iter().zip(b.iter()).any(|(x, y)| x.abs_diff(*y) > 3))
+        .count();
+    assert!(off * 500 <= (img.width * img.height) as usize, "{name}: {off} pixels differ from the golden image");
+}
+
+#[test]
+fn png_decoder_round_trips_and_rejects_other_formats() {
+    let rgba: Vec<u8> = (0..7 * 5 * 4).map(|i| (i * 37 % 251) as u8).collect();
+    let img = decode_rgba(&encode_rgba(7, 5, &rgba)).unwrap();
+    assert_eq!((img.width, img.height, img.pixels), (7, 5, rgba));
+
+    // 画像編集ソフトで保存し直した RGB は不透明として読む。グレースケールは読まない
+    let other = |color: png::ColorType, data: &[u8]| {
+        let mut out = Vec::new();
+        let mut enc = png::Encoder::new(&mut out, 2, 1);
+        enc.set_color(color);
+        enc.set_depth(png::BitDepth::Eight);
+        enc.write_header().unwrap().write_image_data(data).unwrap();
+        out
+    };
+    assert_eq!(decode_rgba(&other(png::ColorType::Rgb, &[1, 2, 3, 4, 5, 6])).unwrap().pixels, [1, 2, 3, 255, 4, 5, 6, 255]);
+    assert!(matches!(decode_rgba(&other(png::ColorType::Grayscale, &[7, 8])), Err(PngError::Unsupported(_))));
+
+    let corrupt = encode_rgba(2, 2, &[0; 16]);
+    assert!(matches!(decode_rgba(&corrupt[..20]), Err(PngError::Decode(_))));
+    assert!(matches!(decode_rgba(b"GIF89a"), Err(PngError::Decode(_))));
+}
+
+#[test]
+fn framebuffer_blends_additively_and_clips_depth() {
+    let ident = glam::Mat4::IDENTITY.to_cols_array_2d();
+    let mut u = UGraph::still(ident, 1.0, 0.0, &Default::default(), false, 0, [0.0; 4]);
+    u.misc1[3] = 0.0; // 霧なし
+    u.misc0[3] = 0.0; // ドリフトなし
+    let node = |z: f32| NodeInst { center: [0.0, 0.0, z], size: 20.0, color: [0.6, 0.3, 0.1, 1.0], phase: 0.0 };
+    let none = InstanceAttrs { edge_emphasis: &[], node_emphasis: &[], edge_pulse: &[] };
+
+    let mut fb = Framebuffer::new(32, 32);
+    fb.clear(wgpu::Color::TRANSPARENT);
+    fb.draw(&u, &[], &[node(0.5)], none);
+    let one = fb.to_image();
+    assert_eq!(one.pixel(0, 0), [0, 0, 0, 0], "outside the quad stays clear");
+    let c = one.pixel(16, 16);
+    assert_eq!(c[3], 255);
+    assert!(c[0] > c[1] && c[1] > c[2], "keeps the node colour {c:?}");
+
+    // 同じノードを重ねると明るくなり、1.0 で飽和する
+    fb.draw(&u, &[], &[node(0.5); 4], none);
+    let many = fb.to_image().pixel(16, 16);
+    assert!(many[1] > c[1]);
+    assert_eq!(many[0], 255);
+
+    // 深度 0..1 の外（カメラの後ろ/遠方）は描かない
+    let mut clipped = Framebuffer::new(32, 32);
+    clipped.clear(wgpu::Color::TRANSPARENT);
+    clipped.draw(&u, &[], &[node(-0.5), node(1.5)], none);
+    assert!(clipped.to_image().pixels.iter().all(|&b| b == 0));
+
+    // 画面を横切るエッジ: 線上だけ光る
+    let edge = EdgeInst { p1: [-0.9, 0.0, 0.5], p2: [0.9, 0.0, 0.5], color: [0.0, 1.0, 1.0, 1.0], params: [0.0, 1.0, 0.0] };
+    u.misc2[1] = 10.0; // 距離で消さない
+    u.misc0[1] = 0.1; // 32px でも数画素の太さ
+    let mut line = Framebuffer::new(32, 32);
+    line.clear(wgpu::Color::TRANSPARENT);
+    line.draw(&u, &[edge], &[], none);
+    let img = line.to_image();
+    assert!(img.pixel(16, 16)[1] > 100);
+    assert_eq!(img.pixel(16, 2), [0, 0, 0, 0]);
+    assert_eq!(img.pixel(0, 16), [0, 0, 0, 0], "beyond the endpoint");
+
+    assert_eq!([0.0, 0.5, 1.0, 2.0].map(linear_to_srgb8), [0, 188, 255, 255]);
+}
+
+#[test]
+fn snapshots_match_golden_images() {
+    let graph = sphere();
+    let base = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let img = rasterize_snapshot(&base, W, H);
+    assert_eq!(img, rasterize_snapshot(&base, W, H), "deterministic");
+    assert_eq!(img.pixel(0, 0), [0, 0, 0, 0], "transparent like the canvas");
+    assert!(lit(&img, 32) > 50);
+    assert_golden("sphere_default", &img);
+
+    // プリセット・色味・不透明な背景
+    let focus = Snapshot {
+        tint: [1.0, 0.4, 0.8, 0.5],
+        background: wgpu::Color { r: 0.01, g: 0.0, b: 0.03, a: 1.0 },
+        ..base.clone().with_config(&builtin_preset("presentation").unwrap())
+    };
+    let img = rasterize_snapshot(&focus, W, H);
+    assert_eq!(img.pixel(0, 0)[3], 255);
+    assert!(lit(&img, 32) > 50);
+    assert_golden("sphere_presentation_tinted", &img);
+}
+
+#[test]
+fn pulse_emphasis_and_nucleus_match_golden_image() {
+    let graph = sphere();
+    let mut snap = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    snap.modes.nucleus = true;
+    let mut frame = snap.still_frame(W, H);
+    // 3 hop/s で 0 秒に発生したパルス、先頭のエッジ群を強調・残りを減光
+    frame.uniforms.misc2[2] = 0.0;
+    frame.uniforms.misc2[3] = 3.0;
+    frame.uniforms.misc4[0] = 1.5;
+    frame.uniforms.misc4[1] = 1.3;
+    for (i, p) in frame.edge_pulse.iter_mut().enumerate().take(40) { *p = [(i % 5) as f32, 1.0]; }
+    for (i, e) in frame.edge_emphasis.iter_mut().enumerate() { *e = if i < 20 { 2.0 } else { 0.4 }; }
+    for (i, e) in frame.node_emphasis.iter_mut().enumerate() { *e = if i < 10 { 2.0 } else { 0.4 }; }
+
+    let mut fb = Framebuffer::new(W, H);
+    fb.clear(wgpu::Color::TRANSPARENT);
+    fb.draw(&frame.uniforms, &frame.edges, &frame.nodes, frame.attrs());
+    let img = fb.to_image();
+    assert_ne!(img, rasterize_snapshot(&snap, W, H));
+    assert_golden("sphere_pulse_emphasis", &img);
+}
+
+#[test]
+fn software_resolution_caps_pixels_and_keeps_aspect() {
+    assert_eq!(software_resolution(320, 200, SOFTWARE_MAX_PIXELS), (320, 200));
+    assert_eq!(software_resolution(0, 0, SOFTWARE_MAX_PIXELS), (1, 1));
+    let (w, h) = software_resolution(1920, 1080, SOFTWARE_MAX_PIXELS);
+    assert!(w * h <= SOFTWARE_MAX_PIXELS);
+    assert!((w as f32 / h as f32 - 16.0 / 9.0).abs() < 0.01);
+    assert_eq!(software_resolution(100_000, 10, 1000), (1000, 1));
+}
+
+/// GPU（lavapipe/llvmpipe 等）があれば、同じシーンの GPU 描画とほぼ一致すること
+#[test]
+fn cpu_matches_gpu_offscreen_render() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let graph = sphere();
+    let snap = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let gpu = r.snapshot(&snap).unwrap();
+    let cpu = rasterize_snapshot(&snap, 160, 100);
+    let mean = mean_diff(&gpu, &cpu);
+    // 違いは細い線の縁の画素（サンプリング規則の差）程度
+    let far = gpu.pixels.iter().zip(&cpu.pixels).filter(|(a, b)| a.abs_diff(**b) > 32).count();
+    assert!(mean < 3.0, "mean abs diff {mean}");
+    assert!(far * 50 < gpu.pixels.len(), "{far} channels differ by more than 32");
+    assert!(lit(&cpu, 32).abs_diff(lit(&gpu, 32)) * 10 < lit(&gpu, 32));
+}
@@ -15,7 +15,11 @@ fn curve_variants_include_both_signs_and_thinner() {
     // 期待: 少なくとも2本、曲率符号が両方あり、太さ倍率<1のものを含む
     assert!(v.len() >= 2);
     let mut has_pos=false; let mut has_neg=false; let mut has_thin=false;
-    for (k, ts) in v { if k>0.0 { has_pos=true; } if k<0.0 { has_neg=true; } if ts<1.0 { has_thin=true; } }
+    for (k, ts) in v {
+        if k>0.0 { has_pos=true; }
+        if k<0.0 { has_neg=true; }
+        if ts<1.0 { has_thin=true; }
+    }
     assert!(has_pos && has_neg && has_thin, "pos:{} neg:{} thin:{}", has_pos, has_neg, has_thin);
 }
 
@@ -0,0 +1,20 @@
+use wasm_wgpu_demo::recovery::{retry_delay_ms, surface_error_action, GraphStatus, SurfaceAction};
+
+#[test]
+fn surface_errors_map_to_recovery_actions() {
+    assert_eq!(surface_error_action(&wgpu::SurfaceError::Timeout), SurfaceAction::SkipFrame);
+    assert_eq!(surface_error_action(&wgpu::SurfaceError::Outdated), SurfaceAction::Reconfigure);
+    assert_eq!(surface_error_action(&wgpu::SurfaceError::Lost), SurfaceAction::Reconfigure);
+    assert_eq!(surface_error_action(&wgpu::SurfaceError::OutOfMemory), SurfaceAction::RecoverDevice);
+}
+
+#[test]
+fn retry_delay_backs_off_and_is_capped() {
+    assert_eq!(retry_delay_ms(0), 500.0);
+    assert_eq!(retry_delay_ms(1), 1000.0);
+    assert!(retry_delay_ms(2) > retry_delay_ms(1));
+    assert_eq!(retry_delay_ms(100), 8000.0);
+    // ホストへ返す文字列は固定
+    assert_eq!(GraphStatus::Recovering.as_str(), "recovering");
+    assert_eq!(GraphStatus::NotStarted.as_str(), "not_started");
+}
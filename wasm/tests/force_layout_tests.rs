@@ -0,0 +1,107 @@
+use wasm_wgpu_demo::graph3d::force::{ForceLayout, ForceLayoutConfig};
+use wasm_wgpu_demo::graph3d::{generate_cloud, Edge3, EdgeKind3, Node3};
+
+fn dist(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0]-b[0]).powi(2) + (a[1]-b[1]).powi(2) + (a[2]-b[2]).powi(2)).sqrt()
+}
+
+// 2つのクリーク（各12ノード）を球内にばら撒いた状態から開始
+fn two_cliques() -> (Vec<Node3>, Vec<Edge3>) {
+    let (nodes, _) = generate_cloud(7, 24, 1.0, 1, 0.0, 0.0);
+    let mut edges = Vec::new();
+    for g in 0..2u32 {
+        for a in 0..12 { for b in (a+1)..12 { edges.push(Edge3 { a: g*12 + a, b: g*12 + b, kind: EdgeKind3::Mesh }); } }
+    }
+    (nodes, edges)
+}
+
+#[test]
+fn connected_nodes_end_up_closer_than_unconnected() {
+    let (mut nodes, edges) = two_cliques();
+    let mut layout = ForceLayout::new(&nodes, &edges, ForceLayoutConfig { iterations: 400, ..Default::default() });
+    layout.run();
+    assert!(layout.is_done());
+    layout.write_to(&mut nodes);
+    let (mut intra, mut inter, mut ni, mut nx) = (0.0, 0.0, 0, 0);
+    for a in 0..24 { for b in (a+1)..24 {
+        let d = dist(nodes[a].pos, nodes[b].pos);
+        if a / 12 == b / 12 { intra += d; ni += 1; } else { inter += d; nx += 1; }
+    } }
+    assert!(intra / ni as f32 * 1.5 < inter / nx as f32, "clusters should separate");
+}
+
+#[test]
+fn incremental_steps_match_a_single_run() {
+    let (nodes, edges) = two_cliques();
+    let config = ForceLayoutConfig { iterations: 90, ..Default::default() };
+    let mut once = ForceLayout::new(&nodes, &edges, config);
+    once.run();
+    let mut frames = ForceLayout::new(&nodes, &edges, config);
+    while frames.step(config.steps_per_frame) {}
+    assert_eq!(frames.iteration(), 90);
+    assert_eq!(once.positions(), frames.positions());
+}
+
+#[test]
+fn pinned_nodes_stay_and_radial_constraint_holds_shells() {
+    let (nodes, edges) = two_cliques();
+    let mut layout = ForceLayout::new(&nodes, &edges, ForceLayoutConfig { iterations: 50, radial_strength: 1.0, ..Default::default() });
+    layout.set_pinned(3, true);
+    layout.move_node(3, [0.5, 0.0, 0.0]);
+    layout.run();
+    assert_eq!(layout.positions()[3], [0.5, 0.0, 0.0]);
+    for (i, p) in layout.positions().iter().enumerate() {
+        if i == 3 { continue; }
+        let r0 = dist(nodes[i].pos, [0.0; 3]);
+        assert!((dist(*p, [0.0; 3]) - r0).abs() < 1e-3, "node {i} left its shell");
+    }
+}
+
+#[test]
+fn barnes_hut_handles_thousands_of_nodes() {
+    let (nodes, edges) = generate_cloud(3, 5000, 1.2, 3, 0.0, 0.01);
+    let mut layout = ForceLayout::new(&nodes, &edges, ForceLayoutConfig { iterations: 3, ..Default::default() });
+    layout.run();
+    assert!(layout.positions().iter().all(|p| p.iter().all(|c| c.is_finite())));
+}
+
+/// 八分木の近似: theta 0 は総当たりの反発力と一致し、既定の theta でも誤差は小さい
+#[test]
+fn barnes_hut_repulsion_approximates_the_exact_sum() {
+    let (nodes, _) = generate_cloud(5, 400, 1.0, 1, 0.0, 0.0);
+    let k = 1e-3;
+    // k を小さくして温度の上限にかからないようにする（1反復の移動量 = 反発力そのもの）
+    let first_step = |theta: f32| {
+        let config = ForceLayoutConfig { iterations: 1, ideal_length: Some(k), theta, gravity: 0.0, ..Default::default() };
+        let mut layout = ForceLayout::new(&nodes, &[], config);
+        let start = layout.positions().to_vec();
+        layout.run();
+        let moved: Vec<[f32; 3]> = layout.positions().iter().zip(&start).map(|(p, s)| [p[0] - s[0], p[1] - s[1], p[2] - s[2]]).collect();
+        (start, moved)
+    };
+    let (start, exact_step) = first_step(0.0);
+    // 総当たり: k²/d を相手から離れる向きに
+    let exact: Vec<[f32; 3]> = start.iter().enumerate().map(|(i, p)| {
+        let mut f = [0.0f64; 3];
+        for (j, q) in start.iter().enumerate() {
+            if i == j { continue; }
+            let d = [(p[0] - q[0]) as f64, (p[1] - q[1]) as f64, (p[2] - q[2]) as f64];
+            let d2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+            for c in 0..3 { f[c] += d[c] * (k * k) as f64 / d2; }
+        }
+        f.map(|v| v as f32)
+    }).collect();
+    let rel_error = |got: &[[f32; 3]]| -> Vec<f32> {
+        got.iter().zip(&exact).map(|(g, e)| dist(*g, *e) / dist(*e, [0.0; 3])).collect()
+    };
+
+    let tree_exact = rel_error(&exact_step);
+    assert!(tree_exact.iter().all(|&e| e < 1e-3), "theta 0: max {}", tree_exact.iter().fold(0.0f32, |a, &b| a.max(b)));
+
+    let (_, approx_step) = first_step(ForceLayoutConfig::default().theta);
+    assert_ne!(approx_step, exact_step, "default theta should approximate");
+    let approx = rel_error(&approx_step);
+    let mean = approx.iter().sum::<f32>() / approx.len() as f32;
+    let max = approx.iter().fold(0.0f32, |a, &b| a.max(b));
+    assert!(mean < 0.05 && max < 0.15, "default theta: mean {mean}, max {max}");
+}
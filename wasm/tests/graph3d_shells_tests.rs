@@ -1,7 +1,7 @@
 use wasm_wgpu_demo::graph3d::generate_shells;
 
 fn largest_component_ratio(n: usize, edges: &[(u32,u32)]) -> f32 {
-    use std::collections::{VecDeque, HashSet};
+    use std::collections::VecDeque;
     let mut adj = vec![Vec::<usize>::new(); n];
     for &(a,b) in edges { let a=a as usize; let b=b as usize; if a<n && b<n { adj[a].push(b); adj[b].push(a); } }
     let mut seen = vec![false;n];
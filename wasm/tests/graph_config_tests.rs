@@ -0,0 +1,78 @@
+use wasm_wgpu_demo::graph3d::ALLPAIRS_MAX_NODES;
+use wasm_wgpu_demo::params::{clamp_fog, clamp_graph_params, fit_modes_to_graph, parse_graph_config, param_range, GraphConfig, GraphParamInput, ModeFlags, ParamsConfig};
+use wasm_wgpu_demo::session::{SessionLook, SessionState};
+use wasm_wgpu_demo::tween::ParamField;
+
+#[test]
+fn defaults_round_trip_without_warnings() {
+    let json = serde_json::to_string(&GraphConfig::default()).unwrap();
+    let (cfg, warnings) = parse_graph_config(&json).unwrap();
+    assert_eq!(cfg, GraphConfig::default());
+    assert!(warnings.is_empty(), "{warnings:?}");
+    // 省略した節/項目は既定値、layout 省略は None（現在のグラフを保つ）
+    let (cfg, _) = parse_graph_config(r#"{ "params": { "node_size": 0.1 } }"#).unwrap();
+    assert_eq!(cfg.params.node_size, 0.1);
+    assert_eq!(cfg.params.fog_strength, wasm_wgpu_demo::DEFAULT_GRAPH_FOG_STRENGTH);
+    assert!(!cfg.modes.nucleus && cfg.layout.is_none());
+}
+
+#[test]
+fn out_of_range_values_are_clamped_with_warnings() {
+    let (cfg, warnings) = parse_graph_config(
+        r#"{ "params": { "fog_strength": 1.5, "rot_speed": -3, "nuc_link_on": 4, "nuc_link_off": 1 }, "modes": { "nucleus": true } }"#,
+    ).unwrap();
+    assert_eq!(cfg.params.fog_strength, 1.0);
+    assert_eq!(cfg.params.rot_speed, -1.0);
+    assert_eq!((cfg.params.nuc_link_on, cfg.params.nuc_link_off), (1.0, 4.0));
+    assert!(cfg.modes.nucleus);
+    let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(paths, ["params.rot_speed", "params.fog_strength", "params.nuc_link_on", "params.nuc_link_off"]);
+}
+
+#[test]
+fn malformed_or_unknown_fields_are_errors() {
+    for json in ["{", r#"{ "params": { "fog": 1 } }"#, r#"{ "params": { "node_size": "big" } }"#, r#"{ "extra": 1 }"#] {
+        let errors = parse_graph_config(json).unwrap_err();
+        assert_eq!(errors.len(), 1, "{json}");
+        assert!(errors[0].message.starts_with("invalid graph config json"), "{}", errors[0].message);
+    }
+}
+
+#[test]
+fn setters_share_one_range_table() {
+    // 旧 set_graph3d_fog は強さを上限なしで通していた
+    assert_eq!(clamp_fog(0.9, 0.1, 2.0), (0.1, 0.9, 1.0));
+    let out = clamp_graph_params(GraphParamInput {
+        edge_thickness: 1.0, node_size: 1.0, flow_speed: 1.0, fog_start: 0.0, fog_end: 1.0, fog_strength: 1.0, link_on: 9.0, link_off: 9.0,
+    });
+    assert_eq!(out.edge_thickness, *param_range(ParamField::EdgeThickness).end());
+    assert_eq!(out.link_off, *param_range(ParamField::LinkOff).end());
+    // セッションのプリセットも同じ範囲で丸める
+    let look = SessionLook { fog_strength: 3.0, nuc_link_off: 50.0, ..SessionLook::preset(SessionState::Idle) }.clamped();
+    assert_eq!(look.fog_strength, 1.0);
+    assert_eq!(look.nuc_link_off, *param_range(ParamField::NucLinkOff).end());
+}
+
+#[test]
+fn non_finite_values_fall_back_to_defaults() {
+    let p = ParamsConfig { flow_speed: f32::NAN, fog_end: f32::INFINITY, ..ParamsConfig::default() }.clamped();
+    assert_eq!(p, ParamsConfig::default());
+}
+
+/// 全ペア表示は適用先のグラフが上限以下の時だけ。超えれば false にして警告する（黙って無視しない）
+#[test]
+fn allpairs_is_turned_off_with_a_warning_above_the_node_cap() {
+    let (cfg, warnings) = parse_graph_config(r#"{ "modes": { "allpairs": true } }"#).unwrap();
+    assert!(cfg.modes.allpairs && warnings.is_empty());
+
+    assert_eq!(fit_modes_to_graph(cfg.clone(), ALLPAIRS_MAX_NODES), (cfg.clone(), None));
+    let (fitted, warning) = fit_modes_to_graph(cfg.clone(), ALLPAIRS_MAX_NODES + 1);
+    assert_eq!(fitted, GraphConfig { modes: ModeFlags { allpairs: false, ..cfg.modes }, ..cfg.clone() });
+    let warning = warning.expect("warning");
+    assert_eq!(warning.path, "modes.allpairs");
+    assert!(warning.message.contains(&format!("{}", ALLPAIRS_MAX_NODES + 1)), "{}", warning.message);
+
+    // 全ペア表示を頼んでいなければ何もしない
+    let nucleus = GraphConfig { modes: ModeFlags { allpairs: false, nucleus: true }, ..GraphConfig::default() };
+    assert_eq!(fit_modes_to_graph(nucleus.clone(), 50_000), (nucleus, None));
+}
@@ -0,0 +1,65 @@
+use wasm_wgpu_demo::graph_data::{load_graph_json, GraphDataError, GraphDataLimits};
+use wasm_wgpu_demo::graph3d::EdgeKind3;
+
+const LIMITS: GraphDataLimits = GraphDataLimits { max_nodes: 16, max_edges: 32 };
+
+#[test]
+fn loads_nodes_and_maps_edge_ids_to_indices() {
+    let json = r#"{
+        "nodes": [
+            { "id": 10, "pos": [0.1, 0.2, 0.3], "category": 2 },
+            { "id": 20 },
+            { "id": 30, "pos": [-0.5, 0.0, 0.5] }
+        ],
+        "edges": [
+            { "a": 10, "b": 30, "kind": "extra", "weight": 2.0 },
+            { "a": 20, "b": 10 }
+        ]
+    }"#;
+    let g = load_graph_json(json, LIMITS, 1337).expect("valid graph");
+    assert_eq!(g.nodes.len(), 3);
+    assert_eq!(g.nodes[0].id, 10);
+    assert_eq!(g.nodes[0].pos, [0.1, 0.2, 0.3]);
+    assert_eq!(g.categories, vec![Some(2), None, None]);
+    // 端点はノード配列のインデックスへ変換される
+    assert_eq!((g.edges[0].a, g.edges[0].b, g.edges[0].kind), (0, 2, EdgeKind3::Extra));
+    assert_eq!((g.edges[1].a, g.edges[1].b, g.edges[1].kind), (1, 0, EdgeKind3::Mesh));
+    assert_eq!(g.weights, vec![2.0, 1.0]);
+    // 位置未指定ノードはシェル生成器で補完（原点以外の有限値）
+    let p = g.nodes[1].pos;
+    let r = (p[0]*p[0]+p[1]*p[1]+p[2]*p[2]).sqrt();
+    assert!(r > 0.3 && r.is_finite(), "filled radius {}", r);
+    assert_eq!(g.index_of(30), Some(2));
+}
+
+#[test]
+fn rejects_invalid_input_with_descriptive_errors() {
+    let dup = r#"{ "nodes": [{ "id": 1 }, { "id": 1 }] }"#;
+    assert_eq!(load_graph_json(dup, LIMITS, 1).unwrap_err(), GraphDataError::DuplicateNodeId { id: 1, first: 0, second: 1 });
+
+    let dangling = r#"{ "nodes": [{ "id": 1 }], "edges": [{ "a": 1, "b": 7 }] }"#;
+    let err = load_graph_json(dangling, LIMITS, 1).unwrap_err();
+    assert_eq!(err, GraphDataError::DanglingEdge { edge: 0, id: 7 });
+    assert!(err.to_string().contains("unknown node id 7"), "{}", err);
+
+    // f32に収まらない値は非有限として扱う
+    let inf = r#"{ "nodes": [{ "id": 4, "pos": [1e39, 0, 0] }] }"#;
+    assert_eq!(load_graph_json(inf, LIMITS, 1).unwrap_err(), GraphDataError::NonFinitePosition { id: 4, index: 0 });
+
+    let self_loop = r#"{ "nodes": [{ "id": 1 }, { "id": 2 }], "edges": [{ "a": 2, "b": 2 }] }"#;
+    assert_eq!(load_graph_json(self_loop, LIMITS, 1).unwrap_err(), GraphDataError::SelfLoop { edge: 0, id: 2 });
+
+    assert_eq!(load_graph_json(r#"{ "nodes": [] }"#, LIMITS, 1).unwrap_err(), GraphDataError::NoNodes);
+
+    let bad_kind = r#"{ "nodes": [{ "id": 1 }, { "id": 2 }], "edges": [{ "a": 1, "b": 2, "kind": "spine" }] }"#;
+    assert!(matches!(load_graph_json(bad_kind, LIMITS, 1), Err(GraphDataError::Parse(_))));
+}
+
+#[test]
+fn enforces_configurable_limits() {
+    let nodes: Vec<String> = (0..5).map(|i| format!("{{ \"id\": {} }}", i)).collect();
+    let json = format!("{{ \"nodes\": [{}] }}", nodes.join(","));
+    let small = GraphDataLimits { max_nodes: 4, max_edges: 4 };
+    assert_eq!(load_graph_json(&json, small, 1).unwrap_err(), GraphDataError::TooManyNodes { count: 5, max: 4 });
+    assert!(load_graph_json(&json, LIMITS, 1).is_ok());
+}
@@ -0,0 +1,102 @@
+// 破棄経路: GraphScene / SceneTargets / OffscreenRenderer は GC を待たずに GPU 資源を解放する。
+// 解放後の資源を使うと検証エラーになることで確かめる（GPU が無ければ飛ばす）
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::future::Future;
+
+use common::{renderer, sphere};
+use wasm_wgpu_demo::offscreen::{Snapshot, OFFSCREEN_FORMAT};
+use wasm_wgpu_demo::render::{graph_clear_color, GraphScene, SceneTargets, GRAPH_SHADER_WGSL};
+
+const W: u32 = 64;
+const H: u32 = 48;
+
+/// `f` の GPU 操作で出た検証エラー（ネイティブでは pop_error_scope は即座に終わる）
+fn validation_error(device: &wgpu::Device, f: impl FnOnce()) -> Option<wgpu::Error> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    f();
+    let mut pop = std::pin::pin!(device.pop_error_scope());
+    match pop.as_mut().poll(&mut std::task::Context::from_waker(std::task::Waker::noop())) {
+        std::task::Poll::Ready(error) => error,
+        std::task::Poll::Pending => panic!("error scope did not resolve"),
+    }
+}
+
+fn color_target(device: &wgpu::Device) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("dispose_test_target"),
+        size: wgpu::Extent3d { width: W, height: H, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: OFFSCREEN_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+#[test]
+fn destroyed_scene_buffers_and_targets_can_no_longer_be_drawn() {
+    let Some(r) = renderer(W, H) else { return };
+    let (device, queue) = (r.device(), r.queue());
+    let graph = sphere();
+    let frame = Snapshot::new(&graph).still_frame(W, H);
+    let color = color_target(device);
+    let view = color.create_view(&wgpu::TextureViewDescriptor::default());
+    let draw = |scene: &GraphScene, targets: &SceneTargets| {
+        scene.write_uniforms(queue, &frame.uniforms);
+        let mut enc = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        scene.encode(&mut enc, &view, targets, graph_clear_color());
+        queue.submit([enc.finish()]);
+    };
+
+    let scene = GraphScene::new(device, GRAPH_SHADER_WGSL, OFFSCREEN_FORMAT, &frame.edges, &frame.nodes, frame.attrs());
+    for samples in [1, 4] {
+        let mut scene = GraphScene::new(device, GRAPH_SHADER_WGSL, OFFSCREEN_FORMAT, &frame.edges, &frame.nodes, frame.attrs());
+        scene.set_color_target(device, OFFSCREEN_FORMAT, samples);
+        let targets = SceneTargets::new(device, OFFSCREEN_FORMAT, samples, W, H);
+        assert!(validation_error(device, || draw(&scene, &targets)).is_none(), "{samples}x: draws before dispose");
+        // 深度（と MSAA のカラー）を解放すると、そのパスは描けない
+        targets.destroy();
+        assert!(validation_error(device, || draw(&scene, &targets)).is_some(), "{samples}x: depth/msaa still alive");
+        scene.destroy();
+    }
+
+    // インスタンス・uniform のバッファを解放すると、同じシーンは描けない
+    let targets = SceneTargets::new(device, OFFSCREEN_FORMAT, 1, W, H);
+    scene.destroy();
+    assert!(validation_error(device, || draw(&scene, &targets)).is_some(), "scene buffers still alive");
+    targets.destroy();
+    color.destroy();
+}
+
+#[test]
+fn dropping_the_offscreen_renderer_releases_its_target() {
+    let Some(mut r) = renderer(W, H) else { return };
+    let graph = sphere();
+    r.snapshot(&Snapshot::new(&graph)).unwrap();
+    let (device, queue, target) = (r.device().clone(), r.queue().clone(), r.texture().clone());
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("dispose_test_readback"),
+        size: (256 * H) as u64,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let copy = || {
+        let mut enc = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        enc.copy_texture_to_buffer(
+            target.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(256), rows_per_image: None },
+            },
+            wgpu::Extent3d { width: W, height: H, depth_or_array_layers: 1 },
+        );
+        queue.submit([enc.finish()]);
+    };
+    assert!(validation_error(&device, copy).is_none());
+    drop(r);
+    assert!(validation_error(&device, copy).is_some(), "render target outlived the renderer");
+}
@@ -18,3 +18,14 @@ fn node_colors_are_magenta_for_hub_and_cyan_for_normal() {
     assert_eq!(theme_node_color(0), magenta); // ハブ
     assert_eq!(theme_node_color(1), cyan);    // 通常
 }
+
+#[test]
+fn category_colors_cycle_palette_and_weight_scale_is_bounded() {
+    use wasm_wgpu_demo::graph::{edge_weight_scale, theme_category_color};
+    assert_eq!(theme_category_color(0), palette_color(Palette::Cyan));
+    assert_eq!(theme_category_color(1), palette_color(Palette::Magenta));
+    assert_eq!(theme_category_color(5), theme_category_color(0));
+    assert!((edge_weight_scale(1.0) - 1.0).abs() < 1e-6);
+    assert!(edge_weight_scale(100.0) <= 2.0 + 1e-6);
+    assert!(edge_weight_scale(-3.0) >= 0.5 - 1e-6);
+}
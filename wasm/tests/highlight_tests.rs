@@ -0,0 +1,60 @@
+use wasm_wgpu_demo::graph3d::adjacency::Adjacency;
+use wasm_wgpu_demo::graph3d::{Edge3, EdgeKind3};
+use wasm_wgpu_demo::highlight::{edge_emphasis, node_emphasis, EmphasisFade, HighlightConfig};
+
+fn edge(a: u32, b: u32) -> Edge3 { Edge3 { a, b, kind: EdgeKind3::Extra } }
+
+#[test]
+fn adjacency_lists_neighbours_and_skips_invalid_edges() {
+    let edges = [edge(0, 1), edge(1, 2), edge(2, 2), edge(3, 9), edge(0, 3)];
+    let adj = Adjacency::new(4, &edges);
+    assert_eq!(adj.node_count(), 4);
+    assert_eq!(adj.neighbours(0), &[1, 3]);
+    assert_eq!(adj.neighbours(1), &[0, 2]);
+    assert_eq!(adj.neighbours(2), &[1]);
+    assert_eq!(adj.neighbours(3), &[0]);
+    assert_eq!(adj.hop_distances(&[2], 1), vec![None, Some(1), Some(0), None]);
+    assert_eq!(adj.hop_distances(&[2], 5), vec![Some(2), Some(1), Some(0), Some(3)]);
+}
+
+#[test]
+fn emphasis_boosts_neighbourhood_and_dims_the_rest() {
+    // 0-1-2-3 の鎖 + 1-3 の近道 + 4-5
+    let edges = [edge(0, 1), edge(1, 2), edge(2, 3), edge(1, 3), edge(4, 5)];
+    let adj = Adjacency::new(6, &edges);
+    let cfg = HighlightConfig { hops: 1, floor: 0.2, boost: 2.0, fade_rate: 0.0 };
+    let hops = adj.hop_distances(&[1], cfg.hops);
+    let nodes = node_emphasis(&hops, &cfg);
+    assert_eq!(nodes, vec![1.5, 2.0, 1.5, 1.5, 0.2, 0.2]);
+    // 注目ノードに直結するエッジは boost、近傍どうし（ホップ差0）はホップ1相当、外は floor
+    let e = edge_emphasis(&hops, &edges, &cfg);
+    assert_eq!(e, vec![2.0, 2.0, 1.5, 2.0, 0.2]);
+    assert!(nodes.iter().chain(&e).all(|&w| (cfg.floor..=cfg.boost).contains(&w)));
+}
+
+#[test]
+fn config_is_clamped() {
+    let c = HighlightConfig { hops: 100, floor: -1.0, boost: 10.0, fade_rate: f32::NAN }.clamped();
+    assert_eq!(c, HighlightConfig { hops: 8, floor: 0.0, boost: 4.0, fade_rate: 0.0 });
+}
+
+#[test]
+fn fade_moves_toward_target_and_settles() {
+    let mut f = EmphasisFade::default();
+    f.set_target(vec![1.0, 1.0]);
+    assert!(f.is_settled() && !f.step(0.016, 6.0));
+    f.set_target(vec![2.0, 0.2]);
+    assert!(f.step(0.1, 6.0));
+    let [a, b] = [f.values()[0], f.values()[1]];
+    assert!(a > 1.0 && a < 2.0 && b < 1.0 && b > 0.2);
+    for _ in 0..100 { f.step(0.1, 6.0); }
+    assert!(f.is_settled());
+    assert_eq!(f.values(), &[2.0, 0.2]);
+    // 件数が変わったら即時に切り替える
+    f.set_target(vec![1.0; 3]);
+    assert_eq!(f.values(), &[1.0; 3]);
+    // fade_rate 0 は即時
+    f.set_target(vec![0.5; 3]);
+    f.step(0.016, 0.0);
+    assert_eq!(f.values(), &[0.5; 3]);
+}
@@ -0,0 +1,83 @@
+use wasm_wgpu_demo::graph;
+use wasm_wgpu_demo::graph3d::{generate_sphere, Edge3, EdgeKind3, Node3};
+use wasm_wgpu_demo::layout::{FactoryError, Layout, LayoutError, LayoutRegistry, SpineLayout};
+use wasm_wgpu_demo::shell_config::ShellConfig;
+
+#[test]
+fn builtins_are_registered_by_name() {
+    let reg = LayoutRegistry::with_builtins();
+    assert_eq!(reg.names(), vec!["cloud", "shells", "sphere", "spine"]);
+}
+
+#[test]
+fn registry_layouts_match_direct_generators() {
+    let reg = LayoutRegistry::with_builtins();
+    let (nodes, edges) = reg.create("sphere", r#"{"seed": 9, "node_count": 120}"#).unwrap().generate();
+    let (exp_nodes, exp_edges) = generate_sphere(9, 120, 4, 0.1);
+    assert_eq!(nodes, exp_nodes);
+    assert_eq!(edges, exp_edges);
+
+    // パラメータ省略（空文字）は既定プリセットのシェル
+    let (nodes, _) = reg.create("shells", "").unwrap().generate();
+    assert_eq!(nodes, ShellConfig::default().generate().0);
+}
+
+#[test]
+fn spine_layout_lifts_2d_graph() {
+    let spine = SpineLayout { seed: 3, node_count: 200, spine_segments: 10, depth: 0.5 };
+    let (nodes2, edges2) = graph::generate(3, 200, 10);
+    let (nodes, edges) = spine.generate();
+    assert_eq!(nodes.len(), nodes2.len());
+    for (n3, n2) in nodes.iter().zip(&nodes2) {
+        assert_eq!([n3.pos[0], n3.pos[1]], n2.pos);
+        if n2.level == 0 { assert_eq!(n3.pos[2], 0.0); }
+        assert!(n3.pos[2].abs() <= 0.5 * n2.pos[0].abs().max(0.1) + 1e-6);
+    }
+    let spine_edges = edges2.iter().filter(|e| e.kind == graph::EdgeKind::Spine).count();
+    assert_eq!(edges.iter().filter(|e| e.kind == EdgeKind3::Mesh).count(), spine_edges);
+    assert!(edges.iter().all(|e| (e.a as usize) < nodes.len() && (e.b as usize) < nodes.len() && e.a != e.b));
+    assert_eq!(spine.generate(), (nodes, edges));
+}
+
+#[test]
+fn unknown_names_and_bad_params_are_reported() {
+    let reg = LayoutRegistry::with_builtins();
+    let err = reg.create("grid", "{}").err().unwrap();
+    assert!(matches!(&err, LayoutError::UnknownLayout { name, .. } if name == "grid"));
+    assert!(err.to_string().contains("spine"));
+    assert!(matches!(reg.create("cloud", "{"), Err(LayoutError::Parse { .. })));
+    assert!(matches!(reg.create("sphere", r#"{"node_count": 2}"#), Err(LayoutError::Invalid { .. })));
+    assert!(matches!(reg.create("cloud", r#"{"radius": 0}"#), Err(LayoutError::Invalid { .. })));
+    assert!(matches!(reg.create("shells", r#"{"shells": []}"#), Err(LayoutError::Invalid { .. })));
+    for bad in [
+        r#"{"spine_segments": 0}"#,
+        r#"{"node_count": 40, "spine_segments": 40}"#,
+        r#"{"spine_segments": 18446744073709551615}"#,
+        r#"{"depth": -0.1}"#,
+        r#"{"depth": 2.5}"#,
+    ] {
+        assert!(matches!(reg.create("spine", bad), Err(LayoutError::Invalid { .. })), "{bad}");
+    }
+    assert!(reg.create("spine", r#"{"node_count": 40, "spine_segments": 39, "depth": 2}"#).is_ok());
+}
+
+struct Line(usize);
+
+impl Layout for Line {
+    fn generate(&self) -> (Vec<Node3>, Vec<Edge3>) {
+        let nodes = (0..self.0).map(|i| Node3 { id: i as u32, pos: [i as f32, 0.0, 0.0] }).collect();
+        let edges = (1..self.0 as u32).map(|i| Edge3 { a: i - 1, b: i, kind: EdgeKind3::Mesh }).collect();
+        (nodes, edges)
+    }
+}
+
+#[test]
+fn custom_layouts_can_be_registered() {
+    let mut reg = LayoutRegistry::with_builtins();
+    reg.register("line", |json| {
+        let n: usize = serde_json::from_str(json).map_err(|e| FactoryError::Parse(e.to_string()))?;
+        Ok(Box::new(Line(n)))
+    });
+    let (nodes, edges) = reg.create("line", "5").unwrap().generate();
+    assert_eq!((nodes.len(), edges.len()), (5, 4));
+}
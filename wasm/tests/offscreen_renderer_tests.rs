@@ -0,0 +1,60 @@
+// オフスクリーン描画はネイティブのみ
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{lit, renderer, sphere};
+use wasm_wgpu_demo::offscreen::{padded_bytes_per_row, unpad_rows, OffscreenError, Snapshot};
+use wasm_wgpu_demo::params::GraphConfig;
+use wasm_wgpu_demo::png::{decode_rgba, encode_rgba, RgbaImage};
+use wasm_wgpu_demo::presets::builtin_preset;
+
+#[test]
+fn png_round_trips_pixels() {
+    let (w, h) = (3u32, 2u32);
+    let rgba: Vec<u8> = (0..w * h * 4).map(|i| i as u8).collect();
+    let png = encode_rgba(w, h, &rgba);
+    assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+    assert_eq!(decode_rgba(&png).unwrap(), RgbaImage { width: w, height: h, pixels: rgba });
+    assert!(encode_rgba(100, 100, &[0; 40_000]).len() < 1_000, "runs of transparent pixels compress");
+}
+
+#[test]
+fn readback_rows_drop_alignment_padding() {
+    assert_eq!(padded_bytes_per_row(1), 256);
+    assert_eq!(padded_bytes_per_row(64), 256);
+    assert_eq!(padded_bytes_per_row(65), 512);
+    let padded: Vec<u8> = (0..512).map(|i| (i % 256) as u8).collect();
+    assert_eq!(unpad_rows(&padded, 2, 2), [0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn snapshots_are_deterministic_and_draw_the_graph() {
+    let Some(mut r) = renderer(96, 64) else { return };
+    let graph = sphere();
+    let snap = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let a = r.snapshot(&snap).unwrap();
+    assert_eq!((a.width, a.height, a.pixels.len()), (96, 64, 96 * 64 * 4));
+    assert_eq!(a, r.snapshot(&snap).unwrap(), "same inputs, same pixels");
+    assert_eq!(a.pixel(0, 0), [0, 0, 0, 0], "transparent like the canvas");
+    let lit = lit(&a, 32);
+    assert!(lit > 50, "graph should be visible ({lit} lit pixels)");
+
+    // 設定（プリセット）と不透明な背景
+    let focus = builtin_preset("focus").unwrap();
+    let opaque = Snapshot { background: wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }, ..snap.clone().with_config(&focus) };
+    let b = r.snapshot(&opaque).unwrap();
+    assert_eq!(b.pixel(0, 0), [0, 0, 0, 255]);
+    assert_ne!(a.pixels, b.pixels);
+
+    r.resize(40, 30).unwrap();
+    let png = r.snapshot_png(&Snapshot::new(&graph).with_config(&GraphConfig::default())).unwrap();
+    assert_eq!(png[16..24], [0, 0, 0, 40, 0, 0, 0, 30]);
+}
+
+#[test]
+fn invalid_sizes_are_rejected() {
+    let Some(mut r) = renderer(8, 8) else { return };
+    assert!(matches!(r.resize(0, 8), Err(OffscreenError::InvalidSize { width: 0, height: 8, .. })));
+    assert_eq!(r.size(), (8, 8));
+}
@@ -0,0 +1,94 @@
+use glam::{Mat4, Vec3};
+use wasm_wgpu_demo::camera::{CameraDelta, Gestures, OrbitCamera, ROTATE_RAD_PER_PX};
+
+#[test]
+fn default_camera_matches_previous_fixed_orbit() {
+    let cam = OrbitCamera::default();
+    let view = Mat4::look_at_rh(Vec3::new(3.0, 0.9, 0.0), Vec3::ZERO, Vec3::Y);
+    let proj = Mat4::perspective_rh(45.0f32.to_radians(), 1.5, 0.1, 100.0);
+    let expected = (proj * view).to_cols_array();
+    let actual = Mat4::from_cols_array_2d(&cam.view_proj(1.5)).to_cols_array();
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-5, "{a} vs {e}");
+    }
+}
+
+#[test]
+fn drag_rotates_then_coasts_with_damping() {
+    let mut cam = OrbitCamera::default();
+    let yaw0 = cam.yaw;
+    let drag = CameraDelta { rotate: [20.0, 0.0], ..Default::default() };
+    cam.update(drag, true, 0.016, 600.0);
+    assert!((cam.yaw - (yaw0 + 20.0 * ROTATE_RAD_PER_PX)).abs() < 1e-5);
+    // 離した後も慣性で回り続け、速度は減衰する
+    let mut steps = Vec::new();
+    for _ in 0..3 {
+        let before = cam.yaw;
+        cam.update(CameraDelta::default(), false, 0.016, 600.0);
+        steps.push(cam.yaw - before);
+    }
+    assert!(steps[0] > 0.0 && steps[1] < steps[0] && steps[2] < steps[1]);
+    for _ in 0..200 { cam.update(CameraDelta::default(), false, 0.016, 600.0); }
+    let settled = cam.yaw;
+    cam.update(CameraDelta::default(), false, 0.016, 600.0);
+    assert_eq!(cam.yaw, settled, "auto-rotate speed is 0, so it must come to rest");
+}
+
+#[test]
+fn auto_rotation_resumes_after_inactivity() {
+    let mut cam = OrbitCamera::default();
+    cam.auto_rotate_speed = 0.5;
+    cam.resume_after_s = 2.0;
+    // 起動直後は自動回転
+    cam.update(CameraDelta::default(), false, 0.1, 600.0);
+    assert!((cam.yaw - 0.05).abs() < 1e-5);
+    cam.update(CameraDelta { zoom: 0.1, ..Default::default() }, false, 0.1, 600.0);
+    let yaw = cam.yaw;
+    for _ in 0..15 { cam.update(CameraDelta::default(), false, 0.1, 600.0); }
+    assert_eq!(cam.yaw, yaw, "idle for 1.5s < 2s: no auto rotation yet");
+    for _ in 0..30 { cam.update(CameraDelta::default(), false, 0.1, 600.0); }
+    assert!(cam.is_idle() && cam.yaw > yaw);
+}
+
+#[test]
+fn zoom_pitch_and_pan_are_clamped() {
+    let mut cam = OrbitCamera::default();
+    cam.update(CameraDelta { zoom: 10.0, rotate: [0.0, 10_000.0], pan: [1e6, 0.0] }, true, 0.016, 600.0);
+    assert_eq!(cam.distance, cam.limits.max_distance);
+    assert_eq!(cam.pitch, cam.limits.max_pitch);
+    assert!((Vec3::from(cam.target).length() - cam.limits.max_target_offset).abs() < 1e-4);
+    cam.update(CameraDelta { zoom: -10.0, ..Default::default() }, false, 0.016, 600.0);
+    assert_eq!(cam.distance, cam.limits.min_distance);
+    cam.reset_view();
+    assert_eq!(cam.eye(), OrbitCamera::default().eye());
+}
+
+#[test]
+fn gestures_map_drag_wheel_and_pinch() {
+    let mut g = Gestures::default();
+    g.pointer_down(1, 100.0, 100.0, false);
+    g.pointer_move(1, 110.0, 95.0);
+    assert!(g.is_held() && g.was_drag());
+    assert_eq!(g.take(), CameraDelta { rotate: [10.0, -5.0], ..Default::default() });
+    g.pointer_up(1);
+    assert!(!g.is_held());
+
+    // 2本指: 間隔が倍になる = 距離 1/2（対数 -ln2）、中点の移動はパン
+    g.pointer_down(1, 100.0, 100.0, false);
+    g.pointer_down(2, 200.0, 100.0, false);
+    g.pointer_move(2, 300.0, 100.0);
+    let d = g.take();
+    assert!((d.zoom + 2f32.ln()).abs() < 1e-5);
+    assert_eq!(d.pan, [50.0, 0.0]);
+    assert_eq!(d.rotate, [0.0, 0.0]);
+    g.pointer_up(1);
+    g.pointer_up(2);
+
+    g.pointer_down(3, 0.0, 0.0, true);
+    g.pointer_move(3, 2.0, 1.0);
+    g.wheel(100.0);
+    let d = g.take();
+    assert_eq!(d.pan, [2.0, 1.0]);
+    assert!((d.zoom - 0.1).abs() < 1e-6);
+    assert!(!g.was_drag(), "small moves still count as a click");
+}
@@ -0,0 +1,56 @@
+use wasm_wgpu_demo::easing::Easing;
+use wasm_wgpu_demo::tween::{ParamField, ParamTweens, TweenCurve, MAX_TWEEN_S, PARAM_COUNT};
+
+#[test]
+fn curves_parse_and_hit_both_ends() {
+    assert_eq!("spring".parse::<TweenCurve>(), Ok(TweenCurve::Spring));
+    assert_eq!("linear".parse::<TweenCurve>(), Ok(TweenCurve::Ease(Easing::Linear)));
+    assert_eq!("".parse::<TweenCurve>(), Ok(TweenCurve::default()));
+    let err = "bouncy".parse::<TweenCurve>().unwrap_err().to_string();
+    assert!(err.contains("spring") && err.contains("ease-in-out-cubic"), "{err}");
+    for c in [TweenCurve::Spring, TweenCurve::Ease(Easing::EaseInOutCubic)] {
+        assert_eq!(c.apply(0.0), 0.0);
+        assert_eq!(c.apply(1.0), 1.0);
+    }
+    // バネは一度行き過ぎる
+    let peak = (0..100).map(|i| TweenCurve::Spring.apply(i as f32 / 100.0)).fold(0.0f32, f32::max);
+    assert!(peak > 1.05 && peak < 1.2, "{peak}");
+}
+
+#[test]
+fn fields_move_independently_to_their_targets() {
+    let mut tw = ParamTweens::default();
+    let mut v = [0.0; PARAM_COUNT];
+    tw.start(ParamField::FogStart, 0.0, 1.0, 0.8, TweenCurve::Ease(Easing::Linear));
+    tw.start(ParamField::RotSpeed, 0.0, 2.0, 0.4, TweenCurve::Ease(Easing::Linear));
+    tw.step(0.2, &mut v);
+    assert!((v[ParamField::FogStart.index()] - 0.25).abs() < 1e-6);
+    assert!((v[ParamField::RotSpeed.index()] - 1.0).abs() < 1e-6);
+    assert_eq!(v[ParamField::FogEnd.index()], 0.0, "untouched fields keep their value");
+    tw.step(0.2, &mut v);
+    assert_eq!(v[ParamField::RotSpeed.index()], 2.0);
+    assert_eq!(tw.target(ParamField::RotSpeed), None);
+    assert_eq!(tw.target(ParamField::FogStart), Some(1.0));
+    tw.step(1.0, &mut v);
+    assert_eq!(v[ParamField::FogStart.index()], 1.0);
+    assert!(!tw.is_active());
+}
+
+#[test]
+fn retarget_restarts_from_current_value_and_zero_duration_snaps() {
+    let mut tw = ParamTweens::default();
+    let mut v = [0.0; PARAM_COUNT];
+    let f = ParamField::LinkOff;
+    tw.start(f, 0.0, 1.0, 1.0, TweenCurve::Ease(Easing::Linear));
+    tw.step(0.5, &mut v);
+    tw.start(f, v[f.index()], 0.0, 1.0, TweenCurve::Ease(Easing::Linear));
+    tw.step(0.5, &mut v);
+    assert!((v[f.index()] - 0.25).abs() < 1e-6);
+    tw.start(f, v[f.index()], 3.0, 0.0, TweenCurve::Spring);
+    tw.step(0.0, &mut v);
+    assert_eq!(v[f.index()], 3.0);
+    // 上限を超える時間は丸める
+    tw.start(f, 0.0, 1.0, 1e6, TweenCurve::Ease(Easing::Linear));
+    tw.step(MAX_TWEEN_S, &mut v);
+    assert_eq!(v[f.index()], 1.0);
+}
@@ -0,0 +1,65 @@
+use glam::{Mat4, Vec3};
+use wasm_wgpu_demo::graph3d::{Edge3, EdgeKind3};
+use wasm_wgpu_demo::picking::{drifted_position, pick_edge, pick_node, shader_drift, PickView};
+
+fn view(time: f32) -> PickView {
+    // 既定の OrbitCamera（yaw=0）と同じ視点
+    let view = Mat4::look_at_rh(Vec3::new(3.0, 0.9, 0.0), Vec3::ZERO, Vec3::Y);
+    let proj = Mat4::perspective_rh(45.0f32.to_radians(), 800.0 / 600.0, 0.1, 100.0);
+    PickView { view_proj: (proj * view).to_cols_array_2d(), time, flow_speed: 1.0, width: 800.0, height: 600.0 }
+}
+
+fn to_pixel(v: &PickView, p: [f32; 3]) -> (f32, f32) {
+    let [x, y, _] = v.project(p).expect("in front of camera");
+    ((x + 1.0) * 0.5 * v.width, (1.0 - y) * 0.5 * v.height)
+}
+
+#[test]
+fn drift_is_tangent_and_keeps_radius() {
+    assert_eq!(shader_drift([0.0; 3], 3.0, 1.0), [0.0; 3]);
+    let p = [0.3, -0.7, 0.9];
+    let r = Vec3::from(p).length();
+    for t in [0.0, 1.5, 42.0] {
+        let off = Vec3::from(shader_drift(p, t, 1.0));
+        assert!(off.length() <= 0.025 * r * 1.5);
+        assert!(off.dot(Vec3::from(p)).abs() < 1e-4);
+        assert!((Vec3::from(drifted_position(p, t, 1.0)).length() - r).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn picks_node_under_cursor_and_prefers_front() {
+    let v = view(2.0);
+    // 2番目は1番目とカメラを結ぶ線上（手前）にあり、画面上でほぼ重なる
+    let nodes = [[0.0, 0.0, 0.5], [1.0, 0.3, 1.0 / 3.0], [0.0, 0.8, -0.8]];
+    let radius = |_| 0.03;
+    let (x, y) = to_pixel(&v, nodes[2]);
+    assert_eq!(pick_node(&v, &nodes, radius, x, y, 0.0), Some(2));
+    // ドリフト量の差を吸収できる大きさのビルボードなら両方に当たり、手前が選ばれる
+    let (x, y) = to_pixel(&v, nodes[0]);
+    assert_eq!(pick_node(&v, &nodes, |_| 0.1, x, y, 0.0), Some(1));
+    assert_eq!(pick_node(&v, &nodes, radius, 5.0, 5.0, 3.0), None);
+    // 余裕（ピクセル）で当たり範囲が広がる
+    let (x, y) = to_pixel(&v, nodes[2]);
+    let off = 0.03 * v.height * 0.5 + 2.0;
+    assert_eq!(pick_node(&v, &nodes, radius, x, y + off, 0.0), None);
+    assert_eq!(pick_node(&v, &nodes, radius, x, y + off, 3.0), Some(2));
+}
+
+#[test]
+fn picks_visible_edges_only() {
+    let v = view(0.5);
+    let nodes = [[0.0, 0.5, -0.5], [0.0, 0.5, 0.5], [0.0, -0.6, 0.0]];
+    let edges = [
+        Edge3 { a: 0, b: 1, kind: EdgeKind3::Mesh },
+        Edge3 { a: 1, b: 2, kind: EdgeKind3::Extra },
+    ];
+    let a = to_pixel(&v, nodes[0]);
+    let b = to_pixel(&v, nodes[1]);
+    let mid = ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+    let hw = |_| 0.01;
+    assert_eq!(pick_edge(&v, &nodes, &edges, hw, (0.8, 1.4), mid.0, mid.1, 3.0), Some(0));
+    // リンクのフェード距離より長いエッジは描かれないので当たらない
+    assert_eq!(pick_edge(&v, &nodes, &edges, hw, (0.1, 0.2), mid.0, mid.1, 3.0), None);
+    assert_eq!(pick_edge(&v, &nodes, &edges, hw, (0.8, 1.4), 5.0, 5.0, 3.0), None);
+}
@@ -0,0 +1,216 @@
+// ポストエフェクトの列（周辺減光・色収差・フィルムグレイン・走査線・色調整）。設定は純関数で、描画はオフスクリーンで確認する
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{gl_renderer, mean_diff, renderer, sphere};
+use wasm_wgpu_demo::graph_data::LoadedGraph;
+use wasm_wgpu_demo::offscreen::Snapshot;
+use wasm_wgpu_demo::params::{parse_graph_config, GraphConfig};
+use wasm_wgpu_demo::png::RgbaImage;
+use wasm_wgpu_demo::postfx::{
+    post_active, tune_post_pass, validate_post_passes, ChromaticAberrationParams, ColorGradeParams, FilmGrainParams, PostEffect,
+    PostPass, ScanlinesParams, VignetteParams, MAX_POST_PASSES,
+};
+use wasm_wgpu_demo::presets::{decode_preset, encode_preset, PresetError};
+
+#[test]
+fn post_list_parses_by_effect_name_with_defaults() {
+    let (cfg, warnings) = parse_graph_config(
+        r#"{ "post": [ { "effect": "vignette", "strength": 0.5 }, { "effect": "film_grain", "enabled": false }, { "effect": "color_grade", "gain": [1, 0.5, 2] } ] }"#,
+    )
+    .unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(cfg.post, [
+        PostPass::Vignette(VignetteParams { strength: 0.5, ..VignetteParams::default() }),
+        PostPass::FilmGrain(FilmGrainParams { enabled: false, ..FilmGrainParams::default() }),
+        PostPass::ColorGrade(ColorGradeParams { gain: [1.0, 0.5, 2.0], ..ColorGradeParams::default() }),
+    ]);
+    assert!(post_active(&cfg.post));
+    assert!(!post_active(&[PostPass::FilmGrain(FilmGrainParams { enabled: false, ..FilmGrainParams::default() })]));
+    assert_eq!(parse_graph_config("{}").unwrap().0.post, []);
+
+    // 名前は snake_case で往復する
+    for effect in PostEffect::ALL {
+        assert_eq!(PostEffect::from_name(effect.name()), Some(effect));
+        let json = serde_json::to_value(PostPass::new(effect)).unwrap();
+        assert_eq!(json["effect"], effect.name());
+        assert_eq!(serde_json::from_value::<PostPass>(json).unwrap(), PostPass::new(effect));
+    }
+    assert_eq!(PostEffect::from_name("bloom"), None);
+
+    for bad in [r#"{ "post": [ { "effect": "sepia" } ] }"#, r#"{ "post": [ { "effect": "scanlines", "strength": 1 } ] }"#, r#"{ "post": [ { "amount": 1 } ] }"#] {
+        assert!(parse_graph_config(bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn post_params_are_clamped_and_truncated_with_warnings() {
+    let passes = vec![
+        PostPass::Scanlines(ScanlinesParams { spacing: 0.0, speed: f32::NAN, ..ScanlinesParams::default() }),
+        PostPass::ColorGrade(ColorGradeParams { gamma: [1.0, 9.0, 1.0], ..ColorGradeParams::default() }),
+    ];
+    let (out, warnings) = validate_post_passes(passes);
+    assert_eq!(out[0], PostPass::Scanlines(ScanlinesParams { spacing: 1.0, ..ScanlinesParams::default() }));
+    assert_eq!(out[1], PostPass::ColorGrade(ColorGradeParams { gamma: [1.0, 5.0, 1.0], ..ColorGradeParams::default() }));
+    let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(paths, ["post[0].spacing", "post[0].speed", "post[1].gamma[1]"]);
+
+    let (out, warnings) = validate_post_passes(vec![PostPass::new(PostEffect::Vignette); MAX_POST_PASSES + 2]);
+    assert_eq!(out.len(), MAX_POST_PASSES);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].path, "post");
+}
+
+#[test]
+fn tuning_merges_into_the_first_pass_or_appends() {
+    let mut passes = vec![PostPass::new(PostEffect::Scanlines), PostPass::new(PostEffect::Vignette)];
+    let warnings = tune_post_pass(&mut passes, PostEffect::Vignette, r#"{ "strength": 2, "radius": 0.5 }"#).unwrap();
+    assert_eq!(passes[1], PostPass::Vignette(VignetteParams { strength: 1.0, radius: 0.5, ..VignetteParams::default() }));
+    assert_eq!(warnings.iter().map(|w| w.path.as_str()).collect::<Vec<_>>(), ["post[1].strength"]);
+
+    // 無ければ既定値で足す。"effect" で種類は変わらない
+    tune_post_pass(&mut passes, PostEffect::ChromaticAberration, r#"{ "effect": "vignette", "amount": 0.01 }"#).unwrap();
+    assert_eq!(passes[2], PostPass::ChromaticAberration(ChromaticAberrationParams { amount: 0.01, ..ChromaticAberrationParams::default() }));
+
+    let before = passes.clone();
+    assert!(tune_post_pass(&mut passes, PostEffect::Scanlines, r#"{ "amount": 1 }"#).is_err());
+    assert!(tune_post_pass(&mut passes, PostEffect::Scanlines, "[1]").is_err());
+    assert_eq!(passes, before);
+
+    let mut full = vec![PostPass::new(PostEffect::Vignette); MAX_POST_PASSES];
+    assert!(tune_post_pass(&mut full, PostEffect::FilmGrain, "{}").is_err());
+}
+
+#[test]
+fn preset_codes_carry_the_post_list() {
+    let plain = GraphConfig::default();
+    let graded = GraphConfig { post: vec![PostPass::new(PostEffect::Scanlines), PostPass::new(PostEffect::ColorGrade)], ..plain.clone() };
+    assert!(encode_preset(&graded).len() > encode_preset(&plain).len());
+    assert_eq!(decode_preset(&encode_preset(&graded)).unwrap(), graded);
+    assert_eq!(decode_preset(&encode_preset(&plain)).unwrap().post, []);
+
+    // 版1・ポストエフェクトのタグに壊れた JSON
+    let broken = wasm_wgpu_demo::presets::base64url_encode(&[1, 0x22, 2, b'[', b'{']);
+    assert!(matches!(decode_preset(&broken), Err(PresetError::InvalidPost(_))));
+}
+
+#[test]
+fn post_shaders_validate_and_translate_to_webgl2() {
+    use naga::back::glsl;
+    let options = glsl::Options {
+        version: glsl::Version::Embedded { version: 300, is_webgl: true },
+        writer_flags: glsl::WriterFlags::ADJUST_COORDINATE_SPACE | glsl::WriterFlags::FORCE_POINT_SIZE,
+        ..Default::default()
+    };
+    for effect in PostEffect::ALL {
+        let module = naga::front::wgsl::parse_str(&effect.shader_source()).unwrap_or_else(|e| panic!("{}: {e}", effect.name()));
+        let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+            .validate(&module)
+            .unwrap_or_else(|e| panic!("{}: {e:?}", effect.name()));
+        for (stage, entry) in [(naga::ShaderStage::Vertex, "vs_fullscreen"), (naga::ShaderStage::Fragment, "fs_post")] {
+            let pipeline = glsl::PipelineOptions { shader_stage: stage, entry_point: entry.into(), multiview: None };
+            let mut out = String::new();
+            glsl::Writer::new(&mut out, &module, &info, &options, &pipeline, naga::proc::BoundsCheckPolicies::default())
+                .and_then(|mut w| w.write())
+                .unwrap_or_else(|e| panic!("{} {entry}: {e}", effect.name()));
+        }
+    }
+}
+
+fn luma(img: &RgbaImage, x: u32, y: u32) -> u32 {
+    img.pixel(x, y)[..3].iter().map(|&c| c as u32).sum()
+}
+
+/// 不透明な灰色の背景（エフェクトの差が角でも見える）
+fn gray<'a>(graph: &'a LoadedGraph) -> Snapshot<'a> {
+    Snapshot { time: 1.25, background: wgpu::Color { r: 0.2, g: 0.2, b: 0.2, a: 1.0 }, ..Snapshot::new(graph) }
+}
+
+#[test]
+fn each_effect_changes_the_image_as_configured() {
+    let Some(mut r) = renderer(160, 100) else { return };
+    let graph = sphere();
+    let plain = gray(&graph);
+    let direct = r.snapshot(&plain).unwrap();
+    let with = |post: Vec<PostPass>| Snapshot { post, ..plain.clone() };
+
+    // 無効なパスだけなら直接描画と同じ
+    let off = PostPass::Vignette(VignetteParams { enabled: false, ..VignetteParams::default() });
+    assert_eq!(r.snapshot(&with(vec![off])).unwrap(), direct);
+
+    // 周辺減光: 角は暗く、中央は変わらない
+    let vignette = r.snapshot(&with(vec![PostPass::Vignette(VignetteParams { strength: 0.8, ..VignetteParams::default() })])).unwrap();
+    assert!(luma(&vignette, 0, 0) * 2 < luma(&direct, 0, 0), "{} vs {}", luma(&vignette, 0, 0), luma(&direct, 0, 0));
+    assert_eq!(vignette.pixel(80, 50), direct.pixel(80, 50));
+
+    // 走査線: 背景の1列が周期的に明暗を繰り返す
+    let scan = ScanlinesParams { intensity: 0.8, spacing: 4.0, ..ScanlinesParams::default() };
+    let lines = r.snapshot(&with(vec![PostPass::Scanlines(scan)])).unwrap();
+    let column: Vec<u32> = (0..8).map(|y| luma(&lines, 2, y)).collect();
+    assert_eq!(column[..4], column[4..], "{column:?}");
+    assert!(column.iter().max().unwrap() > &(column.iter().min().unwrap() + 20), "{column:?}");
+
+    // 色調整: gain 0 で真っ黒（アルファは保つ）
+    let black = r.snapshot(&with(vec![PostPass::ColorGrade(ColorGradeParams { gain: [0.0; 3], ..ColorGradeParams::default() })])).unwrap();
+    assert!(black.pixels.chunks(4).all(|p| p[..3] == [0, 0, 0] && p[3] == 255));
+
+    // 色収差: 中心から離れた所で r と b がずれる（中央はほぼ同じ）
+    let ca = r.snapshot(&with(vec![PostPass::ChromaticAberration(ChromaticAberrationParams { amount: 0.03, ..ChromaticAberrationParams::default() })])).unwrap();
+    let shifted = ca.pixels.chunks(4).zip(direct.pixels.chunks(4)).filter(|(a, b)| a[0] != b[0] || a[2] != b[2]).count();
+    assert!(shifted > 100, "{shifted}");
+    assert_eq!(ca.pixel(80, 50)[1], direct.pixel(80, 50)[1]);
+}
+
+#[test]
+fn film_grain_is_deterministic_per_time_and_animates() {
+    let Some(mut r) = renderer(96, 64) else { return };
+    let graph = LoadedGraph::from_generated(Vec::new(), Vec::new());
+    let grain = PostPass::FilmGrain(FilmGrainParams { amount: 0.3, ..FilmGrainParams::default() });
+    let at = |time: f32| Snapshot { time, post: vec![grain], ..gray(&graph) };
+    let a = r.snapshot(&at(1.0)).unwrap();
+    assert_eq!(r.snapshot(&at(1.0)).unwrap(), a);
+    // 同じ粒の区間（1/24 秒）の中では変わらず、次の区間で変わる
+    assert_eq!(r.snapshot(&at(1.01)).unwrap(), a);
+    let b = r.snapshot(&at(1.1)).unwrap();
+    assert_ne!(a, b);
+    let distinct: std::collections::BTreeSet<u32> = (0..96).map(|x| luma(&a, x, 10)).collect();
+    assert!(distinct.len() > 4, "{distinct:?}");
+    // 止めれば時刻に依らない
+    let still = |time: f32| Snapshot { time, post: vec![PostPass::FilmGrain(FilmGrainParams { amount: 0.3, fps: 0.0, ..FilmGrainParams::default() })], ..gray(&graph) };
+    assert_eq!(r.snapshot(&still(1.0)).unwrap(), r.snapshot(&still(7.0)).unwrap());
+}
+
+/// 列の順に掛かる（色調整で黒くしてから減光するのと、その逆とでは結果が違う）。結果は透過の背景も保つ
+#[test]
+fn passes_run_in_order_and_follow_resize() {
+    let Some(mut r) = renderer(80, 60) else { return };
+    let graph = LoadedGraph::from_generated(Vec::new(), Vec::new());
+    let lift = PostPass::ColorGrade(ColorGradeParams { lift: [0.5; 3], ..ColorGradeParams::default() });
+    let vignette = PostPass::Vignette(VignetteParams { strength: 1.0, radius: 0.9, softness: 0.5, ..VignetteParams::default() });
+    let lift_then_dim = r.snapshot(&Snapshot { post: vec![lift, vignette], ..gray(&graph) }).unwrap();
+    let dim_then_lift = r.snapshot(&Snapshot { post: vec![vignette, lift], ..gray(&graph) }).unwrap();
+    assert_eq!(luma(&lift_then_dim, 0, 0), 0);
+    assert!(luma(&dim_then_lift, 0, 0) > 100);
+
+    let transparent = r.snapshot(&Snapshot { post: vec![lift, vignette], ..Snapshot::new(&graph) }).unwrap();
+    assert!(transparent.pixels.iter().all(|&c| c == 0));
+
+    r.resize(120, 40).unwrap();
+    let resized = r.snapshot(&Snapshot { post: vec![lift, vignette], ..gray(&graph) }).unwrap();
+    assert_eq!((resized.width, resized.height), (120, 40));
+    assert_eq!(luma(&resized, 0, 0), 0);
+    assert!(luma(&resized, 60, 20) > luma(&resized, 0, 0));
+}
+
+/// WebGL2 と同じ GL 経路でも同じ画像になる
+#[test]
+fn gl_backend_renders_the_same_post_effects() {
+    let Some(mut r) = renderer(120, 80) else { return };
+    let Some(mut gl) = gl_renderer(120, 80) else { return };
+    let graph = sphere();
+    let post = PostEffect::ALL.iter().filter(|&&e| e != PostEffect::FilmGrain).map(|&e| PostPass::new(e)).collect();
+    let snap = Snapshot { post, ..gray(&graph) };
+    let mean = mean_diff(&r.snapshot(&snap).unwrap(), &gl.snapshot(&snap).unwrap());
+    assert!(mean < 2.0, "mean abs diff {mean}");
+}
@@ -0,0 +1,90 @@
+use wasm_wgpu_demo::params::{validate_graph_config, AntialiasConfig, BloomConfig, GraphConfig, LayoutSettings, ModeFlags, ParamsConfig};
+use wasm_wgpu_demo::postfx::{PostEffect, PostPass, VignetteParams};
+use wasm_wgpu_demo::presets::{
+    base64url_decode, base64url_encode, builtin_preset, decode_preset, encode_preset, PresetError, PresetLibrary, BUILTIN_PRESETS,
+};
+use wasm_wgpu_demo::session::{SessionLook, SessionState};
+
+#[test]
+fn builtins_are_in_range_and_differ_from_defaults() {
+    for name in BUILTIN_PRESETS {
+        let cfg = builtin_preset(name).unwrap();
+        let (clamped, warnings) = validate_graph_config(cfg.clone());
+        assert!(warnings.is_empty(), "{name}: {warnings:?}");
+        assert_eq!(clamped, cfg);
+        assert_ne!(cfg.params, ParamsConfig::default(), "{name}");
+        assert!(cfg.layout.is_none(), "built-ins keep the current graph");
+    }
+}
+
+#[test]
+fn active_research_follows_the_researching_session_look() {
+    let cfg = builtin_preset("active-research").unwrap();
+    let look = SessionLook::preset(SessionState::Researching);
+    assert_eq!(cfg.params, look.params());
+    assert_eq!(cfg.modes, ModeFlags { allpairs: false, nucleus: true });
+}
+
+#[test]
+fn library_registers_custom_presets_but_protects_builtins() {
+    let mut lib = PresetLibrary::default();
+    let cfg = GraphConfig { modes: ModeFlags { allpairs: true, nucleus: false }, ..GraphConfig::default() };
+    lib.register("mine", cfg.clone()).unwrap();
+    assert_eq!(lib.get("mine").unwrap(), cfg);
+    assert_eq!(lib.names().last().map(String::as_str), Some("mine"));
+    assert_eq!(lib.register("focus", cfg.clone()), Err(PresetError::BuiltinName("focus".into())));
+    assert!(matches!(lib.register("  ", cfg), Err(PresetError::InvalidName(_))));
+    let err = lib.get("nope").unwrap_err().to_string();
+    assert!(err.contains("calm-background") && err.contains("mine"), "{err}");
+    assert!(lib.remove("mine") && !lib.remove("focus"));
+}
+
+#[test]
+fn codes_round_trip_exact_values() {
+    let cfg = GraphConfig {
+        params: ParamsConfig { rot_speed: -0.123_456_7, fog_strength: 0.33, ..ParamsConfig::default() },
+        modes: ModeFlags { allpairs: false, nucleus: true },
+        bloom: BloomConfig { enabled: true, threshold: 1.25, intensity: 0.3, radius: 0.9 },
+        post: vec![
+            PostPass::Vignette(VignetteParams { strength: 0.123_456_7, ..VignetteParams::default() }),
+            PostPass::new(PostEffect::FilmGrain),
+        ],
+        antialias: AntialiasConfig { msaa: 8, analytic: true },
+        layout: Some(LayoutSettings { name: "sphere".into(), params: serde_json::json!({ "seed": 7, "node_count": 300 }) }),
+    };
+    let code = encode_preset(&cfg);
+    assert!(code.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'), "{code}");
+    assert_eq!(decode_preset(&code).unwrap(), cfg);
+    // レイアウトなしなら短い
+    assert!(encode_preset(&builtin_preset("focus").unwrap()).len() < 96);
+}
+
+#[test]
+fn old_codes_decode_and_unknown_tags_are_skipped() {
+    // 版1で params の一部とモードだけを持つ古いコード: 無い項目は既定値
+    let mut bytes = vec![1u8, 0x02, 4];
+    bytes.extend_from_slice(&0.12f32.to_le_bytes());
+    bytes.extend_from_slice(&[0x20, 1, 0b10]);
+    // 将来足されるタグ（長さ付きなので読み飛ばせる）
+    bytes.extend_from_slice(&[0x7e, 3, 9, 9, 9]);
+    let cfg = decode_preset(&base64url_encode(&bytes)).unwrap();
+    assert_eq!(cfg.params, ParamsConfig { node_size: 0.12, ..ParamsConfig::default() });
+    assert!(cfg.modes.nucleus && !cfg.modes.allpairs);
+}
+
+#[test]
+fn broken_codes_are_rejected() {
+    assert_eq!(decode_preset("!!"), Err(PresetError::Base64));
+    assert_eq!(decode_preset(""), Err(PresetError::Truncated));
+    assert_eq!(decode_preset(&base64url_encode(&[9])), Err(PresetError::UnsupportedVersion(9)));
+    assert_eq!(decode_preset(&base64url_encode(&[1, 0x01, 4, 0])), Err(PresetError::Truncated));
+}
+
+#[test]
+fn base64url_matches_rfc4648_vectors() {
+    for (raw, enc) in [("", ""), ("f", "Zg"), ("fo", "Zm8"), ("foo", "Zm9v"), ("foob", "Zm9vYg"), ("fooba", "Zm9vYmE"), ("foobar", "Zm9vYmFy")] {
+        assert_eq!(base64url_encode(raw.as_bytes()), enc);
+        assert_eq!(base64url_decode(enc).unwrap(), raw.as_bytes());
+    }
+    assert_eq!(base64url_encode(&[0xfb, 0xff]), "-_8");
+}
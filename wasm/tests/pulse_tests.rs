@@ -0,0 +1,45 @@
+use wasm_wgpu_demo::graph3d::adjacency::Adjacency;
+use wasm_wgpu_demo::graph3d::{Edge3, EdgeKind3};
+use wasm_wgpu_demo::pulse::{pulse_edge_hops, pulse_intensity, Pulse, PULSE_NONE};
+
+fn edge(a: u32, b: u32) -> Edge3 { Edge3 { a, b, kind: EdgeKind3::Extra } }
+
+#[test]
+fn edge_hops_follow_bfs_order_and_direction() {
+    // 0-1-2-3 の鎖（1-2 は逆向きに格納）+ 1-3 の近道 + 4-5（非連結）
+    let edges = [edge(0, 1), edge(2, 1), edge(2, 3), edge(1, 3), edge(4, 5)];
+    let adj = Adjacency::new(6, &edges);
+    let hops = adj.hop_distances(&[0], 3);
+    let e = pulse_edge_hops(&hops, &edges, 3);
+    assert_eq!(e, vec![[0.0, 1.0], [1.0, -1.0], [2.0, 0.0], [1.0, 1.0], PULSE_NONE]);
+    // 深さ1: 起点側がホップ1以上のエッジは対象外
+    let e = pulse_edge_hops(&adj.hop_distances(&[0], 1), &edges, 1);
+    assert_eq!(e, vec![[0.0, 1.0], PULSE_NONE, PULSE_NONE, PULSE_NONE, PULSE_NONE]);
+}
+
+#[test]
+fn front_travels_along_edge_and_falls_off_per_hop() {
+    let e = [0.0, 1.0];
+    // 波頭の位置で最大、まだ届いていない先は暗い
+    assert!((pulse_intensity(0.5, e, 0.5, 0.7) - 1.0).abs() < 1e-6);
+    assert!(pulse_intensity(0.5, e, 0.9, 0.7) < 0.01);
+    // 通過後は尾を引いて減衰
+    let tail = pulse_intensity(0.5, e, 0.2, 0.7);
+    assert!(tail > 0.3 && tail < 1.0);
+    // 向き -1 は p2 側から進む
+    assert!((pulse_intensity(0.25, [0.0, -1.0], 0.75, 0.7) - 1.0).abs() < 1e-6);
+    // 両端が同ホップのエッジは両側から中央へ
+    assert!((pulse_intensity(2.5, [2.0, 0.0], 0.5, 0.5) - 0.25).abs() < 1e-6);
+    // ホップ毎に falloff 倍
+    let near = pulse_intensity(0.5, [0.0, 1.0], 0.5, 0.7);
+    let far = pulse_intensity(2.5, [2.0, 1.0], 0.5, 0.7);
+    assert!((far - near * 0.49).abs() < 1e-5);
+    assert_eq!(pulse_intensity(1.0, PULSE_NONE, 0.5, 0.7), 0.0);
+}
+
+#[test]
+fn pulse_finishes_after_front_passes_last_hop() {
+    let p = Pulse { source: 0, hops: 3, speed: 2.0, start: 10.0 };
+    assert!(!p.is_finished(11.0));
+    assert!(p.is_finished(14.0));
+}
@@ -29,6 +29,10 @@ fn cloud_points_within_radius_and_connected() {
     assert!(ratio >= 0.8, "largest component ratio too small: {}", ratio);
     // 分布に偏りが少なく、中心/外側双方が含まれる（ゆるいチェック）
     let mut inner=0usize; let mut outer=0usize;
-    for n in &nodes { let p=n.pos; let r=(p[0]*p[0]+p[1]*p[1]+p[2]*p[2]).sqrt(); if r<0.5 { inner+=1; } if r>0.9 { outer+=1; } }
+    for n in &nodes {
+        let p=n.pos; let r=(p[0]*p[0]+p[1]*p[1]+p[2]*p[2]).sqrt();
+        if r<0.5 { inner+=1; }
+        if r>0.9 { outer+=1; }
+    }
     assert!(inner>10 && outer>5, "inner {}, outer {}", inner, outer);
 }
@@ -0,0 +1,40 @@
+use wasm_wgpu_demo::renderer_slot::{RendererSlot, NOT_STARTED};
+
+// 互換関数は未開始・破棄後の呼び出しを黙って無視せず、エラーを返す
+#[test]
+fn legacy_calls_report_not_started_until_a_renderer_is_installed() {
+    let mut slot: RendererSlot<Vec<u32>> = RendererSlot::new();
+    let mut calls = 0;
+    let err = slot.with(|r| { calls += 1; r.push(1); Ok::<_, String>(()) }).unwrap_err();
+    assert_eq!((err.as_str(), calls), (NOT_STARTED, 0));
+    assert!(slot.get().is_none());
+
+    let ticket = slot.begin();
+    assert_eq!(slot.install(ticket, vec![7]), None);
+    assert_eq!(slot.with(|r| { r.push(8); Ok::<_, String>(r.len()) }), Ok(2));
+    // 呼び出し側のエラーはそのまま返る
+    assert_eq!(slot.with(|_| Err::<(), _>("renderer disposed".to_string())), Err("renderer disposed".to_string()));
+
+    assert_eq!(slot.take(), Some(vec![7, 8]));
+    assert_eq!(slot.with(|r| Ok::<_, String>(r.len())), Err(NOT_STARTED.to_string()));
+}
+
+/// 再マウント: 先に始めた開始が後から終わっても、新しい方のレンダラを上書きしない
+#[test]
+fn stale_starts_are_handed_back_for_disposal() {
+    let mut slot: RendererSlot<&str> = RendererSlot::new();
+    let stale = slot.begin();
+    let live = slot.begin();
+    assert_eq!(slot.install(live, "live"), None);
+    assert_eq!(slot.install(stale, "stale"), Some("stale"));
+    assert_eq!(slot.get(), Some(&"live"));
+
+    // 同じ世代で入れ直すと、前のレンダラを返す
+    assert_eq!(slot.install(live, "again"), Some("live"));
+
+    // 開始を待つ間に破棄されたら、その結果も入れない
+    let pending = slot.begin();
+    assert_eq!(slot.take(), Some("again"));
+    assert_eq!(slot.install(pending, "late"), Some("late"));
+    assert!(slot.get().is_none());
+}
@@ -0,0 +1,69 @@
+use wasm_wgpu_demo::easing::Easing;
+use wasm_wgpu_demo::pulse::AutoPulse;
+use wasm_wgpu_demo::session::{parse_session_look, LookTransition, SessionLook, SessionPresets, SessionState};
+use wasm_wgpu_demo::{DEFAULT_GRAPH_FOG_STRENGTH, DEFAULT_GRAPH_ROT_SPEED};
+
+#[test]
+fn states_parse_from_spec_names() {
+    for s in SessionState::ALL {
+        assert_eq!(s.name().parse::<SessionState>(), Ok(s));
+    }
+    assert_eq!(" ready ".parse::<SessionState>(), Ok(SessionState::Ready));
+    let err = "done".parse::<SessionState>().unwrap_err().to_string();
+    assert!(err.contains("idle, optimizing, ready, researching"), "{err}");
+}
+
+#[test]
+fn idle_preset_matches_defaults_and_others_differ() {
+    let idle = SessionLook::preset(SessionState::Idle);
+    assert_eq!(idle.rot_speed, DEFAULT_GRAPH_ROT_SPEED);
+    assert_eq!(idle.fog_strength, DEFAULT_GRAPH_FOG_STRENGTH);
+    assert_eq!((idle.nucleus, idle.tint[3], idle.pulse_rate), (0.0, 0.0, 0.0));
+    for s in [SessionState::Optimizing, SessionState::Ready, SessionState::Researching] {
+        let look = SessionLook::preset(s);
+        assert_ne!(look, idle);
+        assert_eq!(look, look.clamped(), "{s} preset is within setter ranges");
+    }
+}
+
+#[test]
+fn transition_crossfades_every_field_with_easing() {
+    let from = SessionLook::preset(SessionState::Idle);
+    let to = SessionLook::preset(SessionState::Researching);
+    let mut tr = LookTransition::new(from, to, 2.0, Easing::Linear);
+    let mid = tr.step(1.0);
+    assert_eq!(mid, from.lerp(&to, 0.5));
+    assert!((mid.rot_speed - 0.5 * (from.rot_speed + to.rot_speed)).abs() < 1e-6);
+    assert!((mid.tint[3] - 0.1).abs() < 1e-6);
+    assert!(!tr.is_finished());
+    assert_eq!(tr.step(1.5), to);
+    assert!(tr.is_finished());
+    // 0秒は即時
+    let mut snap = LookTransition::new(from, to, 0.0, Easing::default());
+    assert_eq!(snap.step(0.0), to);
+}
+
+#[test]
+fn custom_presets_are_parsed_and_clamped() {
+    let look = parse_session_look(r#"{ "rot_speed": 0.3, "fog_start": 0.9, "fog_end": 0.2, "node_size": 5, "nucleus": 2 }"#).unwrap();
+    assert_eq!(look.rot_speed, 0.3);
+    assert_eq!((look.fog_start, look.fog_end), (0.2, 0.9));
+    assert_eq!(look.node_size, 0.3);
+    assert_eq!(look.nucleus, 1.0);
+    assert_eq!(look.edge_thickness, SessionLook::default().edge_thickness);
+    assert!(parse_session_look("{ \"rot_speed\": \"fast\" }").is_err());
+    let mut presets = SessionPresets::default();
+    presets.set(SessionState::Ready, look);
+    assert_eq!(*presets.get(SessionState::Ready), look);
+    assert_eq!(*presets.get(SessionState::Idle), SessionLook::default());
+}
+
+#[test]
+fn auto_pulse_fires_at_rate() {
+    let mut p = AutoPulse::default();
+    p.rate = 2.0;
+    let fired = (0..60).filter(|_| p.step(1.0 / 60.0)).count();
+    assert_eq!(fired, 2);
+    p.rate = 0.0;
+    assert!(!(0..600).any(|_| p.step(0.1)));
+}
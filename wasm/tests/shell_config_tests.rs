@@ -0,0 +1,71 @@
+use wasm_wgpu_demo::graph3d::generate_shells;
+use wasm_wgpu_demo::graph_data::{load_graph_json, GraphDataLimits, LoadedGraph};
+use wasm_wgpu_demo::shell_config::{parse_shell_config, ShellConfig, ShellConfigError};
+
+#[test]
+fn presets_match_previous_shell_profiles() {
+    let radii = [0.6, 0.8, 1.0, 1.2, 1.4];
+    let balanced = [0.15, 0.20, 0.30, 0.20, 0.15];
+    let inner = [0.34, 0.26, 0.20, 0.12, 0.08];
+    assert_eq!(ShellConfig::default(), ShellConfig::preset(0));
+    // 位置だけでなくエッジの端点と種類まで同じ（シェルの割り当てが変わると k-NN の結線も変わる）
+    for (profile, weights) in [(0, balanced), (1, inner)] {
+        let (nodes, edges) = ShellConfig::preset(profile).generate();
+        let (exp_nodes, exp_edges) = generate_shells(1337, 240, &radii, &weights, 4, 1, 0.15, 0.05);
+        assert_eq!(nodes, exp_nodes, "profile {profile}");
+        assert_eq!(edges, exp_edges, "profile {profile}");
+    }
+    let reseeded = ShellConfig { seed: 99, node_count: 300, ..ShellConfig::preset(1) };
+    let (nodes, edges) = reseeded.generate();
+    let (exp_nodes, exp_edges) = generate_shells(99, 300, &radii, &inner, 4, 1, 0.15, 0.05);
+    assert_eq!((nodes, edges), (exp_nodes, exp_edges));
+}
+
+#[test]
+fn partial_json_overrides_defaults_and_accepts_any_shell_count() {
+    let cfg = parse_shell_config(r#"{
+        "seed": 7, "node_count": 500,
+        "shells": [{ "radius": 0.5, "weight": 1 }, { "radius": 1.0, "weight": 2 }, { "radius": 1.5, "weight": 1 },
+                   { "radius": 2.0, "weight": 1 }, { "radius": 2.5, "weight": 1 }, { "radius": 3.0, "weight": 1 }, { "radius": 3.5, "weight": 1 }]
+    }"#).expect("valid config");
+    assert_eq!(cfg.seed, 7);
+    assert_eq!(cfg.shells.len(), 7);
+    assert_eq!(cfg.k_intra, ShellConfig::default().k_intra);
+    let (nodes, _) = cfg.generate();
+    assert_eq!(nodes.len(), 500);
+    // 最外殻を超える半径のノードは無い（ジッタ分の余裕のみ）
+    assert!(nodes.iter().all(|n| (n.pos[0]*n.pos[0] + n.pos[1]*n.pos[1] + n.pos[2]*n.pos[2]).sqrt() < 3.5 * 1.2));
+}
+
+#[test]
+fn rejects_invalid_configs() {
+    assert!(matches!(parse_shell_config("{"), Err(ShellConfigError::Parse(_))));
+    assert!(matches!(parse_shell_config(r#"{"node_count": 1}"#), Err(ShellConfigError::NodeCount { .. })));
+    assert!(matches!(parse_shell_config(r#"{"shells": []}"#), Err(ShellConfigError::ShellCount { .. })));
+    assert_eq!(
+        parse_shell_config(r#"{"shells": [{"radius": -1, "weight": 1}]}"#),
+        Err(ShellConfigError::InvalidRadius { index: 0 })
+    );
+    assert_eq!(
+        parse_shell_config(r#"{"shells": [{"radius": 1, "weight": 0}]}"#),
+        Err(ShellConfigError::ZeroTotalWeight)
+    );
+    assert_eq!(
+        parse_shell_config(r#"{"hub_ratio": 2}"#),
+        Err(ShellConfigError::RatioOutOfRange { field: "hub_ratio" })
+    );
+}
+
+/// set_data の後に現在と同じプロファイルを指定しても、読み込んだデータのままにせずシェルへ戻す
+#[test]
+fn current_profile_after_loading_data_restores_the_shells() {
+    let current = ShellConfig::preset(0);
+    let data = load_graph_json(r#"{ "nodes": [{ "id": 10 }, { "id": 20 }], "edges": [{ "a": 10, "b": 20 }] }"#, GraphDataLimits::default(), current.seed).unwrap();
+    let restored = current.graph();
+    assert_ne!(restored, data);
+    let (nodes, edges) = current.generate();
+    assert_eq!(restored, LoadedGraph::from_generated(nodes, edges));
+    assert_eq!(restored.nodes.len(), current.node_count);
+    // 何度指定しても同じグラフ
+    assert_eq!(ShellConfig::preset(0).graph(), restored);
+}
@@ -0,0 +1,49 @@
+use wasm_wgpu_demo::graph3d::generate_shells;
+use wasm_wgpu_demo::graph3d::spatial::KdTree;
+
+fn brute(points: &[[f32; 3]], q: [f32; 3], k: usize, exclude: Option<usize>) -> Vec<(f32, usize)> {
+    // 旧実装と同じ: 全距離→安定ソート→先頭k件
+    let mut d: Vec<(f32, usize)> = (0..points.len()).filter(|&j| Some(j) != exclude)
+        .map(|j| { let b = points[j]; let dx=q[0]-b[0]; let dy=q[1]-b[1]; let dz=q[2]-b[2]; (dx*dx+dy*dy+dz*dz, j) })
+        .collect();
+    d.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    d.truncate(k);
+    d
+}
+
+#[test]
+fn kd_tree_matches_brute_force_including_ties() {
+    // 格子点（同距離が大量に出る）＋擬似乱数点
+    let mut pts: Vec<[f32; 3]> = Vec::new();
+    for x in 0..8 { for y in 0..8 { for z in 0..4 { pts.push([x as f32 * 0.25, y as f32 * 0.25, z as f32 * 0.25]); } } }
+    let mut s = 12345u32;
+    for _ in 0..500 {
+        let mut r = || { s = s.wrapping_mul(1664525).wrapping_add(1013904223); (s >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0 };
+        pts.push([r(), r(), r()]);
+    }
+    let tree = KdTree::from_subset(&pts, 0..pts.len());
+    assert_eq!(tree.len(), pts.len());
+    for k in [1, 4, 9] {
+        for i in (0..pts.len()).step_by(7) {
+            assert_eq!(tree.nearest(pts[i], k, Some(i)), brute(&pts, pts[i], k, Some(i)), "k={k} i={i}");
+        }
+        // 点集合の外からの問い合わせ（隣接レイヤ接続と同じ使い方）
+        let q = [1.7, -0.3, 0.9];
+        assert_eq!(tree.nearest(q, k, None), brute(&pts, q, k, None));
+    }
+}
+
+#[test]
+fn shells_scale_to_50k_nodes_deterministically() {
+    let radii = [0.6, 0.8, 1.0, 1.2, 1.4];
+    let probs = [0.15, 0.20, 0.30, 0.20, 0.15];
+    let (nodes, edges) = generate_shells(1337, 50_000, &radii, &probs, 4, 1, 0.15, 0.05);
+    assert_eq!(nodes.len(), 50_000);
+    // 全ノードが少なくとも層内k近傍の本数ぶん接続される
+    let mut degree = vec![0u32; nodes.len()];
+    for e in &edges { degree[e.a as usize] += 1; degree[e.b as usize] += 1; }
+    assert!(degree.iter().all(|&d| d >= 4));
+    // 同じseedなら順序まで同一
+    let (_, again) = generate_shells(1337, 50_000, &radii, &probs, 4, 1, 0.15, 0.05);
+    assert_eq!(edges, again);
+}
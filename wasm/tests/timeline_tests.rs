@@ -0,0 +1,192 @@
+use wasm_wgpu_demo::camera::{CameraPose, OrbitCamera};
+use wasm_wgpu_demo::graph3d::ALLPAIRS_MAX_NODES;
+use wasm_wgpu_demo::params::ParamsConfig;
+use wasm_wgpu_demo::render::UGraph;
+use wasm_wgpu_demo::timeline::{camera_pose_at, parse_timeline, Playback, TimelineBase, TimelineClock, TimelineError};
+use wasm_wgpu_demo::tween::{ParamField, PARAM_COUNT};
+
+const SCRIPT: &str = r#"{
+  "events": [
+    { "at": 0, "action": "nucleus", "on": false },
+    { "at": 2, "action": "nucleus", "on": true, "duration_s": 2 },
+    { "at": 4, "action": "pulse", "node": 0 },
+    { "at": 1, "action": "params", "params": { "fog_strength": 0.2, "flow_speed": 3 }, "duration_s": 2, "curve": "linear" },
+    { "at": 6, "action": "focus", "nodes": [42], "duration_s": 1.5, "easing": "ease-out-cubic" }
+  ]
+}"#;
+
+fn base() -> TimelineBase {
+    let mut params = [0.5; PARAM_COUNT];
+    params[ParamField::FlowSpeed.index()] = 1.0;
+    TimelineBase { params, nucleus: 1.0, allpairs: false, camera: OrbitCamera::home_pose() }
+}
+
+#[test]
+fn parses_sorts_and_derives_duration() {
+    let tl = parse_timeline(SCRIPT, 100, |_| true).unwrap();
+    let ats: Vec<f32> = tl.events.iter().map(|e| e.at).collect();
+    assert_eq!(ats, [0.0, 1.0, 2.0, 4.0, 6.0]);
+    assert_eq!(tl.duration, 7.5, "ends when the last flight lands");
+    assert!(!tl.looping && tl.drives_camera());
+}
+
+#[test]
+fn rejects_bad_scripts() {
+    let err = |json: &str| parse_timeline(json, 10, |id| id < 10).unwrap_err();
+    assert!(matches!(err("{"), TimelineError::Parse(_)));
+    assert_eq!(err(r#"{ "events": [{ "at": -1, "action": "allpairs", "on": true }] }"#), TimelineError::InvalidTime { index: 0, field: "at" });
+    assert_eq!(err(r#"{ "events": [{ "at": 0, "action": "pulse", "node": 99 }] }"#), TimelineError::UnknownNode { index: 0, id: 99 });
+    assert_eq!(err(r#"{ "events": [{ "at": 0, "action": "focus", "nodes": [] }] }"#), TimelineError::EmptyFocus { index: 0 });
+    let msg = err(r#"{ "events": [{ "at": 0, "action": "params", "params": { "fog": 1 } }] }"#).to_string();
+    assert!(msg.contains("fog_strength"), "{msg}");
+    assert!(matches!(err(r#"{ "events": [{ "at": 0, "action": "explode" }] }"#), TimelineError::Parse(_)));
+}
+
+#[test]
+fn allpairs_events_are_refused_above_the_node_cap() {
+    const ON: &str = r#"{ "events": [{ "at": 0, "action": "allpairs", "on": false }, { "at": 1, "action": "allpairs", "on": true }] }"#;
+    const OFF: &str = r#"{ "events": [{ "at": 1, "action": "allpairs", "on": false }] }"#;
+    assert!(parse_timeline(ON, ALLPAIRS_MAX_NODES, |_| true).is_ok());
+    let err = parse_timeline(ON, ALLPAIRS_MAX_NODES + 1, |_| true).unwrap_err();
+    assert!(matches!(err, TimelineError::Allpairs { index: 1, .. }), "{err:?}");
+    let msg = err.to_string();
+    assert!(msg.starts_with("events[1]") && msg.contains("1001"), "{msg}");
+    // 外すだけの台本は大きなグラフでも読める
+    assert!(parse_timeline(OFF, ALLPAIRS_MAX_NODES + 1, |_| true).is_ok());
+}
+
+#[test]
+fn sampling_depends_only_on_time() {
+    let tl = parse_timeline(SCRIPT, 100, |_| true).unwrap();
+    let b = base();
+    let f = tl.sample(2.0, &b);
+    let fog = ParamField::FogStrength.index();
+    assert!((f.params[fog].unwrap() - 0.35).abs() < 1e-6, "halfway from 0.5 to 0.2");
+    assert_eq!(f.params[ParamField::NodeSize.index()], None, "untouched fields stay with the caller");
+    assert_eq!(f.nucleus, Some(0.0), "fade-in just started");
+    assert_eq!(tl.sample(5.0, &b).nucleus, Some(1.0));
+    assert_eq!(tl.sample(0.5, &b).params[fog], Some(0.5), "before the first cue the base value holds");
+    assert_eq!(tl.sample(4.5, &b).pulse, Some(3));
+    // シークの順番に依らない
+    assert_eq!(tl.sample(3.3, &b), { tl.sample(6.1, &b); tl.sample(3.3, &b) });
+}
+
+#[test]
+fn camera_flights_chain_from_the_previous_pose() {
+    let tl = parse_timeline(SCRIPT, 100, |_| true).unwrap();
+    let home = OrbitCamera::home_pose();
+    let target = CameraPose { distance: 1.5, target: [0.2, 0.0, 0.0], ..home };
+    let flights = tl.camera_flights(home, home, |nodes, k_hops, from| {
+        assert_eq!((nodes, k_hops), (&[42][..], 1));
+        assert_eq!(from, home);
+        target
+    });
+    assert_eq!(flights.len(), 1);
+    assert_eq!(camera_pose_at(&flights, home, 5.0), home);
+    assert_eq!(camera_pose_at(&flights, home, 9.0), target);
+    let mid = camera_pose_at(&flights, home, 6.75);
+    assert!(mid.distance < home.distance && mid.distance > target.distance);
+}
+
+#[test]
+fn flow_phase_integrates_the_scripted_speed() {
+    let tl = parse_timeline(SCRIPT, 100, |_| true).unwrap();
+    let clock = TimelineClock::new(&tl, &base(), Vec::new());
+    // 1秒まで速さ1、1..3秒で 1→3（線形）、以降 3
+    for (t, expected) in [(0.25, 0.25), (1.0, 0.0), (2.0, 0.5), (3.0, 0.0), (3.1, 0.3)] {
+        let phase = clock.flow_phase(t);
+        let d = (phase - expected).abs();
+        assert!(d.min(1.0 - d) < 1e-4, "t={t}: {phase} vs {expected}");
+    }
+}
+
+const SPIN: &str = r#"{
+  "events": [
+    { "at": 1, "action": "params", "params": { "rot_speed": 0.8, "flow_speed": 2 }, "duration_s": 3, "curve": "spring" },
+    { "at": 5, "action": "params", "params": { "rot_speed": -0.3 }, "duration_s": 1, "curve": "ease-in-out-sine" },
+    { "at": 7, "action": "params", "params": { "edge_thickness": 0.004 } }
+  ]
+}"#;
+
+/// lib.rs の frame と同じく、台本の時刻から params・カメラ・フロー縞の位相を決めて uniform にする
+fn scripted_frame(tl: &wasm_wgpu_demo::timeline::Timeline, clock: &TimelineClock, b: &TimelineBase, t: f32) -> ([[f32; 4]; 4], UGraph) {
+    let frame = tl.sample(t, b);
+    let mut values = b.params;
+    for (v, f) in values.iter_mut().zip(frame.params) {
+        if let Some(f) = f { *v = f; }
+    }
+    let mut camera = OrbitCamera::default();
+    camera.show_pose(clock.camera(t));
+    let vp = camera.view_proj(1.5);
+    let mut u = UGraph::still(vp, 1.5, t, &ParamsConfig::from_array(values), false, 240, [1.0; 4]);
+    u.misc4[3] = clock.flow_phase(t);
+    (vp, u)
+}
+
+#[test]
+fn seeking_to_the_same_time_gives_the_same_view_and_uniforms() {
+    let tl = parse_timeline(SPIN, 100, |_| true).unwrap();
+    assert!(!tl.drives_camera());
+    let b = base();
+    let clock = TimelineClock::new(&tl, &b, Vec::new());
+    let mut pb = Playback::default();
+    pb.seek(6.5);
+    let first = scripted_frame(&tl, &clock, &b, tl.local_time(pb.advance(10.0)));
+    // 刻みを変えて再生・逆方向へシークしてから同じ時刻へ戻る
+    pb.play();
+    let mut wall = 10.0;
+    for dt in [0.016, 0.1, 0.033, 0.7, 0.004, 1.3] {
+        wall += dt;
+        scripted_frame(&tl, &clock, &b, tl.local_time(pb.advance(wall)));
+    }
+    pb.seek(0.2);
+    scripted_frame(&tl, &clock, &b, tl.local_time(pb.advance(wall + 0.5)));
+    pb.pause();
+    pb.seek(6.5);
+    let again = scripted_frame(&tl, &clock, &b, tl.local_time(pb.advance(wall + 3.0)));
+    assert_eq!(first, again);
+    // 自動回転は rot_speed の積分（1秒までは base の 0.5 rad/s）
+    let yaw0 = b.camera.yaw;
+    assert!((clock.camera(0.8).yaw - (yaw0 + 0.4).rem_euclid(std::f32::consts::TAU)).abs() < 1e-5);
+    assert_ne!(clock.camera(6.5).yaw, clock.camera(6.0).yaw);
+    assert_eq!(clock.camera(6.5).distance, b.camera.distance);
+}
+
+#[test]
+fn seeking_a_long_script_does_not_replay_it() {
+    let events: Vec<String> = (0..1024)
+        .map(|i| format!(r#"{{ "at": {}, "action": "params", "params": {{ "flow_speed": {}, "rot_speed": 0.2 }}, "duration_s": 2, "curve": "spring" }}"#, i as f32 * 3.5, 0.5 + (i % 7) as f32))
+        .collect();
+    let json = format!(r#"{{ "duration_s": 3600, "loop": true, "events": [{}] }}"#, events.join(","));
+    let tl = parse_timeline(&json, 100, |_| true).unwrap();
+    let clock = TimelineClock::new(&tl, &base(), Vec::new());
+    let started = std::time::Instant::now();
+    let mut phases = Vec::new();
+    for i in 0..200 {
+        let t = if i % 2 == 0 { 3600.0 - i as f32 * 7.0 } else { i as f32 * 3.0 };
+        phases.push(clock.flow_phase(t));
+        clock.camera(t);
+    }
+    assert!(started.elapsed().as_millis() < 100, "{:?}", started.elapsed());
+    assert!(phases.iter().all(|p| (0.0..1.0).contains(p)));
+    assert_eq!(clock.flow_phase(600.0), clock.flow_phase(600.0));
+}
+
+#[test]
+fn playback_follows_the_clock_while_playing() {
+    let mut tl = parse_timeline(SCRIPT, 100, |_| true).unwrap();
+    let mut pb = Playback::default();
+    assert_eq!(pb.advance(100.0), 0.0, "loaded paused");
+    pb.play();
+    pb.advance(100.0);
+    assert_eq!(pb.advance(102.5), 2.5);
+    pb.pause();
+    assert_eq!(pb.advance(110.0), 2.5);
+    pb.seek(7.0);
+    pb.play();
+    pb.advance(200.0);
+    let p = pb.advance(201.0);
+    assert_eq!(tl.local_time(p), 7.5, "clamps at the end");
+    tl.looping = true;
+    assert!((tl.local_time(p) - 0.5).abs() < 1e-6, "wraps when looping");
+}
@@ -0,0 +1,116 @@
+// WebGL2（wgpu の GL バックエンド）への対応。シェーダの変換と GL での描画はネイティブで確認する
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{gl_renderer, lit, sphere};
+use naga::back::glsl;
+use wasm_wgpu_demo::backend::{choose_backend, preferred_alpha_mode, preferred_surface_format, BackendError, GraphBackend};
+use wasm_wgpu_demo::offscreen::Snapshot;
+use wasm_wgpu_demo::raster::rasterize_snapshot;
+use wasm_wgpu_demo::render::GRAPH_SHADER_WGSL;
+
+const ENTRY_POINTS: [(naga::ShaderStage, &str); 4] = [
+    (naga::ShaderStage::Vertex, "vs_edge"),
+    (naga::ShaderStage::Fragment, "fs_edge"),
+    (naga::ShaderStage::Vertex, "vs_node"),
+    (naga::ShaderStage::Fragment, "fs_node"),
+];
+
+#[test]
+fn webgpu_is_preferred_and_webgl_needs_the_feature() {
+    assert_eq!(choose_backend(true, false), Ok(GraphBackend::WebGpu));
+    assert_eq!(choose_backend(true, true), Ok(GraphBackend::WebGpu));
+    assert_eq!(choose_backend(false, true), Ok(GraphBackend::WebGl2));
+    let err = choose_backend(false, false).unwrap_err();
+    assert_eq!(err, BackendError::WebGpuUnavailable);
+    assert!(err.to_string().contains("`webgl` feature"));
+
+    assert_eq!(GraphBackend::WebGpu.wgpu_backends(), Some(wgpu::Backends::BROWSER_WEBGPU));
+    assert_eq!(GraphBackend::WebGl2.wgpu_backends(), Some(wgpu::Backends::GL));
+    assert_eq!(GraphBackend::Software.wgpu_backends(), None);
+    assert_eq!([GraphBackend::WebGpu, GraphBackend::WebGl2, GraphBackend::Software].map(GraphBackend::as_str), ["webgpu", "webgl2", "software"]);
+}
+
+#[test]
+fn surface_settings_accept_what_webgl2_reports() {
+    use wgpu::{CompositeAlphaMode as A, TextureFormat as F};
+    // wgpu の WebGL2 サーフェスが返す内容
+    assert_eq!(preferred_surface_format(&[F::Rgba8Unorm, F::Rgba8UnormSrgb, F::Rgba16Float]), Some(F::Rgba8UnormSrgb));
+    assert_eq!(preferred_alpha_mode(&[A::Opaque]), Some(A::Opaque));
+    // WebGPU（Chrome）
+    assert_eq!(preferred_surface_format(&[F::Bgra8Unorm, F::Rgba8Unorm]), Some(F::Bgra8Unorm));
+    assert_eq!(preferred_alpha_mode(&[A::Opaque, A::PreMultiplied]), Some(A::PreMultiplied));
+    assert_eq!(preferred_surface_format(&[]), None);
+    assert_eq!(preferred_alpha_mode(&[]), None);
+}
+
+fn parse_shader() -> (naga::Module, naga::valid::ModuleInfo) {
+    let module = naga::front::wgsl::parse_str(GRAPH_SHADER_WGSL).expect("parse WGSL");
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .expect("validate WGSL");
+    (module, info)
+}
+
+/// wgpu の GL バックエンドと同じ設定で GLSL ES 3.00（WebGL2）へ変換できること
+#[test]
+fn shader_translates_to_webgl2_glsl() {
+    let (module, info) = parse_shader();
+    let options = glsl::Options {
+        version: glsl::Version::Embedded { version: 300, is_webgl: true },
+        writer_flags: glsl::WriterFlags::ADJUST_COORDINATE_SPACE | glsl::WriterFlags::FORCE_POINT_SIZE,
+        ..Default::default()
+    };
+    for (stage, entry) in ENTRY_POINTS {
+        let pipeline = glsl::PipelineOptions { shader_stage: stage, entry_point: entry.into(), multiview: None };
+        let mut out = String::new();
+        glsl::Writer::new(&mut out, &module, &info, &options, &pipeline, naga::proc::BoundsCheckPolicies::default())
+            .and_then(|mut w| w.write())
+            .unwrap_or_else(|e| panic!("{entry}: {e}"));
+        assert!(out.starts_with("#version 300 es"), "{entry}");
+    }
+}
+
+/// 頂点属性と段間の受け渡しが WebGL2 の下限（downlevel_webgl2_defaults）に収まること
+#[test]
+fn shader_interface_fits_webgl2_limits() {
+    let (module, _) = parse_shader();
+    let limits = wgpu::Limits::downlevel_webgl2_defaults();
+    let components = |ty: naga::Handle<naga::Type>| match module.types[ty].inner {
+        naga::TypeInner::Scalar(_) => 1,
+        naga::TypeInner::Vector { size, .. } => size as u32,
+        ref other => panic!("unexpected interface type {other:?}"),
+    };
+    let is_location = |b: &Option<naga::Binding>| matches!(b, Some(naga::Binding::Location { .. }));
+    for ep in module.entry_points.iter().filter(|ep| ep.stage == naga::ShaderStage::Vertex) {
+        let attributes = ep.function.arguments.iter().filter(|a| is_location(&a.binding)).count() as u32;
+        assert!(attributes <= limits.max_vertex_attributes, "{}: {attributes} attributes", ep.name);
+        let result = ep.function.result.as_ref().unwrap();
+        let naga::TypeInner::Struct { ref members, .. } = module.types[result.ty].inner else { panic!("{}: result is not a struct", ep.name) };
+        let varyings: u32 = members.iter().filter(|m| is_location(&m.binding)).map(|m| components(m.ty)).sum();
+        assert!(varyings <= limits.max_inter_stage_shader_components, "{}: {varyings} inter-stage components", ep.name);
+    }
+}
+
+#[cfg(feature = "webgl")]
+#[test]
+fn webgl_feature_enables_the_fallback() {
+    assert!(wasm_wgpu_demo::backend::WEBGL_ENABLED);
+}
+
+/// GL バックエンド（ネイティブの GL ドライバ）で描いた画像が CPU のリファレンスとほぼ一致すること
+#[test]
+fn gl_backend_renders_like_the_reference() {
+    let Some(mut r) = gl_renderer(160, 100) else { return };
+    let graph = sphere();
+    let snap = Snapshot { time: 1.25, ..Snapshot::new(&graph) };
+    let gl = r.snapshot(&snap).unwrap();
+    assert_eq!(gl.pixel(0, 0), [0, 0, 0, 0], "transparent clear survives the GL path");
+    let cpu = rasterize_snapshot(&snap, 160, 100);
+    let diffs: Vec<u32> = gl.pixels.iter().zip(&cpu.pixels).map(|(a, b)| a.abs_diff(*b) as u32).collect();
+    let mean = diffs.iter().sum::<u32>() as f32 / diffs.len() as f32;
+    assert!(mean < 3.0, "mean abs diff {mean}");
+    // 上下反転や深度範囲（GL は -1..1）の取り違えがあれば大きくずれる
+    assert!(lit(&gl, 32).abs_diff(lit(&cpu, 32)) * 10 < lit(&cpu, 32));
+}
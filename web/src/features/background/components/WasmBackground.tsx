@@ -18,25 +18,32 @@ const DEFAULT_PARAMS = {
   nucleusFadeEnd: 2.0,
 };
 
+interface GraphRendererHandle {
+  frame: (time: number) => void;
+  resize: (width: number, height: number) => void;
+  set_params: (edgeThickness: number, nodeSize: number, flowSpeed: number) => void;
+  set_rot_speed: (rotateSpeed: number) => void;
+  set_fog: (start: number, end: number, intensity: number) => void;
+  set_allpairs: (enabled: boolean) => void;
+  set_link_fade: (start: number, end: number) => void;
+  set_shell_profile: (profile: number) => void;
+  set_nucleus: (enabled: boolean) => void;
+  set_nucleus_fade: (start: number, end: number) => void;
+  dispose: () => void;
+}
+
 interface GraphModule {
   default: () => Promise<void>;
-  start_graph: (canvasId: string) => Promise<void> | void;
-  frame_graph: (time: number) => void;
-  resize_graph: (width: number, height: number) => void;
-  set_graph_params: (
-    edgeThickness: number,
-    nodeSize: number,
-    flowSpeed: number,
-  ) => void;
-  set_graph3d_params: (rotateSpeed: number) => void;
-  set_graph3d_fog: (start: number, end: number, intensity: number) => void;
-  set_graph3d_allpairs: (enabled: boolean) => void;
-  set_graph3d_link_fade: (start: number, end: number) => void;
-  set_graph3d_shell_profile: (profile: number) => void;
-  set_graph3d_nucleus: (enabled: boolean) => void;
-  set_graph3d_nucleus_fade?: (start: number, end: number) => void;
+  GraphRenderer: {
+    new: (canvas: HTMLCanvasElement) => Promise<GraphRendererHandle>;
+  };
 }
 
+// The canvas element survives a StrictMode/fast remount, so a new mount waits
+// until the previous one has finished starting (and released its renderer)
+// before binding the canvas again.
+let previousMount: Promise<void> = Promise.resolve();
+
 export function WasmBackground() {
   const canvasRef = useRef<HTMLCanvasElement | null>(null);
 
@@ -44,6 +51,7 @@ export function WasmBackground() {
     let rafId: number | null = null;
     let resizeObserver: ResizeObserver | null = null;
     let cancelled = false;
+    let renderer: GraphRendererHandle | null = null;
 
     const bootstrap = async () => {
       if (!("gpu" in navigator)) {
@@ -54,7 +62,8 @@ export function WasmBackground() {
       }
 
       const canvas = canvasRef.current;
-      if (!canvas) {
+      // Unmounted while waiting for the previous mount to settle.
+      if (!canvas || cancelled) {
         return;
       }
 
@@ -68,38 +77,38 @@ export function WasmBackground() {
         canvas.id = CANVAS_ID;
         canvas.style.backgroundColor = "transparent";
         canvas.style.mixBlendMode = "screen";
-        await wasm.start_graph(CANVAS_ID);
+        // Each mount owns its renderer; nothing is shared through the module's default slot.
+        const graph = await wasm.GraphRenderer.new(canvas);
+        // Unmounted while the GPU device was being acquired: release it right away.
+        if (cancelled) {
+          graph.dispose();
+          return;
+        }
+        renderer = graph;
 
         // Apply baseline parameters aligned with wasm/web/network3d.html defaults.
-        wasm.set_graph_params(
+        graph.set_params(
           DEFAULT_PARAMS.edgeThickness,
           DEFAULT_PARAMS.nodeSize,
           DEFAULT_PARAMS.flowSpeed,
         );
-        wasm.set_graph3d_params(DEFAULT_PARAMS.rotateSpeed);
-        wasm.set_graph3d_fog(
+        graph.set_rot_speed(DEFAULT_PARAMS.rotateSpeed);
+        graph.set_fog(
           DEFAULT_PARAMS.fogStart,
           DEFAULT_PARAMS.fogEnd,
           DEFAULT_PARAMS.fogIntensity,
         );
-        wasm.set_graph3d_nucleus(false);
-        wasm.set_graph3d_allpairs(false);
-        wasm.set_graph3d_link_fade(
+        graph.set_nucleus(false);
+        graph.set_allpairs(false);
+        graph.set_link_fade(
           DEFAULT_PARAMS.linkFadeStart,
           DEFAULT_PARAMS.linkFadeEnd,
         );
-        wasm.set_graph3d_shell_profile(0);
-        if (wasm.set_graph3d_nucleus_fade) {
-          wasm.set_graph3d_nucleus_fade(
-            DEFAULT_PARAMS.nucleusFadeStart,
-            DEFAULT_PARAMS.nucleusFadeEnd,
-          );
-        } else {
-          wasm.set_graph3d_link_fade(
-            DEFAULT_PARAMS.nucleusFadeStart,
-            DEFAULT_PARAMS.nucleusFadeEnd,
-          );
-        }
+        graph.set_shell_profile(0);
+        graph.set_nucleus_fade(
+          DEFAULT_PARAMS.nucleusFadeStart,
+          DEFAULT_PARAMS.nucleusFadeEnd,
+        );
 
         const updateSize = () => {
           const canvasEl = canvasRef.current;
@@ -116,7 +125,7 @@ export function WasmBackground() {
           if (canvasEl.width !== nextWidth || canvasEl.height !== nextHeight) {
             canvasEl.width = nextWidth;
             canvasEl.height = nextHeight;
-            wasm.resize_graph(nextWidth, nextHeight);
+            graph.resize(nextWidth, nextHeight);
           }
         };
 
@@ -128,7 +137,7 @@ export function WasmBackground() {
           if (cancelled) {
             return;
           }
-          wasm.frame_graph(time);
+          graph.frame(time);
           rafId = requestAnimationFrame(frame);
         };
         rafId = requestAnimationFrame(frame);
@@ -137,7 +146,8 @@ export function WasmBackground() {
       }
     };
 
-    bootstrap();
+    const mount = previousMount.then(bootstrap);
+    previousMount = mount;
 
     return () => {
       cancelled = true;
@@ -147,6 +157,9 @@ export function WasmBackground() {
       if (resizeObserver && canvasRef.current) {
         resizeObserver.unobserve(canvasRef.current);
       }
+      // Release the GPU device/buffers so remounts don't accumulate resources.
+      renderer?.dispose();
+      renderer = null;
     };
   }, []);
 
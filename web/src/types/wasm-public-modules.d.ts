@@ -1,6 +1,146 @@
 declare module "/wasm/wasm_wgpu_demo.js" {
   export default function init(): Promise<void>;
+  export type GraphStatus = "ready" | "recovering" | "lost" | "disposed";
+  export type GraphBackend = "webgpu" | "webgl2" | "software";
+  export type GraphLayoutName = "shells" | "sphere" | "cloud" | "spine";
+  export type Easing =
+    | "linear"
+    | "ease-in-quad"
+    | "ease-out-quad"
+    | "ease-in-out-quad"
+    | "ease-in-cubic"
+    | "ease-out-cubic"
+    | "ease-in-out-cubic"
+    | "ease-in-out-sine"
+    | "ease-out-expo"
+    | "";
+  export type TweenCurve = Easing | "spring";
+  export type SessionState = "idle" | "optimizing" | "ready" | "researching";
+  export type PostEffectName =
+    | "vignette"
+    | "chromatic_aberration"
+    | "film_grain"
+    | "scanlines"
+    | "color_grade";
+  export type NodeHoverCallback = (nodeId: number | null) => void;
+  export type NodeClickCallback = (
+    nodeId: number | null,
+    edgeIndex: number | null,
+  ) => void;
+  export class GraphRenderer {
+    static new(canvas: HTMLCanvasElement): Promise<GraphRenderer>;
+    static new_software(canvas: HTMLCanvasElement): GraphRenderer;
+    frame(time: number): void;
+    resize(width: number, height: number): void;
+    set_params(edgeThickness: number, nodeSize: number, flowSpeed: number): void;
+    set_rot_speed(rotateSpeed: number): void;
+    set_fog(start: number, end: number, intensity: number): void;
+    set_link_fade(start: number, end: number): void;
+    set_nucleus_fade(start: number, end: number): void;
+    set_allpairs(enabled: boolean): void;
+    set_nucleus(enabled: boolean): void;
+    set_shell_profile(profile: number): void;
+    regenerate(configJson: string): void;
+    set_layout(name: GraphLayoutName, paramsJson: string): void;
+    start_force_layout(configJson: string, pinnedIds: Uint32Array): void;
+    stop_force_layout(): void;
+    set_data(json: string): void;
+    set_camera_controls(enabled: boolean): void;
+    reset_camera(): void;
+    set_camera_auto_resume(seconds: number): void;
+    focus_nodes(
+      ids: Uint32Array,
+      kHops: number,
+      durationS: number,
+      easing: Easing,
+    ): void;
+    unfocus(durationS: number, easing: Easing): void;
+    highlight_nodes(ids: Uint32Array): void;
+    clear_highlight(): void;
+    set_highlight_style(
+      hops: number,
+      floor: number,
+      boost: number,
+      fadeRate: number,
+    ): void;
+    set_bloom(
+      enabled: boolean,
+      threshold: number,
+      intensity: number,
+      radius: number,
+    ): void;
+    set_post_effects(passesJson: string): string;
+    set_post_effect_enabled(name: PostEffectName, enabled: boolean): void;
+    tune_post_effect(name: PostEffectName, paramsJson: string): string;
+    set_antialias(msaa: number, analytic: boolean): void;
+    msaa_samples(): number;
+    emit_pulse(nodeId: number, speed: number, hops: number): void;
+    set_audio_input(level: number, spectrum: Float32Array): void;
+    set_audio_mapping(configJson: string): void;
+    tween_params(
+      edgeThickness: number,
+      nodeSize: number,
+      flowSpeed: number,
+      durationS: number,
+      curve: TweenCurve,
+    ): void;
+    tween_rot_speed(rotSpeed: number, durationS: number, curve: TweenCurve): void;
+    tween_fog(
+      start: number,
+      end: number,
+      strength: number,
+      durationS: number,
+      curve: TweenCurve,
+    ): void;
+    tween_link_fade(
+      linkOn: number,
+      linkOff: number,
+      durationS: number,
+      curve: TweenCurve,
+    ): void;
+    tween_nucleus_fade(
+      linkOn: number,
+      linkOff: number,
+      durationS: number,
+      curve: TweenCurve,
+    ): void;
+    set_session_state(name: SessionState): void;
+    session_state(): SessionState;
+    set_session_transition(durationS: number, easing: Easing): void;
+    set_session_preset(name: SessionState, lookJson: string): void;
+    set_graph_config(configJson: string): string;
+    get_graph_config(): string;
+    preset_names(): string[];
+    load_preset(name: string): string;
+    save_preset(name: string): void;
+    register_preset(name: string, configJson: string): void;
+    remove_preset(name: string): boolean;
+    encode_preset(name: string): string;
+    load_preset_code(code: string): string;
+    load_timeline(json: string): void;
+    play_timeline(): void;
+    pause_timeline(): void;
+    seek_timeline(seconds: number): void;
+    set_timeline_loop(enabled: boolean): void;
+    timeline_time(): number;
+    clear_timeline(): void;
+    on_node_hover(callback: NodeHoverCallback | null): void;
+    on_node_click(callback: NodeClickCallback | null): void;
+    pick_node(x: number, y: number): number | undefined;
+    pick_edge(x: number, y: number): number | undefined;
+    set_data_limits(maxNodes: number, maxEdges: number): void;
+    dispose(): void;
+    is_disposed(): boolean;
+    is_software(): boolean;
+    backend(): GraphBackend;
+    status(): GraphStatus;
+    free(): void;
+  }
   export function start_graph(canvasId: string): Promise<void> | void;
+  export function start_graph_software(canvasId: string): void;
+  export function dispose_graph(): void;
+  export function graph_status(): GraphStatus | "not_started";
+  export function graph_backend(): GraphBackend;
   export function frame_graph(time: number): void;
   export function resize_graph(width: number, height: number): void;
   export function set_graph_params(
@@ -17,6 +157,126 @@ declare module "/wasm/wasm_wgpu_demo.js" {
   export function set_graph3d_allpairs(enabled: boolean): void;
   export function set_graph3d_link_fade(start: number, end: number): void;
   export function set_graph3d_shell_profile(profile: number): void;
+  export function regenerate_graph3d(configJson: string): void;
+  export function set_graph3d_layout(
+    name: GraphLayoutName,
+    jsonParams: string,
+  ): void;
+  export function start_graph3d_force_layout(
+    configJson: string,
+    pinnedIds: Uint32Array,
+  ): void;
+  export function stop_graph3d_force_layout(): void;
+  export function set_graph3d_camera_controls(enabled: boolean): void;
+  export function reset_graph3d_camera(): void;
+  export function set_graph3d_camera_auto_resume(seconds: number): void;
+  export function focus_graph3d_nodes(
+    ids: Uint32Array,
+    kHops: number,
+    durationS: number,
+    easing: Easing,
+  ): void;
+  export function unfocus_graph3d(durationS: number, easing: Easing): void;
+  export function highlight_graph3d_nodes(ids: Uint32Array): void;
+  export function clear_graph3d_highlight(): void;
+  export function set_graph3d_highlight_style(
+    hops: number,
+    floor: number,
+    boost: number,
+    fadeRate: number,
+  ): void;
+  export function set_graph3d_bloom(
+    enabled: boolean,
+    threshold: number,
+    intensity: number,
+    radius: number,
+  ): void;
+  export function set_graph3d_post_effects(passesJson: string): string;
+  export function set_graph3d_post_effect_enabled(
+    name: PostEffectName,
+    enabled: boolean,
+  ): void;
+  export function tune_graph3d_post_effect(
+    name: PostEffectName,
+    paramsJson: string,
+  ): string;
+  export function set_graph3d_antialias(msaa: number, analytic: boolean): void;
+  export function graph3d_msaa_samples(): number;
+  export function emit_graph3d_pulse(
+    nodeId: number,
+    speed: number,
+    hops: number,
+  ): void;
+  export function set_graph3d_audio_input(
+    level: number,
+    spectrum: Float32Array,
+  ): void;
+  export function set_graph3d_audio_mapping(configJson: string): void;
+  export function tween_graph_params(
+    edgeThickness: number,
+    nodeSize: number,
+    flowSpeed: number,
+    durationS: number,
+    curve: TweenCurve,
+  ): void;
+  export function tween_graph3d_params(
+    rotSpeed: number,
+    durationS: number,
+    curve: TweenCurve,
+  ): void;
+  export function tween_graph3d_fog(
+    start: number,
+    end: number,
+    strength: number,
+    durationS: number,
+    curve: TweenCurve,
+  ): void;
+  export function tween_graph3d_link_fade(
+    linkOn: number,
+    linkOff: number,
+    durationS: number,
+    curve: TweenCurve,
+  ): void;
+  export function tween_graph3d_nucleus_fade(
+    linkOn: number,
+    linkOff: number,
+    durationS: number,
+    curve: TweenCurve,
+  ): void;
+  export function set_graph3d_config(configJson: string): string;
+  export function get_graph3d_config(): string;
+  export function graph3d_preset_names(): string[];
+  export function load_graph3d_preset(name: string): string;
+  export function save_graph3d_preset(name: string): void;
+  export function register_graph3d_preset(name: string, configJson: string): void;
+  export function remove_graph3d_preset(name: string): boolean;
+  export function encode_graph3d_preset(name: string): string;
+  export function load_graph3d_preset_code(code: string): string;
+  export function load_graph3d_timeline(json: string): void;
+  export function play_graph3d_timeline(): void;
+  export function pause_graph3d_timeline(): void;
+  export function seek_graph3d_timeline(seconds: number): void;
+  export function set_graph3d_timeline_loop(enabled: boolean): void;
+  export function graph3d_timeline_time(): number;
+  export function clear_graph3d_timeline(): void;
+  export function set_graph3d_session_state(name: SessionState): void;
+  export function graph3d_session_state(): SessionState;
+  export function set_graph3d_session_transition(
+    durationS: number,
+    easing: Easing,
+  ): void;
+  export function set_graph3d_session_preset(
+    name: SessionState,
+    lookJson: string,
+  ): void;
+  export function on_graph3d_node_hover(
+    callback: NodeHoverCallback | null,
+  ): void;
+  export function on_graph3d_node_click(
+    callback: NodeClickCallback | null,
+  ): void;
+  export function pick_graph3d_node(x: number, y: number): number | undefined;
+  export function pick_graph3d_edge(x: number, y: number): number | undefined;
   export function set_graph3d_nucleus(enabled: boolean): void;
   export const set_graph3d_nucleus_fade:
     | ((start: number, end: number) => void)